@@ -0,0 +1,394 @@
+//! Background-thread GIF encoding.
+//!
+//! The render loop pushes raw RGB frames into a bounded channel; a worker thread
+//! quantizes each frame to a 256-color palette and writes it, so palette work
+//! overlaps with Voronoi computation of the next frame.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::mpsc::{self, SyncSender};
+use std::thread::JoinHandle;
+use anyhow::Context;
+use clap::ValueEnum;
+
+/// Frames buffered between the render thread and the encoder thread.
+/// When full, `write_frame` blocks (backpressure).
+const QUEUE_CAPACITY: usize = 2;
+
+/// Palette quantization quality
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum GifQuality {
+    /// First 256 distinct colors, nearest match for the rest (exact for typical Voronoi frames)
+    Fast,
+    /// NeuQuant palette, sampling every 10th pixel
+    Balanced,
+    /// NeuQuant palette, sampling every pixel
+    Best,
+}
+
+/// GIF encoding options
+#[derive(Debug, Clone, Copy)]
+pub struct GifEncoderConfig {
+    pub quality: GifQuality,
+    /// Floyd-Steinberg error diffusion when mapping pixels to the palette
+    pub dither: bool,
+    /// Encode pixels unchanged since the previous frame as transparent (smaller files)
+    pub transparent: bool,
+}
+
+impl Default for GifEncoderConfig {
+    fn default() -> Self {
+        Self { quality: GifQuality::Fast, dither: false, transparent: false }
+    }
+}
+
+/// Handle to a GIF encoder running on a background thread
+pub struct GifEncoder {
+    sender: Option<SyncSender<Vec<u8>>>,
+    handle: Option<JoinHandle<anyhow::Result<()>>>,
+}
+
+impl GifEncoder {
+    /// Create the output file and start the encoder thread
+    pub fn spawn(
+        output: &Path,
+        width: u32,
+        height: u32,
+        fps: u32,
+        config: GifEncoderConfig,
+    ) -> anyhow::Result<Self> {
+        use gif::{Encoder, Repeat};
+        let file = std::fs::File::create(output)?;
+        let mut encoder = Encoder::new(file, width as u16, height as u16, &[])?;
+        encoder.set_repeat(Repeat::Infinite)?;
+        let frame_delay = (100 / fps).max(1) as u16;
+
+        let (sender, receiver) = mpsc::sync_channel::<Vec<u8>>(QUEUE_CAPACITY);
+        let handle = std::thread::Builder::new()
+            .name("gif-encoder".into())
+            .spawn(move || -> anyhow::Result<()> {
+                let mut prev: Option<Vec<u8>> = None;
+                let frame_len = width as usize * height as usize * 3;
+                for rgb in receiver {
+                    anyhow::ensure!(rgb.len() == frame_len, "GIF frame is {} bytes, expected {}", rgb.len(), frame_len);
+                    let mut frame = quantize_frame(&rgb, prev.as_deref(), width, height, &config);
+                    frame.delay = frame_delay;
+                    encoder.write_frame(&frame)?;
+                    if config.transparent {
+                        prev = Some(rgb);
+                    }
+                }
+                // Encoder writes the trailer on drop
+                Ok(())
+            })
+            .context("failed to spawn GIF encoder thread")?;
+
+        Ok(Self { sender: Some(sender), handle: Some(handle) })
+    }
+
+    /// Queue one frame's raw RGB pixel data; blocks while the queue is full
+    pub fn write_frame(&mut self, rgb_data: &[u8]) -> anyhow::Result<()> {
+        let sent = self.sender.as_ref()
+            .map(|s| s.send(rgb_data.to_vec()).is_ok())
+            .unwrap_or(false);
+        if !sent {
+            // The worker exited early; surface its error
+            self.join()?;
+            anyhow::bail!("GIF encoder thread exited unexpectedly");
+        }
+        Ok(())
+    }
+
+    /// Drain queued frames, then wait for the encoder thread to finish writing
+    pub fn finish(mut self) -> anyhow::Result<()> {
+        self.join()
+    }
+
+    fn join(&mut self) -> anyhow::Result<()> {
+        drop(self.sender.take());
+        match self.handle.take() {
+            Some(handle) => handle.join()
+                .map_err(|_| anyhow::anyhow!("GIF encoder thread panicked"))?,
+            None => Ok(()),
+        }
+    }
+}
+
+/// Quantize an RGB frame to an indexed GIF frame.
+/// With `prev` (transparent mode), unchanged pixels map to a reserved transparent index.
+fn quantize_frame(
+    rgb: &[u8],
+    prev: Option<&[u8]>,
+    width: u32,
+    height: u32,
+    config: &GifEncoderConfig,
+) -> gif::Frame<'static> {
+    // Reserve the last palette slot for transparency when diffing against a previous frame
+    let max_colors = if config.transparent { 255 } else { 256 };
+    let transparent_idx = prev.map(|_| max_colors as u8);
+
+    let (mut palette, nq) = match config.quality {
+        GifQuality::Fast => (first_colors(rgb, max_colors), None),
+        GifQuality::Balanced | GifQuality::Best => {
+            let rgba: Vec<u8> = rgb.chunks_exact(3)
+                .flat_map(|c| [c[0], c[1], c[2], 255])
+                .collect();
+            let speed = if config.quality == GifQuality::Best { 1 } else { 10 };
+            let nq = color_quant::NeuQuant::new(speed, max_colors, &rgba);
+            let palette = nq.color_map_rgb()
+                .chunks_exact(3)
+                .map(|c| [c[0], c[1], c[2]])
+                .collect();
+            (palette, Some(nq))
+        }
+    };
+
+    // Palette lookup: NeuQuant's own index when available, else cached nearest match
+    let mut cache: HashMap<[u8; 3], u8> = HashMap::new();
+    let mut lookup = |c: [u8; 3], palette: &[[u8; 3]]| -> u8 {
+        if let Some(nq) = &nq {
+            return nq.index_of(&[c[0], c[1], c[2], 255]) as u8;
+        }
+        *cache.entry(c).or_insert_with(|| nearest_color(palette, c))
+    };
+
+    let (w, h) = (width as usize, height as usize);
+    let mut pixels = vec![0u8; w * h];
+    // Floyd-Steinberg error buffers for the current and next row
+    let mut err_cur = vec![[0.0f32; 3]; if config.dither { w + 2 } else { 0 }];
+    let mut err_next = err_cur.clone();
+
+    for y in 0..h {
+        for x in 0..w {
+            let i = y * w + x;
+            let src = [rgb[i * 3], rgb[i * 3 + 1], rgb[i * 3 + 2]];
+
+            if let (Some(prev), Some(t)) = (prev, transparent_idx) {
+                if prev[i * 3..i * 3 + 3] == src {
+                    pixels[i] = t;
+                    continue;
+                }
+            }
+
+            if !config.dither {
+                pixels[i] = lookup(src, &palette);
+                continue;
+            }
+
+            let e = err_cur[x + 1];
+            let want = [
+                (src[0] as f32 + e[0]).clamp(0.0, 255.0),
+                (src[1] as f32 + e[1]).clamp(0.0, 255.0),
+                (src[2] as f32 + e[2]).clamp(0.0, 255.0),
+            ];
+            let idx = lookup([want[0] as u8, want[1] as u8, want[2] as u8], &palette);
+            pixels[i] = idx;
+            let got = palette[idx as usize];
+            for ch in 0..3 {
+                let err = want[ch] - got[ch] as f32;
+                err_cur[x + 2][ch] += err * 7.0 / 16.0;
+                err_next[x][ch] += err * 3.0 / 16.0;
+                err_next[x + 1][ch] += err * 5.0 / 16.0;
+                err_next[x + 2][ch] += err * 1.0 / 16.0;
+            }
+        }
+        if config.dither {
+            std::mem::swap(&mut err_cur, &mut err_next);
+            err_next.iter_mut().for_each(|e| *e = [0.0; 3]);
+        }
+    }
+
+    palette.resize(256, [0, 0, 0]);
+    let flat_palette: Vec<u8> = palette.iter().flat_map(|c| c.iter().copied()).collect();
+    let mut frame = gif::Frame::from_palette_pixels(
+        width as u16, height as u16, pixels, flat_palette, transparent_idx,
+    );
+    if config.transparent {
+        // Keep the previous frame underneath so transparent pixels show through
+        frame.dispose = gif::DisposalMethod::Keep;
+    }
+    frame
+}
+
+/// First `max` distinct colors in scan order
+fn first_colors(rgb: &[u8], max: usize) -> Vec<[u8; 3]> {
+    let mut seen: HashSet<[u8; 3]> = HashSet::new();
+    let mut palette = Vec::new();
+    for chunk in rgb.chunks_exact(3) {
+        let c = [chunk[0], chunk[1], chunk[2]];
+        if seen.insert(c) {
+            palette.push(c);
+            if palette.len() == max {
+                break;
+            }
+        }
+    }
+    if palette.is_empty() {
+        palette.push([0, 0, 0]);
+    }
+    palette
+}
+
+/// Index of the palette entry closest to `c` (squared RGB distance)
+fn nearest_color(palette: &[[u8; 3]], c: [u8; 3]) -> u8 {
+    palette.iter()
+        .enumerate()
+        .min_by_key(|(_, p)| {
+            let dr = p[0] as i32 - c[0] as i32;
+            let dg = p[1] as i32 - c[1] as i32;
+            let db = p[2] as i32 - c[2] as i32;
+            dr * dr + dg * dg + db * db
+        })
+        .map(|(i, _)| i as u8)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const W: u32 = 24;
+    const H: u32 = 16;
+
+    /// Gradient frame shifted by `t`, with more colors than a GIF palette holds
+    fn frame(t: u32) -> Vec<u8> {
+        (0..W * H)
+            .flat_map(|i| {
+                let (x, y) = (i % W, i / W);
+                [(x * 10 + t) as u8, (y * 15) as u8, ((x * y + t) * 3) as u8]
+            })
+            .collect()
+    }
+
+    /// Encode `frames` through the background thread and decode the file back
+    /// into indexed frames
+    fn roundtrip(name: &str, frames: &[Vec<u8>], config: GifEncoderConfig) -> Vec<gif::Frame<'static>> {
+        let path = std::env::temp_dir().join(format!("voronoi-gif-{}-{}.gif", name, std::process::id()));
+        let mut encoder = GifEncoder::spawn(&path, W, H, 10, config).unwrap();
+        for rgb in frames {
+            encoder.write_frame(rgb).unwrap();
+        }
+        encoder.finish().unwrap();
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(std::fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (W as u16, H as u16));
+        let mut decoded = vec![];
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            decoded.push(frame.clone());
+        }
+        let _ = std::fs::remove_file(&path);
+        decoded
+    }
+
+    #[test]
+    fn test_encodes_every_frame() {
+        let frames: Vec<Vec<u8>> = (0..6).map(frame).collect();
+        for quality in [GifQuality::Fast, GifQuality::Balanced, GifQuality::Best] {
+            for dither in [false, true] {
+                let config = GifEncoderConfig { quality, dither, transparent: false };
+                let decoded = roundtrip(&format!("{:?}-{}", quality, dither), &frames, config);
+                assert_eq!(decoded.len(), frames.len(), "{:?}", config);
+                for f in &decoded {
+                    assert_eq!((f.width, f.height), (W as u16, H as u16));
+                    assert_eq!(f.buffer.len(), (W * H) as usize);
+                    assert_eq!(f.delay, 10);
+                    assert_eq!(f.transparent, None);
+                }
+            }
+        }
+    }
+
+    /// Unchanged pixels after the first frame are encoded as the transparent index
+    #[test]
+    fn test_transparent_unchanged_pixels() {
+        let mut changed = frame(0);
+        changed[..3].copy_from_slice(&[1, 2, 3]);
+        let frames = vec![frame(0), frame(0), changed];
+        let config = GifEncoderConfig { transparent: true, ..Default::default() };
+        let decoded = roundtrip("transparent", &frames, config);
+        assert_eq!(decoded.len(), 3);
+
+        assert_eq!(decoded[0].transparent, None);
+        let t = decoded[1].transparent.expect("transparent index after the first frame");
+        assert_eq!(t, 255);
+        assert!(decoded[1].buffer.iter().all(|&p| p == t));
+        assert_ne!(decoded[2].buffer[0], t);
+        assert!(decoded[2].buffer[1..].iter().all(|&p| p == t));
+        assert!(decoded.iter().all(|f| f.dispose == gif::DisposalMethod::Keep));
+    }
+
+    /// A failure on the encoder thread surfaces from `finish`
+    #[test]
+    fn test_finish_reports_encoder_failure() {
+        let path = std::env::temp_dir().join(format!("voronoi-gif-fail-{}.gif", std::process::id()));
+        let mut encoder = GifEncoder::spawn(&path, W, H, 10, GifEncoderConfig::default()).unwrap();
+        // Too short for a W×H frame; the worker rejects it
+        encoder.write_frame(&[0; 6]).unwrap();
+        let err = encoder.finish().unwrap_err();
+        assert!(err.to_string().contains("expected 1152"), "{}", err);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    /// With render work comparable to encode work, the background thread should
+    /// hide most of the encoding: at least 20% faster than encoding inline.
+    /// Timing-sensitive, so run explicitly with `--ignored --release`.
+    #[test]
+    #[ignore]
+    fn test_background_encoding_faster_than_inline() {
+        use std::time::{Duration, Instant};
+        const BIG_W: u32 = 320;
+        const BIG_H: u32 = 240;
+        const FRAMES: u32 = 20;
+        let config = GifEncoderConfig { quality: GifQuality::Balanced, ..Default::default() };
+        let frames: Vec<Vec<u8>> = (0..FRAMES)
+            .map(|t| (0..BIG_W * BIG_H)
+                .flat_map(|i| {
+                    let (x, y) = (i % BIG_W, i / BIG_W);
+                    [(x + t) as u8, (y * 2) as u8, ((x * y + t) / 7) as u8]
+                })
+                .collect())
+            .collect();
+        let path = |name: &str| std::env::temp_dir().join(format!("voronoi-gif-{}-{}.gif", name, std::process::id()));
+
+        let encode_inline = |render: Duration| -> Duration {
+            let start = Instant::now();
+            let file = std::fs::File::create(path("inline")).unwrap();
+            let mut encoder = gif::Encoder::new(file, BIG_W as u16, BIG_H as u16, &[]).unwrap();
+            for rgb in &frames {
+                spin(render);
+                encoder.write_frame(&quantize_frame(rgb, None, BIG_W, BIG_H, &config)).unwrap();
+            }
+            drop(encoder);
+            start.elapsed()
+        };
+        // Stand-in for Voronoi rendering: as long as one frame's encode
+        let render = encode_inline(Duration::ZERO) / FRAMES;
+        let inline = encode_inline(render);
+
+        let start = Instant::now();
+        let mut encoder = GifEncoder::spawn(&path("threaded"), BIG_W, BIG_H, 10, config).unwrap();
+        for rgb in &frames {
+            spin(render);
+            encoder.write_frame(rgb).unwrap();
+        }
+        encoder.finish().unwrap();
+        let threaded = start.elapsed();
+
+        let _ = std::fs::remove_file(path("inline"));
+        let _ = std::fs::remove_file(path("threaded"));
+        assert!(
+            threaded.as_secs_f64() <= 0.8 * inline.as_secs_f64(),
+            "threaded {:?} vs inline {:?} (render {:?}/frame)", threaded, inline, render,
+        );
+    }
+
+    /// Busy-wait, keeping a core occupied like real rendering would
+    fn spin(d: std::time::Duration) {
+        let start = std::time::Instant::now();
+        while start.elapsed() < d {
+            std::hint::spin_loop();
+        }
+    }
+}
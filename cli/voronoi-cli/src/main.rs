@@ -28,11 +28,19 @@
 //!   voronoi -i img.jpg -o out.mp4 --sites-start 25 \
 //!     -p n=25600,dt=1 -p t=3 -p n=25,dt=1
 //!
+//! ## Batch mode
+//!
+//! Render a folder of images with the same spec, writing `{stem}.mp4` per input:
+//!
+//!   voronoi --multi-image 'photos/*.jpg' out/ --spec anim.yaml --jobs 2
+//!
 //! ## Graceful interruption
 //!
 //! Frames are streamed to disk as they render. Press Ctrl+C to interrupt
 //! and encode a partial video from frames rendered so far.
 
+use std::borrow::Cow;
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -42,19 +50,36 @@ use clap::{Parser, ValueEnum};
 use indicatif::{ProgressBar, ProgressStyle};
 use serde::Deserialize;
 
-use voronoi_core::{CpuBackend, SiteCollection, ComputeBackend, Position, SplitStrategy};
+mod gif_encoder;
+mod stats;
+use gif_encoder::{GifEncoder, GifEncoderConfig, GifQuality};
+use stats::{FrameStats, StatsWriter};
+
+use voronoi_core::{
+    AdjustParams, CpuBackend, SiteCollection, ComputeBackend, EdgeBehavior, HeatmapColormap, InitStrategy,
+    Position, PullMode, SplitStrategy, VoronoiResult,
+};
 
 #[cfg(feature = "gpu")]
-use voronoi_core::GpuBackend;
+use voronoi_core::{FallbackBackend, GpuBackend};
+
+/// Consecutive GPU errors before `--gpu` switches to the CPU backend
+#[cfg(feature = "gpu")]
+const GPU_FAILURE_THRESHOLD: u32 = 3;
+/// Frames between GPU re-initialization attempts after switching to the CPU
+#[cfg(feature = "gpu")]
+const GPU_RETRY_INTERVAL: usize = 300;
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
 enum OutputFormat {
     Mp4,
     Gif,
+    /// Numbered PNGs (frame_00000.png, ...) in the --output directory
+    PngSequence,
 }
 
 /// A single animation phase (grow, shrink, hold, or fade)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 struct Phase {
     /// Target site count (None = hold at current)
     target_sites: Option<usize>,
@@ -64,6 +89,16 @@ struct Phase {
     duration: f64,
     /// Crossfade to original image (0→1 blend over duration)
     fade: bool,
+    /// Site count to jump to at the start of the phase (None = continue from previous)
+    start_sites: Option<usize>,
+    /// Centroid pull to reach by the end of the phase, interpolated linearly from the
+    /// pull in effect at its start (None = keep it constant)
+    centroid_pull_end: Option<f64>,
+    /// Run a Lloyd relaxation step on the sites before the phase's first frame
+    redistribute: bool,
+    /// Fraction of the way to move sites toward their centroids when redistributing
+    /// (None = --redistribute-strength)
+    redistribute_strength: Option<f64>,
 }
 
 /// YAML spec file format
@@ -104,15 +139,39 @@ struct PhaseSpec {
     /// Crossfade to original image duration (seconds)
     #[serde(default)]
     fade: Option<f64>,
+    /// Jump to this site count in the phase's first frame (e.g. a "shatter" reset);
+    /// n/dt/t are then relative to it
+    #[serde(default)]
+    start_sites: Option<usize>,
+    /// Ramp centroid pull linearly to this value over the phase (e.g. motion settling
+    /// into Lloyd relaxation); later phases keep the final value
+    #[serde(default)]
+    centroid_pull_end: Option<f64>,
+    /// Lloyd-relax sites before the phase starts (e.g. to spread out sites left
+    /// clustered by a shrink phase)
+    #[serde(default)]
+    redistribute: bool,
+    /// Partial Lloyd step for `redistribute` (0-1)
+    #[serde(default)]
+    redistribute_strength: Option<f64>,
 }
 
 impl PhaseSpec {
     fn to_phase(&self, current_sites: usize) -> anyhow::Result<Phase> {
+        let current_sites = self.start_sites.unwrap_or(current_sites);
+        let base = Phase {
+            start_sites: self.start_sites,
+            centroid_pull_end: self.centroid_pull_end,
+            redistribute: self.redistribute || self.redistribute_strength.is_some(),
+            redistribute_strength: self.redistribute_strength,
+            ..Phase::default()
+        };
+
         // fade phase: hold sites, crossfade to original image
         if let Some(fade_duration) = self.fade {
             return Ok(Phase {
                 target_sites: None, doubling_time: 1.0,
-                duration: fade_duration, fade: true,
+                duration: fade_duration, fade: true, ..base
             });
         }
 
@@ -121,17 +180,17 @@ impl PhaseSpec {
             (Some(target), Some(doubling_time), None) => {
                 let num_doublings = (target as f64 / current_sites as f64).log2().abs();
                 let duration = num_doublings * doubling_time;
-                Ok(Phase { target_sites: Some(target), doubling_time, duration, fade: false })
+                Ok(Phase { target_sites: Some(target), doubling_time, duration, ..base })
             }
             // n + t -> compute doubling time
             (Some(target), None, Some(duration)) => {
                 let num_doublings = (target as f64 / current_sites as f64).log2().abs();
                 let doubling_time = if num_doublings > 0.0 { duration / num_doublings } else { 1.0 };
-                Ok(Phase { target_sites: Some(target), doubling_time, duration, fade: false })
+                Ok(Phase { target_sites: Some(target), doubling_time, duration, ..base })
             }
             // just t -> hold phase
             (None, _, Some(duration)) => {
-                Ok(Phase { target_sites: None, doubling_time: 1.0, duration, fade: false })
+                Ok(Phase { target_sites: None, doubling_time: 1.0, duration, ..base })
             }
             // all three -> use n + dt, warn if t inconsistent
             (Some(target), Some(doubling_time), Some(duration)) => {
@@ -143,7 +202,7 @@ impl PhaseSpec {
                         duration, computed, target, doubling_time, duration
                     );
                 }
-                Ok(Phase { target_sites: Some(target), doubling_time, duration, fade: false })
+                Ok(Phase { target_sites: Some(target), doubling_time, duration, ..base })
             }
             _ => anyhow::bail!(
                 "invalid phase: provide n+dt, n+t, t for hold, or fade. Got: {:?}",
@@ -153,6 +212,12 @@ impl PhaseSpec {
     }
 }
 
+/// Lloyd strength to redistribute sites with before phase `phase_idx`, if it does
+fn redistribution(phase: &Phase, phase_idx: usize, args: &Args) -> Option<f64> {
+    (phase.redistribute || (args.redistribute_at_phase_boundary && phase_idx > 0))
+        .then(|| phase.redistribute_strength.unwrap_or(args.redistribute_strength))
+}
+
 fn load_spec(path: &PathBuf) -> anyhow::Result<AnimSpec> {
     let contents = std::fs::read_to_string(path)
         .with_context(|| format!("failed to read spec file: {:?}", path))?;
@@ -160,12 +225,16 @@ fn load_spec(path: &PathBuf) -> anyhow::Result<AnimSpec> {
         .with_context(|| format!("failed to parse spec file: {:?}", path))
 }
 
-/// Parse a phase spec string like "n=25600,dt=1", "t=5", or "fade=1.5"
+/// Parse a phase spec string like "n=25600,dt=1", "t=5", "fade=1.5", "start_sites=5,t=2",
+/// "t=3,centroid_pull_end=0.5", or "t=2,redistribute=0.5"
 fn parse_phase(spec: &str, current_sites: usize) -> anyhow::Result<Phase> {
     let mut n: Option<usize> = None;
     let mut dt: Option<f64> = None;
     let mut t: Option<f64> = None;
     let mut fade: Option<f64> = None;
+    let mut start_sites: Option<usize> = None;
+    let mut centroid_pull_end: Option<f64> = None;
+    let mut redistribute_strength: Option<f64> = None;
 
     for part in spec.split(',') {
         let part = part.trim();
@@ -177,16 +246,33 @@ fn parse_phase(spec: &str, current_sites: usize) -> anyhow::Result<Phase> {
             t = Some(val.parse().context("invalid t")?);
         } else if let Some(val) = part.strip_prefix("fade=") {
             fade = Some(val.parse().context("invalid fade")?);
+        } else if let Some(val) = part.strip_prefix("start_sites=") {
+            start_sites = Some(val.parse().context("invalid start_sites")?);
+        } else if let Some(val) = part.strip_prefix("centroid_pull_end=") {
+            centroid_pull_end = Some(val.parse().context("invalid centroid_pull_end")?);
+        } else if let Some(val) = part.strip_prefix("redistribute=") {
+            redistribute_strength = Some(val.parse().context("invalid redistribute")?);
         } else {
-            anyhow::bail!("unknown phase key in '{}' (expected n=, dt=, t=, or fade=)", part);
+            anyhow::bail!(
+                "unknown phase key in '{}' (expected n=, dt=, t=, fade=, start_sites=, centroid_pull_end=, or redistribute=)",
+                part,
+            );
         }
     }
+    let current_sites = start_sites.unwrap_or(current_sites);
+    let base = Phase {
+        start_sites,
+        centroid_pull_end,
+        redistribute: redistribute_strength.is_some(),
+        redistribute_strength,
+        ..Phase::default()
+    };
 
     // fade phase: hold sites, crossfade to original image
     if let Some(fade_duration) = fade {
         return Ok(Phase {
             target_sites: None, doubling_time: 1.0,
-            duration: fade_duration, fade: true,
+            duration: fade_duration, fade: true, ..base
         });
     }
 
@@ -195,17 +281,17 @@ fn parse_phase(spec: &str, current_sites: usize) -> anyhow::Result<Phase> {
         (Some(target), Some(doubling_time), None) => {
             let num_doublings = (target as f64 / current_sites as f64).log2().abs();
             let duration = num_doublings * doubling_time;
-            Ok(Phase { target_sites: Some(target), doubling_time, duration, fade: false })
+            Ok(Phase { target_sites: Some(target), doubling_time, duration, ..base })
         }
         // n + t -> compute doubling time
         (Some(target), None, Some(duration)) => {
             let num_doublings = (target as f64 / current_sites as f64).log2().abs();
             let doubling_time = if num_doublings > 0.0 { duration / num_doublings } else { 1.0 };
-            Ok(Phase { target_sites: Some(target), doubling_time, duration, fade: false })
+            Ok(Phase { target_sites: Some(target), doubling_time, duration, ..base })
         }
         // just t -> hold phase
         (None, _, Some(duration)) => {
-            Ok(Phase { target_sites: None, doubling_time: 1.0, duration, fade: false })
+            Ok(Phase { target_sites: None, doubling_time: 1.0, duration, ..base })
         }
         // all three -> use n + dt, warn if t inconsistent
         (Some(target), Some(doubling_time), Some(duration)) => {
@@ -217,7 +303,7 @@ fn parse_phase(spec: &str, current_sites: usize) -> anyhow::Result<Phase> {
                     duration, computed, target, doubling_time, duration
                 );
             }
-            Ok(Phase { target_sites: Some(target), doubling_time, duration, fade: false })
+            Ok(Phase { target_sites: Some(target), doubling_time, duration, ..base })
         }
         _ => anyhow::bail!(
             "invalid phase '{}': provide n+dt, n+t, t for hold, or fade",
@@ -226,6 +312,29 @@ fn parse_phase(spec: &str, current_sites: usize) -> anyhow::Result<Phase> {
     }
 }
 
+/// Centroid pull for frame `frame_in_phase` of `phase_frames`: `start` throughout, or
+/// interpolated linearly from `start` (first frame) to `end` (last frame)
+fn phase_pull(start: f64, end: Option<f64>, frame_in_phase: usize, phase_frames: usize) -> f64 {
+    match end {
+        Some(end) if phase_frames > 1 => {
+            let t = frame_in_phase as f64 / (phase_frames - 1) as f64;
+            start + (end - start) * t
+        }
+        Some(end) => end,
+        None => start,
+    }
+}
+
+/// `cells` without the entries at the ascending indices `removed`, so per-cell
+/// data computed before a step stays indexed like the sites that survived it
+fn surviving_cells<'a, T: Clone>(cells: &'a [T], removed: &[usize]) -> Cow<'a, [T]> {
+    if removed.is_empty() {
+        return Cow::Borrowed(cells);
+    }
+    let kept = cells.iter().enumerate().filter(|(i, _)| removed.binary_search(i).is_err());
+    Cow::Owned(kept.map(|(_, cell)| cell.clone()).collect())
+}
+
 /// Resolve target dimensions from spec and CLI overrides.
 /// CLI args take precedence over spec values.
 /// If only one dimension is given, the other is computed to preserve aspect ratio.
@@ -255,14 +364,25 @@ fn resolve_dimensions(
     (rw & !1, rh & !1)
 }
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[command(name = "voronoi")]
 #[command(about = "Render Voronoi animations", long_about = None)]
 #[command(arg_required_else_help = true)]
 struct Args {
     /// Input image path
-    #[arg(short, long)]
-    input: PathBuf,
+    #[arg(short, long, required_unless_present = "multi_image")]
+    input: Option<PathBuf>,
+
+    /// Batch mode: render every image matching GLOB with the same spec and
+    /// parameters, writing {input_stem}.{mp4,gif} into OUTPUT_DIR. Extra outputs
+    /// (--output-heatmap etc.) go there too, as {input_stem}_{file name}
+    #[arg(long, num_args = 2, value_names = ["GLOB", "OUTPUT_DIR"], conflicts_with_all = ["input", "output"])]
+    multi_image: Option<Vec<String>>,
+
+    /// Number of images to render in parallel in --multi-image mode
+    /// (capped at the Rayon thread count, which they share)
+    #[arg(long, default_value = "1")]
+    jobs: usize,
 
     /// Output file path
     #[arg(short, long)]
@@ -272,10 +392,134 @@ struct Args {
     #[arg(short, long, value_enum, default_value = "mp4")]
     format: OutputFormat,
 
+    /// GIF palette quality
+    #[arg(long, value_enum, default_value = "fast")]
+    gif_quality: GifQuality,
+
+    /// Dither GIF frames (Floyd-Steinberg)
+    #[arg(long)]
+    gif_dither: bool,
+
+    /// Encode unchanged GIF pixels as transparent (smaller files)
+    #[arg(long)]
+    gif_transparent: bool,
+
     /// Starting number of sites
     #[arg(long, default_value = "25")]
     sites_start: usize,
 
+    /// Pick the starting site count so cells match the scale of the image's
+    /// detail (its luminance correlation length) instead of --sites-start
+    #[arg(long, conflicts_with_all = ["sites_start", "spec"])]
+    auto_sites: bool,
+
+    /// Autocorrelation level marking the detail scale for --auto-sites (0-1;
+    /// higher = finer detail, more sites)
+    #[arg(long, default_value = "0.5", requires = "auto_sites")]
+    detail_threshold: f64,
+
+    /// Initial site placement strategy
+    #[arg(long, value_enum, default_value = "random")]
+    init_strategy: InitStrategy,
+
+    /// Step length in pixels for --init-strategy walk
+    #[arg(long, default_value = "20.0")]
+    walk_step: f64,
+
+    /// Escape-time iteration limit for --init-strategy mandelbrot
+    #[arg(long, default_value = "100")]
+    fractal_iters: u32,
+
+    /// Vertex count for --init-strategy polygon
+    #[arg(long, default_value = "6")]
+    polygon_sides: usize,
+
+    /// Circumradius in pixels for --init-strategy polygon (default: 40% of the
+    /// smaller image dimension)
+    #[arg(long)]
+    polygon_radius: Option<f64>,
+
+    /// Rings for --init-strategy rings, as comma-separated count:radius pairs
+    #[arg(long, default_value = "8:100,16:200,32:300")]
+    ring_specs: String,
+
+    /// x frequency for --init-strategy lissajous
+    #[arg(long, default_value = "3.0")]
+    lj_a: f64,
+
+    /// y frequency for --init-strategy lissajous
+    #[arg(long, default_value = "2.0")]
+    lj_b: f64,
+
+    /// x phase offset in radians for --init-strategy lissajous
+    #[arg(long, default_value = "1.5708")]
+    lj_delta: f64,
+
+    /// Repulsion rounds for --init-strategy scatter
+    #[arg(long, default_value = "20")]
+    scatter_iters: usize,
+
+    /// Stipple image (dark dots on a light background) for --init-strategy
+    /// stipple, resized to the render dimensions if needed
+    #[arg(long)]
+    stipple_image: Option<PathBuf>,
+
+    /// Gray level below which a stipple image pixel is part of a dot
+    #[arg(long, default_value = "64")]
+    dot_threshold: u8,
+
+    /// Fraction of sites placed on Harris corners for --init-strategy hybrid
+    #[arg(long, default_value = "0.2")]
+    corner_frac: f64,
+
+    /// Fraction of sites placed on edge pixels for --init-strategy hybrid
+    /// (--corner-frac + --edge-frac <= 1)
+    #[arg(long, default_value = "0.5")]
+    edge_frac: f64,
+
+    /// Sobel gradient magnitude (on 0-255 luma) above which a pixel counts as an
+    /// edge for --init-strategy hybrid
+    #[arg(long, default_value = "100.0")]
+    edge_threshold: f32,
+
+    /// Load initial sites from a CSV written by --export-sites (overrides --init-strategy)
+    #[arg(long)]
+    import_sites: Option<PathBuf>,
+
+    /// Resume from site state saved with `SiteCollection::save` (JSON, including
+    /// RNG state), e.g. by an interrupted run (overrides --init-strategy)
+    #[arg(long, conflicts_with = "import_sites")]
+    resume_sites: Option<PathBuf>,
+
+    /// Write final site state (positions, velocities, dynamics) to this CSV path
+    #[arg(long)]
+    export_sites: Option<PathBuf>,
+
+    /// Write per-frame diagnostics (site count, hull coverage, velocity coherence) to this CSV path
+    #[arg(long)]
+    output_stats: Option<PathBuf>,
+
+    /// Neighbor radius in pixels for the velocity_coherence column of --output-stats
+    #[arg(long, default_value = "50.0", requires = "output_stats")]
+    coherence_radius: f64,
+
+    /// First of two video frames; sites start on the fastest-moving blocks between
+    /// A and B, heading along their motion (overrides --init-strategy)
+    #[cfg(feature = "video-init")]
+    #[arg(long, requires = "init_video_frame_b")]
+    init_video_frame_a: Option<PathBuf>,
+
+    /// Second video frame for --init-video-frame-a
+    #[cfg(feature = "video-init")]
+    #[arg(long, requires = "init_video_frame_a")]
+    init_video_frame_b: Option<PathBuf>,
+
+    /// Place initial sites evenly along this SVG path data (e.g. "M 10 10 L 90 90 Q 50 0 90 10"),
+    /// heading along the curve (overrides --init-strategy)
+    #[cfg(feature = "svg-init")]
+    #[arg(long, value_name = "PATH_D")]
+    init_svg_path: Option<String>,
+
     /// Ending number of sites (legacy single-phase mode)
     #[arg(long, default_value = "1000")]
     sites_end: usize,
@@ -304,6 +548,10 @@ struct Args {
     #[arg(long)]
     gpu: bool,
 
+    /// Record GPU timestamp queries and report GPU execution time (gpu_ms), if supported
+    #[arg(long)]
+    gpu_timestamps: bool,
+
     /// Animation phase: n=<sites>,dt=<secs> | n=<sites>,t=<secs> | t=<secs> (hold)
     #[arg(short = 'p', long = "phase")]
     phase: Vec<String>,
@@ -332,22 +580,221 @@ struct Args {
     #[arg(long, default_value = "500")]
     bench_sites: usize,
 
+    /// Binary-search the fewest random sites (--seed) whose diagram reaches
+    /// --target-psnr against the input, print it, and exit
+    #[arg(long)]
+    find_optimal_sites: bool,
+
+    /// PSNR (dB) for --find-optimal-sites
+    #[arg(long, default_value = "30.0", requires = "find_optimal_sites")]
+    target_psnr: f64,
+
+    /// Upper bound for --find-optimal-sites
+    #[arg(long, default_value = "10000", requires = "find_optimal_sites")]
+    max_sites: usize,
+
     /// Render a single frame (PNG) instead of animation
     #[arg(long)]
     single_frame: bool,
 
+    /// Print an ASCII-art preview of the initial diagram before rendering
+    /// (colored when stdout is a terminal)
+    #[arg(long)]
+    preview_terminal: bool,
+
+    /// Preview width in characters for --preview-terminal
+    #[arg(long, default_value = "80", requires = "preview_terminal")]
+    term_width: usize,
+
+    /// Preview height in lines for --preview-terminal
+    #[arg(long, default_value = "40", requires = "preview_terminal")]
+    term_height: usize,
+
     /// Draw site positions as dots on each frame
     #[arg(long)]
     show_sites: bool,
 
+    /// Label each site with its cell index on every frame
+    #[arg(long)]
+    show_site_labels: bool,
+
+    /// Label text height in pixels for --show-site-labels (0 = site pixels only)
+    #[arg(long, default_value = "10", requires = "show_site_labels")]
+    site_label_size: u32,
+
+    /// Blend N interpolated site layouts between consecutive frames (motion blur;
+    /// 1 = off)
+    #[arg(long, default_value = "1", value_name = "N", conflicts_with = "show_site_labels")]
+    motion_blur_samples: usize,
+
+    /// Render frames onto a tilted plane: "horizon=0.5,vanishing=0.5,scale_bottom=1.0,scale_top=0.1"
+    /// (horizon/vanishing as fractions of height/width; omitted keys take these defaults)
+    #[arg(long, value_name = "SPEC", conflicts_with_all = ["show_site_labels", "motion_blur_samples"])]
+    perspective_warp: Option<String>,
+
+    /// Color each cell by its index (mod --palette-size) instead of its average color,
+    /// to show which cells are new
+    #[arg(long, conflicts_with_all = ["show_site_labels", "motion_blur_samples", "perspective_warp"])]
+    debug_cell_colors: bool,
+
+    /// Number of distinct colors for --debug-cell-colors
+    #[arg(long, default_value = "12", requires = "debug_cell_colors")]
+    palette_size: usize,
+
+    /// Quantize each cell's color to N levels per channel (2-256; 2 = 8 colors)
+    #[arg(long, value_name = "N", conflicts_with_all = ["show_site_labels", "motion_blur_samples", "perspective_warp", "debug_cell_colors"])]
+    posterize: Option<u16>,
+
+    /// Pixelate each frame into NxN blocks, each the color of the cell covering
+    /// most of it
+    #[arg(long, value_name = "N", conflicts_with_all = ["show_site_labels", "motion_blur_samples", "perspective_warp", "debug_cell_colors", "posterize"])]
+    pixel_art_block_size: Option<u32>,
+
+    /// Histogram-equalize cell lightness across cells (keeping hue and saturation),
+    /// for more contrast when cells sample a narrow range of colors
+    #[arg(long, conflicts_with_all = ["show_site_labels", "motion_blur_samples", "perspective_warp", "debug_cell_colors", "posterize", "pixel_art_block_size"])]
+    equalize_colors: bool,
+
+    /// Blend each pixel between its nearest and second-nearest cells' colors by
+    /// relative distance, softening cell boundaries (CPU backend)
+    #[arg(long, conflicts_with_all = ["show_site_labels", "motion_blur_samples", "perspective_warp", "debug_cell_colors", "posterize", "pixel_art_block_size", "equalize_colors", "gpu", "multi_pass"])]
+    soft_boundary: bool,
+
+    /// Exponent on the --soft-boundary weights (higher = narrower blend at the edges)
+    #[arg(long, default_value = "2.0", requires = "soft_boundary")]
+    softness: f64,
+
+    /// Grow by cell division instead of the phase schedule: split any cell larger
+    /// than about --target-area pixels (see --bifurcation-sigma) each frame
+    #[arg(long)]
+    bifurcation: bool,
+
+    /// Cell area (pixels) above which --bifurcation splits cells
+    #[arg(long, default_value = "1000", requires = "bifurcation")]
+    target_area: u32,
+
+    /// Relative spread of the --bifurcation split threshold, staggering splits
+    #[arg(long, default_value = "0.2", requires = "bifurcation")]
+    bifurcation_sigma: f64,
+
     /// Centroid pull strength (0=disabled, ~1-10 steers sites toward cell centers)
     #[arg(long, default_value = "0.0")]
     centroid_pull: f64,
 
-    /// Growth strategy: max | weighted | isolated | centroid | farthest | poisson | poisson(k,lambda)
+    /// Each frame, nudge sites toward equal cell areas (neighbors of oversized cells
+    /// close in, crowded sites spread out); see --density-step
+    #[arg(long)]
+    density_equalization: bool,
+
+    /// Per-frame step for --density-equalization, in site spacings per unit of
+    /// relative area difference
+    #[arg(long, default_value = "1.0", requires = "density_equalization")]
+    density_step: f64,
+
+    /// Tournament selection: each second, replace RATE × (site count) sites that
+    /// lose a --tournament-k tournament (smallest cell) with offspring of the
+    /// largest cell, keeping the site count
+    #[arg(long, value_name = "RATE")]
+    death_rate: Option<f64>,
+
+    /// Sites drawn per tournament for --death-rate (higher = the smallest cells
+    /// die more reliably)
+    #[arg(long, default_value = "5", requires = "death_rate")]
+    tournament_k: usize,
+
+    /// After each physics step, average each site's heading with its N nearest
+    /// neighbors' (0=disabled; higher = smoother, flock-like motion)
+    #[arg(long, default_value = "0", value_name = "N")]
+    velocity_smoothing: usize,
+
+    /// Lloyd-relax sites at the start of every phase after the first
+    #[arg(long)]
+    redistribute_at_phase_boundary: bool,
+
+    /// How far redistribution moves sites toward their cell centroids (0-1; 1 = full Lloyd step)
+    #[arg(long, default_value = "1.0")]
+    redistribute_strength: f64,
+
+    /// Growth strategy: max | weighted | isolated | centroid | farthest | color-variance | bisect | kmeans | poisson | poisson(k,lambda) | spiral | spiral(arms,turns)
     #[arg(long, default_value = "max")]
     split_strategy: String,
 
+    /// Spiral arm count for the spiral split strategy (default 3)
+    #[arg(long)]
+    spiral_arms: Option<u32>,
+
+    /// Full turns of twist from center to rim for the spiral split strategy (default 5)
+    #[arg(long)]
+    spiral_turns: Option<f64>,
+
+    /// Speed up growth/shrinkage while cell areas are uneven: the doubling time is
+    /// divided by 1 + disparity_factor * (max_area / mean_area - 1)
+    #[arg(long)]
+    adaptive_growth: bool,
+
+    /// How strongly --adaptive-growth reacts to area disparity
+    #[arg(long, default_value = "2.0", requires = "adaptive_growth")]
+    disparity_factor: f64,
+
+    /// Add or remove at most N sites per frame; excess growth carries over (default: unlimited)
+    #[arg(long)]
+    max_splits_per_frame: Option<usize>,
+
+    /// While growing or shrinking, first remove sites whose cell was empty last frame
+    #[arg(long)]
+    remove_dead_sites: bool,
+
+    /// At the start of each shrink phase, merge sites closer than this many pixels
+    /// (the larger cell survives)
+    #[arg(long, value_name = "PX")]
+    coalesce_threshold: Option<f64>,
+
+    /// At the end of each phase, remove sites whose cell is smaller than
+    /// --area-threshold and flatter than --variance-threshold
+    #[arg(long)]
+    prune_uniform_sites: bool,
+
+    /// Cell area in pixels below which --prune-uniform-sites may remove a site
+    #[arg(long, default_value = "50", requires = "prune_uniform_sites")]
+    area_threshold: u32,
+
+    /// Mean per-channel color variance below which --prune-uniform-sites treats
+    /// a cell as flat
+    #[arg(long, default_value = "10.0", requires = "prune_uniform_sites")]
+    variance_threshold: f32,
+
+    /// Jitter every site by up to --perturb-magnitude pixels per axis just before
+    /// rendering frame N (0-based), e.g. to shake loose a relaxed layout
+    #[arg(long, value_name = "N")]
+    perturb_at_frame: Option<usize>,
+
+    /// Maximum per-axis displacement in pixels for --perturb-at-frame
+    #[arg(long, default_value = "5.0", value_name = "PX", requires = "perturb_at_frame")]
+    perturb_magnitude: f64,
+
+    /// Move a random --randomize-fraction of the sites to random positions, with
+    /// fresh headings, just before rendering frame N (0-based)
+    #[arg(long, value_name = "N")]
+    randomize_at_frame: Option<usize>,
+
+    /// Fraction of sites (0-1) re-randomized at --randomize-at-frame
+    #[arg(long, default_value = "0.1", requires = "randomize_at_frame")]
+    randomize_fraction: f64,
+
+    /// What sites do at the image edges: reflect | wrap | absorb | clamp
+    #[arg(long, default_value = "reflect")]
+    edge_behavior: String,
+
+    /// Grayscale image scaling site speed by position: black = full speed,
+    /// white = 10% (stretched over the frame)
+    #[arg(long, value_name = "PATH")]
+    speed_map: Option<PathBuf>,
+
+    /// How centroid pull turns headings: lerp (rotate by a fraction of the angle)
+    /// | slerp (spherical interpolation, clamped to the centroid direction)
+    #[arg(long, default_value = "lerp")]
+    pull_mode: String,
+
     /// Use legacy multi-pass compute (for benchmarking vs merged single-pass)
     #[arg(long)]
     multi_pass: bool,
@@ -355,11 +802,75 @@ struct Args {
     /// Log average site velocity (direction + magnitude) per frame to stderr
     #[arg(long)]
     log_velocity: bool,
+
+    /// Also save rendered frames as numbered PNGs (frame_00000.png, ...) in this directory
+    #[arg(long, conflicts_with = "multi_image")]
+    output_frames_dir: Option<PathBuf>,
+
+    /// With --output-frames-dir, save only every Nth frame
+    #[arg(long, default_value = "1", value_parser = clap::value_parser!(u64).range(1..))]
+    frames_step: u64,
+
+    /// Write a cell-area heatmap of the final frame to this PNG path
+    #[arg(long)]
+    output_heatmap: Option<PathBuf>,
+
+    /// Heatmap colormap: inferno | viridis | turbo | grayscale
+    #[arg(long, default_value = "viridis")]
+    heatmap_colormap: String,
+
+    /// Write the final frame re-rendered at --ss-scale× resolution to this PNG path
+    #[arg(long)]
+    output_supersampled: Option<PathBuf>,
+
+    /// Supersampling factor for --output-supersampled
+    #[arg(long, default_value = "2", value_parser = clap::value_parser!(u32).range(1..=16))]
+    ss_scale: u32,
+
+    /// Write the final frame's Delaunay dual (triangles filled with the mean of
+    /// their three cell colors) to this PNG path
+    #[arg(long)]
+    output_dual: Option<PathBuf>,
+
+    /// Write the final frame as mean-color dots on white (more dots in larger
+    /// cells) to this PNG path
+    #[arg(long)]
+    output_textured_stipple: Option<PathBuf>,
+
+    /// Maximum dots per cell for --output-textured-stipple (one per 100 px of area)
+    #[arg(long, default_value = "10")]
+    dots_per_cell: usize,
+
+    /// Write the final frame's sites' minimum spanning tree, as SVG line segments
+    /// over the frame's cells, to this path
+    #[arg(long)]
+    output_mst_svg: Option<PathBuf>,
+
+    /// Write the final frame's cells as SVG polygons filled with their mean colors
+    /// to this path
+    #[arg(long, alias = "svg")]
+    output_svg: Option<PathBuf>,
+
+    /// Write the final frame's cells as a GeoJSON FeatureCollection to this path
+    #[arg(long)]
+    output_geojson: Option<PathBuf>,
+
+    /// Map pixel coordinates onto "lon_min,lat_min,lon_max,lat_max" for --output-geojson
+    /// (top edge at lat_max); default is pixel coordinates
+    #[arg(long, requires = "output_geojson")]
+    geo_bbox: Option<String>,
 }
 
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
 
+    // Validate post-render output options up front, before a potentially long render
+    args.heatmap_colormap.parse::<HeatmapColormap>()
+        .map_err(|e: String| anyhow::anyhow!(e))?;
+    if let Some(ref bbox) = args.geo_bbox {
+        parse_geo_bbox(bbox)?;
+    }
+
     // Set up SIGINT handler
     let interrupted = Arc::new(AtomicBool::new(false));
     {
@@ -369,12 +880,21 @@ fn main() -> anyhow::Result<()> {
         }).expect("Failed to set Ctrl-C handler");
     }
 
+    if let Some(ref multi) = args.multi_image {
+        return run_multi_image(&args, &multi[0], Path::new(&multi[1]), &interrupted);
+    }
+    let input = args.input.as_ref().expect("clap requires --input without --multi-image");
+    render(&args, input, args.output.as_deref(), &interrupted)
+}
+
+/// Render one input image (single frame, benchmark, or animation per `args`)
+fn render(args: &Args, input: &Path, output: Option<&Path>, interrupted: &AtomicBool) -> anyhow::Result<()> {
     // Load spec file early (if provided) so we can use it for dimensions and phases
     let spec = args.spec.as_ref().map(load_spec).transpose()?;
 
     // Load input image
-    println!("Loading image: {:?}", args.input);
-    let mut image = image::open(&args.input)?.to_rgb8();
+    println!("Loading image: {:?}", input);
+    let mut image = image::open(input)?.to_rgb8();
     let (orig_w, orig_h) = image.dimensions();
 
     // Resolve target dimensions (CLI overrides spec)
@@ -391,13 +911,9 @@ fn main() -> anyhow::Result<()> {
 
     // Run benchmark mode if requested
     if args.benchmark {
-        return run_benchmark(&image, &args);
+        return run_benchmark(&image, args);
     }
 
-    // Require output path for normal rendering
-    let output = args.output.as_ref()
-        .ok_or_else(|| anyhow::anyhow!("Output path required (use -o/--output)"))?;
-
     // Create backend
     let make_cpu = || -> Box<dyn ComputeBackend> {
         if args.multi_pass {
@@ -405,15 +921,27 @@ fn main() -> anyhow::Result<()> {
             Box::new(CpuBackend::new_multi_pass())
         } else {
             println!("Using CPU backend (Rayon, merged)");
-            Box::new(CpuBackend::new())
+            Box::new(CpuBackend::with_second_nearest(args.soft_boundary))
         }
     };
 
     #[cfg(feature = "gpu")]
     let mut backend: Box<dyn ComputeBackend> = if args.gpu {
         println!("Using GPU backend (wgpu)");
-        match GpuBackend::new() {
-            Ok(gpu) => Box::new(gpu),
+        match GpuBackend::with_timestamps(args.gpu_timestamps) {
+            Ok(gpu) => {
+                if args.gpu_timestamps && !gpu.timestamps_enabled() {
+                    eprintln!("Warning: GPU adapter does not support timestamp queries");
+                }
+                // Switch to the CPU if the device is lost mid-render, retrying periodically
+                let timestamps = args.gpu_timestamps;
+                Box::new(FallbackBackend::with_primary(
+                    Ok(gpu),
+                    move || GpuBackend::with_timestamps(timestamps),
+                    GPU_FAILURE_THRESHOLD,
+                    GPU_RETRY_INTERVAL,
+                ))
+            }
             Err(e) => {
                 eprintln!("Warning: GPU initialization failed: {}. Falling back to CPU.", e);
                 make_cpu()
@@ -431,17 +959,75 @@ fn main() -> anyhow::Result<()> {
         make_cpu()
     };
 
+    if args.find_optimal_sites {
+        println!("Searching for the fewest sites reaching {} dB PSNR (max {})...", args.target_psnr, args.max_sites);
+        let count = SiteCollection::find_optimal_site_count(
+            backend.as_mut(), &image, args.target_psnr, args.seed, args.max_sites,
+        )?;
+        println!("Optimal site count: {}", count);
+        return Ok(());
+    }
+
+    // Require output path for normal rendering
+    let output = output
+        .ok_or_else(|| anyhow::anyhow!("Output path required (use -o/--output)"))?;
+
+    let start_sites = if args.auto_sites {
+        let count = SiteCollection::sites_for_perceptual_detail(&image, args.detail_threshold);
+        println!(
+            "Auto site count: {} (~{:.0} px per cell)",
+            count, SiteCollection::expected_cell_area(count, width as f64, height as f64),
+        );
+        count
+    } else {
+        args.sites_start
+    };
+
     // Single frame mode: render one frame and save as PNG
     if args.single_frame {
-        let sites = SiteCollection::random(args.sites_start, width as f64, height as f64, args.seed);
-        println!("Rendering single frame with {} sites (seed: {})", args.sites_start, args.seed);
+        let sites = init_sites(args, &image, start_sites, args.seed)?;
+        println!("Rendering single frame with {} sites (seed: {})", sites.len(), args.seed);
 
         let positions = sites.positions();
         let result = backend.compute(&image, &positions)?;
-        let frame_image = result.to_image();
+        if args.preview_terminal {
+            print_terminal_preview(&result, args);
+        }
+        let frame_image = if args.show_site_labels {
+            result.render_with_site_labels(&positions, args.site_label_size)
+        } else if args.debug_cell_colors {
+            result.render_cell_index_coloring(args.palette_size)
+        } else if let Some(ref spec) = args.perspective_warp {
+            let (horizon, vanishing, bottom, top) = parse_perspective_warp(spec)?;
+            result.render_perspective_warp(horizon, vanishing, bottom, top)
+        } else if let Some(levels) = args.posterize {
+            result.render_posterized(levels)
+        } else if let Some(block_size) = args.pixel_art_block_size {
+            result.render_pixel_art(block_size)
+        } else if args.equalize_colors {
+            result.render_equalized()
+        } else if args.soft_boundary {
+            result.render_soft_boundary(args.softness)
+        } else {
+            result.to_image()
+        };
         frame_image.save(output)?;
 
         println!("Output saved to: {:?}", output);
+        if let Some(ref path) = args.output_stats {
+            let mut stats = StatsWriter::create(path)?;
+            stats.write(&FrameStats {
+                frame: 0,
+                sites: sites.len(),
+                hull_coverage: sites.convex_hull_coverage(width as f64, height as f64),
+                velocity_coherence: sites.velocity_coherence(args.coherence_radius),
+                cell_consistency: None,
+                color_consistency: None,
+                centroid_pull: args.centroid_pull,
+            })?;
+            stats.finish()?;
+        }
+        write_extra_outputs(&result, &positions, &sites, args)?;
         return Ok(());
     }
 
@@ -449,13 +1035,21 @@ fn main() -> anyhow::Result<()> {
     // Parse split strategy (CLI overrides spec)
     let cli_split_strategy: SplitStrategy = args.split_strategy.parse()
         .map_err(|e: String| anyhow::anyhow!(e))?;
+    let edge_behavior: EdgeBehavior = args.edge_behavior.parse()
+        .map_err(|e: String| anyhow::anyhow!(e))?;
+    let pull_mode: PullMode = args.pull_mode.parse()
+        .map_err(|e: String| anyhow::anyhow!(e))?;
+    let speed_map = args.speed_map.as_ref()
+        .map(|path| image::open(path).with_context(|| format!("failed to open speed map: {:?}", path)))
+        .transpose()?
+        .map(|map| map.to_luma8());
 
     let (sites_start, fps, speed, seed, show_sites, centroid_pull, split_strategy, phases) = if let Some(ref spec) = spec {
         let mut phases = Vec::new();
         let mut current = spec.start;
         for ps in &spec.phases {
             let phase = ps.to_phase(current)?;
-            if let Some(target) = phase.target_sites {
+            if let Some(target) = phase.target_sites.or(phase.start_sites) {
                 current = target;
             }
             phases.push(phase);
@@ -474,29 +1068,43 @@ fn main() -> anyhow::Result<()> {
         (spec.start, spec.fps, spec.speed, spec.seed, show, pull, strategy, phases)
     } else if !args.phase.is_empty() {
         let mut phases = Vec::new();
-        let mut current = args.sites_start;
+        let mut current = start_sites;
         for spec in &args.phase {
             let phase = parse_phase(spec, current)?;
-            if let Some(target) = phase.target_sites {
+            if let Some(target) = phase.target_sites.or(phase.start_sites) {
                 current = target;
             }
             phases.push(phase);
         }
-        (args.sites_start, args.fps, args.speed, args.seed, args.show_sites, args.centroid_pull, cli_split_strategy, phases)
+        (start_sites, args.fps, args.speed, args.seed, args.show_sites, args.centroid_pull, cli_split_strategy, phases)
     } else {
         // Legacy single-phase mode
         let phases = vec![Phase {
             target_sites: Some(args.sites_end),
             doubling_time: args.doubling_time,
             duration: args.duration,
-            fade: false,
+            ..Phase::default()
         }];
-        (args.sites_start, args.fps, args.speed, args.seed, args.show_sites, args.centroid_pull, cli_split_strategy, phases)
+        (start_sites, args.fps, args.speed, args.seed, args.show_sites, args.centroid_pull, cli_split_strategy, phases)
+    };
+    // --spiral-arms / --spiral-turns override the spiral's parameters, wherever it came from
+    let split_strategy = match split_strategy {
+        SplitStrategy::Spiral { arm_count, turns } => SplitStrategy::Spiral {
+            arm_count: args.spiral_arms.unwrap_or(arm_count),
+            turns: args.spiral_turns.unwrap_or(turns),
+        },
+        s => s,
     };
 
+    let perspective = args.perspective_warp.as_deref().map(parse_perspective_warp).transpose()?;
+
     // Initialize sites with seeded RNG for reproducibility
-    let mut sites = SiteCollection::random(sites_start, width as f64, height as f64, seed);
+    let mut sites = init_sites(args, &image, sites_start, seed)?;
+    sites.pull_mode = pull_mode;
     println!("Using seed: {}", seed);
+    if args.preview_terminal && !sites.is_empty() {
+        print_terminal_preview(&backend.compute(&image, &sites.positions())?, args);
+    }
 
     let total_duration: f64 = phases.iter().map(|p| p.duration).sum();
     let total_frames: usize = phases.iter()
@@ -513,6 +1121,13 @@ fn main() -> anyhow::Result<()> {
     // Print phase summary
     let mut phase_start_sites = sites_start;
     for (i, phase) in phases.iter().enumerate() {
+        if let Some(start) = phase.start_sites {
+            println!("  Phase {}: reset {} -> {} sites", i + 1, phase_start_sites, start);
+            phase_start_sites = start;
+        }
+        if let Some(strength) = redistribution(phase, i, args) {
+            println!("  Phase {}: redistribute sites (Lloyd strength {})", i + 1, strength);
+        }
         if phase.fade {
             println!(
                 "  Phase {}: fade to image over {:.1}s ({} sites)",
@@ -542,21 +1157,75 @@ fn main() -> anyhow::Result<()> {
     let progress = ProgressBar::new(total_frames as u64);
     progress.set_style(
         ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")?
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}")?
             .progress_chars("#>-"),
     );
 
     // Spawn encoder process, pipe raw frames into it
-    let mut encoder = spawn_encoder(output, &args.format, width, height, fps)?;
+    let gif_config = GifEncoderConfig {
+        quality: args.gif_quality,
+        dither: args.gif_dither,
+        transparent: args.gif_transparent,
+    };
+    let mut encoder = spawn_encoder(output, &args.format, width, height, fps, gif_config)?;
+    if let Some(ref dir) = args.output_frames_dir {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create frames directory: {:?}", dir))?;
+    }
     let mut frames_rendered: usize = 0;
     let render_start = Instant::now();
 
     // Per-frame timing data: (frame_index, site_count, ms)
-    let mut frame_timings: Vec<(usize, usize, f64)> = Vec::with_capacity(total_frames);
+    // (frame, sites, wall ms, GPU ms if timestamps are enabled)
+    let mut frame_timings: Vec<(usize, usize, f64, Option<f64>)> = Vec::with_capacity(total_frames);
+    let mut stats = args.output_stats.as_deref().map(StatsWriter::create).transpose()?;
+
+    // Final frame's result and the site positions it was computed from
+    let mut last_result: Option<(VoronoiResult, Vec<Position>)> = None;
+
+    // Centroid pull in effect, carried across phases once a phase ramps it
+    let mut current_pull = centroid_pull;
 
     // Render frames, piping each directly into the encoder
-    'render: for phase in &phases {
+    'render: for (phase_idx, phase) in phases.iter().enumerate() {
         let phase_frames = (phase.duration * fps as f64).round() as usize;
+        let pull_start = current_pull;
+
+        // Jump straight to the phase's start count: a near-zero doubling time makes
+        // a single adjust_count call add/remove every site at once
+        if let Some(start) = phase.start_sites.filter(|&n| n != sites.len() && phase_frames > 0) {
+            let positions = sites.positions();
+            let result = backend.compute(&image, &positions)?;
+            let bisections = (split_strategy == SplitStrategy::BisectLargest)
+                .then(|| result.bisect_centroids(&positions));
+            let variances = (split_strategy == SplitStrategy::ColorVariance)
+                .then(|| result.cell_color_variance(&image));
+            // Poisson gating defers spawns; spawn at centroids like it would, but all at once
+            let strategy = match split_strategy {
+                SplitStrategy::Poisson(_, _) => SplitStrategy::Centroid,
+                s => s,
+            };
+            sites.adjust_count(start, 1e-9, dt, strategy, AdjustParams {
+                cell_areas: Some(&result.cell_areas),
+                centroids: Some(&result.cell_centroids),
+                farthest_point: Some(result.farthest_point),
+                img_area: (width * height) as f64,
+                bisections: bisections.as_deref(),
+                cell_variances: variances.as_deref(),
+                max_per_frame: usize::MAX,
+                auto_remove_dead: args.remove_dead_sites,
+            });
+        }
+        // Spread sites out before the phase starts (Lloyd step from the current layout)
+        if let Some(strength) = redistribution(phase, phase_idx, args).filter(|_| phase_frames > 0) {
+            let result = backend.compute(&image, &sites.positions())?;
+            sites.apply_lloyd(&result.cell_centroids, strength);
+        }
+        let shrinking = !phase.fade && phase.target_sites.is_some_and(|t| t < sites.len());
+        if let Some(threshold) = args.coalesce_threshold.filter(|_| shrinking && phase_frames > 0) {
+            let result = backend.compute(&image, &sites.positions())?;
+            sites.coalesce_clusters(threshold, Some(&result.cell_areas));
+        }
         let target = phase.target_sites.unwrap_or(sites.len());
 
         // Track phase start state for continuous exponential targeting
@@ -574,22 +1243,67 @@ fn main() -> anyhow::Result<()> {
                 break 'render;
             }
 
+            if sites.is_empty() {
+                progress.abandon_with_message("No sites left");
+                eprintln!("All sites absorbed at the image edges after {} frames", frames_rendered);
+                break 'render;
+            }
+
+            if args.perturb_at_frame == Some(frames_rendered) {
+                sites.perturb_positions(args.perturb_magnitude, 0, width as f64, height as f64);
+            }
+            if args.randomize_at_frame == Some(frames_rendered) {
+                sites.randomize_subset(args.randomize_fraction, width as f64, height as f64);
+            }
+
             let frame_start = Instant::now();
             let n_sites = sites.len();
+            let pull = phase_pull(pull_start, phase.centroid_pull_end, frame_in_phase, phase_frames);
 
             // Compute Voronoi (before step, so we have centroids for steering)
             let positions = sites.positions();
             let result = backend.compute(&image, &positions)?;
+            if let Some(ref mut stats) = stats {
+                stats.write(&FrameStats {
+                    frame: frames_rendered,
+                    sites: n_sites,
+                    hull_coverage: sites.convex_hull_coverage(width as f64, height as f64),
+                    velocity_coherence: sites.velocity_coherence(args.coherence_radius),
+                    cell_consistency: last_result.as_ref().map(|(prev, _)| result.temporal_consistency(prev)),
+                    color_consistency: last_result.as_ref().map(|(prev, _)| result.temporal_color_consistency(prev)),
+                    centroid_pull: pull,
+                })?;
+            }
+
+            if args.density_equalization {
+                sites.density_equalization_step(&result.cell_areas, width as f64, height as f64, args.density_step);
+            }
+            if let Some(death_rate) = args.death_rate {
+                sites.tournament_step(&result.cell_areas, &result.cell_centroids, args.tournament_k, death_rate, dt);
+            }
 
             // Step physics (with centroid pull if enabled)
-            sites.step(
-                speed, dt, width as f64, height as f64,
-                Some(&result.cell_centroids), centroid_pull,
-                3.0, 3.0,
-            );
+            let absorbed = match speed_map {
+                Some(ref map) => sites.step_with_speed_map(
+                    map, speed, dt, width as f64, height as f64,
+                    Some(&result.cell_centroids), pull,
+                    3.0, 3.0, edge_behavior,
+                ),
+                None => sites.step_with_edge_behavior(
+                    speed, dt, width as f64, height as f64,
+                    Some(&result.cell_centroids), pull,
+                    3.0, 3.0, edge_behavior,
+                ),
+            };
+            sites.smooth_velocities(args.velocity_smoothing);
+            // Sites absorbed at the edges no longer have cells
+            let cell_areas = surviving_cells(&result.cell_areas, &absorbed);
+            let cell_centroids = surviving_cells(&result.cell_centroids, &absorbed);
 
             // Gradually adjust site count (skip if hold or already at target)
-            if target != sites.len() {
+            if args.bifurcation {
+                sites.bifurcation_step(&cell_centroids, &cell_areas, args.target_area, args.bifurcation_sigma);
+            } else if target != sites.len() {
                 // Compute where the continuous exponential says we should be,
                 // and inject any shortfall into fractional_sites so we catch up.
                 let elapsed = (frame_in_phase + 1) as f64 * dt;
@@ -604,20 +1318,42 @@ fn main() -> anyhow::Result<()> {
                     sites.fractional_sites += shortfall;
                 }
 
-                sites.adjust_count(
-                    target,
-                    phase.doubling_time,
-                    dt,
-                    Some(&result.cell_areas),
-                    split_strategy,
-                    Some(&result.cell_centroids),
-                    Some(result.farthest_point),
-                    (width * height) as f64,
-                );
+                let bisections = (split_strategy == SplitStrategy::BisectLargest)
+                    .then(|| surviving_cells(&result.bisect_centroids(&positions), &absorbed).into_owned());
+                let variances = (split_strategy == SplitStrategy::ColorVariance)
+                    .then(|| surviving_cells(&result.cell_color_variance(&image), &absorbed).into_owned());
+                // A disparity factor of 0 keeps the phase's doubling time
+                let disparity_factor = if args.adaptive_growth { args.disparity_factor } else { 0.0 };
+                sites.adjust_count_adaptive(target, phase.doubling_time, disparity_factor, dt, split_strategy, AdjustParams {
+                    cell_areas: Some(&cell_areas),
+                    centroids: Some(&cell_centroids),
+                    farthest_point: Some(result.farthest_point),
+                    img_area: (width * height) as f64,
+                    bisections: bisections.as_deref(),
+                    cell_variances: variances.as_deref(),
+                    max_per_frame: args.max_splits_per_frame.unwrap_or(usize::MAX),
+                    auto_remove_dead: args.remove_dead_sites,
+                });
             }
 
-            // Render frame, optionally with site markers and fade blending
-            let mut frame_image = result.to_image();
+            // Render in the first style requested, then overlay sites and fade
+            let mut frame_image = match (&last_result, perspective) {
+                _ if args.show_site_labels => result.render_with_site_labels(&positions, args.site_label_size),
+                _ if args.debug_cell_colors => result.render_cell_index_coloring(args.palette_size),
+                (_, Some((horizon, vanishing, bottom, top))) => {
+                    result.render_perspective_warp(horizon, vanishing, bottom, top)
+                }
+                (Some((prev, prev_positions)), _) if args.motion_blur_samples > 1 => {
+                    result.render_motion_blur(prev, args.motion_blur_samples, prev_positions, &positions)
+                }
+                _ if args.equalize_colors => result.render_equalized(),
+                _ if args.soft_boundary => result.render_soft_boundary(args.softness),
+                _ => match (args.posterize, args.pixel_art_block_size) {
+                    (Some(levels), _) => result.render_posterized(levels),
+                    (None, Some(block_size)) => result.render_pixel_art(block_size),
+                    (None, None) => result.to_image(),
+                },
+            };
             if show_sites {
                 draw_sites(&mut frame_image, &positions);
             }
@@ -626,8 +1362,19 @@ fn main() -> anyhow::Result<()> {
                 blend_with_source(&mut frame_image, &image, blend_t);
             }
             encoder.write_frame(frame_image.as_raw())?;
+            if let FrameEncoder::PngSequence { ref dir, frame_idx, .. } = encoder {
+                let last = frame_path(dir, frame_idx - 1);
+                progress.set_message(last.file_name().unwrap_or_default().to_string_lossy().into_owned());
+            }
+            if let Some(ref dir) = args.output_frames_dir {
+                if frames_rendered.is_multiple_of(args.frames_step as usize) {
+                    let path = frame_path(dir, frames_rendered);
+                    frame_image.save(&path)
+                        .with_context(|| format!("failed to write frame: {:?}", path))?;
+                }
+            }
 
-            if args.log_velocity && frames_rendered % 30 == 0 {
+            if args.log_velocity && frames_rendered.is_multiple_of(30) {
                 let (vx, vy) = sites.avg_velocity();
                 let mag = (vx * vx + vy * vy).sqrt();
                 let angle_deg = vy.atan2(vx).to_degrees();
@@ -638,11 +1385,21 @@ fn main() -> anyhow::Result<()> {
             }
 
             let frame_ms = frame_start.elapsed().as_secs_f64() * 1000.0;
-            frame_timings.push((frames_rendered, n_sites, frame_ms));
+            let gpu_ms = backend.last_gpu_time_ns().map(|ns| ns as f64 / 1e6);
+            frame_timings.push((frames_rendered, n_sites, frame_ms, gpu_ms));
             frames_rendered += 1;
+            last_result = Some((result, positions));
 
             progress.inc(1);
         }
+        if args.prune_uniform_sites && phase_frames > 0 && !sites.is_empty() {
+            let result = backend.compute(&image, &sites.positions())?;
+            let variance = result.cell_color_variance(&image);
+            sites.merge_isolated_sites(&result.cell_areas, &variance, args.area_threshold, args.variance_threshold);
+        }
+        if let Some(end) = phase.centroid_pull_end {
+            current_pull = end;
+        }
     }
 
     if frames_rendered == 0 {
@@ -656,6 +1413,13 @@ fn main() -> anyhow::Result<()> {
 
     // Close stdin to signal EOF, wait for encoder to finish
     let status_msg = encoder.finish()?;
+    if let Some(ref dir) = args.output_frames_dir {
+        println!("Frames saved to: {:?}", dir);
+    }
+    if let Some(stats) = stats {
+        stats.finish()?;
+        println!("Stats saved to: {:?}", args.output_stats.as_ref().unwrap());
+    }
 
     let total_wall = render_start.elapsed();
     let avg_fps = frames_rendered as f64 / total_wall.as_secs_f64();
@@ -663,7 +1427,7 @@ fn main() -> anyhow::Result<()> {
     let partial = if interrupted.load(Ordering::Relaxed) { "partial" } else { "complete" };
     let final_sites = sites.len();
     let final_target = phases.iter().rev()
-        .find_map(|p| p.target_sites)
+        .find_map(|p| p.target_sites.or(p.start_sites))
         .unwrap_or(final_sites);
     println!(
         "Output saved to: {:?} ({} frames, {}{})",
@@ -682,28 +1446,348 @@ fn main() -> anyhow::Result<()> {
         total_wall.as_secs_f64(), avg_fps,
     );
 
+    if let Some((ref result, ref positions)) = last_result {
+        write_extra_outputs(result, positions, &sites, args)?;
+    }
+
     // Print timing summary by site-count buckets
     if !frame_timings.is_empty() {
+        let show_gpu = frame_timings.iter().any(|t| t.3.is_some());
         println!("\nFrame timing by site count:");
-        println!("{:>8} {:>8} {:>8} {:>8} {:>6}", "sites", "frames", "avg_ms", "max_ms", "fps");
+        print!("{:>8} {:>8} {:>8} {:>8} {:>6}", "sites", "frames", "avg_ms", "max_ms", "fps");
+        if show_gpu {
+            print!(" {:>8}", "gpu_ms");
+        }
+        println!();
 
-        // Bucket by powers of 2
-        let mut buckets: std::collections::BTreeMap<usize, Vec<f64>> = std::collections::BTreeMap::new();
-        for &(_, n_sites, ms) in &frame_timings {
+        // Bucket by powers of 2: (wall ms, GPU ms)
+        let mut buckets: std::collections::BTreeMap<usize, Vec<(f64, Option<f64>)>> = std::collections::BTreeMap::new();
+        for &(_, n_sites, ms, gpu_ms) in &frame_timings {
             let bucket = if n_sites == 0 { 0 } else { 1 << ((n_sites as f64).log2().floor() as u32) };
-            buckets.entry(bucket).or_default().push(ms);
+            buckets.entry(bucket).or_default().push((ms, gpu_ms));
         }
         for (bucket, times) in &buckets {
             let count = times.len();
-            let avg = times.iter().sum::<f64>() / count as f64;
-            let max = times.iter().cloned().fold(0.0f64, f64::max);
+            let avg = times.iter().map(|t| t.0).sum::<f64>() / count as f64;
+            let max = times.iter().map(|t| t.0).fold(0.0f64, f64::max);
             let fps = 1000.0 / avg;
-            println!("{:>8} {:>8} {:>8.1} {:>8.1} {:>6.1}", bucket, count, avg, max, fps);
+            print!("{:>8} {:>8} {:>8.1} {:>8.1} {:>6.1}", bucket, count, avg, max, fps);
+            if show_gpu {
+                let gpu: Vec<f64> = times.iter().filter_map(|t| t.1).collect();
+                if gpu.is_empty() {
+                    print!(" {:>8}", "-");
+                } else {
+                    print!(" {:>8.2}", gpu.iter().sum::<f64>() / gpu.len() as f64);
+                }
+            }
+            println!();
+        }
+    }
+    Ok(())
+}
+
+/// Batch mode: render every image matching `pattern` into `output_dir`, with
+/// `args.jobs` images in flight at once (capped at the Rayon thread count)
+fn run_multi_image(args: &Args, pattern: &str, output_dir: &Path, interrupted: &AtomicBool) -> anyhow::Result<()> {
+    let inputs: Vec<PathBuf> = glob::glob(pattern)
+        .with_context(|| format!("invalid glob pattern: {:?}", pattern))?
+        .filter_map(|entry| entry.ok())
+        .filter(|path| path.is_file())
+        .collect();
+    if inputs.is_empty() {
+        anyhow::bail!("no images match {:?}", pattern);
+    }
+    std::fs::create_dir_all(output_dir)
+        .with_context(|| format!("failed to create output directory: {:?}", output_dir))?;
+
+    let ext = match args.format {
+        OutputFormat::Mp4 => Some("mp4"),
+        OutputFormat::Gif => Some("gif"),
+        // One frames directory per image
+        OutputFormat::PngSequence => None,
+    };
+    let jobs = args.jobs.clamp(1, rayon::current_num_threads());
+    let total = inputs.len();
+    println!("Rendering {} images into {:?} ({} at a time)", total, output_dir, jobs);
+
+    let batch_start = Instant::now();
+    let completed = std::sync::atomic::AtomicUsize::new(0);
+    let render_one = |input: &PathBuf| -> Option<(PathBuf, anyhow::Error)> {
+        if interrupted.load(Ordering::Relaxed) {
+            return None;
         }
+        let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+        let output = match ext {
+            Some(ext) => output_dir.join(format!("{}.{}", stem, ext)),
+            None => output_dir.join(&*stem),
+        };
+        let image_start = Instant::now();
+        let result = render(&per_input_args(args, output_dir, &stem), input, Some(&output), interrupted);
+
+        // Remaining-time estimate from the average wall time per finished image,
+        // divided across the parallel jobs
+        let done = completed.fetch_add(1, Ordering::SeqCst) + 1;
+        let per_image = batch_start.elapsed().as_secs_f64() * jobs as f64 / done as f64;
+        let remaining = per_image * (total - done) as f64 / jobs as f64;
+        println!(
+            "[{}/{}] {:?} {} in {:.1}s (~{:.0}s remaining)",
+            done, total, input,
+            if result.is_ok() { "done" } else { "FAILED" },
+            image_start.elapsed().as_secs_f64(), remaining,
+        );
+        result.err().map(|e| (input.clone(), e))
+    };
+
+    // Plain threads, not a Rayon pool: each render's own parallel work should
+    // spread over the global pool instead of being confined to its job
+    let next = std::sync::atomic::AtomicUsize::new(0);
+    let failures = std::sync::Mutex::new(vec![]);
+    std::thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| {
+                while let Some(input) = inputs.get(next.fetch_add(1, Ordering::SeqCst)) {
+                    if let Some(failure) = render_one(input) {
+                        failures.lock().unwrap().push(failure);
+                    }
+                }
+            });
+        }
+    });
+    let failures = failures.into_inner().unwrap();
+
+    println!(
+        "\nBatch finished: {} of {} images in {:.1}s",
+        total - failures.len(), total, batch_start.elapsed().as_secs_f64(),
+    );
+    if !failures.is_empty() {
+        for (input, err) in &failures {
+            eprintln!("  {:?}: {:#}", input, err);
+        }
+        anyhow::bail!("{} of {} images failed", failures.len(), total);
     }
     Ok(())
 }
 
+/// `args` for one --multi-image input: each extra output path becomes
+/// `{stem}_{file name}` in `output_dir`, so the batch's images don't overwrite
+/// each other's
+fn per_input_args(args: &Args, output_dir: &Path, stem: &str) -> Args {
+    let mut args = args.clone();
+    for path in [
+        &mut args.export_sites,
+        &mut args.output_stats,
+        &mut args.output_heatmap,
+        &mut args.output_supersampled,
+        &mut args.output_dual,
+        &mut args.output_textured_stipple,
+        &mut args.output_mst_svg,
+        &mut args.output_svg,
+        &mut args.output_geojson,
+    ].into_iter().flatten() {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        *path = output_dir.join(format!("{}_{}", stem, name));
+    }
+    args
+}
+
+/// Create the initial site collection from `--import-sites`, or else the chosen placement strategy
+fn init_sites(args: &Args, image: &image::RgbImage, count: usize, seed: u64) -> anyhow::Result<SiteCollection> {
+    if let Some(ref path) = args.resume_sites {
+        let sites = SiteCollection::load(path)
+            .with_context(|| format!("failed to resume sites from {:?}", path))?;
+        println!("Resumed {} sites from {:?}", sites.len(), path);
+        return Ok(sites);
+    }
+    if let Some(ref path) = args.import_sites {
+        let data = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read sites CSV: {:?}", path))?;
+        let sites = SiteCollection::import_csv(&data, seed)
+            .with_context(|| format!("failed to parse sites CSV: {:?}", path))?;
+        println!("Imported {} sites from {:?}", sites.len(), path);
+        return Ok(sites);
+    }
+    #[cfg(feature = "video-init")]
+    if let (Some(path_a), Some(path_b)) = (&args.init_video_frame_a, &args.init_video_frame_b) {
+        // Match the (possibly resized) render dimensions so sites land in image space
+        let load = |path: &PathBuf| -> anyhow::Result<image::RgbImage> {
+            let frame = image::open(path)
+                .with_context(|| format!("failed to open video frame: {:?}", path))?
+                .to_rgb8();
+            Ok(if frame.dimensions() == image.dimensions() {
+                frame
+            } else {
+                image::imageops::resize(&frame, image.width(), image.height(), image::imageops::FilterType::Lanczos3)
+            })
+        };
+        let sites = SiteCollection::from_video_frames(&load(path_a)?, &load(path_b)?, count, seed);
+        println!("Initialized {} sites from motion between {:?} and {:?}", sites.len(), path_a, path_b);
+        return Ok(sites);
+    }
+    #[cfg(feature = "svg-init")]
+    if let Some(ref path_d) = args.init_svg_path {
+        let sites = SiteCollection::from_svg_path(path_d, count, seed)
+            .context("invalid --init-svg-path")?;
+        println!("Initialized {} sites along SVG path", sites.len());
+        return Ok(sites);
+    }
+    let mut builder = SiteCollection::builder()
+        .count(count)
+        .seed(seed)
+        .init_strategy(args.init_strategy)
+        .walk_step(args.walk_step)
+        .fractal_iters(args.fractal_iters)
+        .polygon(args.polygon_sides, args.polygon_radius)
+        .lissajous(args.lj_a, args.lj_b, args.lj_delta)
+        .scatter_iters(args.scatter_iters)
+        .hybrid(args.corner_frac, args.edge_frac, args.edge_threshold);
+    match args.init_strategy {
+        InitStrategy::Rings => builder = builder.ring_specs(parse_ring_specs(&args.ring_specs)?),
+        InitStrategy::Stipple => {
+            let path = args.stipple_image.as_ref()
+                .context("--init-strategy stipple requires --stipple-image")?;
+            let stipple = image::open(path)
+                .with_context(|| format!("failed to open stipple image: {:?}", path))?
+                .to_luma8();
+            let stipple = if stipple.dimensions() == image.dimensions() {
+                stipple
+            } else {
+                image::imageops::resize(&stipple, image.width(), image.height(), image::imageops::FilterType::Triangle)
+            };
+            builder = builder.stipple(stipple, args.dot_threshold);
+        }
+        _ => {}
+    }
+    let sites = builder.build(Some(image))
+        .with_context(|| format!("invalid parameters for --init-strategy {}", args.init_strategy))?;
+    if let (InitStrategy::Stipple, Some(path)) = (args.init_strategy, &args.stipple_image) {
+        println!("Found {} stipple dots in {:?}", sites.len(), path);
+    }
+    Ok(sites)
+}
+
+/// Print `result` as ASCII art per --term-width / --term-height, in color on a terminal
+fn print_terminal_preview(result: &VoronoiResult, args: &Args) {
+    let colored = std::io::stdout().is_terminal();
+    print!("{}", result.render_ascii_art(args.term_width, args.term_height, colored));
+}
+
+/// Write optional per-result outputs (heatmap, site CSV, etc.) requested via CLI flags.
+/// `positions` are the sites `result` was computed from; `sites` is the current state.
+fn write_extra_outputs(
+    result: &VoronoiResult,
+    positions: &[Position],
+    sites: &SiteCollection,
+    args: &Args,
+) -> anyhow::Result<()> {
+    if let Some(ref path) = args.output_heatmap {
+        let colormap: HeatmapColormap = args.heatmap_colormap.parse()
+            .map_err(|e: String| anyhow::anyhow!(e))?;
+        result.render_heatmap(colormap).save(path)
+            .with_context(|| format!("failed to write heatmap: {:?}", path))?;
+        println!("Heatmap ({}) saved to: {:?}", colormap, path);
+    }
+    if let Some(ref path) = args.output_supersampled {
+        result.supersample(positions, args.ss_scale).save(path)
+            .with_context(|| format!("failed to write supersampled image: {:?}", path))?;
+        println!("Supersampled ({}x) saved to: {:?}", args.ss_scale, path);
+    }
+    if let Some(ref path) = args.output_dual {
+        let triangles = result.delaunay_triangulation();
+        result.render_dual(positions, &triangles).save(path)
+            .with_context(|| format!("failed to write dual image: {:?}", path))?;
+        println!("Delaunay dual ({} triangles) saved to: {:?}", triangles.len(), path);
+    }
+    if let Some(ref path) = args.output_textured_stipple {
+        result.render_voronoi_stipple_textured(args.dots_per_cell, args.seed).save(path)
+            .with_context(|| format!("failed to write stipple image: {:?}", path))?;
+        println!("Textured stipple saved to: {:?}", path);
+    }
+    if let Some(ref path) = args.output_mst_svg {
+        let edges = SiteCollection::random_from_positions(positions.to_vec(), 0).min_spanning_tree();
+        std::fs::write(path, result.edges_svg(positions, &edges))
+            .with_context(|| format!("failed to write MST SVG: {:?}", path))?;
+        println!("MST ({} edges) saved to: {:?}", edges.len(), path);
+    }
+    if let Some(ref path) = args.output_svg {
+        std::fs::write(path, result.to_svg(positions))
+            .with_context(|| format!("failed to write SVG: {:?}", path))?;
+        println!("SVG saved to: {:?}", path);
+    }
+    if let Some(ref path) = args.output_geojson {
+        let (w, h) = (result.width as f64, result.height as f64);
+        let geojson = match args.geo_bbox {
+            Some(ref bbox) => {
+                let [lon_min, lat_min, lon_max, lat_max] = parse_geo_bbox(bbox)?;
+                let to_lonlat = |x: f64, y: f64| (
+                    lon_min + x / w * (lon_max - lon_min),
+                    lat_max - y / h * (lat_max - lat_min),
+                );
+                result.to_geojson(positions, Some(&to_lonlat))
+            }
+            None => result.to_geojson(positions, None),
+        };
+        std::fs::write(path, geojson)
+            .with_context(|| format!("failed to write GeoJSON: {:?}", path))?;
+        println!("GeoJSON saved to: {:?}", path);
+    }
+    if let Some(ref path) = args.export_sites {
+        std::fs::write(path, sites.export_csv())
+            .with_context(|| format!("failed to write sites CSV: {:?}", path))?;
+        println!("Sites ({}) saved to: {:?}", sites.len(), path);
+    }
+    Ok(())
+}
+
+/// Parse a "lon_min,lat_min,lon_max,lat_max" bounding box
+fn parse_geo_bbox(s: &str) -> anyhow::Result<[f64; 4]> {
+    let values: Vec<f64> = s.split(',')
+        .map(|v| v.trim().parse::<f64>())
+        .collect::<Result<_, _>>()
+        .with_context(|| format!("invalid --geo-bbox {:?}", s))?;
+    let bbox: [f64; 4] = values.try_into()
+        .map_err(|_| anyhow::anyhow!("--geo-bbox needs 4 values: lon_min,lat_min,lon_max,lat_max"))?;
+    if bbox[0] >= bbox[2] || bbox[1] >= bbox[3] {
+        anyhow::bail!("--geo-bbox min must be less than max: {:?}", s);
+    }
+    Ok(bbox)
+}
+
+/// Parse --ring-specs: comma-separated `count:radius` pairs
+fn parse_ring_specs(s: &str) -> anyhow::Result<Vec<(usize, f64)>> {
+    s.split(',').map(str::trim).filter(|p| !p.is_empty())
+        .map(|part| {
+            let (count, radius) = part.split_once(':')
+                .ok_or_else(|| anyhow::anyhow!("expected count:radius in --ring-specs, got {:?}", part))?;
+            Ok((
+                count.trim().parse().with_context(|| format!("invalid --ring-specs count {:?}", part))?,
+                radius.trim().parse().with_context(|| format!("invalid --ring-specs radius {:?}", part))?,
+            ))
+        })
+        .collect()
+}
+
+/// `--perspective-warp` parameters: (horizon_y, vanishing_x, scale_bottom, scale_top)
+fn parse_perspective_warp(s: &str) -> anyhow::Result<(f64, f64, f64, f64)> {
+    let (mut horizon, mut vanishing, mut scale_bottom, mut scale_top) = (0.5, 0.5, 1.0, 0.1);
+    for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (key, value) = part.split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("expected key=value in --perspective-warp, got {:?}", part))?;
+        let value: f64 = value.trim().parse()
+            .with_context(|| format!("invalid --perspective-warp value {:?}", part))?;
+        match key.trim() {
+            "horizon" => horizon = value,
+            "vanishing" => vanishing = value,
+            "scale_bottom" => scale_bottom = value,
+            "scale_top" => scale_top = value,
+            k => anyhow::bail!("unknown --perspective-warp key {:?} (expected horizon, vanishing, scale_bottom, or scale_top)", k),
+        }
+    }
+    if scale_bottom <= 0.0 {
+        anyhow::bail!("--perspective-warp scale_bottom must be positive");
+    }
+    Ok((horizon, vanishing, scale_bottom, scale_top))
+}
+
 /// Draw 3x3 black dots at each site position
 fn draw_sites(image: &mut image::RgbImage, sites: &[Position]) {
     let (w, h) = (image.width() as i32, image.height() as i32);
@@ -750,7 +1834,7 @@ fn run_benchmark(image: &image::RgbImage, args: &Args) -> anyhow::Result<()> {
 
     // Benchmark CPU
     println!("Benchmarking CPU (Rayon)...");
-    let cpu_time = benchmark_backend(&mut CpuBackend::new(), image, &positions, num_frames)?;
+    let (cpu_time, _) = benchmark_backend(&mut CpuBackend::new(), image, &positions, num_frames)?;
     let cpu_fps = num_frames as f64 / cpu_time.as_secs_f64();
     println!(
         "  CPU: {:?} total, {:.2} fps, {:.2} ms/frame",
@@ -763,9 +1847,12 @@ fn run_benchmark(image: &image::RgbImage, args: &Args) -> anyhow::Result<()> {
     #[cfg(feature = "gpu")]
     {
         println!("Benchmarking GPU (wgpu)...");
-        match GpuBackend::new() {
+        match GpuBackend::with_timestamps(args.gpu_timestamps) {
             Ok(mut gpu) => {
-                let gpu_time = benchmark_backend(&mut gpu, image, &positions, num_frames)?;
+                if args.gpu_timestamps && !gpu.timestamps_enabled() {
+                    eprintln!("  GPU adapter does not support timestamp queries");
+                }
+                let (gpu_time, gpu_exec) = benchmark_backend(&mut gpu, image, &positions, num_frames)?;
                 let gpu_fps = num_frames as f64 / gpu_time.as_secs_f64();
                 println!(
                     "  GPU: {:?} total, {:.2} fps, {:.2} ms/frame",
@@ -773,6 +1860,12 @@ fn run_benchmark(image: &image::RgbImage, args: &Args) -> anyhow::Result<()> {
                     gpu_fps,
                     gpu_time.as_secs_f64() * 1000.0 / num_frames as f64
                 );
+                if let Some(exec) = gpu_exec {
+                    println!(
+                        "       gpu_ms {:.3}/frame (GPU execution, excludes CPU sync and readback)",
+                        exec.as_secs_f64() * 1000.0 / num_frames as f64
+                    );
+                }
 
                 // Summary
                 println!();
@@ -798,35 +1891,49 @@ fn run_benchmark(image: &image::RgbImage, args: &Args) -> anyhow::Result<()> {
     Ok(())
 }
 
-/// Benchmark a single backend
+/// Benchmark a single backend. Returns total wall time, plus total GPU execution
+/// time if the backend records timestamp queries.
 fn benchmark_backend(
     backend: &mut dyn ComputeBackend,
     image: &image::RgbImage,
     positions: &[Position],
     num_frames: usize,
-) -> anyhow::Result<Duration> {
+) -> anyhow::Result<(Duration, Option<Duration>)> {
     // Warmup frame (GPU needs to compile shaders, etc.)
     let _ = backend.compute(image, positions)?;
 
     // Timed frames
     let start = Instant::now();
+    let mut gpu_ns: Option<u64> = None;
     for _ in 0..num_frames {
         let _ = backend.compute(image, positions)?;
+        if let Some(ns) = backend.last_gpu_time_ns() {
+            *gpu_ns.get_or_insert(0) += ns;
+        }
     }
-    Ok(start.elapsed())
+    Ok((start.elapsed(), gpu_ns.map(Duration::from_nanos)))
 }
 
-/// Streaming frame encoder — pipes raw RGB data directly into ffmpeg or GIF encoder.
-/// No temp files, no frame accumulation in memory.
+/// Path of frame `idx` (0-based) in a PNG sequence directory, for both
+/// `--format png-sequence` and `--output-frames-dir`. Zero-padded so names sort
+/// in order.
+fn frame_path(dir: &Path, idx: usize) -> PathBuf {
+    dir.join(format!("frame_{:05}.png", idx))
+}
+
+/// Streaming frame encoder — pipes raw RGB data directly into ffmpeg or GIF encoder,
+/// or writes each frame as its own PNG. No frame accumulation in memory.
 enum FrameEncoder {
     Mp4 {
         child: std::process::Child,
     },
-    Gif {
-        encoder: gif::Encoder<std::fs::File>,
-        width: u16,
-        height: u16,
-        frame_delay: u16,
+    Gif(GifEncoder),
+    /// Frames written as numbered PNGs in `dir`; `frame_idx` counts those written
+    PngSequence {
+        dir: PathBuf,
+        frame_idx: usize,
+        width: u32,
+        height: u32,
     },
 }
 
@@ -841,48 +1948,18 @@ impl FrameEncoder {
                 stdin.write_all(rgb_data)
                     .context("failed to write frame to ffmpeg")?;
             }
-            FrameEncoder::Gif { encoder, width, height, frame_delay } => {
-                let w = *width as u32;
-                let h = *height as u32;
-                let delay = *frame_delay;
-
-                // Convert RGB to indexed color (simple quantization)
-                let mut pixels: Vec<u8> = Vec::with_capacity((w * h) as usize);
-                let mut palette: Vec<[u8; 3]> = Vec::new();
-
-                for chunk in rgb_data.chunks_exact(3) {
-                    let rgb = [chunk[0], chunk[1], chunk[2]];
-                    let idx = palette.iter().position(|&c| c == rgb).unwrap_or_else(|| {
-                        if palette.len() < 256 {
-                            palette.push(rgb);
-                            palette.len() - 1
-                        } else {
-                            palette
-                                .iter()
-                                .enumerate()
-                                .min_by_key(|(_, c)| {
-                                    let dr = c[0] as i32 - rgb[0] as i32;
-                                    let dg = c[1] as i32 - rgb[1] as i32;
-                                    let db = c[2] as i32 - rgb[2] as i32;
-                                    dr * dr + dg * dg + db * db
-                                })
-                                .map(|(i, _)| i)
-                                .unwrap_or(0)
-                        }
-                    });
-                    pixels.push(idx as u8);
-                }
-
-                while palette.len() < 256 {
-                    palette.push([0, 0, 0]);
-                }
-                let flat_palette: Vec<u8> = palette.iter().flat_map(|c| c.iter().copied()).collect();
-
-                let mut frame = gif::Frame::from_palette_pixels(
-                    w as u16, h as u16, pixels, flat_palette, None,
-                );
-                frame.delay = delay;
-                encoder.write_frame(&frame)?;
+            FrameEncoder::Gif(encoder) => encoder.write_frame(rgb_data)?,
+            FrameEncoder::PngSequence { dir, frame_idx, width, height } => {
+                let frame = image::RgbImage::from_raw(*width, *height, rgb_data.to_vec())
+                    .ok_or_else(|| anyhow::anyhow!("frame buffer size mismatch"))?;
+                // Write under a temporary name, then rename, so no partial PNG is ever visible
+                let path = frame_path(dir, *frame_idx);
+                *frame_idx += 1;
+                let tmp = path.with_extension("png.tmp");
+                frame.save_with_format(&tmp, image::ImageFormat::Png)
+                    .with_context(|| format!("failed to write frame: {:?}", tmp))?;
+                std::fs::rename(&tmp, &path)
+                    .with_context(|| format!("failed to write frame: {:?}", path))?;
             }
         }
         Ok(())
@@ -900,10 +1977,11 @@ impl FrameEncoder {
                 }
                 Ok(String::new())
             }
-            FrameEncoder::Gif { .. } => {
-                // GIF encoder flushes on drop
+            FrameEncoder::Gif(encoder) => {
+                encoder.finish()?;
                 Ok(String::new())
             }
+            FrameEncoder::PngSequence { .. } => Ok(String::new()),
         }
     }
 }
@@ -915,6 +1993,7 @@ fn spawn_encoder(
     width: u32,
     height: u32,
     fps: u32,
+    gif_config: GifEncoderConfig,
 ) -> anyhow::Result<FrameEncoder> {
     match format {
         OutputFormat::Mp4 => {
@@ -941,12 +2020,13 @@ fn spawn_encoder(
             Ok(FrameEncoder::Mp4 { child })
         }
         OutputFormat::Gif => {
-            use gif::{Encoder, Repeat};
-            let file = std::fs::File::create(output)?;
-            let mut encoder = Encoder::new(file, width as u16, height as u16, &[])?;
-            encoder.set_repeat(Repeat::Infinite)?;
-            let frame_delay = (100 / fps).max(1) as u16;
-            Ok(FrameEncoder::Gif { encoder, width: width as u16, height: height as u16, frame_delay })
+            let encoder = GifEncoder::spawn(output, width, height, fps, gif_config)?;
+            Ok(FrameEncoder::Gif(encoder))
+        }
+        OutputFormat::PngSequence => {
+            std::fs::create_dir_all(output)
+                .with_context(|| format!("failed to create output directory: {:?}", output))?;
+            Ok(FrameEncoder::PngSequence { dir: output.to_path_buf(), frame_idx: 0, width, height })
         }
     }
 }
@@ -0,0 +1,56 @@
+//! Per-frame diagnostics CSV (`--output-stats`).
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use anyhow::Context;
+
+/// CSV column header, matching `FrameStats` field order
+const HEADER: &str = "frame,sites,hull_coverage,velocity_coherence,cell_consistency,color_consistency,centroid_pull";
+
+/// Diagnostics for one rendered frame
+#[derive(Debug, Clone, Copy)]
+pub struct FrameStats {
+    pub frame: usize,
+    pub sites: usize,
+    /// `SiteCollection::convex_hull_coverage` of the frame's sites
+    pub hull_coverage: f64,
+    /// `SiteCollection::velocity_coherence` of the frame's sites (at `--coherence-radius`)
+    pub velocity_coherence: f64,
+    /// `VoronoiResult::temporal_consistency` against the previous frame (empty for the first)
+    pub cell_consistency: Option<f64>,
+    /// `VoronoiResult::temporal_color_consistency` against the previous frame
+    pub color_consistency: Option<f64>,
+    /// Centroid pull applied when stepping this frame
+    pub centroid_pull: f64,
+}
+
+/// Streams one CSV row per frame, so partial renders still leave usable stats
+pub struct StatsWriter {
+    out: BufWriter<File>,
+}
+
+impl StatsWriter {
+    pub fn create(path: &Path) -> anyhow::Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("failed to create stats CSV: {:?}", path))?;
+        let mut out = BufWriter::new(file);
+        writeln!(out, "{}", HEADER)?;
+        Ok(Self { out })
+    }
+
+    pub fn write(&mut self, stats: &FrameStats) -> anyhow::Result<()> {
+        let optional = |v: Option<f64>| v.map(|v| format!("{:.6}", v)).unwrap_or_default();
+        writeln!(
+            self.out, "{},{},{:.6},{:.6},{},{},{}",
+            stats.frame, stats.sites, stats.hull_coverage, stats.velocity_coherence,
+            optional(stats.cell_consistency), optional(stats.color_consistency), stats.centroid_pull,
+        )?;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> anyhow::Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
@@ -66,6 +66,7 @@ fn assert_images_equal(expected: &image::RgbImage, actual: &image::RgbImage, nam
 }
 
 /// Find all differing pixels between two images
+#[cfg(feature = "gpu")]
 fn find_differing_pixels(img1: &image::RgbImage, img2: &image::RgbImage) -> Vec<(u32, u32)> {
     assert_eq!(img1.dimensions(), img2.dimensions(), "dimensions must match");
 
@@ -223,6 +224,27 @@ mod gpu {
         GpuBackend::new().ok()
     }
 
+    #[test]
+    fn test_gpu_timestamps() {
+        let image = image::RgbImage::from_pixel(256, 256, image::Rgb([40, 80, 120]));
+        let positions = SiteCollection::random(100, 256.0, 256.0, 0).positions();
+
+        let Some(mut plain) = get_gpu_backend() else {
+            eprintln!("GPU not available, skipping test");
+            return;
+        };
+        plain.compute(&image, &positions).expect("Compute failed");
+        assert_eq!(plain.last_gpu_time_ns(), None);
+
+        let mut timed = GpuBackend::with_timestamps(true).expect("GPU init failed");
+        if !timed.timestamps_enabled() {
+            eprintln!("GPU timestamp queries not supported, skipping test");
+            return;
+        }
+        timed.compute(&image, &positions).expect("Compute failed");
+        assert!(timed.last_gpu_time_ns().is_some_and(|t| t > 0));
+    }
+
     /// Most GPU tests produce bitwise identical output to CPU.
     #[test]
     fn test_gpu_vs_cpu_exact_match() {
@@ -0,0 +1,52 @@
+//! End-to-end test for `--edge-behavior absorb` alongside site-count growth.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Fresh scratch directory under the system temp dir
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("voronoi-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Growth right after sites are absorbed splits from the surviving sites' cells
+#[test]
+fn test_absorb_while_growing() {
+    let dir = scratch_dir("absorb-growth");
+    let input = dir.join("input.png");
+    image::RgbImage::from_fn(64, 48, |x, y| image::Rgb([(x * 4) as u8, (y * 5) as u8, 90]))
+        .save(&input).unwrap();
+
+    // Fast sites leave the small image every frame while the count grows 30 -> 120
+    let runs: [&[&str]; 5] = [
+        &["--split-strategy", "max", "--remove-dead-sites"],
+        &["--split-strategy", "bisect"],
+        &["--split-strategy", "color-variance"],
+        &["--split-strategy", "centroid", "--adaptive-growth"],
+        &["--bifurcation", "--target-area", "20"],
+    ];
+    for (run, extra) in runs.iter().enumerate() {
+        let stats_csv = dir.join(format!("stats-{}.csv", run));
+        let output = Command::new(env!("CARGO_BIN_EXE_voronoi"))
+            .arg("-i").arg(&input)
+            .arg("-o").arg(dir.join("out.gif"))
+            .args(["--format", "gif", "--fps", "10", "--speed", "200", "--edge-behavior", "absorb"])
+            .args(["--sites-start", "30", "-p", "n=120,t=1"])
+            .args(*extra)
+            .arg("--output-stats").arg(&stats_csv)
+            .output()
+            .expect("failed to run voronoi");
+        assert!(output.status.success(), "{:?}: {}", extra, String::from_utf8_lossy(&output.stderr));
+
+        let csv = std::fs::read_to_string(&stats_csv).unwrap();
+        let mut lines = csv.lines();
+        let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+        let col = header.iter().position(|&c| c == "sites").unwrap();
+        let counts: Vec<usize> = lines.map(|l| l.split(',').nth(col).unwrap().parse().unwrap()).collect();
+        assert!(!counts.is_empty(), "{:?}", extra);
+        assert!(counts.iter().all(|&n| n <= 120), "{:?}: {:?}", extra, counts);
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,78 @@
+//! End-to-end test for `--output-frames-dir` PNG sequences alongside video output.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+fn ffmpeg_available() -> bool {
+    Command::new("ffmpeg").arg("-version").output().is_ok_and(|o| o.status.success())
+}
+
+/// Fresh scratch directory under the system temp dir
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("voronoi-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Sorted file names in `dir`
+fn list(dir: &PathBuf) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir).unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_output_frames_dir_with_video() {
+    let dir = scratch_dir("frames-dir");
+    let input = dir.join("input.png");
+    image::RgbImage::from_fn(48, 32, |x, y| image::Rgb([(x * 5) as u8, (y * 7) as u8, 40]))
+        .save(&input).unwrap();
+
+    // 5 frames: 1s hold at 5 fps
+    let spec = dir.join("spec.yaml");
+    std::fs::write(&spec, "start: 8\nfps: 5\nphases:\n  - t: 1\n").unwrap();
+
+    // MP4 needs ffmpeg; fall back to GIF so the frame dump is still exercised
+    let ext = if ffmpeg_available() { "mp4" } else {
+        eprintln!("ffmpeg not available, checking GIF output instead");
+        "gif"
+    };
+
+    for (step, expected) in [
+        (1, vec![0, 1, 2, 3, 4]),
+        (2, vec![0, 2, 4]),
+    ] {
+        let frames_dir = dir.join(format!("frames-{}", step));
+        let output = dir.join(format!("out-{}.{}", step, ext));
+        let status = Command::new(env!("CARGO_BIN_EXE_voronoi"))
+            .arg("-i").arg(&input)
+            .arg("-o").arg(&output)
+            .args(["--format", ext, "--spec", spec.to_str().unwrap()])
+            .arg("--output-frames-dir").arg(&frames_dir)
+            .args(["--frames-step", &step.to_string()])
+            .status()
+            .expect("failed to run voronoi");
+        assert!(status.success());
+
+        assert!(std::fs::metadata(&output).unwrap().len() > 0, "empty {:?}", output);
+        let expected: Vec<String> = expected.iter().map(|i| format!("frame_{:05}.png", i)).collect();
+        assert_eq!(list(&frames_dir), expected);
+        let frame = image::open(frames_dir.join(&expected[0])).unwrap();
+        assert_eq!((frame.width(), frame.height()), (48, 32));
+    }
+
+    // --format png-sequence numbers its frames the same way
+    let sequence_dir = dir.join("sequence");
+    let status = Command::new(env!("CARGO_BIN_EXE_voronoi"))
+        .arg("-i").arg(&input)
+        .arg("-o").arg(&sequence_dir)
+        .args(["--format", "png-sequence", "--spec", spec.to_str().unwrap()])
+        .status()
+        .expect("failed to run voronoi");
+    assert!(status.success());
+    assert_eq!(list(&sequence_dir), list(&dir.join("frames-1")));
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,91 @@
+//! End-to-end test for `--multi-image` batch rendering.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+fn ffmpeg_available() -> bool {
+    Command::new("ffmpeg").arg("-version").output().is_ok_and(|o| o.status.success())
+}
+
+/// Fresh scratch directory under the system temp dir
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("voronoi-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_multi_image_writes_one_output_per_input() {
+    let dir = scratch_dir("multi-image");
+    let inputs_dir = dir.join("inputs");
+    let output_dir = dir.join("out");
+    std::fs::create_dir_all(&inputs_dir).unwrap();
+
+    let stems = ["red", "green", "blue"];
+    for (i, stem) in stems.iter().enumerate() {
+        let image = image::RgbImage::from_fn(48, 32, |x, y| {
+            let mut px = [(x * 5) as u8, (y * 7) as u8, 40];
+            px[i] = 255;
+            image::Rgb(px)
+        });
+        image.save(inputs_dir.join(format!("{}.png", stem))).unwrap();
+    }
+
+    // 5 frames: 1s hold at 5 fps
+    let spec = dir.join("spec.yaml");
+    std::fs::write(&spec, "start: 8\nfps: 5\nphases:\n  - t: 1\n").unwrap();
+
+    // MP4 needs ffmpeg; fall back to GIF so the batch plumbing is still exercised
+    let ext = if ffmpeg_available() { "mp4" } else {
+        eprintln!("ffmpeg not available, checking GIF outputs instead");
+        "gif"
+    };
+
+    let status = Command::new(env!("CARGO_BIN_EXE_voronoi"))
+        .arg("--multi-image")
+        .arg(inputs_dir.join("*.png"))
+        .arg(&output_dir)
+        .args(["--spec", spec.to_str().unwrap(), "--format", ext, "--jobs", "2"])
+        .status()
+        .expect("failed to run voronoi");
+    assert!(status.success());
+
+    for stem in stems {
+        let output = output_dir.join(format!("{}.{}", stem, ext));
+        assert!(output.is_file(), "missing {:?}", output);
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_multi_image_extra_outputs_per_input() {
+    let dir = scratch_dir("multi-image-extras");
+    let inputs_dir = dir.join("inputs");
+    let output_dir = dir.join("out");
+    std::fs::create_dir_all(&inputs_dir).unwrap();
+
+    let stems = ["a", "b", "c"];
+    for (i, stem) in stems.iter().enumerate() {
+        image::RgbImage::from_fn(48, 32, |x, y| image::Rgb([(x * 5) as u8, (y * 7) as u8, (i * 80) as u8]))
+            .save(inputs_dir.join(format!("{}.png", stem))).unwrap();
+    }
+
+    let status = Command::new(env!("CARGO_BIN_EXE_voronoi"))
+        .arg("--multi-image")
+        .arg(inputs_dir.join("*.png"))
+        .arg(&output_dir)
+        .args(["-p", "t=0.4", "--fps", "5", "--format", "gif", "--jobs", "3"])
+        .args(["--output-heatmap", "heat.png", "--export-sites", "sites.csv", "--output-stats", "stats.csv"])
+        .status()
+        .expect("failed to run voronoi");
+    assert!(status.success());
+
+    for stem in stems {
+        for name in ["heat.png", "sites.csv", "stats.csv"] {
+            let output = output_dir.join(format!("{}_{}", stem, name));
+            assert!(output.is_file(), "missing {:?}", output);
+        }
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,120 @@
+//! End-to-end tests for animation phase specs.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Fresh scratch directory under the system temp dir
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("voronoi-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_start_sites_resets_count() {
+    let dir = scratch_dir("start-sites");
+    let input = dir.join("input.png");
+    image::RgbImage::from_fn(64, 48, |x, y| image::Rgb([(x * 4) as u8, (y * 5) as u8, 90]))
+        .save(&input).unwrap();
+
+    // Phase 1 grows 8 -> 40; phase 2 is a single frame that must start at exactly 5.
+    // Holding for that one frame means the exported final state is phase 2's first frame.
+    for phase1_end in [40, 12] {
+        let spec = dir.join("spec.yaml");
+        std::fs::write(&spec, format!(
+            "start: 8\nfps: 5\nphases:\n  - n: {}\n    t: 1\n  - start_sites: 5\n    t: 0.2\n",
+            phase1_end,
+        )).unwrap();
+        let sites_csv = dir.join("sites.csv");
+
+        let output = Command::new(env!("CARGO_BIN_EXE_voronoi"))
+            .arg("-i").arg(&input)
+            .arg("-o").arg(dir.join("out.gif"))
+            .args(["--format", "gif", "--spec", spec.to_str().unwrap()])
+            .arg("--export-sites").arg(&sites_csv)
+            .output()
+            .expect("failed to run voronoi");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+        let csv = std::fs::read_to_string(&sites_csv).unwrap();
+        assert_eq!(csv.lines().count() - 1, 5, "after phase 1 ending at {} sites", phase1_end);
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_centroid_pull_end_ramps_over_phase() {
+    let dir = scratch_dir("pull-end");
+    let input = dir.join("input.png");
+    image::RgbImage::from_fn(64, 48, |x, y| image::Rgb([(x * 4) as u8, (y * 5) as u8, 90]))
+        .save(&input).unwrap();
+
+    // Phase 1 ramps 0.2 -> 1.0 over 5 frames; phase 2 holds at the ramp's end value
+    let spec = dir.join("spec.yaml");
+    std::fs::write(
+        &spec,
+        "start: 10\nfps: 5\ncentroid_pull: 0.2\nphases:\n  - t: 1\n    centroid_pull_end: 1.0\n  - t: 0.4\n",
+    ).unwrap();
+    let stats_csv = dir.join("stats.csv");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_voronoi"))
+        .arg("-i").arg(&input)
+        .arg("-o").arg(dir.join("out.gif"))
+        .args(["--format", "gif", "--spec", spec.to_str().unwrap()])
+        .arg("--output-stats").arg(&stats_csv)
+        .output()
+        .expect("failed to run voronoi");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let csv = std::fs::read_to_string(&stats_csv).unwrap();
+    let mut lines = csv.lines();
+    let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+    let col = header.iter().position(|&c| c == "centroid_pull").unwrap();
+    let pulls: Vec<f64> = lines.map(|l| l.split(',').nth(col).unwrap().parse().unwrap()).collect();
+    assert_eq!(pulls.len(), 7);
+    assert_eq!(pulls[0], 0.2);
+    assert!((pulls[2] - 0.6).abs() < 1e-12);
+    assert_eq!(pulls[4], 1.0);
+    assert_eq!(&pulls[5..], [1.0, 1.0]);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_redistribute_evens_cell_areas() {
+    use voronoi_core::{ComputeBackend, CpuBackend, SiteCollection};
+
+    let dir = scratch_dir("redistribute");
+    let input = dir.join("input.png");
+    let image = image::RgbImage::from_fn(64, 48, |x, y| image::Rgb([(x * 4) as u8, (y * 5) as u8, 90]));
+    image.save(&input).unwrap();
+
+    // Sites stay put (speed 0, no pull), so only redistribution moves them
+    let area_ratio = |redistribute: bool| {
+        let spec = dir.join("spec.yaml");
+        std::fs::write(&spec, format!(
+            "start: 30\nfps: 5\nspeed: 0\nphases:\n  - t: 0.2\n  - t: 0.2\n    redistribute: {}\n",
+            redistribute,
+        )).unwrap();
+        let sites_csv = dir.join("sites.csv");
+        let output = Command::new(env!("CARGO_BIN_EXE_voronoi"))
+            .arg("-i").arg(&input)
+            .arg("-o").arg(dir.join("out.gif"))
+            .args(["--format", "gif", "--spec", spec.to_str().unwrap()])
+            .arg("--export-sites").arg(&sites_csv)
+            .output()
+            .expect("failed to run voronoi");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+        let sites = SiteCollection::import_csv(&std::fs::read_to_string(&sites_csv).unwrap(), 0).unwrap();
+        let result = CpuBackend::new().compute(&image, &sites.positions()).unwrap();
+        let max = *result.cell_areas.iter().max().unwrap() as f64;
+        let min = *result.cell_areas.iter().min().unwrap() as f64;
+        max / min.max(1.0)
+    };
+
+    let held = area_ratio(false);
+    let redistributed = area_ratio(true);
+    assert!(redistributed < held, "area ratio {} -> {}", held, redistributed);
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,38 @@
+//! Compare grid vs k-d tree nearest-site search throughput.
+//!
+//! Run with: cargo run --release -p voronoi-core --example kdtree_bench
+
+use std::time::Instant;
+use voronoi_core::{ComputeBackend, CpuBackend, SiteCollection};
+
+const WIDTH: u32 = 1920;
+const HEIGHT: u32 = 1080;
+const FRAMES: u32 = 5;
+
+fn main() {
+    let image = image::RgbImage::from_fn(WIDTH, HEIGHT, |x, y| image::Rgb([x as u8, y as u8, (x ^ y) as u8]));
+    let megapixels = (WIDTH * HEIGHT) as f64 * FRAMES as f64 / 1e6;
+
+    println!("{:>8}  {:>10}  {:>10}", "sites", "grid Mpx/s", "tree Mpx/s");
+    for count in [10_000, 50_000, 100_000] {
+        let mut sites = SiteCollection::random(count, WIDTH as f64, HEIGHT as f64, 42);
+        let frames: Vec<_> = (0..FRAMES)
+            .map(|_| {
+                let positions = sites.positions();
+                sites.step(15.0, 1.0 / 30.0, WIDTH as f64, HEIGHT as f64, None, 0.0, 3.0, 3.0);
+                positions
+            })
+            .collect();
+
+        let throughput = |mut backend: CpuBackend| {
+            let start = Instant::now();
+            for positions in &frames {
+                backend.compute(&image, positions).unwrap();
+            }
+            megapixels / start.elapsed().as_secs_f64()
+        };
+        let grid = throughput(CpuBackend::new());
+        let tree = throughput(CpuBackend::with_kdtree_threshold(0));
+        println!("{:>8}  {:>10.1}  {:>10.1}", count, grid, tree);
+    }
+}
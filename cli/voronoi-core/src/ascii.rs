@@ -0,0 +1,96 @@
+//! Text rendering of a diagram for terminal previews.
+
+use std::fmt::Write;
+use crate::VoronoiResult;
+use crate::voronoi::majority_cell;
+
+/// Characters for cell indices (wrapping after 62 cells)
+const SYMBOLS: &[u8; 62] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+impl VoronoiResult {
+    /// Render as `term_height` lines of `term_width` characters, each ending in
+    /// `\n`. Each character covers a block of pixels and shows the cell owning
+    /// most of them (lowest index on ties) as `SYMBOLS[cell % 62]`, or a space
+    /// for masked pixels. With `colored`, each character is wrapped in an ANSI
+    /// 24-bit foreground color escape for its cell's mean color.
+    pub fn render_ascii_art(&self, term_width: usize, term_height: usize, colored: bool) -> String {
+        let (w, h) = (self.width as usize, self.height as usize);
+        // Pixel range a character covers along one axis; at least one pixel when
+        // there are more characters than pixels
+        let span = |i: usize, chars: usize, pixels: usize| -> (usize, usize) {
+            let start = i * pixels / chars;
+            let end = ((i + 1) * pixels / chars).max(start + 1).min(pixels);
+            (start.min(end), end)
+        };
+        let mut out = String::with_capacity(term_height * (term_width + 1));
+        let mut block = Vec::new();
+        for row in 0..term_height {
+            let (y0, y1) = span(row, term_height, h);
+            for col in 0..term_width {
+                let (x0, x1) = span(col, term_width, w);
+                block.clear();
+                for y in y0..y1 {
+                    block.extend_from_slice(&self.cell_of[y * w + x0..y * w + x1]);
+                }
+                let Ok(cell) = usize::try_from(majority_cell(&mut block)) else {
+                    out.push(' ');
+                    continue;
+                };
+                let symbol = SYMBOLS[cell % SYMBOLS.len()] as char;
+                match self.cell_colors.get(cell).filter(|_| colored) {
+                    Some([r, g, b]) => {
+                        let _ = write!(out, "\x1b[38;2;{};{};{}m{}", r, g, b, symbol);
+                    }
+                    None => out.push(symbol),
+                }
+            }
+            if colored {
+                out.push_str("\x1b[0m");
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Position, VoronoiResult};
+
+    /// 8x4 pixels: cell 0 on the left half, cell 1 on the right, one masked pixel
+    fn halves() -> VoronoiResult {
+        let mut cell_of: Vec<i32> = (0..32).map(|i| if i % 8 < 4 { 0 } else { 1 }).collect();
+        cell_of[0] = -1;
+        VoronoiResult {
+            cell_of,
+            cell_colors: vec![[255, 0, 0], [0, 0, 255]],
+            cell_areas: vec![15, 16],
+            cell_centroids: vec![],
+            farthest_point: Position::new(0.0, 0.0),
+            pixel_distances: None,
+            second_cell_of: None,
+            second_distances: None,
+            width: 8,
+            height: 4,
+        }
+    }
+
+    #[test]
+    fn test_render_ascii_art_dimensions() {
+        let result = halves();
+        for (tw, th) in [(4, 2), (3, 3), (20, 7)] {
+            let art = result.render_ascii_art(tw, th, false);
+            assert_eq!(art.matches('\n').count(), th);
+            assert!(art.lines().all(|line| line.chars().count() == tw), "{:?}", art);
+        }
+        assert_eq!(result.render_ascii_art(2, 1, false), "AB\n");
+        // Each character covers exactly one pixel
+        assert!(result.render_ascii_art(8, 4, false).starts_with(" AAABBBB\nAAAABBBB\n"));
+    }
+
+    #[test]
+    fn test_render_ascii_art_colored() {
+        let art = halves().render_ascii_art(2, 1, true);
+        assert_eq!(art, "\x1b[38;2;255;0;0mA\x1b[38;2;0;0;255mB\x1b[0m\n");
+    }
+}
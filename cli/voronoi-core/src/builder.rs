@@ -0,0 +1,343 @@
+//! Builder for `SiteCollection` with initialization strategy and dynamics options.
+
+use std::fmt;
+use crate::{SiteCollection, VoronoiError};
+
+/// How `SiteCollectionBuilder::build` places the initial sites (and, with the
+/// `clap` feature, the CLI's `--init-strategy` values). Strategy parameters are
+/// set on the builder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum InitStrategy {
+    /// Uniformly random positions
+    Random,
+    /// Strongest Harris corners of the input image
+    Corners,
+    /// Spatial centers of dominant-color regions (k-means on color + position)
+    Clusters,
+    /// Steps of a random walk from the image center
+    Walk,
+    /// Weighted by Mandelbrot escape time, dense along the set's boundary
+    Mandelbrot,
+    /// Vertices of a regular polygon centered in the image (ignores the site count)
+    Polygon,
+    /// Concentric rings centered in the image, heading clockwise (ignores the
+    /// site count)
+    Rings,
+    /// Evenly along a Lissajous curve spanning 80% of the image, heading along the
+    /// curve
+    Lissajous,
+    /// Random positions spread evenly by a few rounds of mutual repulsion, close
+    /// to a Poisson-disk pattern
+    Scatter,
+    /// Centroids of the dark dots of a stipple image, largest first, up to the
+    /// site count
+    Stipple,
+    /// Harris corners, then random strong-gradient (edge) pixels, then random
+    /// positions for the rest
+    Hybrid,
+}
+
+impl InitStrategy {
+    /// Whether `build` needs the source image for this strategy
+    pub fn needs_image(&self) -> bool {
+        matches!(self, InitStrategy::Corners | InitStrategy::Clusters | InitStrategy::Hybrid)
+    }
+}
+
+impl fmt::Display for InitStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            InitStrategy::Random => "random",
+            InitStrategy::Corners => "corners",
+            InitStrategy::Clusters => "clusters",
+            InitStrategy::Walk => "walk",
+            InitStrategy::Mandelbrot => "mandelbrot",
+            InitStrategy::Polygon => "polygon",
+            InitStrategy::Rings => "rings",
+            InitStrategy::Lissajous => "lissajous",
+            InitStrategy::Scatter => "scatter",
+            InitStrategy::Stipple => "stipple",
+            InitStrategy::Hybrid => "hybrid",
+        })
+    }
+}
+
+impl std::str::FromStr for InitStrategy {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "random" => Ok(InitStrategy::Random),
+            "corners" => Ok(InitStrategy::Corners),
+            "clusters" => Ok(InitStrategy::Clusters),
+            "walk" => Ok(InitStrategy::Walk),
+            "mandelbrot" => Ok(InitStrategy::Mandelbrot),
+            "polygon" => Ok(InitStrategy::Polygon),
+            "rings" => Ok(InitStrategy::Rings),
+            "lissajous" => Ok(InitStrategy::Lissajous),
+            "scatter" => Ok(InitStrategy::Scatter),
+            "stipple" => Ok(InitStrategy::Stipple),
+            "hybrid" => Ok(InitStrategy::Hybrid),
+            _ => Err(format!(
+                "unknown init strategy: '{}' (expected random, corners, clusters, walk, mandelbrot, \
+                 polygon, rings, lissajous, scatter, stipple, or hybrid)", s
+            )),
+        }
+    }
+}
+
+/// Configures and creates a `SiteCollection` in one expression; see
+/// `SiteCollection::builder`.
+#[derive(Debug, Clone)]
+pub struct SiteCollectionBuilder {
+    count: usize,
+    dimensions: Option<(f64, f64)>,
+    seed: u64,
+    init_strategy: InitStrategy,
+    history_capacity: usize,
+    ou_params: Option<(f64, f64)>,
+    walk_step: f64,
+    fractal_iters: u32,
+    polygon_sides: usize,
+    polygon_radius: Option<f64>,
+    ring_specs: Vec<(usize, f64)>,
+    lissajous: (f64, f64, f64),
+    scatter_iters: usize,
+    stipple: Option<(image::GrayImage, u8)>,
+    hybrid: (f64, f64, f32),
+}
+
+impl Default for SiteCollectionBuilder {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            dimensions: None,
+            seed: 0,
+            init_strategy: InitStrategy::Random,
+            history_capacity: 0,
+            ou_params: None,
+            walk_step: 20.0,
+            fractal_iters: 100,
+            polygon_sides: 6,
+            polygon_radius: None,
+            ring_specs: vec![(8, 100.0), (16, 200.0), (32, 300.0)],
+            lissajous: (3.0, 2.0, std::f64::consts::FRAC_PI_2),
+            scatter_iters: 20,
+            stipple: None,
+            hybrid: (0.2, 0.5, 100.0),
+        }
+    }
+}
+
+impl SiteCollectionBuilder {
+    /// Number of sites to create (default 0)
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Area to place sites in (default: the image passed to `build`)
+    pub fn dimensions(mut self, width: f64, height: f64) -> Self {
+        self.dimensions = Some((width, height));
+        self
+    }
+
+    /// RNG seed for placement and later dynamics (default 0)
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Initial placement (default `InitStrategy::Random`)
+    pub fn init_strategy(mut self, strategy: InitStrategy) -> Self {
+        self.init_strategy = strategy;
+        self
+    }
+
+    /// Undo history depth (see `SiteCollection::with_history`; default 0 = off)
+    pub fn history_capacity(mut self, capacity: usize) -> Self {
+        self.history_capacity = capacity;
+        self
+    }
+
+    /// Per-site O-U dynamics for every site (see `set_theta_all` / `set_sigma_all`;
+    /// default: each `step` call's theta and sigma)
+    pub fn ou_params(mut self, theta: f64, sigma: f64) -> Self {
+        self.ou_params = Some((theta, sigma));
+        self
+    }
+
+    /// Step length in pixels for `InitStrategy::Walk` (default 20)
+    pub fn walk_step(mut self, step: f64) -> Self {
+        self.walk_step = step;
+        self
+    }
+
+    /// Escape-time iteration limit for `InitStrategy::Mandelbrot` (default 100)
+    pub fn fractal_iters(mut self, max_iter: u32) -> Self {
+        self.fractal_iters = max_iter;
+        self
+    }
+
+    /// Vertex count and circumradius for `InitStrategy::Polygon` (default 6
+    /// sides; radius `None` = 40% of the smaller dimension)
+    pub fn polygon(mut self, sides: usize, radius: Option<f64>) -> Self {
+        self.polygon_sides = sides;
+        self.polygon_radius = radius;
+        self
+    }
+
+    /// `(count, radius)` per ring for `InitStrategy::Rings` (default
+    /// 8:100, 16:200, 32:300)
+    pub fn ring_specs(mut self, specs: Vec<(usize, f64)>) -> Self {
+        self.ring_specs = specs;
+        self
+    }
+
+    /// x/y frequencies and x phase offset (radians) for `InitStrategy::Lissajous`
+    /// (default 3, 2, π/2)
+    pub fn lissajous(mut self, a: f64, b: f64, delta: f64) -> Self {
+        self.lissajous = (a, b, delta);
+        self
+    }
+
+    /// Repulsion rounds for `InitStrategy::Scatter` (default 20)
+    pub fn scatter_iters(mut self, iterations: usize) -> Self {
+        self.scatter_iters = iterations;
+        self
+    }
+
+    /// Stipple image and dot threshold for `InitStrategy::Stipple` (see
+    /// `SiteCollection::from_stipple_image`; required by that strategy)
+    pub fn stipple(mut self, stipple: image::GrayImage, dot_threshold: u8) -> Self {
+        self.stipple = Some((stipple, dot_threshold));
+        self
+    }
+
+    /// Corner and edge fractions and edge threshold for `InitStrategy::Hybrid`
+    /// (see `SiteCollection::from_corners_and_edges`; default 0.2, 0.5, 100)
+    pub fn hybrid(mut self, corner_fraction: f64, edge_fraction: f64, edge_threshold: f32) -> Self {
+        self.hybrid = (corner_fraction, edge_fraction, edge_threshold);
+        self
+    }
+
+    /// Create the collection. `image` is required by image-driven strategies
+    /// (`InitStrategy::needs_image`) and, without `dimensions`, for the area.
+    pub fn build(&self, image: Option<&image::RgbImage>) -> crate::Result<SiteCollection> {
+        let (width, height) = match (self.dimensions, image) {
+            (Some(dims), _) => dims,
+            (None, Some(image)) => (image.width() as f64, image.height() as f64),
+            (None, None) => return Err(VoronoiError::Config("builder needs dimensions or an image".into())),
+        };
+        let (count, seed) = (self.count, self.seed);
+        let (cx, cy) = (width / 2.0, height / 2.0);
+        let mut sites = match (self.init_strategy, image) {
+            (InitStrategy::Random, _) => SiteCollection::random(count, width, height, seed),
+            (InitStrategy::Walk, _) => SiteCollection::from_random_walk(count, self.walk_step, width, height, seed),
+            (InitStrategy::Mandelbrot, _) => SiteCollection::from_mandelbrot(count, width, height, self.fractal_iters, seed),
+            (InitStrategy::Polygon, _) => {
+                let radius = self.polygon_radius.unwrap_or(0.4 * width.min(height));
+                SiteCollection::from_regular_polygon(self.polygon_sides, radius, cx, cy, 0.0, seed)
+            }
+            (InitStrategy::Rings, _) => SiteCollection::from_concentric_rings(&self.ring_specs, cx, cy, seed),
+            (InitStrategy::Lissajous, _) => {
+                let (a, b, delta) = self.lissajous;
+                SiteCollection::from_lissajous(count, a, b, delta, 0.4 * width, 0.4 * height, cx, cy, seed)
+            }
+            (InitStrategy::Scatter, _) => SiteCollection::scatter_with_repulsion(count, width, height, seed, self.scatter_iters),
+            (InitStrategy::Stipple, _) => {
+                let (stipple, dot_threshold) = self.stipple.as_ref()
+                    .ok_or_else(|| VoronoiError::Config("init strategy stipple needs a stipple image".into()))?;
+                SiteCollection::from_stipple_image(stipple, *dot_threshold, count, seed)
+            }
+            (InitStrategy::Corners, Some(image)) => SiteCollection::from_points_of_interest(image, count, seed),
+            (InitStrategy::Clusters, Some(image)) => SiteCollection::from_color_clusters(image, count, seed),
+            (InitStrategy::Hybrid, Some(image)) => {
+                let (corner_fraction, edge_fraction, edge_threshold) = self.hybrid;
+                SiteCollection::from_corners_and_edges(image, corner_fraction, edge_fraction, count, edge_threshold, seed)?
+            }
+            (strategy, None) => {
+                return Err(VoronoiError::Config(format!("init strategy {} needs an image", strategy)));
+            }
+        };
+        if let Some((theta, sigma)) = self.ou_params {
+            sites.set_theta_all(theta);
+            sites.set_sigma_all(sigma);
+        }
+        Ok(sites.with_history(self.history_capacity))
+    }
+}
+
+impl SiteCollection {
+    /// Start building a collection; see `SiteCollectionBuilder`
+    pub fn builder() -> SiteCollectionBuilder {
+        SiteCollectionBuilder::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{InitStrategy, SiteCollection};
+
+    fn assert_same(a: &SiteCollection, b: &SiteCollection) {
+        assert_eq!(a.positions(), b.positions());
+        for (x, y) in a.sites.iter().zip(&b.sites) {
+            assert_eq!((x.vel, x.theta, x.sigma), (y.vel, y.theta, y.sigma));
+        }
+    }
+
+    #[test]
+    fn test_builder_matches_sequential_calls() {
+        let image = image::RgbImage::from_fn(64, 48, |x, y| image::Rgb([(x * 4) as u8, (y * 5) as u8, ((x ^ y) * 8) as u8]));
+        let base = SiteCollection::builder().count(30).seed(9);
+
+        let built = base.clone().dimensions(64.0, 48.0).build(None).unwrap();
+        assert_same(&built, &SiteCollection::random(30, 64.0, 48.0, 9));
+        // Dimensions default to the image's
+        assert_same(&base.build(Some(&image)).unwrap(), &built);
+
+        let stipple = image::GrayImage::from_fn(64, 48, |x, y| image::Luma([if (x / 8 + y / 8) % 2 == 0 { 0 } else { 255 }]));
+        let params = base.clone()
+            .walk_step(7.0)
+            .fractal_iters(40)
+            .polygon(5, Some(12.0))
+            .ring_specs(vec![(4, 10.0), (8, 20.0)])
+            .lissajous(1.0, 2.0, 0.5)
+            .scatter_iters(3)
+            .stipple(stipple.clone(), 64)
+            .hybrid(0.3, 0.3, 50.0);
+        let cases = [
+            (InitStrategy::Corners, SiteCollection::from_points_of_interest(&image, 30, 9)),
+            (InitStrategy::Clusters, SiteCollection::from_color_clusters(&image, 30, 9)),
+            (InitStrategy::Walk, SiteCollection::from_random_walk(30, 7.0, 64.0, 48.0, 9)),
+            (InitStrategy::Mandelbrot, SiteCollection::from_mandelbrot(30, 64.0, 48.0, 40, 9)),
+            (InitStrategy::Polygon, SiteCollection::from_regular_polygon(5, 12.0, 32.0, 24.0, 0.0, 9)),
+            (InitStrategy::Rings, SiteCollection::from_concentric_rings(&[(4, 10.0), (8, 20.0)], 32.0, 24.0, 9)),
+            (InitStrategy::Lissajous, SiteCollection::from_lissajous(30, 1.0, 2.0, 0.5, 0.4 * 64.0, 0.4 * 48.0, 32.0, 24.0, 9)),
+            (InitStrategy::Scatter, SiteCollection::scatter_with_repulsion(30, 64.0, 48.0, 9, 3)),
+            (InitStrategy::Stipple, SiteCollection::from_stipple_image(&stipple, 64, 30, 9)),
+            (InitStrategy::Hybrid, SiteCollection::from_corners_and_edges(&image, 0.3, 0.3, 30, 50.0, 9).unwrap()),
+        ];
+        for (strategy, expected) in &cases {
+            let built = params.clone().init_strategy(*strategy).build(Some(&image)).unwrap();
+            assert_same(&built, expected);
+            assert_eq!(strategy.to_string().parse::<InitStrategy>(), Ok(*strategy));
+        }
+        assert!(base.clone().init_strategy(InitStrategy::Corners).dimensions(64.0, 48.0).build(None).is_err());
+        assert!(base.clone().init_strategy(InitStrategy::Stipple).build(Some(&image)).is_err());
+        assert!(base.build(None).is_err());
+
+        let mut expected = SiteCollection::random(30, 64.0, 48.0, 9).with_history(4);
+        expected.set_theta_all(0.5);
+        expected.set_sigma_all(2.0);
+        let mut built = base.clone().history_capacity(4).ou_params(0.5, 2.0).build(Some(&image)).unwrap();
+        assert_same(&built, &expected);
+        for sites in [&mut built, &mut expected] {
+            sites.snapshot();
+            sites.step(15.0, 1.0 / 30.0, 64.0, 48.0, None, 0.0, 3.0, 3.0);
+        }
+        assert_same(&built, &expected);
+        assert!(built.undo());
+        assert_eq!(built.positions(), SiteCollection::random(30, 64.0, 48.0, 9).positions());
+    }
+}
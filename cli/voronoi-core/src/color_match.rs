@@ -0,0 +1,190 @@
+//! Pairing cells by average color (e.g. for color "handoff" animations).
+
+use crate::{Rgb, VoronoiResult};
+
+/// Above this many cells, `nearest_color_neighbors` searches a k-d tree instead
+/// of scanning every pair
+const BRUTE_FORCE_MAX_CELLS: usize = 100;
+
+/// Squared L2 distance in RGB space (exact in `f32`: at most 3 · 255²)
+fn color_dist_sq(a: Rgb, b: Rgb) -> f32 {
+    (0..3).map(|c| (a[c] as f32 - b[c] as f32).powi(2)).sum()
+}
+
+/// Insert `(index, dist_sq)` into `best`, kept sorted by distance then index and
+/// capped at `k` entries
+fn insert_bounded(best: &mut Vec<(usize, f32)>, k: usize, candidate: (usize, f32)) {
+    let pos = best.partition_point(|&(i, d)| d < candidate.1 || (d == candidate.1 && i < candidate.0));
+    if pos < k {
+        best.insert(pos, candidate);
+        best.truncate(k);
+    }
+}
+
+/// Balanced 3D k-d tree over cell colors, in the same implicit layout as
+/// `kdtree::KdTree` (median of `[lo, hi)` at `(lo + hi) / 2`, axes cycling R, G, B)
+struct ColorTree {
+    /// (color, cell index), in tree order
+    points: Vec<(Rgb, usize)>,
+}
+
+impl ColorTree {
+    fn new(colors: &[Rgb]) -> Self {
+        let mut points: Vec<(Rgb, usize)> = colors.iter().copied().zip(0..).collect();
+        Self::build(&mut points, 0);
+        Self { points }
+    }
+
+    fn build(points: &mut [(Rgb, usize)], depth: usize) {
+        if points.len() <= 1 {
+            return;
+        }
+        let mid = points.len() / 2;
+        let axis = depth % 3;
+        points.select_nth_unstable_by_key(mid, |p| p.0[axis]);
+        let (left, right) = points.split_at_mut(mid);
+        Self::build(left, depth + 1);
+        Self::build(&mut right[1..], depth + 1);
+    }
+
+    /// The `k` cells nearest `query` other than `exclude`, as (index, squared
+    /// distance) sorted by distance then index
+    fn k_nearest(&self, query: Rgb, exclude: usize, k: usize) -> Vec<(usize, f32)> {
+        let mut best = Vec::with_capacity(k + 1);
+        if k > 0 {
+            self.search(0, self.points.len(), 0, query, exclude, k, &mut best);
+        }
+        best
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn search(
+        &self,
+        lo: usize,
+        hi: usize,
+        depth: usize,
+        query: Rgb,
+        exclude: usize,
+        k: usize,
+        best: &mut Vec<(usize, f32)>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let (color, idx) = self.points[mid];
+        if idx != exclude {
+            insert_bounded(best, k, (idx, color_dist_sq(query, color)));
+        }
+
+        let axis = depth % 3;
+        let diff = query[axis] as f32 - color[axis] as f32;
+        let (near, far) = if diff < 0.0 { ((lo, mid), (mid + 1, hi)) } else { ((mid + 1, hi), (lo, mid)) };
+        self.search(near.0, near.1, depth + 1, query, exclude, k, best);
+        // Colors across the split differ by at least |diff| on this channel
+        if best.len() < k || diff * diff <= best[k - 1].1 {
+            self.search(far.0, far.1, depth + 1, query, exclude, k, best);
+        }
+    }
+}
+
+impl VoronoiResult {
+    /// Pairwise L2 distances between cell colors in RGB space (`n × n`, symmetric,
+    /// zero diagonal). O(n²) memory; see `nearest_color_neighbors` for large n.
+    pub fn color_distance_matrix(&self) -> Vec<Vec<f32>> {
+        let colors = &self.cell_colors;
+        colors.iter()
+            .map(|&a| colors.iter().map(|&b| color_dist_sq(a, b).sqrt()).collect())
+            .collect()
+    }
+
+    /// For each cell, its `k` (at most n − 1) nearest other cells by color as
+    /// `(cell index, L2 distance)`, sorted by distance then index. Above 100 cells
+    /// this searches a k-d tree over the colors rather than every pair.
+    pub fn nearest_color_neighbors(&self, k: usize) -> Vec<Vec<(usize, f32)>> {
+        let colors = &self.cell_colors;
+        let k = k.min(colors.len().saturating_sub(1));
+        let tree = (colors.len() > BRUTE_FORCE_MAX_CELLS).then(|| ColorTree::new(colors));
+        let neighbors = |i: usize| -> Vec<(usize, f32)> {
+            let mut best = match &tree {
+                Some(tree) => tree.k_nearest(colors[i], i, k),
+                None => {
+                    let mut best = Vec::with_capacity(k + 1);
+                    for (j, &c) in colors.iter().enumerate().filter(|&(j, _)| j != i) {
+                        insert_bounded(&mut best, k, (j, color_dist_sq(colors[i], c)));
+                    }
+                    best
+                }
+            };
+            for entry in &mut best {
+                entry.1 = entry.1.sqrt();
+            }
+            best
+        };
+
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            (0..colors.len()).into_par_iter().map(neighbors).collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            (0..colors.len()).map(neighbors).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Position, VoronoiResult};
+
+    fn with_colors(cell_colors: Vec<[u8; 3]>) -> VoronoiResult {
+        let n = cell_colors.len();
+        VoronoiResult {
+            cell_of: Vec::new(),
+            cell_colors,
+            cell_areas: vec![0; n],
+            cell_centroids: vec![Position::new(0.0, 0.0); n],
+            farthest_point: Position::new(0.0, 0.0),
+            pixel_distances: None,
+            second_cell_of: None,
+            second_distances: None,
+            width: 0,
+            height: 0,
+        }
+    }
+
+    #[test]
+    fn test_color_distance_matrix() {
+        let result = with_colors(vec![[10, 20, 30], [200, 0, 90], [10, 20, 30], [13, 24, 30]]);
+        let matrix = result.color_distance_matrix();
+        for (i, row) in matrix.iter().enumerate() {
+            assert_eq!(row[i], 0.0);
+        }
+        assert_eq!(matrix[0][2], 0.0);
+        assert_eq!(matrix[0][3], 5.0);
+        assert_eq!(matrix[1][3], matrix[3][1]);
+
+        let neighbors = result.nearest_color_neighbors(2);
+        assert_eq!(neighbors[0], vec![(2, 0.0), (3, 5.0)]);
+        assert_eq!(neighbors[1].len(), 2);
+        assert_eq!(result.nearest_color_neighbors(10)[0].len(), 3);
+    }
+
+    /// The k-d tree path (above 100 cells) matches a full scan
+    #[test]
+    fn test_nearest_color_neighbors_tree_matches_matrix() {
+        let colors: Vec<[u8; 3]> = (0..300u32)
+            .map(|i| [(i * 37 % 256) as u8, (i * 91 % 256) as u8, (i * 13 % 64) as u8])
+            .collect();
+        let result = with_colors(colors);
+        let matrix = result.color_distance_matrix();
+        let k = 5;
+        for (i, neighbors) in result.nearest_color_neighbors(k).iter().enumerate() {
+            let mut expected: Vec<(usize, f32)> = matrix[i].iter().copied().enumerate().filter(|&(j, _)| j != i).collect();
+            expected.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+            expected.truncate(k);
+            assert_eq!(neighbors, &expected, "cell {}", i);
+        }
+    }
+}
@@ -2,32 +2,148 @@
 //! When the `parallel` feature is enabled, uses Rayon for parallelism.
 //! Without it, runs sequentially (suitable for WASM targets).
 
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use crate::{Position, Rgb, Result, VoronoiError, VoronoiResult};
+use crate::colormap::rgb_to_hsl;
+use crate::kdtree::KdTree;
 use crate::voronoi::ComputeBackend;
 
+/// How pixels are weighted in `VoronoiResult::cell_centroids` (see
+/// `CpuBackend::with_centroid_weighting`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CentroidWeighting {
+    /// Every pixel counts equally
+    #[default]
+    Uniform,
+    /// Rec. 601 luma / 255, pulling centroids toward bright pixels
+    Luminance,
+    /// HSL saturation, pulling centroids toward vivid pixels
+    Saturation,
+    /// 1 − luma / 255, pulling centroids toward dark pixels
+    InverseLuminance,
+}
+
+impl CentroidWeighting {
+    /// Weight of a pixel, in [0, 1]
+    #[inline]
+    fn weight(self, rgb: Rgb) -> f64 {
+        let luma = || (0.299 * rgb[0] as f64 + 0.587 * rgb[1] as f64 + 0.114 * rgb[2] as f64) / 255.0;
+        match self {
+            CentroidWeighting::Uniform => 1.0,
+            CentroidWeighting::Luminance => luma(),
+            CentroidWeighting::Saturation => rgb_to_hsl(rgb).1,
+            CentroidWeighting::InverseLuminance => 1.0 - luma(),
+        }
+    }
+}
+
 /// CPU backend using Rayon for parallel computation
 pub struct CpuBackend {
     /// Number of threads to use (0 = Rayon default)
     pub num_threads: usize,
     /// Use merged single-pass computation (phases 1+2+4 combined)
     pub merged: bool,
+    /// Record each pixel's distance to its nearest site in `VoronoiResult::pixel_distances`.
+    /// Costs an extra `width * height * 4` bytes per result. Merged pass only.
+    pub distances: bool,
+    /// Record each pixel's second-nearest site and its distance in
+    /// `VoronoiResult::second_cell_of` / `second_distances` (implies `distances`).
+    /// Merged pass only.
+    pub second_nearest: bool,
+    /// `compute_tiled` only: stream `cell_of` to this file (row-major little-endian
+    /// `i32`) instead of keeping it in memory; the result's `cell_of` is left empty.
+    pub streaming_cell_of: Option<PathBuf>,
+    /// Rows per strip for the strip schedule (0 = row-parallel; see
+    /// `with_strip_tiles`)
+    pub strip_tile_h: u32,
+    /// Search a k-d tree instead of the spatial grid when there are more than this
+    /// many sites (`None` = always the grid; see `with_kdtree_threshold`)
+    pub kdtree_threshold: Option<usize>,
+    /// Pixel weights for `cell_centroids` (see `with_centroid_weighting`)
+    pub centroid_weighting: CentroidWeighting,
+    /// Last k-d tree built, with the positions it was built from
+    kdtree: Option<(Vec<Position>, KdTree)>,
 }
 
 impl CpuBackend {
     pub fn new() -> Self {
-        Self { num_threads: 0, merged: true }
+        Self {
+            num_threads: 0,
+            merged: true,
+            distances: false,
+            second_nearest: false,
+            streaming_cell_of: None,
+            strip_tile_h: 0,
+            kdtree_threshold: None,
+            centroid_weighting: CentroidWeighting::Uniform,
+            kdtree: None,
+        }
     }
 
     pub fn with_threads(num_threads: usize) -> Self {
-        Self { num_threads, merged: true }
+        Self { num_threads, ..Self::new() }
+    }
+
+    /// Create a backend that optionally fills `VoronoiResult::pixel_distances`.
+    ///
+    /// **Memory:** enabling this allocates `width * height * 4` bytes per result
+    /// (one `f32` per pixel), on top of the `cell_of` array of the same size.
+    pub fn with_distances(distances: bool) -> Self {
+        Self { distances, ..Self::new() }
+    }
+
+    /// Create a backend that optionally fills `VoronoiResult::second_cell_of` and
+    /// `second_distances`, along with `pixel_distances` (for `render_soft_boundary`).
+    ///
+    /// **Memory:** enabling this allocates `width * height * 12` bytes per result.
+    /// The nearest-site search also runs until the second-nearest is certain,
+    /// which visits more of the grid per pixel.
+    pub fn with_second_nearest(second_nearest: bool) -> Self {
+        Self { second_nearest, ..Self::new() }
+    }
+
+    /// Create a backend that computes in horizontal strips of `tile_h` rows.
+    ///
+    /// Strips are queued in order with `rayon::scope_fifo`, and each strip's output
+    /// buffers are allocated by the worker that fills them. This is not NUMA-aware:
+    /// workers aren't pinned to nodes and allocation isn't node-local, so any
+    /// locality benefit rests on the OS's first-touch policy. Strips merge into the
+    /// result in order as they finish, so per-site sums for only about one strip per
+    /// worker are held at a time. Output is identical to the row-parallel schedule,
+    /// which is also used without the `parallel` feature.
+    pub fn with_strip_tiles(tile_h: u32) -> Self {
+        Self { strip_tile_h: tile_h, ..Self::new() }
+    }
+
+    /// Create a backend that finds nearest sites with a k-d tree instead of the
+    /// spatial grid once there are more than `n` sites.
+    ///
+    /// The grid assumes sites are spread evenly over the image; the tree's
+    /// O(log n) lookups hold up when they're clustered. The tree is kept between
+    /// calls and only rebuilt when site positions change. Output is identical to
+    /// the grid search, except that pixels exactly equidistant from two sites always
+    /// go to the lower index. Merged pass only.
+    pub fn with_kdtree_threshold(n: usize) -> Self {
+        Self { kdtree_threshold: Some(n), ..Self::new() }
+    }
+
+    /// Create a backend whose `cell_centroids` weight each pixel by `weighting`
+    /// instead of equally: the centroid is `Σ w·p / Σ w` over the cell's pixel
+    /// centers, or the unweighted centroid when every weight is 0 (e.g. an
+    /// all-black cell under `Luminance`). Colors and areas are unaffected.
+    /// Merged pass only (not `compute_tiled`).
+    pub fn with_centroid_weighting(weighting: CentroidWeighting) -> Self {
+        Self { centroid_weighting: weighting, ..Self::new() }
     }
 
     /// Create a backend using the legacy multi-pass implementation (for benchmarking)
     #[cfg(feature = "parallel")]
     pub fn new_multi_pass() -> Self {
-        Self { num_threads: 0, merged: false }
+        Self { merged: false, ..Self::new() }
     }
 }
 
@@ -45,12 +161,21 @@ struct RowAccum {
     x_sums: Vec<u64>,
     y_sums: Vec<u64>,
     areas: Vec<u32>,
+    /// Per-cell (Σ w, Σ w·x, Σ w·y) over pixel centers for a non-uniform
+    /// `CentroidWeighting` (empty otherwise)
+    weighted_sums: Vec<[f64; 3]>,
     farthest_pos: Position,
     farthest_dist: f64,
+    /// Per-pixel nearest-site distance, in pixel order (empty unless requested)
+    distances: Vec<f32>,
+    /// Per-pixel second-nearest site and its distance, in pixel order (empty
+    /// unless requested)
+    second_cells: Vec<i32>,
+    second_distances: Vec<f32>,
 }
 
 impl RowAccum {
-    fn new(num_sites: usize) -> Self {
+    fn new(num_sites: usize, weighted: bool) -> Self {
         Self {
             r_sums: vec![0u64; num_sites],
             g_sums: vec![0u64; num_sites],
@@ -58,8 +183,12 @@ impl RowAccum {
             x_sums: vec![0u64; num_sites],
             y_sums: vec![0u64; num_sites],
             areas: vec![0u32; num_sites],
+            weighted_sums: if weighted { vec![[0.0; 3]; num_sites] } else { Vec::new() },
             farthest_pos: Position::new(0.0, 0.0),
             farthest_dist: 0.0,
+            distances: Vec::new(),
+            second_cells: Vec::new(),
+            second_distances: Vec::new(),
         }
     }
 
@@ -74,17 +203,241 @@ impl RowAccum {
             self.y_sums[i] += other.y_sums[i];
             self.areas[i] += other.areas[i];
         }
+        for (sums, other_sums) in self.weighted_sums.iter_mut().zip(&other.weighted_sums) {
+            for k in 0..3 {
+                sums[k] += other_sums[k];
+            }
+        }
         if other.farthest_dist > self.farthest_dist {
             self.farthest_pos = other.farthest_pos;
             self.farthest_dist = other.farthest_dist;
         }
+        // Accumulators cover consecutive row ranges, so appending keeps pixel order
+        self.distances.extend(other.distances);
+        self.second_cells.extend(other.second_cells);
+        self.second_distances.extend(other.second_distances);
         self
     }
 }
 
+/// Nearest-site search structure for one `compute_merged` call
+enum NearestSearch<'a> {
+    Grid((Vec<Vec<u32>>, usize, usize, f32, f32)),
+    Tree(&'a KdTree),
+    /// Grid search by power distance: squared site weights, and their maximum
+    Power((Vec<Vec<u32>>, usize, usize, f32, f32), &'a [f32], f32),
+}
+
+impl NearestSearch<'_> {
+    /// Nearest site as (index, squared distance). For `Power`, the site with the
+    /// least power distance, with its squared Euclidean distance.
+    #[inline]
+    fn nearest(&self, px: f32, py: f32, sites: &[Position]) -> (u32, f32) {
+        match self {
+            NearestSearch::Grid((grid, cols, rows, cell_w, cell_h)) =>
+                CpuBackend::nearest_site(px, py, grid, *cols, *rows, *cell_w, *cell_h, sites),
+            NearestSearch::Tree(tree) => tree.nearest(px, py),
+            NearestSearch::Power((grid, cols, rows, cell_w, cell_h), weights_sq, max_weight_sq) => {
+                let (nearest, _) = CpuBackend::nearest_site_power(
+                    px, py, grid, *cols, *rows, *cell_w, *cell_h, sites, |i| weights_sq[i as usize], *max_weight_sq,
+                );
+                let site = &sites[nearest as usize];
+                let (dx, dy) = (px - site.x as f32, py - site.y as f32);
+                (nearest, dx * dx + dy * dy)
+            }
+        }
+    }
+
+    /// Nearest and second-nearest sites as ((index, squared distance), (index or
+    /// -1, squared distance)); the nearest matches `nearest`
+    #[inline]
+    fn nearest_two(&self, px: f32, py: f32, sites: &[Position]) -> ((u32, f32), (i32, f32)) {
+        match self {
+            NearestSearch::Grid((grid, cols, rows, cell_w, cell_h)) =>
+                CpuBackend::nearest_two_sites(px, py, grid, *cols, *rows, *cell_w, *cell_h, sites),
+            NearestSearch::Tree(tree) => tree.nearest_two(px, py),
+            // `compute_merged` doesn't record second-nearest sites for power diagrams
+            NearestSearch::Power(..) => unreachable!(),
+        }
+    }
+}
+
+/// Sums for one tile of `compute_tiled`, over only the sites owning pixels in it
+struct TileAccum {
+    /// Tile bounds: columns x0..x1, rows y0..y1
+    x0: u32, x1: u32, y0: u32, y1: u32,
+    /// Nearest site per pixel, tile-local row-major
+    cells: Vec<i32>,
+    distances: Vec<f32>,
+    /// Global site index for each local accumulator slot
+    site_ids: Vec<u32>,
+    /// Per local slot: [r, g, b, 2x+1, 2y+1, area]
+    sums: Vec<[u64; 6]>,
+    /// Largest squared nearest-site distance and its pixel (first in raster order)
+    farthest: (f64, u32, u32),
+}
+
 impl CpuBackend {
+    /// Compute like `compute`, but one `tile_size × tile_size` block of pixels at a
+    /// time, for very large images.
+    ///
+    /// Tiles only accumulate the sites that own pixels in them (nearest-site lookups
+    /// use the shared spatial grid, so they only visit sites near the tile), and are
+    /// merged into the per-site totals a band of tiles at a time. This bounds the
+    /// per-thread accumulator memory, which for `compute` is 6 arrays per Rayon job
+    /// over all sites. Output is identical to `compute`. With `streaming_cell_of`
+    /// set, `cell_of` is written to disk band by band instead of held in memory.
+    pub fn compute_tiled(
+        &mut self,
+        image: &image::RgbImage,
+        sites: &[Position],
+        tile_size: u32,
+    ) -> Result<VoronoiResult> {
+        if sites.is_empty() {
+            return Err(VoronoiError::NoSites);
+        }
+        let (width, height) = image.dimensions();
+        let tile_size = tile_size.max(1);
+        let record_distances = self.distances;
+        let img_raw = image.as_raw();
+        let (grid, grid_cols, grid_rows, gcell_w, gcell_h) =
+            Self::build_grid(sites, width, height);
+
+        let process_tile = |x0: u32, y0: u32| -> TileAccum {
+            let (x1, y1) = ((x0 + tile_size).min(width), (y0 + tile_size).min(height));
+            let num_pixels = ((x1 - x0) * (y1 - y0)) as usize;
+            let mut tile = TileAccum {
+                x0, x1, y0, y1,
+                cells: Vec::with_capacity(num_pixels),
+                distances: Vec::with_capacity(if record_distances { num_pixels } else { 0 }),
+                site_ids: Vec::new(),
+                sums: Vec::new(),
+                farthest: (0.0, 0, 0),
+            };
+            let mut slots: HashMap<u32, usize> = HashMap::new();
+            // Neighboring pixels usually share a cell, so remember the last lookup
+            let mut last: Option<(u32, usize)> = None;
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    let (nearest, dist_sq) = Self::nearest_site(
+                        x as f32 + 0.5, y as f32 + 0.5, &grid, grid_cols, grid_rows,
+                        gcell_w, gcell_h, sites,
+                    );
+                    let slot = match last {
+                        Some((site, slot)) if site == nearest => slot,
+                        _ => {
+                            let slot = *slots.entry(nearest).or_insert_with(|| {
+                                tile.site_ids.push(nearest);
+                                tile.sums.push([0; 6]);
+                                tile.sums.len() - 1
+                            });
+                            last = Some((nearest, slot));
+                            slot
+                        }
+                    };
+                    tile.cells.push(nearest as i32);
+
+                    let px = ((y * width + x) * 3) as usize;
+                    let sums = &mut tile.sums[slot];
+                    sums[0] += img_raw[px] as u64;
+                    sums[1] += img_raw[px + 1] as u64;
+                    sums[2] += img_raw[px + 2] as u64;
+                    sums[3] += 2 * x as u64 + 1;
+                    sums[4] += 2 * y as u64 + 1;
+                    sums[5] += 1;
+                    if record_distances {
+                        tile.distances.push(dist_sq.sqrt());
+                    }
+                    if dist_sq as f64 > tile.farthest.0 {
+                        tile.farthest = (dist_sq as f64, x, y);
+                    }
+                }
+            }
+            tile
+        };
+
+        let mut stream = match self.streaming_cell_of {
+            Some(ref path) => Some(std::io::BufWriter::new(std::fs::File::create(path)?)),
+            None => None,
+        };
+        let mut cell_of: Vec<i32> = if stream.is_some() { Vec::new() } else { vec![0; (width * height) as usize] };
+        let mut distances: Vec<f32> = if record_distances { vec![0.0; (width * height) as usize] } else { Vec::new() };
+        let mut accum = RowAccum::new(sites.len(), false);
+        let mut farthest: (f64, u32, u32) = (0.0, 0, 0);
+        let mut band: Vec<i32> = Vec::new();
+
+        for y0 in (0..height).step_by(tile_size as usize) {
+            let band_h = (y0 + tile_size).min(height) - y0;
+            let xs: Vec<u32> = (0..width).step_by(tile_size as usize).collect();
+            #[cfg(feature = "parallel")]
+            let tiles: Vec<TileAccum> = xs.par_iter().map(|&x0| process_tile(x0, y0)).collect();
+            #[cfg(not(feature = "parallel"))]
+            let tiles: Vec<TileAccum> = xs.iter().map(|&x0| process_tile(x0, y0)).collect();
+
+            if stream.is_some() {
+                band.clear();
+                band.resize((band_h * width) as usize, 0);
+            }
+            for tile in tiles {
+                let tile_w = (tile.x1 - tile.x0) as usize;
+                for (row, y) in (tile.y0..tile.y1).enumerate() {
+                    let src = row * tile_w..(row + 1) * tile_w;
+                    let dst_start = (y * width + tile.x0) as usize;
+                    if stream.is_some() {
+                        let band_start = ((y - y0) * width + tile.x0) as usize;
+                        band[band_start..band_start + tile_w].copy_from_slice(&tile.cells[src.clone()]);
+                    } else {
+                        cell_of[dst_start..dst_start + tile_w].copy_from_slice(&tile.cells[src.clone()]);
+                    }
+                    if record_distances {
+                        distances[dst_start..dst_start + tile_w].copy_from_slice(&tile.distances[src]);
+                    }
+                }
+                for (&site, sums) in tile.site_ids.iter().zip(&tile.sums) {
+                    let i = site as usize;
+                    accum.r_sums[i] += sums[0];
+                    accum.g_sums[i] += sums[1];
+                    accum.b_sums[i] += sums[2];
+                    accum.x_sums[i] += sums[3];
+                    accum.y_sums[i] += sums[4];
+                    accum.areas[i] += sums[5] as u32;
+                }
+                // Ties go to the earliest pixel in raster order, as in `compute`
+                let (d, x, y) = tile.farthest;
+                if d > farthest.0 || (d == farthest.0 && d > 0.0 && (y, x) < (farthest.2, farthest.1)) {
+                    farthest = tile.farthest;
+                }
+            }
+            if let Some(ref mut out) = stream {
+                for cell in &band {
+                    out.write_all(&cell.to_le_bytes())?;
+                }
+            }
+        }
+        if let Some(mut out) = stream {
+            out.flush()?;
+        }
+
+        if farthest.0 > 0.0 {
+            accum.farthest_pos = Position::new(farthest.1 as f64 + 0.5, farthest.2 as f64 + 0.5);
+        }
+        let (cell_colors, cell_centroids) = Self::cell_stats(&accum, sites);
+        Ok(VoronoiResult {
+            cell_of,
+            cell_colors,
+            cell_areas: accum.areas,
+            cell_centroids,
+            farthest_point: accum.farthest_pos,
+            pixel_distances: record_distances.then_some(distances),
+            second_cell_of: None,
+            second_distances: None,
+            width,
+            height,
+        })
+    }
+
     /// Build the spatial grid for O(1)-amortized nearest-site lookup
-    fn build_grid(
+    pub(crate) fn build_grid(
         sites: &[Position], width: u32, height: u32,
     ) -> (Vec<Vec<u32>>, usize, usize, f32, f32) {
         let num_sites = sites.len();
@@ -106,11 +459,27 @@ impl CpuBackend {
     /// Find nearest site for a pixel using expanding ring grid search.
     /// Returns (nearest_site_index, squared_distance_f32).
     #[inline]
-    fn nearest_site(
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn nearest_site(
+        px: f32, py: f32,
+        grid: &[Vec<u32>], grid_cols: usize, grid_rows: usize,
+        gcell_w: f32, gcell_h: f32,
+        sites: &[Position],
+    ) -> (u32, f32) {
+        Self::nearest_site_power(px, py, grid, grid_cols, grid_rows, gcell_w, gcell_h, sites, |_| 0.0, 0.0)
+    }
+
+    /// `nearest_site` by power distance: squared distance minus
+    /// `weight_sq(site)`, which must be at most `max_weight_sq` (all 0 for plain
+    /// nearest-site search). Returns the power distance.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    fn nearest_site_power(
         px: f32, py: f32,
         grid: &[Vec<u32>], grid_cols: usize, grid_rows: usize,
         gcell_w: f32, gcell_h: f32,
         sites: &[Position],
+        weight_sq: impl Fn(u32) -> f32, max_weight_sq: f32,
     ) -> (u32, f32) {
         let gc = ((px / gcell_w) as usize).min(grid_cols - 1);
         let gr = ((py / gcell_h) as usize).min(grid_rows - 1);
@@ -139,7 +508,7 @@ impl CpuBackend {
                         let site = &sites[site_idx as usize];
                         let dx = px - site.x as f32;
                         let dy = py - site.y as f32;
-                        let dist = dx * dx + dy * dy;
+                        let dist = dx * dx + dy * dy - weight_sq(site_idx);
                         if dist < min_dist {
                             min_dist = dist;
                             nearest = site_idx;
@@ -153,7 +522,8 @@ impl CpuBackend {
                 .min(gcell_w * (rf + 1.0) - ox)
                 .min(oy + rf * gcell_h)
                 .min(gcell_h * (rf + 1.0) - oy);
-            if min_dist <= min_unchecked * min_unchecked {
+            // Unvisited sites are at least `min_unchecked` away, less their weight
+            if min_dist <= min_unchecked * min_unchecked - max_weight_sq {
                 break;
             }
             if r_start == 0 && c_start == 0
@@ -166,110 +536,353 @@ impl CpuBackend {
         (nearest, min_dist)
     }
 
-    /// Merged single-pass: nearest-site assignment + accumulation + farthest point
-    fn compute_merged(
-        &self,
+    /// Like `nearest_site`, also tracking the runner-up: returns ((nearest,
+    /// squared distance), (second-nearest or -1, squared distance)). Visits sites
+    /// in the same order as `nearest_site`, so the nearest is the same, but keeps
+    /// widening the ring until the second-nearest is certain.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn nearest_two_sites(
+        px: f32, py: f32,
+        grid: &[Vec<u32>], grid_cols: usize, grid_rows: usize,
+        gcell_w: f32, gcell_h: f32,
+        sites: &[Position],
+    ) -> ((u32, f32), (i32, f32)) {
+        let gc = ((px / gcell_w) as usize).min(grid_cols - 1);
+        let gr = ((py / gcell_h) as usize).min(grid_rows - 1);
+        let ox = px - gc as f32 * gcell_w;
+        let oy = py - gr as f32 * gcell_h;
+
+        let mut best = (0u32, f32::INFINITY);
+        let mut second = (-1i32, f32::INFINITY);
+
+        for radius in 0u32.. {
+            let r = radius as usize;
+            let r_start = gr.saturating_sub(r);
+            let r_end = (gr + r + 1).min(grid_rows);
+            let c_start = gc.saturating_sub(r);
+            let c_end = (gc + r + 1).min(grid_cols);
+
+            for ri in r_start..r_end {
+                for ci in c_start..c_end {
+                    if radius > 0
+                        && ri > r_start && ri < r_end - 1
+                        && ci > c_start && ci < c_end - 1
+                    {
+                        continue;
+                    }
+                    for &site_idx in &grid[ri * grid_cols + ci] {
+                        let site = &sites[site_idx as usize];
+                        let dx = px - site.x as f32;
+                        let dy = py - site.y as f32;
+                        let dist = dx * dx + dy * dy;
+                        // Rings clamped at the grid edge revisit cells, so the
+                        // nearest can come up again as a runner-up candidate
+                        if dist < best.1 {
+                            if best.1 < f32::INFINITY {
+                                second = (best.0 as i32, best.1);
+                            }
+                            best = (site_idx, dist);
+                        } else if dist < second.1 && site_idx != best.0 {
+                            second = (site_idx as i32, dist);
+                        }
+                    }
+                }
+            }
+
+            let rf = radius as f32;
+            let min_unchecked = (ox + rf * gcell_w)
+                .min(gcell_w * (rf + 1.0) - ox)
+                .min(oy + rf * gcell_h)
+                .min(gcell_h * (rf + 1.0) - oy);
+            if second.0 >= 0 && second.1 <= min_unchecked * min_unchecked {
+                break;
+            }
+            if r_start == 0 && c_start == 0
+                && r_end == grid_rows && c_end == grid_cols
+            {
+                break;
+            }
+        }
+
+        (best, second)
+    }
+
+    /// Like `compute`, but only for pixels in `roi = [x_min, y_min, x_max, y_max]`
+    /// (max exclusive, clamped to the image), e.g. to refresh an edited region.
+    ///
+    /// All sites still compete for ROI pixels. Pixels outside the ROI get cell -1
+    /// (and distance 0 if recorded); colors, areas, centroids, and the farthest
+    /// point come from ROI pixels only, so cells without any are empty (mid-gray at
+    /// their site). `[0, 0, width, height]` reproduces `compute`. Always uses the
+    /// merged single pass.
+    pub fn compute_with_roi(
+        &mut self,
         image: &image::RgbImage,
         sites: &[Position],
+        roi: [u32; 4],
+    ) -> Result<VoronoiResult> {
+        if sites.is_empty() {
+            return Err(VoronoiError::NoSites);
+        }
+        self.compute_merged(image.as_raw(), 3, image.width(), image.height(), sites, roi, None)
+    }
+
+    /// Compute the power diagram of `sites` with per-site `weights` (one per site,
+    /// see `SiteCollection::weights`): site `i` owns the pixels `p` where
+    /// `dist(p, i)² - weights[i]²` is least, so heavier sites claim more area and
+    /// can leave lighter neighbors with empty cells. Equal weights give the
+    /// ordinary diagram.
+    ///
+    /// Always uses the merged single pass with the grid search (no k-d tree).
+    /// `pixel_distances` and the farthest point use each pixel's Euclidean
+    /// distance to its owning site; second-nearest sites aren't recorded.
+    pub fn compute_weighted(
+        &mut self,
+        image: &image::RgbImage,
+        sites: &[Position],
+        weights: &[f64],
+    ) -> Result<VoronoiResult> {
+        if sites.is_empty() {
+            return Err(VoronoiError::NoSites);
+        }
+        if weights.len() != sites.len() {
+            return Err(VoronoiError::Config(format!("{} weights for {} sites", weights.len(), sites.len())));
+        }
+        let (width, height) = image.dimensions();
+        self.compute_merged(image.as_raw(), 3, width, height, sites, [0, 0, width, height], Some(weights))
+    }
+
+    /// Lloyd's relaxation: up to `iterations` rounds of computing the diagram and
+    /// moving every site exactly onto its cell centroid, stopping early once no
+    /// site moves more than `tolerance` pixels in a round. Returns the relaxed
+    /// positions (empty cells keep their site). Always uses the merged single
+    /// pass, with this backend's other settings (e.g. `centroid_weighting`).
+    pub fn lloyd_relax(
+        &mut self,
+        image: &image::RgbImage,
+        sites: &[Position],
+        iterations: usize,
+        tolerance: f64,
+    ) -> Result<Vec<Position>> {
+        if sites.is_empty() {
+            return Err(VoronoiError::NoSites);
+        }
+        let (width, height) = image.dimensions();
+        let mut positions = sites.to_vec();
+        for _ in 0..iterations {
+            let result = self.compute_merged(image.as_raw(), 3, width, height, &positions, [0, 0, width, height], None)?;
+            let max_move = positions.iter()
+                .zip(&result.cell_centroids)
+                .map(|(p, c)| p.dist(c))
+                .fold(0.0, f64::max);
+            positions = result.cell_centroids;
+            if max_move < tolerance {
+                break;
+            }
+        }
+        Ok(positions)
+    }
+
+    /// Merged single-pass: nearest-site assignment + accumulation + farthest point.
+    ///
+    /// `img_raw` is row-major with `channels` interleaved bytes per pixel: 3 (RGB),
+    /// 4 (RGBA, alpha ignored), or 1-2 (luma, used for all three color channels).
+    /// Only pixels in `roi` are assigned (see `compute_with_roi`).
+    #[allow(clippy::too_many_arguments)]
+    fn compute_merged(
+        &mut self,
+        img_raw: &[u8],
+        channels: usize,
+        width: u32,
+        height: u32,
+        sites: &[Position],
+        roi: [u32; 4],
+        weights: Option<&[f64]>,
     ) -> Result<VoronoiResult> {
-        let width = image.width();
-        let height = image.height();
         let num_sites = sites.len();
+        let (g_off, b_off) = if channels >= 3 { (1, 2) } else { (0, 0) };
+        let record_second = self.second_nearest && weights.is_none();
+        let record_distances = self.distances || record_second;
+        let weighting = self.centroid_weighting;
+        let weighted = weighting != CentroidWeighting::Uniform;
 
-        let (grid, grid_cols, grid_rows, gcell_w, gcell_h) =
-            Self::build_grid(sites, width, height);
-        let grid_ref = &grid;
-        let img_raw = image.as_raw();
+        let weights_sq: Option<Vec<f32>> = weights.map(|w| w.iter().map(|&w| (w * w) as f32).collect());
+        let use_kdtree = weights.is_none() && self.kdtree_threshold.is_some_and(|n| num_sites > n);
+        if use_kdtree && self.kdtree.as_ref().is_none_or(|(built, _)| built != sites) {
+            self.kdtree = Some((sites.to_vec(), KdTree::new(sites)));
+        }
+        let search = match (&self.kdtree, &weights_sq) {
+            (_, Some(weights_sq)) => NearestSearch::Power(
+                Self::build_grid(sites, width, height),
+                weights_sq,
+                weights_sq.iter().copied().fold(0.0, f32::max),
+            ),
+            (Some((_, tree)), None) if use_kdtree => NearestSearch::Tree(tree),
+            _ => NearestSearch::Grid(Self::build_grid(sites, width, height)),
+        };
+        let search = &search;
+
+        let (x0, x1) = (roi[0].min(width), roi[2].min(width));
+        let (y0, y1) = (roi[1].min(height), roi[3].min(height));
+
+        // Mark `n` pixels outside the ROI: cell -1, distances 0
+        let skip = |n: usize, cells: &mut Vec<i32>, acc: &mut RowAccum| {
+            cells.resize(cells.len() + n, -1);
+            if record_distances {
+                acc.distances.resize(acc.distances.len() + n, 0.0);
+            }
+            if record_second {
+                acc.second_cells.resize(acc.second_cells.len() + n, -1);
+                acc.second_distances.resize(acc.second_distances.len() + n, 0.0);
+            }
+        };
+
+        // Assign row y's pixels to their nearest sites, accumulating into `cells`/`acc`
+        let process_row = |y: u32, cells: &mut Vec<i32>, acc: &mut RowAccum| {
+            if y < y0 || y >= y1 || x0 >= x1 {
+                skip(width as usize, cells, acc);
+                return;
+            }
+            let py = y as f32 + 0.5;
+            let row_offset = (y * width) as usize;
+            skip(x0 as usize, cells, acc);
+
+            for x in x0..x1 {
+                let px = x as f32 + 0.5;
+                let (nearest, dist_sq) = if record_second {
+                    let (nearest, (second, second_sq)) = search.nearest_two(px, py, sites);
+                    acc.second_cells.push(second);
+                    acc.second_distances.push(second_sq.sqrt());
+                    nearest
+                } else {
+                    search.nearest(px, py, sites)
+                };
+                let cell = nearest as usize;
+
+                cells.push(nearest as i32);
+
+                let px_offset = (row_offset + x as usize) * channels;
+                let rgb = [img_raw[px_offset], img_raw[px_offset + g_off], img_raw[px_offset + b_off]];
+                acc.r_sums[cell] += rgb[0] as u64;
+                acc.g_sums[cell] += rgb[1] as u64;
+                acc.b_sums[cell] += rgb[2] as u64;
+                acc.x_sums[cell] += 2 * x as u64 + 1;
+                acc.y_sums[cell] += 2 * y as u64 + 1;
+                acc.areas[cell] += 1;
+                if let Some(sums) = acc.weighted_sums.get_mut(cell) {
+                    let w = weighting.weight(rgb);
+                    sums[0] += w;
+                    sums[1] += w * (x as f64 + 0.5);
+                    sums[2] += w * py as f64;
+                }
+                if record_distances {
+                    acc.distances.push(dist_sq.sqrt());
+                }
+
+                let dist_f64 = dist_sq as f64;
+                if dist_f64 > acc.farthest_dist {
+                    acc.farthest_dist = dist_f64;
+                    acc.farthest_pos = Position::new(
+                        x as f64 + 0.5, y as f64 + 0.5,
+                    );
+                }
+            }
+            skip((width - x1) as usize, cells, acc);
+        };
 
         // Single pass: assign each pixel to nearest site + accumulate
         #[cfg(feature = "parallel")]
-        let (cell_of, accum) = (0..height)
-            .into_par_iter()
-            .fold(
-                || (Vec::with_capacity(0), RowAccum::new(num_sites)),
-                |(mut cells, mut acc), y| {
-                    let py = y as f32 + 0.5;
-                    let row_offset = (y * width) as usize;
-
-                    for x in 0..width {
-                        let px = x as f32 + 0.5;
-                        let (nearest, dist_sq) = Self::nearest_site(
-                            px, py, grid_ref, grid_cols, grid_rows,
-                            gcell_w, gcell_h, sites,
-                        );
-                        let cell = nearest as usize;
-
-                        cells.push(nearest as i32);
-
-                        let px_offset = (row_offset + x as usize) * 3;
-                        acc.r_sums[cell] += img_raw[px_offset] as u64;
-                        acc.g_sums[cell] += img_raw[px_offset + 1] as u64;
-                        acc.b_sums[cell] += img_raw[px_offset + 2] as u64;
-                        acc.x_sums[cell] += 2 * x as u64 + 1;
-                        acc.y_sums[cell] += 2 * y as u64 + 1;
-                        acc.areas[cell] += 1;
-
-                        let dist_f64 = dist_sq as f64;
-                        if dist_f64 > acc.farthest_dist {
-                            acc.farthest_dist = dist_f64;
-                            acc.farthest_pos = Position::new(
-                                x as f64 + 0.5, y as f64 + 0.5,
-                            );
+        let (cell_of, accum) = if self.strip_tile_h > 0 {
+            // One job per strip, queued in order; each allocates its own buffers.
+            // Finished strips merge in order under the lock, so only strips that
+            // finish before an earlier one wait in `pending` (about one per worker),
+            // not every strip's per-site sums
+            let tile_h = self.strip_tile_h;
+            let merged = std::sync::Mutex::new((
+                0usize,
+                Vec::with_capacity((width * height) as usize),
+                RowAccum::new(num_sites, weighted),
+                HashMap::new(),
+            ));
+            rayon::scope_fifo(|scope| {
+                for i in 0..height.div_ceil(tile_h) as usize {
+                    let (process_row, merged) = (&process_row, &merged);
+                    scope.spawn_fifo(move |_| {
+                        let y0 = i as u32 * tile_h;
+                        let y1 = (y0 + tile_h).min(height);
+                        let mut cells = Vec::with_capacity(((y1 - y0) * width) as usize);
+                        let mut acc = RowAccum::new(num_sites, weighted);
+                        for y in y0..y1 {
+                            process_row(y, &mut cells, &mut acc);
                         }
-                    }
-                    (cells, acc)
-                },
-            )
-            .reduce(
-                || (Vec::new(), RowAccum::new(num_sites)),
-                |(mut cells1, acc1), (cells2, acc2)| {
-                    cells1.extend(cells2);
-                    (cells1, acc1.merge(acc2))
-                },
-            );
+                        let mut merged = merged.lock().unwrap();
+                        let (next, all_cells, accum, pending) = &mut *merged;
+                        pending.insert(i, (cells, acc));
+                        while let Some((strip_cells, acc)) = pending.remove(next) {
+                            all_cells.extend(strip_cells);
+                            let done = std::mem::replace(accum, RowAccum::new(0, false));
+                            *accum = done.merge(acc);
+                            *next += 1;
+                        }
+                    });
+                }
+            });
+            let (_, cells, accum, _) = merged.into_inner().unwrap();
+            (cells, accum)
+        } else {
+            (0..height)
+                .into_par_iter()
+                .fold(
+                    || (Vec::with_capacity(0), RowAccum::new(num_sites, weighted)),
+                    |(mut cells, mut acc), y| {
+                        process_row(y, &mut cells, &mut acc);
+                        (cells, acc)
+                    },
+                )
+                .reduce(
+                    || (Vec::new(), RowAccum::new(num_sites, weighted)),
+                    |(mut cells1, acc1), (cells2, acc2)| {
+                        cells1.extend(cells2);
+                        (cells1, acc1.merge(acc2))
+                    },
+                )
+        };
 
         #[cfg(not(feature = "parallel"))]
         let (cell_of, accum) = {
             let num_pixels = (width * height) as usize;
             let mut cells = Vec::with_capacity(num_pixels);
-            let mut acc = RowAccum::new(num_sites);
+            let mut acc = RowAccum::new(num_sites, weighted);
             for y in 0..height {
-                let py = y as f32 + 0.5;
-                let row_offset = (y * width) as usize;
-                for x in 0..width {
-                    let px = x as f32 + 0.5;
-                    let (nearest, dist_sq) = Self::nearest_site(
-                        px, py, grid_ref, grid_cols, grid_rows,
-                        gcell_w, gcell_h, sites,
-                    );
-                    let cell = nearest as usize;
-
-                    cells.push(nearest as i32);
-
-                    let px_offset = (row_offset + x as usize) * 3;
-                    acc.r_sums[cell] += img_raw[px_offset] as u64;
-                    acc.g_sums[cell] += img_raw[px_offset + 1] as u64;
-                    acc.b_sums[cell] += img_raw[px_offset + 2] as u64;
-                    acc.x_sums[cell] += 2 * x as u64 + 1;
-                    acc.y_sums[cell] += 2 * y as u64 + 1;
-                    acc.areas[cell] += 1;
-
-                    let dist_f64 = dist_sq as f64;
-                    if dist_f64 > acc.farthest_dist {
-                        acc.farthest_dist = dist_f64;
-                        acc.farthest_pos = Position::new(
-                            x as f64 + 0.5, y as f64 + 0.5,
-                        );
-                    }
-                }
+                process_row(y, &mut cells, &mut acc);
             }
             (cells, acc)
         };
 
-        // Phase 3: Compute average colors and centroids (sequential, O(num_sites))
+        let (cell_colors, cell_centroids) = Self::cell_stats(&accum, sites);
+        Ok(VoronoiResult {
+            cell_of,
+            cell_colors,
+            cell_areas: accum.areas,
+            cell_centroids,
+            farthest_point: accum.farthest_pos,
+            pixel_distances: record_distances.then_some(accum.distances),
+            second_cell_of: record_second.then_some(accum.second_cells),
+            second_distances: record_second.then_some(accum.second_distances),
+            width,
+            height,
+        })
+    }
+
+    /// Phase 3: average colors and centroids from accumulated sums (sequential, O(num_sites)).
+    /// Empty cells get mid-gray and their site position.
+    #[allow(clippy::manual_checked_ops)]
+    fn cell_stats(accum: &RowAccum, sites: &[Position]) -> (Vec<Rgb>, Vec<Position>) {
+        let num_sites = sites.len();
         let mut cell_colors: Vec<Rgb> = Vec::with_capacity(num_sites);
         let mut cell_centroids: Vec<Position> = Vec::with_capacity(num_sites);
-        for i in 0..num_sites {
+        for (i, &site) in sites.iter().enumerate() {
             let count = accum.areas[i] as u64;
             if count > 0 {
                 cell_colors.push([
@@ -277,29 +890,24 @@ impl CpuBackend {
                     (accum.g_sums[i] / count) as u8,
                     (accum.b_sums[i] / count) as u8,
                 ]);
-                cell_centroids.push(Position::new(
-                    accum.x_sums[i] as f64 / (2.0 * count as f64),
-                    accum.y_sums[i] as f64 / (2.0 * count as f64),
-                ));
+                cell_centroids.push(match accum.weighted_sums.get(i) {
+                    Some(&[w, wx, wy]) if w > 0.0 => Position::new(wx / w, wy / w),
+                    _ => Position::new(
+                        accum.x_sums[i] as f64 / (2.0 * count as f64),
+                        accum.y_sums[i] as f64 / (2.0 * count as f64),
+                    ),
+                });
             } else {
                 cell_colors.push([128, 128, 128]);
-                cell_centroids.push(sites[i]);
+                cell_centroids.push(site);
             }
         }
-
-        Ok(VoronoiResult {
-            cell_of,
-            cell_colors,
-            cell_areas: accum.areas,
-            cell_centroids,
-            farthest_point: accum.farthest_pos,
-            width,
-            height,
-        })
+        (cell_colors, cell_centroids)
     }
 
     /// Legacy multi-pass implementation (for benchmarking comparison)
     #[cfg(feature = "parallel")]
+    #[allow(clippy::manual_checked_ops)]
     fn compute_multi_pass(
         &self,
         image: &image::RgbImage,
@@ -435,6 +1043,9 @@ impl CpuBackend {
             cell_areas: areas,
             cell_centroids,
             farthest_point,
+            pixel_distances: None,
+            second_cell_of: None,
+            second_distances: None,
             width,
             height,
         })
@@ -454,7 +1065,34 @@ impl ComputeBackend for CpuBackend {
         if !self.merged {
             return self.compute_multi_pass(image, sites);
         }
-        self.compute_merged(image, sites)
+        let (width, height) = image.dimensions();
+        self.compute_merged(image.as_raw(), 3, width, height, sites, [0, 0, width, height], None)
+    }
+
+    /// Reads 8-bit RGB, RGBA, and luma buffers in place; other formats are converted.
+    fn compute_dynamic(
+        &mut self,
+        image: &image::DynamicImage,
+        sites: &[Position],
+    ) -> Result<VoronoiResult> {
+        use image::DynamicImage;
+
+        if sites.is_empty() {
+            return Err(VoronoiError::NoSites);
+        }
+        #[cfg(feature = "parallel")]
+        if !self.merged {
+            return self.compute_multi_pass(&image.to_rgb8(), sites);
+        }
+        let (width, height) = (image.width(), image.height());
+        let full = [0, 0, width, height];
+        match image {
+            DynamicImage::ImageRgb8(img) => self.compute_merged(img.as_raw(), 3, width, height, sites, full, None),
+            DynamicImage::ImageRgba8(img) => self.compute_merged(img.as_raw(), 4, width, height, sites, full, None),
+            DynamicImage::ImageLuma8(img) => self.compute_merged(img.as_raw(), 1, width, height, sites, full, None),
+            DynamicImage::ImageLumaA8(img) => self.compute_merged(img.as_raw(), 2, width, height, sites, full, None),
+            _ => self.compute(&image.to_rgb8(), sites),
+        }
     }
 }
 
@@ -462,7 +1100,7 @@ impl ComputeBackend for CpuBackend {
 mod tests {
     use super::*;
 
-    use crate::site::{Site, SiteCollection, SplitStrategy, Velocity};
+    use crate::site::{AdjustParams, Site, SiteCollection, SplitStrategy, Velocity};
 
     #[test]
     fn test_basic_voronoi() {
@@ -518,6 +1156,7 @@ mod tests {
             sites.step(
                 speed, dt, w as f64, h as f64,
                 Some(&result.cell_centroids), centroid_pull,
+                3.0, 3.0,
             );
 
             let max_area = *areas.iter().max().unwrap();
@@ -531,10 +1170,14 @@ mod tests {
 
             if target != sites.len() {
                 let (added, _removed) = sites.adjust_count(
-                    target, doubling_time, dt, Some(areas),
-                    SplitStrategy::Max, Some(&result.cell_centroids),
-                    Some(result.farthest_point),
-                    (w * h) as f64,
+                    target, doubling_time, dt, SplitStrategy::Max,
+                    AdjustParams {
+                        cell_areas: Some(areas),
+                        centroids: Some(&result.cell_centroids),
+                        farthest_point: Some(result.farthest_point),
+                        img_area: (w * h) as f64,
+                        ..Default::default()
+                    },
                 );
                 if !added.is_empty() {
                     for &child_idx in &added {
@@ -615,4 +1258,302 @@ mod tests {
             );
         }
     }
+
+    /// compute_dynamic on RGBA/luma input matches compute on the RGB conversion
+    #[test]
+    fn test_compute_dynamic_matches_rgb() {
+        let (w, h) = (64u32, 48u32);
+        let rgba = image::RgbaImage::from_fn(w, h, |x, y| {
+            image::Rgba([(x * 4) as u8, (y * 5) as u8, ((x + y) * 2) as u8, 200])
+        });
+        let sites = vec![
+            Position::new(10.0, 10.0),
+            Position::new(50.0, 12.0),
+            Position::new(30.0, 40.0),
+        ];
+        let mut backend = CpuBackend::new();
+
+        for dynamic in [
+            image::DynamicImage::ImageRgba8(rgba.clone()),
+            image::DynamicImage::ImageLuma8(image::DynamicImage::ImageRgba8(rgba.clone()).to_luma8()),
+        ] {
+            let expected = backend.compute(&dynamic.to_rgb8(), &sites).unwrap();
+            let actual = backend.compute_dynamic(&dynamic, &sites).unwrap();
+            assert_eq!(actual.cell_of, expected.cell_of);
+            assert_eq!(actual.cell_colors, expected.cell_colors);
+            assert_eq!(actual.cell_areas, expected.cell_areas);
+        }
+    }
+
+    /// Run `iterations` Lloyd steps (each site moved to its cell centroid) on a
+    /// uniform image and check every site ends within 0.01px of `expected`
+    fn assert_lloyd_convergence(width: u32, height: u32, start: &[Position], expected: &[Position], iterations: usize) {
+        let image = image::RgbImage::from_pixel(width, height, image::Rgb([200, 200, 200]));
+        let mut backend = CpuBackend::new();
+        let mut sites = start.to_vec();
+        for _ in 0..iterations {
+            sites = backend.compute(&image, &sites).unwrap().cell_centroids;
+        }
+        for (site, want) in sites.iter().zip(expected) {
+            assert!(site.dist(want) < 0.01, "site at ({}, {}), expected ({}, {})", site.x, site.y, want.x, want.y);
+        }
+    }
+
+    fn area_std(result: &VoronoiResult) -> f64 {
+        let n = result.cell_areas.len() as f64;
+        let mean = result.cell_areas.iter().map(|&a| a as f64).sum::<f64>() / n;
+        (result.cell_areas.iter().map(|&a| (a as f64 - mean).powi(2)).sum::<f64>() / n).sqrt()
+    }
+
+    #[test]
+    fn test_lloyd_relax() {
+        let image = image::RgbImage::from_pixel(120, 90, image::Rgb([50, 50, 50]));
+        let mut backend = CpuBackend::new();
+        let start = SiteCollection::random(40, 120.0, 90.0, 9).positions();
+        let before = area_std(&backend.compute(&image, &start).unwrap());
+        let relaxed = backend.lloyd_relax(&image, &start, 200, 0.01).unwrap();
+        assert_eq!(relaxed.len(), start.len());
+        let result = backend.compute(&image, &relaxed).unwrap();
+        let after = area_std(&result);
+        assert!(after < 0.3 * before, "area std {} -> {}", before, after);
+        // Converged: one more round barely moves anything
+        let moved = relaxed.iter().zip(&result.cell_centroids).map(|(p, c)| p.dist(c)).fold(0.0, f64::max);
+        assert!(moved < 0.01, "max displacement {}", moved);
+
+        assert_eq!(backend.lloyd_relax(&image, &start, 0, 0.01).unwrap(), start);
+        assert!(backend.lloyd_relax(&image, &[], 10, 0.01).is_err());
+    }
+
+    /// Centroids use pixel centers, matching the distance convention, so Lloyd's
+    /// relaxation converges to the true centroid of the pixel grid
+    #[test]
+    fn test_lloyd_converges_to_pixel_center_centroid() {
+        assert_lloyd_convergence(10, 10, &[Position::new(2.0, 7.0)], &[Position::new(5.0, 5.0)], 100);
+        assert_lloyd_convergence(
+            10, 10,
+            &[Position::new(3.0, 5.0), Position::new(8.0, 5.0)],
+            &[Position::new(2.5, 5.0), Position::new(7.5, 5.0)],
+            100,
+        );
+    }
+
+    /// Second-nearest distances match a brute-force scan, on both the grid and
+    /// the k-d tree, without changing the nearest-site assignment
+    #[test]
+    fn test_second_nearest() {
+        let image = image::RgbImage::from_fn(70, 50, |x, y| image::Rgb([(x * 3) as u8, (y * 5) as u8, 40]));
+        let sites = SiteCollection::random(30, 70.0, 50.0, 12).positions();
+        let plain = CpuBackend::new().compute(&image, &sites).unwrap();
+        assert!(plain.second_cell_of.is_none());
+
+        let mut tree = CpuBackend { kdtree_threshold: Some(0), ..CpuBackend::with_second_nearest(true) };
+        for result in [CpuBackend::with_second_nearest(true).compute(&image, &sites).unwrap(), tree.compute(&image, &sites).unwrap()] {
+            assert_eq!(result.cell_of, plain.cell_of);
+            let second = result.second_cell_of.as_ref().unwrap();
+            let second_d = result.second_distances.as_ref().unwrap();
+            assert!(result.pixel_distances.is_some());
+            for (i, (&j, &d)) in second.iter().zip(second_d).enumerate() {
+                let (px, py) = ((i % 70) as f32 + 0.5, (i / 70) as f32 + 0.5);
+                let dist = |k: usize| ((px - sites[k].x as f32).powi(2) + (py - sites[k].y as f32).powi(2)).sqrt();
+                let mut all: Vec<f32> = (0..sites.len()).map(dist).collect();
+                all.sort_by(f32::total_cmp);
+                assert_ne!(j, result.cell_of[i]);
+                assert!((d - all[1]).abs() < 1e-4 && (dist(j as usize) - d).abs() < 1e-4, "pixel {}", i);
+            }
+        }
+
+        let single = CpuBackend::with_second_nearest(true).compute(&image, &sites[..1]).unwrap();
+        assert!(single.second_cell_of.unwrap().iter().all(|&j| j == -1));
+        assert!(single.second_distances.unwrap().iter().all(|d| d.is_infinite()));
+    }
+
+    #[test]
+    fn test_pixel_distances() {
+        let image = image::RgbImage::from_pixel(80, 60, image::Rgb([0, 0, 0]));
+        let mut sites = SiteCollection::random(20, 80.0, 60.0, 9).positions();
+        sites[0] = Position::new(10.3, 20.7);
+
+        let result = CpuBackend::new().compute(&image, &sites).unwrap();
+        assert!(result.pixel_distances.is_none());
+
+        let result = CpuBackend::with_distances(true).compute(&image, &sites).unwrap();
+        let distances = result.pixel_distances.as_ref().unwrap();
+        assert_eq!(distances.len(), 80 * 60);
+
+        // Pixel containing site 0: center (10.5, 20.5) is within half a pixel
+        assert!(distances[20 * 80 + 10] <= 0.5);
+
+        let fp = result.farthest_point;
+        let farthest_idx = (fp.y as usize) * 80 + fp.x as usize;
+        let max_dist = distances[farthest_idx];
+        assert_eq!(distances.iter().cloned().fold(0.0f32, f32::max), max_dist);
+        for c in &result.cell_centroids {
+            let idx = (c.y as usize) * 80 + c.x as usize;
+            assert!(distances[idx] <= max_dist);
+        }
+    }
+
+    #[test]
+    fn test_compute_with_roi() {
+        let image = image::RgbImage::from_fn(90, 70, |x, y| image::Rgb([(x * 2) as u8, (y * 3) as u8, ((x + y) % 200) as u8]));
+        let sites = SiteCollection::random(25, 90.0, 70.0, 8).positions();
+        let mut backend = CpuBackend::with_distances(true);
+        let full = backend.compute(&image, &sites).unwrap();
+        let roi = backend.compute_with_roi(&image, &sites, [0, 0, 90, 70]).unwrap();
+        assert_eq!(roi.cell_of, full.cell_of);
+        assert_eq!(roi.cell_colors, full.cell_colors);
+        assert_eq!(roi.cell_areas, full.cell_areas);
+        assert_eq!(roi.cell_centroids, full.cell_centroids);
+        assert_eq!(roi.farthest_point, full.farthest_point);
+        assert_eq!(roi.pixel_distances, full.pixel_distances);
+
+        // A sub-region keeps the full assignments inside and -1 outside
+        let part = backend.compute_with_roi(&image, &sites, [20, 10, 50, 40]).unwrap();
+        for (i, (&cell, &expected)) in part.cell_of.iter().zip(&full.cell_of).enumerate() {
+            let (x, y) = (i as u32 % 90, i as u32 / 90);
+            let inside = (20..50).contains(&x) && (10..40).contains(&y);
+            assert_eq!(cell, if inside { expected } else { -1 });
+        }
+        assert_eq!(part.cell_areas.iter().sum::<u32>(), 30 * 30);
+    }
+
+    #[test]
+    fn test_compute_tiled_matches_compute() {
+        let image = image::RgbImage::from_fn(600, 410, |x, y| {
+            image::Rgb([(x % 256) as u8, (y * 3 % 256) as u8, ((x ^ y) % 256) as u8])
+        });
+        let sites = SiteCollection::random(300, 600.0, 410.0, 9).positions();
+        let expected = CpuBackend::with_distances(true).compute(&image, &sites).unwrap();
+
+        for tile_size in [256, 100] {
+            let tiled = CpuBackend::with_distances(true).compute_tiled(&image, &sites, tile_size).unwrap();
+            assert_eq!(tiled.cell_of, expected.cell_of);
+            assert_eq!(tiled.cell_colors, expected.cell_colors);
+            assert_eq!(tiled.cell_areas, expected.cell_areas);
+            assert_eq!(tiled.pixel_distances, expected.pixel_distances);
+            assert_eq!(tiled.farthest_point.x, expected.farthest_point.x);
+            assert_eq!(tiled.farthest_point.y, expected.farthest_point.y);
+            for (a, b) in tiled.cell_centroids.iter().zip(&expected.cell_centroids) {
+                assert_eq!((a.x, a.y), (b.x, b.y));
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn test_strip_tiles_match_row_parallel() {
+        let image = image::RgbImage::from_fn(157, 91, |x, y| image::Rgb([(x * 3) as u8, (y * 5) as u8, ((x ^ y) * 7) as u8]));
+        let sites = SiteCollection::random(120, 157.0, 91.0, 8).positions();
+        let expected = CpuBackend::with_distances(true).compute(&image, &sites).unwrap();
+
+        // Strip heights that divide the image unevenly, plus one taller than it
+        for tile_h in [1, 7, 32, 200] {
+            let mut backend = CpuBackend { distances: true, ..CpuBackend::with_strip_tiles(tile_h) };
+            let result = backend.compute(&image, &sites).unwrap();
+            assert_eq!(result.cell_of, expected.cell_of, "tile_h {}", tile_h);
+            assert_eq!(result.cell_areas, expected.cell_areas);
+            assert_eq!(result.cell_colors, expected.cell_colors);
+            assert_eq!(result.cell_centroids, expected.cell_centroids);
+            assert_eq!(result.farthest_point, expected.farthest_point);
+            assert_eq!(result.pixel_distances, expected.pixel_distances);
+        }
+    }
+
+    #[test]
+    fn test_kdtree_matches_grid() {
+        let image = image::RgbImage::from_fn(240, 160, |x, y| image::Rgb([(x * 3) as u8, (y * 5) as u8, ((x ^ y) * 7) as u8]));
+        let mut sites = SiteCollection::random(2000, 240.0, 160.0, 4);
+        let mut grid = CpuBackend::with_distances(true);
+        let mut tree = CpuBackend { distances: true, ..CpuBackend::with_kdtree_threshold(1000) };
+
+        for _ in 0..3 {
+            let positions = sites.positions();
+            let expected = grid.compute(&image, &positions).unwrap();
+            let result = tree.compute(&image, &positions).unwrap();
+            assert_eq!(result.cell_of, expected.cell_of);
+            assert_eq!(result.cell_areas, expected.cell_areas);
+            assert_eq!(result.cell_colors, expected.cell_colors);
+            assert_eq!(result.pixel_distances, expected.pixel_distances);
+            assert_eq!(tree.kdtree.as_ref().unwrap().0, positions);
+            sites.step(15.0, 1.0 / 30.0, 240.0, 160.0, None, 0.0, 3.0, 3.0);
+        }
+
+        // At or below the threshold the grid is used and no tree is built
+        let mut small = CpuBackend::with_kdtree_threshold(2000);
+        small.compute(&image, &sites.positions()).unwrap();
+        assert!(small.kdtree.is_none());
+    }
+
+    #[test]
+    fn test_compute_weighted() {
+        let image = image::RgbImage::from_fn(100, 100, |x, y| image::Rgb([x as u8, y as u8, 0]));
+        let mut backend = CpuBackend::new();
+
+        // Two sites: the boundary moves from x = 50 to where (x-25)² - 30² = (x-75)² - 1²
+        let pair = [Position::new(25.0, 50.0), Position::new(75.0, 50.0)];
+        let plain = backend.compute(&image, &pair).unwrap();
+        assert_eq!(backend.compute_weighted(&image, &pair, &[4.0, 4.0]).unwrap().cell_of, plain.cell_of);
+        let result = backend.compute_weighted(&image, &pair, &[30.0, 1.0]).unwrap();
+        assert_eq!(result.cell_areas, vec![5900, 4100]);
+        assert!(backend.compute_weighted(&image, &pair, &[1.0]).is_err());
+
+        // Many sites: matches a brute-force argmin of power distance
+        let mut sites = SiteCollection::random(300, 100.0, 100.0, 9);
+        for (i, site) in sites.sites.iter_mut().enumerate() {
+            site.weight = (i % 7) as f64 * 1.5;
+        }
+        let (positions, weights) = (sites.positions(), sites.weights());
+        let result = CpuBackend::new().compute_weighted(&image, &positions, &weights).unwrap();
+        for y in 0..100 {
+            for x in 0..100 {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let power = |i: usize| {
+                    let (dx, dy) = (px - positions[i].x as f32, py - positions[i].y as f32);
+                    dx * dx + dy * dy - (weights[i] * weights[i]) as f32
+                };
+                let expected = (0..positions.len()).min_by(|&a, &b| power(a).total_cmp(&power(b))).unwrap();
+                let cell = result.cell_of[y * 100 + x] as usize;
+                assert!(cell == expected || power(cell) == power(expected), "pixel ({}, {})", x, y);
+            }
+        }
+        // Heavy sites crowd out light ones
+        assert!(result.cell_areas.iter().enumerate().any(|(i, &a)| i % 7 == 0 && a == 0));
+    }
+
+    /// One cell over a half-black, half-white image
+    #[test]
+    fn test_centroid_weighting() {
+        let image = image::RgbImage::from_fn(40, 20, |x, _| if x < 20 { image::Rgb([0, 0, 0]) } else { image::Rgb([255, 255, 255]) });
+        let sites = [Position::new(20.0, 10.0)];
+        let centroid_x = |weighting| {
+            CpuBackend::with_centroid_weighting(weighting).compute(&image, &sites).unwrap().cell_centroids[0].x
+        };
+        assert_eq!(centroid_x(CentroidWeighting::Uniform), 20.0);
+        // Only the dark half has weight: its center is x = 10
+        assert!((centroid_x(CentroidWeighting::InverseLuminance) - 10.0).abs() < 1e-9);
+        assert!((centroid_x(CentroidWeighting::Luminance) - 30.0).abs() < 1e-9);
+        // No saturated pixels: falls back to the unweighted centroid
+        assert_eq!(centroid_x(CentroidWeighting::Saturation), 20.0);
+    }
+
+    #[test]
+    fn test_compute_tiled_streams_cell_of() {
+        let image = image::RgbImage::from_fn(300, 200, |x, y| image::Rgb([x as u8, y as u8, 7]));
+        let sites = SiteCollection::random(50, 300.0, 200.0, 2).positions();
+        let expected = CpuBackend::new().compute(&image, &sites).unwrap();
+
+        let path = std::env::temp_dir().join(format!("voronoi-cell-of-{}.bin", std::process::id()));
+        let mut backend = CpuBackend::new();
+        backend.streaming_cell_of = Some(path.clone());
+        let tiled = backend.compute_tiled(&image, &sites, 128).unwrap();
+        assert!(tiled.cell_of.is_empty());
+        assert_eq!(tiled.cell_areas, expected.cell_areas);
+
+        let bytes = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let streamed: Vec<i32> = bytes.chunks_exact(4)
+            .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        assert_eq!(streamed, expected.cell_of);
+    }
 }
@@ -0,0 +1,254 @@
+//! Delaunay dual of a pixel Voronoi diagram: cell adjacency, triangles, rendering.
+
+use std::collections::VecDeque;
+use crate::voronoi::scanline_fill;
+use crate::{Position, SiteCollection, VoronoiResult};
+
+/// Half-width of the non-maximum suppression window in `approximate_voronoi_vertices`
+const VERTEX_NMS_RADIUS: i64 = 2;
+
+impl VoronoiResult {
+    /// Neighbors of each cell: cells sharing at least one pixel edge with it,
+    /// sorted ascending. Masked pixels (`cell_of < 0`) are ignored.
+    pub fn cell_adjacency_graph(&self) -> Vec<Vec<usize>> {
+        let n = self.cell_colors.len();
+        let (w, h) = (self.width as usize, self.height as usize);
+        let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut link = |a: i32, b: i32| {
+            if a != b && a >= 0 && b >= 0 && (a as usize) < n && (b as usize) < n {
+                adjacency[a as usize].push(b as usize);
+                adjacency[b as usize].push(a as usize);
+            }
+        };
+        for y in 0..h {
+            for x in 0..w {
+                let c = self.cell_of[y * w + x];
+                if x + 1 < w { link(c, self.cell_of[y * w + x + 1]); }
+                if y + 1 < h { link(c, self.cell_of[(y + 1) * w + x]); }
+            }
+        }
+        for neighbors in &mut adjacency {
+            neighbors.sort_unstable();
+            neighbors.dedup();
+        }
+        adjacency
+    }
+
+    /// Delaunay triangles as site-index triples `[i, j, k]` (`i < j < k`): every
+    /// triple of mutually adjacent cells in `cell_adjacency_graph`.
+    ///
+    /// Derived from the pixel diagram, so nearly co-circular sites can yield
+    /// overlapping triangles where the pixel grid makes both diagonals adjacent.
+    pub fn delaunay_triangulation(&self) -> Vec<[usize; 3]> {
+        let adjacency = self.cell_adjacency_graph();
+        let mut triangles = Vec::new();
+        for (i, neighbors) in adjacency.iter().enumerate() {
+            for &j in neighbors.iter().filter(|&&j| j > i) {
+                for &k in adjacency[j].iter().filter(|&&k| k > j) {
+                    if neighbors.binary_search(&k).is_ok() {
+                        triangles.push([i, j, k]);
+                    }
+                }
+            }
+        }
+        triangles
+    }
+
+    /// Fewest-hops path from `from_cell` to `to_cell` through cells sharing a
+    /// boundary (BFS over `cell_adjacency_graph`), both ends included. `None` if
+    /// either index is out of range or the cells aren't connected.
+    pub fn shortest_path(&self, from_cell: usize, to_cell: usize) -> Option<Vec<usize>> {
+        let n = self.cell_colors.len();
+        if from_cell >= n || to_cell >= n {
+            return None;
+        }
+        let adjacency = self.cell_adjacency_graph();
+        let mut prev: Vec<Option<usize>> = vec![None; n];
+        let mut queue = VecDeque::from([from_cell]);
+        prev[from_cell] = Some(from_cell);
+        while let Some(cell) = queue.pop_front() {
+            if cell == to_cell {
+                let mut path = vec![to_cell];
+                while let Some(&last) = path.last().filter(|&&c| c != from_cell) {
+                    path.push(prev[last]?);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for &next in &adjacency[cell] {
+                if prev[next].is_none() {
+                    prev[next] = Some(cell);
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    /// Render the Delaunay dual: each triangle (corners at `sites`) filled with the
+    /// mean color of its three cells, over the regular Voronoi rendering (which
+    /// shows through outside the triangulation's hull). Pixels are filled by center.
+    pub fn render_dual(&self, sites: &[Position], triangles: &[[usize; 3]]) -> image::RgbImage {
+        let w = self.width as usize;
+        let mut pixels = self.render();
+        for tri in triangles {
+            if tri.iter().any(|&i| i >= sites.len() || i >= self.cell_colors.len()) {
+                continue;
+            }
+            let mut color = [0u8; 3];
+            for (ch, c) in color.iter_mut().enumerate() {
+                let sum: u32 = tri.iter().map(|&i| self.cell_colors[i][ch] as u32).sum();
+                *c = ((sum + 1) / 3) as u8;
+            }
+            let corners = tri.map(|i| (sites[i].x, sites[i].y));
+            scanline_fill(&corners, w, self.height as usize, |y, start, end| {
+                for px in pixels[(y * w + start) * 3..(y * w + end) * 3].chunks_exact_mut(3) {
+                    px.copy_from_slice(&color);
+                }
+            });
+        }
+        image::RgbImage::from_raw(self.width, self.height, pixels)
+            .expect("Buffer size mismatch")
+    }
+}
+
+impl SiteCollection {
+    /// Approximate Voronoi vertices (points equidistant from three or more sites)
+    /// as local maxima of the nearest-site distance, `(pixel center, distance)`,
+    /// sorted by distance descending.
+    ///
+    /// Uses `result.pixel_distances` when present (`CpuBackend::with_distances`),
+    /// otherwise measures from each pixel to its cell's site. A pixel qualifies if
+    /// no pixel in the 5×5 window around it is farther (ties go to the first in
+    /// raster order) and its distance exceeds `min_dist`. Pixels whose window
+    /// leaves the image are skipped, so image corners and edges don't register.
+    pub fn approximate_voronoi_vertices(&self, result: &VoronoiResult, min_dist: f64) -> Vec<(Position, f64)> {
+        let (w, h) = (result.width as i64, result.height as i64);
+        let dist: Vec<f64> = match result.pixel_distances {
+            Some(ref d) => d.iter().map(|&v| v as f64).collect(),
+            None => result.cell_of.iter().enumerate()
+                .map(|(i, &c)| {
+                    let p = Position::new((i as i64 % w) as f64 + 0.5, (i as i64 / w) as f64 + 0.5);
+                    usize::try_from(c).ok()
+                        .and_then(|c| self.sites.get(c))
+                        .map_or(f64::NEG_INFINITY, |site| p.dist(&site.pos))
+                })
+                .collect(),
+        };
+
+        let r = VERTEX_NMS_RADIUS;
+        let mut vertices = Vec::new();
+        for y in r..h - r {
+            for x in r..w - r {
+                let idx = (y * w + x) as usize;
+                let d = dist[idx];
+                if d <= min_dist || result.cell_of[idx] < 0 {
+                    continue;
+                }
+                let is_max = (-r..=r).all(|dy| (-r..=r).all(|dx| {
+                    let other = dist[((y + dy) * w + x + dx) as usize];
+                    let before = (dy, dx) < (0, 0);
+                    if before { other < d } else { other <= d }
+                }));
+                if is_max {
+                    vertices.push((Position::new(x as f64 + 0.5, y as f64 + 0.5), d));
+                }
+            }
+        }
+        vertices.sort_by(|a, b| b.1.total_cmp(&a.1));
+        vertices
+    }
+}
+
+#[cfg(all(test, feature = "cpu"))]
+mod tests {
+    use crate::{ComputeBackend, CpuBackend, Position, SiteCollection};
+
+    #[test]
+    fn test_dual_three_sites() {
+        let (w, h) = (60u32, 60u32);
+        let image = image::RgbImage::from_fn(w, h, |x, y| {
+            if y >= 30 { image::Rgb([30, 200, 60]) } else if x < 30 { image::Rgb([200, 20, 20]) } else { image::Rgb([10, 40, 250]) }
+        });
+        let sites = [Position::new(10.0, 10.0), Position::new(50.0, 10.0), Position::new(30.0, 50.0)];
+        let result = CpuBackend::new().compute(&image, &sites).unwrap();
+
+        assert_eq!(result.cell_adjacency_graph(), vec![vec![1, 2], vec![0, 2], vec![0, 1]]);
+        let triangles = result.delaunay_triangulation();
+        assert_eq!(triangles, vec![[0, 1, 2]]);
+
+        let dual = result.render_dual(&sites, &triangles);
+        assert_eq!((dual.width(), dual.height()), (w, h));
+        assert_eq!(dual.pixels().count(), (w * h) as usize);
+
+        let mean: [u8; 3] = std::array::from_fn(|ch| {
+            let sum: u32 = result.cell_colors.iter().map(|c| c[ch] as u32).sum();
+            ((sum + 1) / 3) as u8
+        });
+        // Centroid of the triangle takes the mean color; corners outside keep their cell color
+        assert_eq!(dual.get_pixel(30, 23).0, mean);
+        assert_eq!(dual.get_pixel(1, 58).0, result.cell_colors[result.cell_of[58 * 60 + 1] as usize]);
+        // Triangle area is 40 * 40 / 2 = 800 px
+        let filled = dual.pixels().filter(|p| p.0 == mean).count();
+        assert!((filled as i64 - 800).abs() <= 40, "{} pixels filled", filled);
+    }
+
+    #[test]
+    fn test_delaunay_triangle_count() {
+        // A planar triangulation of n points with h on the hull has 2n - 2 - h triangles;
+        // pixel-derived triangulations match up to rare degenerate quads
+        let image = image::RgbImage::from_pixel(200, 150, image::Rgb([100, 100, 100]));
+        let sites = SiteCollection::random(40, 200.0, 150.0, 5).positions();
+        let result = CpuBackend::new().compute(&image, &sites).unwrap();
+        let triangles = result.delaunay_triangulation();
+        assert!(triangles.len() <= 2 * 40 - 2 - 3);
+        assert!(triangles.len() >= 40, "only {} triangles", triangles.len());
+    }
+
+    #[test]
+    fn test_voronoi_vertex_at_circumcenter() {
+        let image = image::RgbImage::from_pixel(200, 200, image::Rgb([90, 90, 90]));
+        let center = Position::new(100.5, 100.5);
+        let positions: Vec<Position> = [90.0f64, 210.0, 330.0].iter()
+            .map(|deg| {
+                let a = deg.to_radians();
+                Position::new(center.x + 40.0 * a.cos(), center.y - 40.0 * a.sin())
+            })
+            .collect();
+        let sites = SiteCollection::random_from_positions(positions.clone(), 0);
+
+        let result = CpuBackend::with_distances(true).compute(&image, &positions).unwrap();
+        let vertices = sites.approximate_voronoi_vertices(&result, 10.0);
+        assert_eq!(vertices.len(), 1, "{:?}", vertices);
+        let (pos, dist) = vertices[0];
+        assert!(pos.dist(&center) <= 1.0, "vertex at {:?}", pos);
+        assert!((dist - 40.0).abs() <= 1.0);
+
+        // Same answer measuring from sites when distances weren't recorded
+        let plain = CpuBackend::new().compute(&image, &positions).unwrap();
+        let from_sites = sites.approximate_voronoi_vertices(&plain, 10.0);
+        assert_eq!(from_sites.len(), 1);
+        assert_eq!((from_sites[0].0.x, from_sites[0].0.y), (pos.x, pos.y));
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let image = image::RgbImage::from_pixel(2, 1, image::Rgb([0, 0, 0]));
+        let two = CpuBackend::new().compute(&image, &[Position::new(0.5, 0.5), Position::new(1.5, 0.5)]).unwrap();
+        assert_eq!(two.shortest_path(0, 1), Some(vec![0, 1]));
+
+        let one = CpuBackend::new().compute(&image, &[Position::new(1.0, 0.5)]).unwrap();
+        assert_eq!(one.shortest_path(0, 0), Some(vec![0]));
+        assert_eq!(one.shortest_path(0, 1), None);
+
+        // A row of 5 cells: the path walks every cell in order
+        let image = image::RgbImage::from_pixel(50, 10, image::Rgb([0, 0, 0]));
+        let sites: Vec<Position> = (0..5).map(|i| Position::new(5.0 + 10.0 * i as f64, 5.0)).collect();
+        let row = CpuBackend::new().compute(&image, &sites).unwrap();
+        assert_eq!(row.shortest_path(4, 0), Some(vec![4, 3, 2, 1, 0]));
+
+        // Cells with no pixels are disconnected
+        let hidden = CpuBackend::new().compute(&image, &[sites[0], sites[1], sites[1]]).unwrap();
+        assert_eq!(hidden.shortest_path(0, 2), None);
+    }
+}
@@ -0,0 +1,65 @@
+//! In-memory WebP/AVIF encoding of rendered frames (`webp` / `avif` features).
+
+use crate::VoronoiResult;
+
+/// AVIF encoder speed (1 = slowest/best, 10 = fastest)
+#[cfg(feature = "avif")]
+const AVIF_SPEED: u8 = 8;
+
+impl VoronoiResult {
+    /// Encode the rendered diagram as lossless WebP.
+    ///
+    /// The pure-Rust encoder behind `image` has no lossy mode; flat-color cells
+    /// compress well losslessly anyway.
+    #[cfg(feature = "webp")]
+    pub fn to_webp_bytes(&self) -> crate::Result<Vec<u8>> {
+        use image::ImageEncoder;
+        let mut bytes = Vec::new();
+        image::codecs::webp::WebPEncoder::new_lossless(&mut bytes)
+            .write_image(&self.render(), self.width, self.height, image::ExtendedColorType::Rgb8)?;
+        Ok(bytes)
+    }
+
+    /// Encode the rendered diagram as lossy AVIF at `quality` (1-100, clamped)
+    #[cfg(feature = "avif")]
+    pub fn to_avif_bytes(&self, quality: u8) -> crate::Result<Vec<u8>> {
+        use image::ImageEncoder;
+        let mut bytes = Vec::new();
+        image::codecs::avif::AvifEncoder::new_with_speed_quality(&mut bytes, AVIF_SPEED, quality.clamp(1, 100))
+            .write_image(&self.render(), self.width, self.height, image::ExtendedColorType::Rgb8)?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(all(test, feature = "cpu"))]
+mod tests {
+    use crate::{ComputeBackend, CpuBackend, SiteCollection, VoronoiResult};
+
+    fn result() -> VoronoiResult {
+        let image = image::RgbImage::from_fn(96, 64, |x, y| image::Rgb([(x * 2) as u8, (y * 3) as u8, 90]));
+        let sites = SiteCollection::random(40, 96.0, 64.0, 5);
+        CpuBackend::new().compute(&image, &sites.positions()).unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "webp")]
+    fn test_webp_round_trip() {
+        let result = result();
+        let bytes = result.to_webp_bytes().unwrap();
+        assert_eq!(&bytes[..4], b"RIFF");
+        assert_eq!(&bytes[8..12], b"WEBP");
+        let decoded = image::load_from_memory(&bytes).unwrap().to_rgb8();
+        assert_eq!((decoded.width(), decoded.height()), (96, 64));
+        assert_eq!(decoded.into_raw(), result.render());
+    }
+
+    #[test]
+    #[cfg(feature = "avif")]
+    fn test_avif_container() {
+        // No AVIF decoder without the native dav1d library, so check the container only
+        let result = result();
+        let bytes = result.to_avif_bytes(80).unwrap();
+        assert_eq!(&bytes[4..12], b"ftypavif");
+        assert!(bytes.len() < result.render().len() / 4, "{} bytes", bytes.len());
+    }
+}
@@ -0,0 +1,300 @@
+//! Vector exports of Voronoi cells (boundary tracing, convex hulls, GeoJSON, SVG).
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use crate::{Position, SiteCollection, VoronoiResult};
+use crate::metrics::convex_hull;
+
+/// Pixel-grid vertex (pixel corners; (x, y) is the top-left corner of pixel (x, y))
+type Vertex = (i32, i32);
+
+/// Drop vertices that lie on a straight run between their neighbors
+fn simplify_ring(ring: Vec<Vertex>) -> Vec<Vertex> {
+    let n = ring.len();
+    (0..n)
+        .filter(|&i| {
+            let (px, py) = ring[(i + n - 1) % n];
+            let (x, y) = ring[i];
+            let (nx, ny) = ring[(i + 1) % n];
+            (x - px) * (ny - y) != (y - py) * (nx - x)
+        })
+        .map(|i| ring[i])
+        .collect()
+}
+
+impl VoronoiResult {
+    /// Trace the outline of each cell along pixel edges.
+    ///
+    /// Returns one ring per cell (empty for cells with no pixels), in pixel-corner
+    /// coordinates, clockwise on screen (y down), without repeating the first
+    /// vertex, and with collinear vertices removed. Cells are convex so they have
+    /// a single outer boundary; if pixel-level pinches split it, the longest loop
+    /// is kept.
+    pub fn cell_outlines(&self) -> Vec<Vec<Position>> {
+        let n = self.cell_colors.len();
+        let (w, h) = (self.width as i32, self.height as i32);
+        let cell = |x: i32, y: i32| -> i32 {
+            if x < 0 || y < 0 || x >= w || y >= h { -1 } else { self.cell_of[(y * w + x) as usize] }
+        };
+
+        // Directed boundary edges per cell, clockwise around each pixel
+        let mut edges: Vec<Vec<(Vertex, Vertex)>> = vec![Vec::new(); n];
+        for y in 0..h {
+            for x in 0..w {
+                let c = cell(x, y);
+                if c < 0 || c as usize >= n { continue; }
+                let e = &mut edges[c as usize];
+                if cell(x, y - 1) != c { e.push(((x, y), (x + 1, y))); }
+                if cell(x + 1, y) != c { e.push(((x + 1, y), (x + 1, y + 1))); }
+                if cell(x, y + 1) != c { e.push(((x + 1, y + 1), (x, y + 1))); }
+                if cell(x - 1, y) != c { e.push(((x, y + 1), (x, y))); }
+            }
+        }
+
+        edges.into_iter()
+            .map(|cell_edges| {
+                let mut next: HashMap<Vertex, Vec<Vertex>> = HashMap::new();
+                for (a, b) in cell_edges {
+                    next.entry(a).or_default().push(b);
+                }
+                // Every vertex has equal in/out degree, so walks always close
+                let mut best: Vec<Vertex> = Vec::new();
+                let mut starts: Vec<Vertex> = next.keys().copied().collect();
+                starts.sort_unstable();
+                for start in starts {
+                    while next.get(&start).is_some_and(|v| !v.is_empty()) {
+                        let mut ring = vec![start];
+                        let mut at = start;
+                        loop {
+                            let to = next.get_mut(&at).and_then(|v| v.pop())
+                                .expect("unbalanced boundary edges");
+                            if to == start { break; }
+                            ring.push(to);
+                            at = to;
+                        }
+                        if ring.len() > best.len() {
+                            best = ring;
+                        }
+                    }
+                }
+                simplify_ring(best).into_iter()
+                    .map(|(x, y)| Position::new(x as f64, y as f64))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Approximate each cell by the convex hull of its pixels.
+    ///
+    /// Only boundary pixels (those with a 4-neighbor in another cell or outside
+    /// the image) contribute, so the cost is about the cells' total perimeter plus
+    /// one scan of `cell_of`. Hulls are in pixel-corner coordinates like
+    /// `cell_outlines`, clockwise on screen (y down) from the top-left vertex,
+    /// without collinear vertices; cells with no pixels get an empty hull.
+    pub fn cell_polygon_approx(&self) -> Vec<Vec<Position>> {
+        let n = self.cell_colors.len();
+        let (w, h) = (self.width as i64, self.height as i64);
+        let cell = |x: i64, y: i64| -> i32 {
+            if x < 0 || y < 0 || x >= w || y >= h { -1 } else { self.cell_of[(y * w + x) as usize] }
+        };
+
+        let mut corners: Vec<Vec<Position>> = vec![Vec::new(); n];
+        for y in 0..h {
+            for x in 0..w {
+                let c = cell(x, y);
+                if c < 0 || c as usize >= n { continue; }
+                if cell(x, y - 1) == c && cell(x + 1, y) == c && cell(x, y + 1) == c && cell(x - 1, y) == c {
+                    continue;
+                }
+                let (x, y) = (x as f64, y as f64);
+                corners[c as usize].extend([
+                    Position::new(x, y), Position::new(x + 1.0, y),
+                    Position::new(x + 1.0, y + 1.0), Position::new(x, y + 1.0),
+                ]);
+            }
+        }
+        corners.iter().map(|points| convex_hull(points)).collect()
+    }
+
+    /// Export non-empty cells as a GeoJSON `FeatureCollection` of `Polygon`s,
+    /// traced with `cell_outlines`.
+    ///
+    /// Each feature's properties are `cell_index`, `site_x`, `site_y` (pixel
+    /// coordinates from `sites`), `cell_color` (`"#RRGGBB"`) and `cell_area`
+    /// (pixels). `coordinate_transform` maps pixel coordinates to output
+    /// coordinates (e.g. lon/lat); without it, rings are in pixel coordinates.
+    pub fn to_geojson(
+        &self,
+        sites: &[Position],
+        coordinate_transform: Option<&dyn Fn(f64, f64) -> (f64, f64)>,
+    ) -> String {
+        let transform = |x: f64, y: f64| match coordinate_transform {
+            Some(f) => f(x, y),
+            None => (x, y),
+        };
+
+        let mut out = String::from(r#"{"type":"FeatureCollection","features":["#);
+        let mut first = true;
+        for (i, ring) in self.cell_outlines().iter().enumerate() {
+            if ring.is_empty() { continue; }
+            if !first { out.push(','); }
+            first = false;
+
+            out.push_str(r#"{"type":"Feature","geometry":{"type":"Polygon","coordinates":[["#);
+            // GeoJSON rings repeat the first position at the end
+            for (j, p) in ring.iter().chain(std::iter::once(&ring[0])).enumerate() {
+                let (x, y) = transform(p.x, p.y);
+                let _ = write!(out, "{}[{},{}]", if j > 0 { "," } else { "" }, x, y);
+            }
+            let site = sites.get(i).copied().unwrap_or(self.cell_centroids[i]);
+            let [r, g, b] = self.cell_colors[i];
+            let _ = write!(
+                out,
+                r##"]]}},"properties":{{"cell_index":{},"site_x":{},"site_y":{},"cell_color":"#{:02X}{:02X}{:02X}","cell_area":{}}}}}"##,
+                i, site.x, site.y, r, g, b, self.cell_areas[i],
+            );
+        }
+        out.push_str("]}");
+        out
+    }
+
+    /// Export non-empty cells as an SVG of `<polygon>`s filled with their mean
+    /// colors, traced with `cell_outlines`, so the regions match `to_image`
+    /// pixel for pixel. Each polygon carries `data-cell` and its site's
+    /// `data-site-x` / `data-site-y` (from `sites`, else the cell centroid).
+    pub fn to_svg(&self, sites: &[Position]) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" shape-rendering="crispEdges">"#,
+            w = self.width, h = self.height,
+        );
+        for (i, ring) in self.cell_outlines().iter().enumerate() {
+            if ring.is_empty() { continue; }
+            out.push_str("<polygon points=\"");
+            for (k, p) in ring.iter().enumerate() {
+                let _ = write!(out, "{}{},{}", if k > 0 { " " } else { "" }, p.x, p.y);
+            }
+            let site = sites.get(i).copied().unwrap_or(self.cell_centroids[i]);
+            let [r, g, b] = self.cell_colors[i];
+            let _ = writeln!(
+                out,
+                r##"" fill="#{:02X}{:02X}{:02X}" data-cell="{}" data-site-x="{:.2}" data-site-y="{:.2}"/>"##,
+                r, g, b, i, site.x, site.y,
+            );
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
+impl SiteCollection {
+    /// Convex-hull polygon of each cell in `result`; see
+    /// `VoronoiResult::cell_polygon_approx`.
+    pub fn cell_polygon_approx(&self, result: &VoronoiResult) -> Vec<Vec<Position>> {
+        result.cell_polygon_approx()
+    }
+}
+
+#[cfg(all(test, feature = "cpu"))]
+mod tests {
+    use crate::{ComputeBackend, CpuBackend, Position, SiteCollection};
+
+    #[test]
+    fn test_cell_outlines_two_halves() {
+        let image = image::RgbImage::from_pixel(20, 10, image::Rgb([0, 0, 0]));
+        let sites = [Position::new(5.0, 5.0), Position::new(15.0, 5.0)];
+        let result = CpuBackend::new().compute(&image, &sites).unwrap();
+
+        let outlines = result.cell_outlines();
+        let as_xy = |ring: &[Position]| ring.iter().map(|p| (p.x, p.y)).collect::<Vec<_>>();
+        assert_eq!(as_xy(&outlines[0]), [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]);
+        assert_eq!(outlines[1].len(), 4);
+    }
+
+    #[test]
+    fn test_cell_polygon_approx() {
+        // A single site owns the whole 100×100 image: the hull is its 4 corners
+        let image = image::RgbImage::from_pixel(100, 100, image::Rgb([0, 0, 0]));
+        let sites = SiteCollection::random_from_positions(vec![Position::new(30.0, 60.0)], 0);
+        let result = CpuBackend::new().compute(&image, &sites.positions()).unwrap();
+        let hulls = sites.cell_polygon_approx(&result);
+        let as_xy = |ring: &[Position]| ring.iter().map(|p| (p.x, p.y)).collect::<Vec<_>>();
+        assert_eq!(as_xy(&hulls[0]), [(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0)]);
+
+        // Voronoi cells are convex, so their hulls enclose about their pixel area
+        let image = image::RgbImage::from_pixel(120, 90, image::Rgb([0, 0, 0]));
+        let sites = SiteCollection::random(30, 120.0, 90.0, 5);
+        let result = CpuBackend::new().compute(&image, &sites.positions()).unwrap();
+        for (hull, &area) in sites.cell_polygon_approx(&result).iter().zip(&result.cell_areas) {
+            let twice_area: f64 = (0..hull.len())
+                .map(|i| {
+                    let (a, b) = (hull[i], hull[(i + 1) % hull.len()]);
+                    a.x * b.y - b.x * a.y
+                })
+                .sum();
+            let hull_area = twice_area.abs() / 2.0;
+            assert!(hull_area >= area as f64, "hull {} < area {}", hull_area, area);
+            assert!(hull_area < area as f64 * 1.3 + 10.0, "hull {} vs area {}", hull_area, area);
+        }
+    }
+
+    #[test]
+    fn test_geojson_feature_per_cell() {
+        let image = image::RgbImage::from_fn(64, 48, |x, y| image::Rgb([(x * 4) as u8, (y * 5) as u8, 7]));
+        let sites = SiteCollection::random(25, 64.0, 48.0, 11).positions();
+        let result = CpuBackend::new().compute(&image, &sites).unwrap();
+
+        let to_lonlat = |x: f64, y: f64| (-10.0 + x / 64.0 * 20.0, 50.0 - y / 48.0 * 10.0);
+        let json: serde_json::Value = serde_json::from_str(&result.to_geojson(&sites, Some(&to_lonlat))).unwrap();
+        assert_eq!(json["type"], "FeatureCollection");
+
+        let features = json["features"].as_array().unwrap();
+        let non_empty = result.cell_areas.iter().filter(|&&a| a > 0).count();
+        assert_eq!(features.len(), non_empty);
+
+        let f = &features[0];
+        assert_eq!(f["geometry"]["type"], "Polygon");
+        let ring = f["geometry"]["coordinates"][0].as_array().unwrap();
+        assert_eq!(ring.first(), ring.last());
+        assert!(ring.iter().all(|p| {
+            let (lon, lat) = (p[0].as_f64().unwrap(), p[1].as_f64().unwrap());
+            (-10.0..=10.0).contains(&lon) && (40.0..=50.0).contains(&lat)
+        }));
+        let color = f["properties"]["cell_color"].as_str().unwrap();
+        assert!(color.starts_with('#') && color.len() == 7);
+        assert_eq!(f["properties"]["cell_area"], result.cell_areas[0]);
+    }
+
+    #[test]
+    fn test_svg_polygon_per_cell() {
+        let image = image::RgbImage::from_fn(64, 48, |x, y| image::Rgb([(x * 4) as u8, (y * 5) as u8, 7]));
+        let sites = SiteCollection::random(25, 64.0, 48.0, 11).positions();
+        let result = CpuBackend::new().compute(&image, &sites).unwrap();
+        let svg = result.to_svg(&sites);
+        assert!(svg.starts_with("<svg ") && svg.ends_with("</svg>\n"));
+
+        let polygons: Vec<&str> = svg.lines().filter(|l| l.starts_with("<polygon ")).collect();
+        assert_eq!(polygons.len(), result.cell_areas.iter().filter(|&&a| a > 0).count());
+        // Traced along pixel edges, each polygon covers exactly its cell's pixels
+        for line in polygons {
+            let attr = |name: &str| line.split(&format!(" {}=\"", name)).nth(1).unwrap().split('"').next().unwrap();
+            let i: usize = attr("data-cell").parse().unwrap();
+            let ring: Vec<(f64, f64)> = attr("points").split(' ')
+                .map(|xy| {
+                    let (x, y) = xy.split_once(',').unwrap();
+                    (x.parse().unwrap(), y.parse().unwrap())
+                })
+                .collect();
+            let twice_area: f64 = (0..ring.len())
+                .map(|k| {
+                    let (a, b) = (ring[k], ring[(k + 1) % ring.len()]);
+                    a.0 * b.1 - b.0 * a.1
+                })
+                .sum();
+            assert_eq!(twice_area.abs() / 2.0, result.cell_areas[i] as f64);
+            let [r, g, b] = result.cell_colors[i];
+            assert_eq!(attr("fill"), format!("#{:02X}{:02X}{:02X}", r, g, b));
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! GPU backend that degrades to the CPU when the GPU fails mid-session.
+
+use crate::{CpuBackend, GpuBackend, Position, Result, VoronoiError, VoronoiResult};
+use crate::voronoi::ComputeBackend;
+
+/// Runs a primary (GPU) backend, switching to a `CpuBackend` after
+/// `failure_threshold` consecutive primary errors (e.g. a lost device), and
+/// re-initializing the primary every `retry_interval` frames while switched.
+///
+/// Frames the primary fails are recomputed on the CPU, so callers only see
+/// errors the CPU backend also returns.
+pub struct FallbackBackend<P = GpuBackend> {
+    /// Active primary backend (`None` while running on the fallback)
+    primary: Option<P>,
+    fallback: CpuBackend,
+    consecutive_failures: u32,
+    failure_threshold: u32,
+    /// Frames between primary re-initialization attempts (0 = never retry)
+    retry_interval: usize,
+    /// Frames computed on the fallback since the last switch or retry
+    frames_since_retry: usize,
+    /// Creates a new primary backend, for retries
+    reinit: Box<dyn FnMut() -> Result<P>>,
+    /// Whether the primary computed the most recent frame
+    last_from_primary: bool,
+}
+
+impl FallbackBackend<GpuBackend> {
+    /// Create a GPU backend that falls back to the CPU after `failure_threshold`
+    /// consecutive GPU errors and retries the GPU every `retry_interval` frames.
+    /// If the GPU can't be initialized now, starts on the CPU.
+    pub fn new(failure_threshold: u32, retry_interval: usize) -> Self {
+        Self::with_primary(GpuBackend::new(), GpuBackend::new, failure_threshold, retry_interval)
+    }
+}
+
+impl<P: ComputeBackend> FallbackBackend<P> {
+    /// Create from an already-initialized primary (an `Err` starts on the CPU) and
+    /// a function to re-create it on retries.
+    pub fn with_primary(
+        primary: Result<P>,
+        reinit: impl FnMut() -> Result<P> + 'static,
+        failure_threshold: u32,
+        retry_interval: usize,
+    ) -> Self {
+        Self {
+            primary: primary.ok(),
+            fallback: CpuBackend::new(),
+            consecutive_failures: 0,
+            failure_threshold: failure_threshold.max(1),
+            retry_interval,
+            frames_since_retry: 0,
+            reinit: Box::new(reinit),
+            last_from_primary: false,
+        }
+    }
+
+    /// Whether frames are currently computed on the CPU fallback
+    pub fn using_fallback(&self) -> bool {
+        self.primary.is_none()
+    }
+
+    /// The active primary backend, if not switched to the fallback
+    pub fn primary(&self) -> Option<&P> {
+        self.primary.as_ref()
+    }
+
+    /// Re-create the primary if `retry_interval` frames have passed on the fallback
+    fn maybe_retry(&mut self) {
+        if self.primary.is_some() || self.retry_interval == 0 {
+            return;
+        }
+        self.frames_since_retry += 1;
+        if self.frames_since_retry >= self.retry_interval {
+            self.frames_since_retry = 0;
+            if let Ok(primary) = (self.reinit)() {
+                self.primary = Some(primary);
+                self.consecutive_failures = 0;
+            }
+        }
+    }
+
+    /// Run `compute` on the primary if it's active, falling back to the CPU (and
+    /// counting the failure) if it errs
+    fn compute_with(
+        &mut self,
+        sites: &[Position],
+        mut compute: impl FnMut(&mut dyn ComputeBackend) -> Result<VoronoiResult>,
+    ) -> Result<VoronoiResult> {
+        if sites.is_empty() {
+            return Err(VoronoiError::NoSites);
+        }
+        self.maybe_retry();
+        self.last_from_primary = false;
+        if let Some(primary) = self.primary.as_mut() {
+            match compute(primary) {
+                Ok(result) => {
+                    self.consecutive_failures = 0;
+                    self.last_from_primary = true;
+                    return Ok(result);
+                }
+                Err(_) => {
+                    self.consecutive_failures += 1;
+                    if self.consecutive_failures >= self.failure_threshold {
+                        self.primary = None;
+                        self.frames_since_retry = 0;
+                    }
+                }
+            }
+        }
+        compute(&mut self.fallback)
+    }
+}
+
+impl<P: ComputeBackend> ComputeBackend for FallbackBackend<P> {
+    fn compute(
+        &mut self,
+        image: &image::RgbImage,
+        sites: &[Position],
+    ) -> Result<VoronoiResult> {
+        self.compute_with(sites, |backend| backend.compute(image, sites))
+    }
+
+    fn compute_dynamic(
+        &mut self,
+        image: &image::DynamicImage,
+        sites: &[Position],
+    ) -> Result<VoronoiResult> {
+        self.compute_with(sites, |backend| backend.compute_dynamic(image, sites))
+    }
+
+    fn last_gpu_time_ns(&self) -> Option<u64> {
+        self.primary.as_ref()
+            .filter(|_| self.last_from_primary)
+            .and_then(|p| p.last_gpu_time_ns())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Primary that fails every compute, like a GPU after device loss
+    struct LostDevice {
+        calls: Rc<Cell<u32>>,
+    }
+
+    impl ComputeBackend for LostDevice {
+        fn compute(&mut self, _image: &image::RgbImage, _sites: &[Position]) -> Result<VoronoiResult> {
+            self.calls.set(self.calls.get() + 1);
+            Err(VoronoiError::Gpu("Buffer map failed".into()))
+        }
+    }
+
+    #[test]
+    fn test_fallback_after_consecutive_failures() {
+        let image = image::RgbImage::from_fn(40, 30, |x, y| image::Rgb([x as u8 * 6, y as u8 * 8, 50]));
+        let sites = [Position::new(10.0, 10.0), Position::new(30.0, 20.0)];
+        let expected = CpuBackend::new().compute(&image, &sites).unwrap();
+
+        let calls = Rc::new(Cell::new(0));
+        let (primary_calls, reinit_calls) = (calls.clone(), calls.clone());
+        let mut backend = FallbackBackend::with_primary(
+            Ok(LostDevice { calls: primary_calls }),
+            move || Ok(LostDevice { calls: reinit_calls.clone() }),
+            3,
+            5,
+        );
+
+        // Failing frames are recomputed on the CPU; the 3rd failure switches over
+        for frame in 1..=3 {
+            let result = backend.compute(&image, &sites).unwrap();
+            assert_eq!(result.cell_of, expected.cell_of);
+            assert_eq!(result.cell_colors, expected.cell_colors);
+            assert_eq!(calls.get(), frame);
+        }
+        assert!(backend.using_fallback());
+
+        // The GPU isn't touched again until the retry interval has passed
+        for _ in 0..4 {
+            assert_eq!(backend.compute(&image, &sites).unwrap().cell_areas, expected.cell_areas);
+        }
+        assert_eq!(calls.get(), 3);
+        backend.compute(&image, &sites).unwrap();
+        assert_eq!(calls.get(), 4);
+        assert!(!backend.using_fallback());
+        assert_eq!(backend.last_gpu_time_ns(), None);
+    }
+}
@@ -0,0 +1,145 @@
+//! Force-based site dynamics (repulsion + springs).
+
+use crate::{Position, SiteCollection};
+
+impl SiteCollection {
+    /// Move sites by combined repulsion and spring forces (overdamped: each site is
+    /// displaced by `force * dt`; velocities are untouched).
+    ///
+    /// Repulsion pushes apart every pair of sites closer than `spring_rest`, with
+    /// magnitude `repulsion_strength * (spring_rest - d)`; nearby pairs come from a
+    /// spatial grid. Springs pull or push each `adjacency` pair toward distance
+    /// `spring_rest` with magnitude `spring_k * (d - spring_rest)`. All forces are
+    /// computed from the same snapshot (in parallel) before any site moves, so the
+    /// result doesn't depend on site order. Coincident sites exert no force on each
+    /// other. Positions aren't clamped to the image.
+    pub fn repulsion_spring_step(
+        &mut self,
+        adjacency: &[(usize, usize)],
+        repulsion_strength: f64,
+        spring_k: f64,
+        spring_rest: f64,
+        dt: f64,
+    ) {
+        let n = self.sites.len();
+        if n == 0 {
+            return;
+        }
+        let positions: Vec<Position> = self.positions();
+
+        let mut springs: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for &(a, b) in adjacency {
+            if a != b && a < n && b < n {
+                springs[a].push(b);
+                springs[b].push(a);
+            }
+        }
+
+        // Grid with cell size = repulsion radius, so repelling sites are in the 3x3 block
+        let radius = spring_rest.max(0.0);
+        let repel = repulsion_strength != 0.0 && radius > 0.0;
+        let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for p in &positions {
+            min_x = min_x.min(p.x);
+            min_y = min_y.min(p.y);
+            max_x = max_x.max(p.x);
+            max_y = max_y.max(p.y);
+        }
+        let cell = if repel { radius } else { 1.0 };
+        let cols = (((max_x - min_x) / cell) as usize + 1).min(n.max(1) * 4);
+        let rows = (((max_y - min_y) / cell) as usize + 1).min(n.max(1) * 4);
+        let cell_of = |p: &Position| -> (usize, usize) {
+            (
+                (((p.x - min_x) / cell) as usize).min(cols - 1),
+                (((p.y - min_y) / cell) as usize).min(rows - 1),
+            )
+        };
+        let mut grid: Vec<Vec<usize>> = vec![Vec::new(); if repel { cols * rows } else { 0 }];
+        if repel {
+            for (i, p) in positions.iter().enumerate() {
+                let (cx, cy) = cell_of(p);
+                grid[cy * cols + cx].push(i);
+            }
+        }
+
+        // Force on site i; positive magnitude pushes it away from the other site
+        let force = |i: usize| -> (f64, f64) {
+            let p = positions[i];
+            let (mut fx, mut fy) = (0.0, 0.0);
+            let mut push = |j: usize, magnitude: f64| {
+                let (dx, dy) = (p.x - positions[j].x, p.y - positions[j].y);
+                let d = (dx * dx + dy * dy).sqrt();
+                if d > 0.0 {
+                    fx += magnitude * dx / d;
+                    fy += magnitude * dy / d;
+                }
+            };
+            if repel {
+                let (cx, cy) = cell_of(&p);
+                for gy in cy.saturating_sub(1)..(cy + 2).min(rows) {
+                    for gx in cx.saturating_sub(1)..(cx + 2).min(cols) {
+                        for &j in &grid[gy * cols + gx] {
+                            let d = p.dist(&positions[j]);
+                            if j != i && d < radius {
+                                push(j, repulsion_strength * (radius - d));
+                            }
+                        }
+                    }
+                }
+            }
+            for &j in &springs[i] {
+                let d = p.dist(&positions[j]);
+                push(j, spring_k * (spring_rest - d));
+            }
+            (fx, fy)
+        };
+
+        #[cfg(feature = "parallel")]
+        let forces: Vec<(f64, f64)> = {
+            use rayon::prelude::*;
+            (0..n).into_par_iter().map(force).collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let forces: Vec<(f64, f64)> = (0..n).map(force).collect();
+
+        for (site, (fx, fy)) in self.sites.iter_mut().zip(forces) {
+            site.pos.x += fx * dt;
+            site.pos.y += fy * dt;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Position, SiteCollection};
+
+    #[test]
+    fn test_repulsion_spring_step_is_stable() {
+        let mut sites = SiteCollection::random(200, 200.0, 200.0, 12);
+        // Chain springs between consecutive sites, plus a ring closure
+        let adjacency: Vec<(usize, usize)> = (0..200).map(|i| (i, (i + 1) % 200)).collect();
+
+        let spread = |sites: &SiteCollection| {
+            let ps = sites.positions();
+            let n = ps.len() as f64;
+            let (mx, my) = ps.iter().fold((0.0, 0.0), |(x, y), p| (x + p.x / n, y + p.y / n));
+            ps.iter().map(|p| p.dist(&Position::new(mx, my))).fold(0.0, f64::max)
+        };
+        let initial = spread(&sites);
+        for _ in 0..100 {
+            sites.repulsion_spring_step(&adjacency, 1.0, 0.5, 20.0, 1.0 / 30.0);
+        }
+        assert!(sites.positions().iter().all(|p| p.x.is_finite() && p.y.is_finite()));
+        // Bounded: repulsion only acts within spring_rest, springs only stretch to rest
+        assert!(spread(&sites) < initial + 200.0, "spread {} -> {}", initial, spread(&sites));
+
+        // Two sites on one spring settle at the rest length
+        let mut pair = SiteCollection::random_from_positions(vec![Position::new(0.0, 0.0), Position::new(5.0, 0.0)], 0);
+        for _ in 0..300 {
+            pair.repulsion_spring_step(&[(0, 1)], 1.0, 0.5, 20.0, 1.0 / 30.0);
+        }
+        let d = pair.sites[0].pos.dist(&pair.sites[1].pos);
+        assert!((d - 20.0).abs() < 0.01, "pair distance {}", d);
+    }
+}
@@ -6,6 +6,9 @@
 use crate::{Position, Rgb, Result, VoronoiError, VoronoiResult};
 use crate::voronoi::ComputeBackend;
 use bytemuck::{Pod, Zeroable};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
 use wgpu::util::DeviceExt;
 
 /// Per-site data for compute shader
@@ -30,6 +33,40 @@ pub struct GpuBackend {
     queue: wgpu::Queue,
     grid_pipeline: wgpu::ComputePipeline,
     grid_bind_group_layout: wgpu::BindGroupLayout,
+    timestamps: Option<TimestampQueries>,
+    last_gpu_time_ns: Option<u64>,
+    /// Frames queued by `submit_frame`, awaiting `retrieve_frame`
+    queued: HashMap<u64, QueuedFrame>,
+    next_token: u64,
+    /// Texture `compute` also renders cell indices into (see `with_shared_texture`)
+    shared_texture: Option<Arc<wgpu::Texture>>,
+    /// Render pipeline copying cell indices into a texture, built on first use
+    texture_pipeline: OnceLock<(wgpu::RenderPipeline, wgpu::BindGroupLayout)>,
+}
+
+/// Opaque handle to a frame queued with `GpuBackend::submit_frame`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FrameToken(u64);
+
+/// Result of mapping a readback buffer, sent from wgpu's `map_async` callback
+type MapResult = std::result::Result<(), wgpu::BufferAsyncError>;
+
+/// A submitted frame's staging buffer, with the inputs for the CPU-side pass
+struct QueuedFrame {
+    staging_buffer: wgpu::Buffer,
+    mapped: Receiver<MapResult>,
+    image: image::RgbImage,
+    sites: Vec<Position>,
+}
+
+/// Timestamp query set and buffers for GPU-side profiling.
+/// Queries: 0 = compute pass start, 1 = compute pass end, 2 = after output copy
+/// (only when the adapter supports timestamps inside encoders).
+struct TimestampQueries {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    count: u32,
 }
 
 #[repr(C)]
@@ -48,6 +85,13 @@ struct Uniforms {
 impl GpuBackend {
     /// Create a new GPU backend
     pub fn new() -> Result<Self> {
+        Self::with_timestamps(false)
+    }
+
+    /// Create a GPU backend, optionally recording timestamp queries around each
+    /// compute dispatch (see `last_gpu_time_ns`). Timestamps are silently left off
+    /// if the adapter lacks `TIMESTAMP_QUERY`.
+    pub fn with_timestamps(timestamps: bool) -> Result<Self> {
         let instance = wgpu::Instance::default();
 
         let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
@@ -57,10 +101,20 @@ impl GpuBackend {
         }))
         .ok_or_else(|| VoronoiError::Gpu("No suitable GPU adapter found".into()))?;
 
+        let supported = adapter.features();
+        let mut required_features = wgpu::Features::empty();
+        if timestamps && supported.contains(wgpu::Features::TIMESTAMP_QUERY) {
+            required_features |= wgpu::Features::TIMESTAMP_QUERY;
+            // Needed to timestamp the output copy, which happens outside any pass
+            if supported.contains(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS) {
+                required_features |= wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS;
+            }
+        }
+
         let (device, queue) = pollster::block_on(adapter.request_device(
             &wgpu::DeviceDescriptor {
                 label: Some("Voronoi GPU"),
-                required_features: wgpu::Features::empty(),
+                required_features,
                 required_limits: wgpu::Limits::default(),
                 memory_hints: Default::default(),
             },
@@ -134,13 +188,67 @@ impl GpuBackend {
             cache: None,
         });
 
+        let timestamps = required_features.contains(wgpu::Features::TIMESTAMP_QUERY).then(|| {
+            let count = if required_features.contains(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS) { 3 } else { 2 };
+            let size = count as u64 * wgpu::QUERY_SIZE as u64;
+            TimestampQueries {
+                query_set: device.create_query_set(&wgpu::QuerySetDescriptor {
+                    label: Some("Voronoi Timestamps"),
+                    ty: wgpu::QueryType::Timestamp,
+                    count,
+                }),
+                resolve_buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Timestamp Resolve Buffer"),
+                    size,
+                    usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                    mapped_at_creation: false,
+                }),
+                readback_buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Timestamp Readback Buffer"),
+                    size,
+                    usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                    mapped_at_creation: false,
+                }),
+                count,
+            }
+        });
+
         Ok(Self {
             device,
             queue,
             grid_pipeline,
             grid_bind_group_layout,
+            timestamps,
+            last_gpu_time_ns: None,
+            queued: HashMap::new(),
+            next_token: 0,
+            shared_texture: None,
+            texture_pipeline: OnceLock::new(),
         })
     }
+
+    /// Have `compute` also render each frame's cell indices into `texture`, as
+    /// `compute_into_texture` does. The texture must come from this backend's
+    /// `device()`; see `compute_into_texture` for its requirements.
+    pub fn with_shared_texture(mut self, texture: Arc<wgpu::Texture>) -> Self {
+        self.shared_texture = Some(texture);
+        self
+    }
+
+    /// The wgpu device, e.g. for creating textures to pass to `compute_into_texture`
+    pub fn device(&self) -> &wgpu::Device {
+        &self.device
+    }
+
+    /// The wgpu queue this backend submits work on
+    pub fn queue(&self) -> &wgpu::Queue {
+        &self.queue
+    }
+
+    /// Whether timestamp queries are active (requested and supported by the adapter)
+    pub fn timestamps_enabled(&self) -> bool {
+        self.timestamps.is_some()
+    }
 }
 
 const GRID_SHADER: &str = r#"
@@ -246,6 +354,29 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
 }
 "#;
 
+const TEXTURE_SHADER: &str = r#"
+struct Dims {
+    width: u32,
+    height: u32,
+}
+
+@group(0) @binding(0) var<uniform> dims: Dims;
+@group(0) @binding(1) var<storage, read> cells: array<i32>;
+
+// One triangle covering the viewport
+@vertex
+fn vs_main(@builtin(vertex_index) i: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((i << 1u) & 2u), f32(i & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<i32> {
+    let idx = u32(pos.y) * dims.width + u32(pos.x);
+    return vec4<i32>(cells[idx], 0, 0, 0);
+}
+"#;
+
 impl GpuBackend {
     /// Build flattened grid for GPU upload. Returns (offsets, indices, cols, rows, cell_w, cell_h).
     fn build_grid_flat(
@@ -286,16 +417,56 @@ impl GpuBackend {
     }
 }
 
-impl ComputeBackend for GpuBackend {
-    fn compute(
-        &mut self,
+impl GpuBackend {
+    /// Dispatch the nearest-site shader and copy its output to a staging buffer,
+    /// requesting a read mapping. With `timestamps`, also records and resolves the
+    /// timestamp queries (if enabled). Returns the staging buffer and the channel
+    /// its mapping result arrives on once the device is polled.
+    fn submit(
+        &self,
         image: &image::RgbImage,
         sites: &[Position],
-    ) -> Result<VoronoiResult> {
-        if sites.is_empty() {
-            return Err(VoronoiError::NoSites);
+        timestamps: bool,
+    ) -> (wgpu::Buffer, Receiver<MapResult>) {
+        let (mut encoder, output_buffer) = self.encode_dispatch(image, sites, timestamps);
+        let output_buffer_size = output_buffer.size();
+
+        // Create staging buffer for reading back
+        let staging_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Staging Buffer"),
+            size: output_buffer_size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        // Copy output to staging buffer
+        encoder.copy_buffer_to_buffer(&output_buffer, 0, &staging_buffer, 0, output_buffer_size);
+        if let Some(ts) = self.timestamps.as_ref().filter(|_| timestamps) {
+            if ts.count > 2 {
+                encoder.write_timestamp(&ts.query_set, 2);
+            }
+            encoder.resolve_query_set(&ts.query_set, 0..ts.count, &ts.resolve_buffer, 0);
+            encoder.copy_buffer_to_buffer(&ts.resolve_buffer, 0, &ts.readback_buffer, 0, ts.resolve_buffer.size());
         }
+        self.queue.submit(std::iter::once(encoder.finish()));
 
+        let (tx, rx) = mpsc::channel();
+        staging_buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            // The receiver is gone if the frame was abandoned
+            let _ = tx.send(result);
+        });
+        (staging_buffer, rx)
+    }
+
+    /// Record the nearest-site compute pass (with timestamp writes if `timestamps`
+    /// and enabled) into a new encoder. Returns the encoder and the storage buffer
+    /// the pass writes per-pixel cell indices (`i32`) to.
+    fn encode_dispatch(
+        &self,
+        image: &image::RgbImage,
+        sites: &[Position],
+        timestamps: bool,
+    ) -> (wgpu::CommandEncoder, wgpu::Buffer) {
         let width = image.width();
         let height = image.height();
         let num_pixels = (width * height) as usize;
@@ -354,14 +525,6 @@ impl ComputeBackend for GpuBackend {
             usage: wgpu::BufferUsages::STORAGE,
         });
 
-        // Create staging buffer for reading back
-        let staging_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Staging Buffer"),
-            size: output_buffer_size,
-            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
-            mapped_at_creation: false,
-        });
-
         // Create bind group
         let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("Voronoi Grid Bind Group"),
@@ -395,36 +558,213 @@ impl ComputeBackend for GpuBackend {
             label: Some("Voronoi Encoder"),
         });
         {
+            let timestamp_writes = self.timestamps.as_ref().filter(|_| timestamps).map(|ts| wgpu::ComputePassTimestampWrites {
+                query_set: &ts.query_set,
+                beginning_of_pass_write_index: Some(0),
+                end_of_pass_write_index: Some(1),
+            });
             let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
                 label: Some("Voronoi Grid Compute Pass"),
-                timestamp_writes: None,
+                timestamp_writes,
             });
             compute_pass.set_pipeline(&self.grid_pipeline);
             compute_pass.set_bind_group(0, &bind_group, &[]);
-            let workgroups_x = (width + 15) / 16;
-            let workgroups_y = (height + 15) / 16;
+            let workgroups_x = width.div_ceil(16);
+            let workgroups_y = height.div_ceil(16);
             compute_pass.dispatch_workgroups(workgroups_x, workgroups_y, 1);
         }
+        (encoder, output_buffer)
+    }
 
-        // Copy output to staging buffer
-        encoder.copy_buffer_to_buffer(&output_buffer, 0, &staging_buffer, 0, output_buffer_size);
-        self.queue.submit(std::iter::once(encoder.finish()));
+    /// Like `compute`, but also renders the per-pixel cell indices into
+    /// `output_texture`, and takes `cell_of` from a readback of that texture.
+    ///
+    /// The texture must be created on this backend's `device()`, match the
+    /// image's size, have format `R32Sint` (texel = cell index), and have
+    /// `RENDER_ATTACHMENT | COPY_SRC` usage. The work is submitted on
+    /// `output_queue` (normally `queue()`). Colors, areas, and centroids are
+    /// still accumulated on the CPU.
+    pub fn compute_into_texture(
+        &self,
+        image: &image::RgbImage,
+        sites: &[Position],
+        output_texture: &wgpu::Texture,
+        output_queue: &wgpu::Queue,
+    ) -> Result<VoronoiResult> {
+        if sites.is_empty() {
+            return Err(VoronoiError::NoSites);
+        }
+        let (width, height) = image.dimensions();
+        let required = wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC;
+        if output_texture.format() != wgpu::TextureFormat::R32Sint
+            || !output_texture.usage().contains(required)
+            || (output_texture.width(), output_texture.height()) != (width, height)
+        {
+            return Err(VoronoiError::Gpu(format!(
+                "output texture must be {}x{} R32Sint with RENDER_ATTACHMENT | COPY_SRC usage \
+                 (got {}x{} {:?}, {:?})",
+                width, height, output_texture.width(), output_texture.height(),
+                output_texture.format(), output_texture.usage(),
+            )));
+        }
+
+        let (mut encoder, output_buffer) = self.encode_dispatch(image, sites, false);
+        let (pipeline, layout) = self.texture_pipeline.get_or_init(|| Self::create_texture_pipeline(&self.device));
+        let dims = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Texture Dims Buffer"),
+            contents: bytemuck::cast_slice(&[width, height]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Voronoi Texture Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: dims.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: output_buffer.as_entire_binding() },
+            ],
+        });
+        let view = output_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Voronoi Texture Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+
+        // Texture copies need rows padded to COPY_BYTES_PER_ROW_ALIGNMENT
+        let row_bytes = width * 4;
+        let padded_row_bytes = row_bytes.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let staging_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Texture Staging Buffer"),
+            size: padded_row_bytes as u64 * height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        encoder.copy_texture_to_buffer(
+            output_texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &staging_buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_bytes),
+                    rows_per_image: Some(height),
+                },
+            },
+            output_texture.size(),
+        );
+        output_queue.submit(std::iter::once(encoder.finish()));
 
-        // Read back results
-        let buffer_slice = staging_buffer.slice(..);
-        let (tx, rx) = std::sync::mpsc::channel();
-        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
-            tx.send(result).unwrap();
+        let (tx, rx) = mpsc::channel();
+        staging_buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
         });
         self.device.poll(wgpu::Maintain::Wait);
         rx.recv().unwrap().map_err(|e| VoronoiError::Gpu(format!("Buffer map failed: {:?}", e)))?;
+        let data = staging_buffer.slice(..).get_mapped_range();
+        let cell_of: Vec<i32> = data.chunks_exact(padded_row_bytes as usize)
+            .flat_map(|row| bytemuck::cast_slice::<u8, i32>(&row[..row_bytes as usize]).iter().copied())
+            .collect();
+        drop(data);
+        staging_buffer.unmap();
+        Ok(Self::assemble_result(image, sites, cell_of))
+    }
+
+    /// Fullscreen-triangle pipeline writing the compute pass's cell indices into
+    /// an `R32Sint` render target
+    fn create_texture_pipeline(device: &wgpu::Device) -> (wgpu::RenderPipeline, wgpu::BindGroupLayout) {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Voronoi Texture Bind Group Layout"),
+            entries: &[
+                // 0: Texture dimensions
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // 1: Cell indices from the compute pass
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Voronoi Texture Shader"),
+            source: wgpu::ShaderSource::Wgsl(TEXTURE_SHADER.into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Voronoi Texture Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Voronoi Texture Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: wgpu::TextureFormat::R32Sint,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+        (pipeline, layout)
+    }
 
-        let data = buffer_slice.get_mapped_range();
+    /// Read back a mapped staging buffer as per-pixel cell indices
+    fn read_cell_of(staging_buffer: &wgpu::Buffer) -> Vec<i32> {
+        let data = staging_buffer.slice(..).get_mapped_range();
         let cell_of: Vec<i32> = bytemuck::cast_slice(&data).to_vec();
         drop(data);
         staging_buffer.unmap();
+        cell_of
+    }
 
+    /// CPU-side pass over the GPU's cell assignments: colors, areas, centroids,
+    /// and the farthest point
+    #[allow(clippy::manual_checked_ops)]
+    fn assemble_result(image: &image::RgbImage, sites: &[Position], cell_of: Vec<i32>) -> VoronoiResult {
         // CPU-side: accumulate colors, centroids, farthest point
+        let width = image.width();
+        let num_sites = sites.len();
         let img_raw = image.as_raw();
         let mut r_sums = vec![0u64; num_sites];
         let mut g_sums = vec![0u64; num_sites];
@@ -481,14 +821,217 @@ impl ComputeBackend for GpuBackend {
             }
         }
 
-        Ok(VoronoiResult {
+        VoronoiResult {
             cell_of,
             cell_colors,
             cell_areas,
             cell_centroids,
             farthest_point,
+            pixel_distances: None,
+            second_cell_of: None,
+            second_distances: None,
             width,
-            height,
+            height: image.height(),
+        }
+    }
+
+    /// Queue a frame without waiting for the GPU; collect it with `retrieve_frame`
+    /// (or `await_frame`), leaving the CPU free in between. Several frames may be in
+    /// flight. Queued frames don't record timestamp queries.
+    pub fn submit_frame(&mut self, image: &image::RgbImage, sites: &[Position]) -> Result<FrameToken> {
+        if sites.is_empty() {
+            return Err(VoronoiError::NoSites);
+        }
+        let (staging_buffer, mapped) = self.submit(image, sites, false);
+        let token = self.next_token;
+        self.next_token += 1;
+        self.queued.insert(token, QueuedFrame { staging_buffer, mapped, image: image.clone(), sites: sites.to_vec() });
+        Ok(FrameToken(token))
+    }
+
+    /// The frame for `token` if the GPU has finished it (polling the device without
+    /// blocking), or `None` if it's still in flight. Each token yields a result
+    /// once; unknown or already-retrieved tokens give an error.
+    pub fn retrieve_frame(&mut self, token: FrameToken) -> Option<Result<VoronoiResult>> {
+        let Some(frame) = self.queued.get(&token.0) else {
+            return Some(Err(VoronoiError::Gpu(format!("unknown frame token {}", token.0))));
+        };
+        self.device.poll(wgpu::Maintain::Poll);
+        let mapped = match frame.mapped.try_recv() {
+            Err(TryRecvError::Empty) => return None,
+            Ok(mapped) => mapped.map_err(|e| VoronoiError::Gpu(format!("Buffer map failed: {:?}", e))),
+            Err(TryRecvError::Disconnected) => Err(VoronoiError::Gpu("Buffer map callback dropped".into())),
+        };
+        let frame = self.queued.remove(&token.0)?;
+        Some(mapped.map(|()| {
+            let cell_of = Self::read_cell_of(&frame.staging_buffer);
+            Self::assemble_result(&frame.image, &frame.sites, cell_of)
+        }))
+    }
+
+    /// Block until the frame for `token` is done; see `retrieve_frame`
+    pub fn await_frame(&mut self, token: FrameToken) -> Result<VoronoiResult> {
+        loop {
+            if let Some(result) = self.retrieve_frame(token) {
+                return result;
+            }
+            self.device.poll(wgpu::Maintain::Wait);
+        }
+    }
+}
+
+impl ComputeBackend for GpuBackend {
+    fn last_gpu_time_ns(&self) -> Option<u64> {
+        self.last_gpu_time_ns
+    }
+
+    fn compute(
+        &mut self,
+        image: &image::RgbImage,
+        sites: &[Position],
+    ) -> Result<VoronoiResult> {
+        if sites.is_empty() {
+            return Err(VoronoiError::NoSites);
+        }
+        if let Some(texture) = &self.shared_texture {
+            return self.compute_into_texture(image, sites, texture, &self.queue);
+        }
+
+        let (staging_buffer, rx) = self.submit(image, sites, true);
+        let ts_rx = self.timestamps.as_ref().map(|ts| {
+            let (ts_tx, ts_rx) = mpsc::channel();
+            ts.readback_buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+                ts_tx.send(result).unwrap();
+            });
+            ts_rx
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        rx.recv().unwrap().map_err(|e| VoronoiError::Gpu(format!("Buffer map failed: {:?}", e)))?;
+
+        // GPU execution time: compute start to copy end (or compute end without in-encoder timestamps)
+        self.last_gpu_time_ns = None;
+        if let (Some(ts), Some(ts_rx)) = (&self.timestamps, ts_rx) {
+            ts_rx.recv().unwrap()
+                .map_err(|e| VoronoiError::Gpu(format!("Timestamp map failed: {:?}", e)))?;
+            let data = ts.readback_buffer.slice(..).get_mapped_range();
+            let ticks: &[u64] = bytemuck::cast_slice(&data);
+            let elapsed = ticks[ts.count as usize - 1].saturating_sub(ticks[0]);
+            self.last_gpu_time_ns = Some((elapsed as f64 * self.queue.get_timestamp_period() as f64) as u64);
+            drop(data);
+            ts.readback_buffer.unmap();
+        }
+
+        let cell_of = Self::read_cell_of(&staging_buffer);
+        Ok(Self::assemble_result(image, sites, cell_of))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_submit_and_retrieve_frame() {
+        // Skip on machines without a usable adapter
+        let Ok(mut backend) = GpuBackend::new() else { return };
+        let image = image::RgbImage::from_fn(64, 48, |x, y| image::Rgb([x as u8 * 4, y as u8 * 5, 80]));
+        let sites = [Position::new(10.0, 10.0), Position::new(50.0, 20.0), Position::new(30.0, 40.0)];
+        let expected = backend.compute(&image, &sites).unwrap();
+
+        let token = backend.submit_frame(&image, &sites).unwrap();
+        let other = backend.submit_frame(&image, &sites[..2]).unwrap();
+        assert_ne!(token, other);
+        let result = loop {
+            if let Some(result) = backend.retrieve_frame(token) {
+                break result.unwrap();
+            }
+            std::thread::yield_now();
+        };
+        assert_eq!(result.cell_of, expected.cell_of);
+        assert_eq!(result.cell_colors, expected.cell_colors);
+        assert_eq!(result.cell_areas, expected.cell_areas);
+        assert!(matches!(backend.retrieve_frame(token), Some(Err(_))));
+
+        assert_eq!(backend.await_frame(other).unwrap().cell_areas.len(), 2);
+    }
+
+    fn index_texture(backend: &GpuBackend, width: u32, height: u32, format: wgpu::TextureFormat) -> wgpu::Texture {
+        backend.device().create_texture(&wgpu::TextureDescriptor {
+            label: Some("Test Index Texture"),
+            size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
         })
     }
+
+    /// Read an `R32Sint` texture back, independently of `compute_into_texture`'s own readback
+    fn read_texture(backend: &GpuBackend, texture: &wgpu::Texture) -> Vec<i32> {
+        let (width, height) = (texture.width(), texture.height());
+        let padded = (width * 4).div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let buffer = backend.device().create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: (padded * height) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = backend.device().create_command_encoder(&Default::default());
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout { offset: 0, bytes_per_row: Some(padded), rows_per_image: None },
+            },
+            texture.size(),
+        );
+        backend.queue().submit(std::iter::once(encoder.finish()));
+        buffer.slice(..).map_async(wgpu::MapMode::Read, |r| r.unwrap());
+        backend.device().poll(wgpu::Maintain::Wait);
+        let data = buffer.slice(..).get_mapped_range();
+        data.chunks_exact(padded as usize)
+            .flat_map(|row| bytemuck::cast_slice::<u8, i32>(&row[..width as usize * 4]).to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn test_compute_into_texture() {
+        let Ok(mut backend) = GpuBackend::new() else { return };
+        // Width not a multiple of 64 texels, so texture rows are padded on readback
+        let image = image::RgbImage::from_fn(70, 45, |x, y| image::Rgb([x as u8 * 3, y as u8 * 5, 80]));
+        let sites = [Position::new(10.0, 10.0), Position::new(60.0, 20.0), Position::new(30.0, 40.0)];
+        let expected = backend.compute(&image, &sites).unwrap();
+
+        let texture = index_texture(&backend, 70, 45, wgpu::TextureFormat::R32Sint);
+        let result = backend.compute_into_texture(&image, &sites, &texture, backend.queue()).unwrap();
+        assert_eq!(result.cell_of, expected.cell_of);
+        assert_eq!(result.cell_colors, expected.cell_colors);
+        assert_eq!(read_texture(&backend, &texture), expected.cell_of);
+
+        // Every texel was overwritten, so a smaller site set shows through on reuse
+        backend.compute_into_texture(&image, &sites[..1], &texture, backend.queue()).unwrap();
+        assert!(read_texture(&backend, &texture).iter().all(|&c| c == 0));
+
+        let wrong_format = index_texture(&backend, 70, 45, wgpu::TextureFormat::R32Uint);
+        assert!(matches!(
+            backend.compute_into_texture(&image, &sites, &wrong_format, backend.queue()),
+            Err(VoronoiError::Gpu(_))
+        ));
+        let wrong_size = index_texture(&backend, 64, 45, wgpu::TextureFormat::R32Sint);
+        assert!(backend.compute_into_texture(&image, &sites, &wrong_size, backend.queue()).is_err());
+    }
+
+    #[test]
+    fn test_with_shared_texture() {
+        let Ok(backend) = GpuBackend::new() else { return };
+        let image = image::RgbImage::from_fn(32, 32, |x, y| image::Rgb([x as u8 * 8, y as u8 * 8, 0]));
+        let sites = [Position::new(5.0, 5.0), Position::new(25.0, 12.0)];
+        let texture = Arc::new(index_texture(&backend, 32, 32, wgpu::TextureFormat::R32Sint));
+        let mut backend = backend.with_shared_texture(texture.clone());
+        let result = backend.compute(&image, &sites).unwrap();
+        assert_eq!(read_texture(&backend, &texture), result.cell_of);
+        assert!(result.cell_of.contains(&0) && result.cell_of.contains(&1));
+    }
 }
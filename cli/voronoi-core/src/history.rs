@@ -0,0 +1,126 @@
+//! Undo history for site physics.
+
+use rand_chacha::ChaCha8Rng;
+use crate::{Site, SiteCollection};
+
+/// Saved simulation state: every site's position, velocity, turn rate and speed
+/// multiplier, plus the spawn buffer, spiral spawn counter and RNG state
+#[derive(Debug, Clone)]
+pub(crate) struct SnapshotEntry {
+    sites: Vec<Site>,
+    fractional_sites: f64,
+    spiral_spawn_counter: u64,
+    rng: ChaCha8Rng,
+}
+
+impl SiteCollection {
+    /// Enable undo history holding up to `capacity` snapshots (oldest dropped first).
+    ///
+    /// Each snapshot costs about `n_sites × 48` bytes.
+    pub fn with_history(mut self, capacity: usize) -> Self {
+        self.history_capacity = capacity;
+        while self.history.len() > capacity {
+            self.history.pop_front();
+        }
+        self
+    }
+
+    /// Maximum snapshots kept (0 when history is disabled)
+    pub fn history_capacity(&self) -> usize {
+        self.history_capacity
+    }
+
+    /// Save the current state; call before each step to be undone. No-op unless
+    /// history is enabled.
+    pub fn snapshot(&mut self) {
+        if self.history_capacity == 0 {
+            return;
+        }
+        if self.history.len() == self.history_capacity {
+            self.history.pop_front();
+        }
+        self.history.push_back(SnapshotEntry {
+            sites: self.sites.clone(),
+            fractional_sites: self.fractional_sites,
+            spiral_spawn_counter: self.spiral_spawn_counter,
+            rng: self.rng.clone(),
+        });
+    }
+
+    /// Restore the most recent snapshot, removing it from the history.
+    /// Returns false if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(entry) = self.history.pop_back() else {
+            return false;
+        };
+        self.sites = entry.sites;
+        self.fractional_sites = entry.fractional_sites;
+        self.spiral_spawn_counter = entry.spiral_spawn_counter;
+        self.rng = entry.rng;
+        true
+    }
+
+    /// Number of snapshots available to `undo`
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SiteCollection;
+
+    fn step(sites: &mut SiteCollection) {
+        sites.step(20.0, 0.1, 100.0, 80.0, None, 0.0, 3.0, 2.0);
+    }
+
+    fn state(sites: &SiteCollection) -> Vec<(f64, f64, f64, f64, f64, f64)> {
+        sites.sites.iter()
+            .map(|s| (s.pos.x, s.pos.y, s.vel.x, s.vel.y, s.turn_rate, s.speed_mult))
+            .collect()
+    }
+
+    #[test]
+    fn test_undo_restores_previous_step() {
+        let mut reference = SiteCollection::random(30, 100.0, 80.0, 5);
+        let mut sites = reference.clone().with_history(8);
+
+        for _ in 0..2 {
+            step(&mut reference);
+        }
+        let after_two = state(&reference);
+        step(&mut reference);
+        let after_three = state(&reference);
+
+        for _ in 0..3 {
+            sites.snapshot();
+            step(&mut sites);
+        }
+        assert_eq!(state(&sites), after_three);
+        assert!(sites.undo());
+        assert_eq!(state(&sites), after_two);
+        assert_eq!(sites.history_len(), 2);
+
+        // RNG state is restored too, so replaying the step is identical
+        step(&mut sites);
+        assert_eq!(state(&sites), after_three);
+    }
+
+    #[test]
+    fn test_history_capacity() {
+        let mut sites = SiteCollection::random(5, 100.0, 80.0, 1);
+        sites.snapshot();
+        assert!(!sites.undo());
+        assert_eq!(sites.history_capacity(), 0);
+
+        let mut sites = sites.with_history(2);
+        assert_eq!(sites.history_capacity(), 2);
+        for _ in 0..3 {
+            sites.snapshot();
+            step(&mut sites);
+        }
+        assert!(sites.undo());
+        assert!(sites.undo());
+        assert!(!sites.undo());
+    }
+}
@@ -0,0 +1,807 @@
+//! Image-driven and procedural site initialization strategies.
+
+use rand::Rng;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use crate::{Position, Result, Site, SiteCollection, Velocity, VoronoiError};
+
+/// Harris detector sensitivity
+const HARRIS_K: f32 = 0.04;
+/// Half-width of the structure-tensor box blur and non-max suppression windows (5x5)
+const HARRIS_RADIUS: i32 = 2;
+/// Lloyd iterations run by `from_color_clusters`
+const KMEANS_ITERATIONS: usize = 5;
+/// Pixels sampled (on a regular stride) for color clustering, bounding cost on large images
+const KMEANS_MAX_SAMPLES: usize = 65_536;
+/// Region of the complex plane (re_min, re_max, im_min, im_max) mapped onto the
+/// image by `from_mandelbrot`
+const MANDELBROT_VIEW: (f64, f64, f64, f64) = (-2.5, 1.0, -1.25, 1.25);
+/// Repulsion strength for `scatter_with_repulsion`: a lone overlapping pair
+/// closes this fraction of its gap from each side per iteration
+const SCATTER_REPULSION: f64 = 0.25;
+
+/// Squared distance between two k-means feature vectors
+fn feature_dist_sq(a: &[f32; 5], b: &[f32; 5]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+/// Iterations of z → z² + c before |z| > 2, or `max_iter` if it stays bounded
+pub(crate) fn mandelbrot_escape(c_re: f64, c_im: f64, max_iter: u32) -> u32 {
+    let (mut re, mut im) = (0.0f64, 0.0f64);
+    for n in 0..max_iter {
+        if re * re + im * im > 4.0 {
+            return n;
+        }
+        (re, im) = (re * re - im * im + c_re, 2.0 * re * im + c_im);
+    }
+    max_iter
+}
+
+/// Rec. 601 luma of each pixel, row-major
+pub(crate) fn luminance(image: &image::RgbImage) -> Vec<f32> {
+    image.pixels()
+        .map(|p| 0.299 * p[0] as f32 + 0.587 * p[1] as f32 + 0.114 * p[2] as f32)
+        .collect()
+}
+
+/// Separable box blur with the given radius (edges clamped)
+fn box_blur(src: &[f32], width: usize, height: usize, radius: i32) -> Vec<f32> {
+    let clamp = |v: i32, max: usize| v.clamp(0, max as i32 - 1) as usize;
+    let mut tmp = vec![0.0f32; src.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = 0.0;
+            for d in -radius..=radius {
+                sum += src[y * width + clamp(x as i32 + d, width)];
+            }
+            tmp[y * width + x] = sum;
+        }
+    }
+    let mut out = vec![0.0f32; src.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = 0.0;
+            for d in -radius..=radius {
+                sum += tmp[clamp(y as i32 + d, height) * width + x];
+            }
+            out[y * width + x] = sum;
+        }
+    }
+    out
+}
+
+/// Sobel derivatives (gx, gy) of `image`'s luma per pixel (edges clamped)
+fn sobel_gradients(image: &image::RgbImage) -> Vec<(f32, f32)> {
+    let width = image.width() as usize;
+    let height = image.height() as usize;
+    let lum = luminance(image);
+    let at = |x: i32, y: i32| {
+        let cx = x.clamp(0, width as i32 - 1) as usize;
+        let cy = y.clamp(0, height as i32 - 1) as usize;
+        lum[cy * width + cx]
+    };
+    let mut gradients = Vec::with_capacity(width * height);
+    for y in 0..height as i32 {
+        for x in 0..width as i32 {
+            let gx = (at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1))
+                - (at(x - 1, y - 1) + 2.0 * at(x - 1, y) + at(x - 1, y + 1));
+            let gy = (at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1))
+                - (at(x - 1, y - 1) + 2.0 * at(x, y - 1) + at(x + 1, y - 1));
+            gradients.push((gx, gy));
+        }
+    }
+    gradients
+}
+
+/// Harris corner response `R = det(M) - k * trace(M)^2` per pixel
+fn harris_response(image: &image::RgbImage) -> Vec<f32> {
+    let width = image.width() as usize;
+    let height = image.height() as usize;
+
+    // Sobel derivatives -> structure tensor products
+    let gradients = sobel_gradients(image);
+    let n = width * height;
+    let ixx: Vec<f32> = gradients.iter().map(|&(gx, _)| gx * gx).collect();
+    let iyy: Vec<f32> = gradients.iter().map(|&(_, gy)| gy * gy).collect();
+    let ixy: Vec<f32> = gradients.iter().map(|&(gx, gy)| gx * gy).collect();
+
+    let sxx = box_blur(&ixx, width, height, HARRIS_RADIUS);
+    let syy = box_blur(&iyy, width, height, HARRIS_RADIUS);
+    let sxy = box_blur(&ixy, width, height, HARRIS_RADIUS);
+
+    (0..n)
+        .map(|i| {
+            let det = sxx[i] * syy[i] - sxy[i] * sxy[i];
+            let trace = sxx[i] + syy[i];
+            det - HARRIS_K * trace * trace
+        })
+        .collect()
+}
+
+/// Pixel indices of Harris corners (local maxima of a positive response under
+/// 5x5 non-max suppression), strongest first
+fn harris_corners(image: &image::RgbImage) -> Vec<usize> {
+    let width = image.width() as usize;
+    let height = image.height() as usize;
+    let mut corners: Vec<(f32, usize)> = Vec::new();
+    if width > 0 && height > 0 {
+        let response = harris_response(image);
+        for y in 0..height {
+            for x in 0..width {
+                let r = response[y * width + x];
+                if r <= 0.0 { continue; }
+                let mut is_max = true;
+                'window: for dy in -HARRIS_RADIUS..=HARRIS_RADIUS {
+                    for dx in -HARRIS_RADIUS..=HARRIS_RADIUS {
+                        let nx = x as i32 + dx;
+                        let ny = y as i32 + dy;
+                        if (dx == 0 && dy == 0)
+                            || nx < 0 || ny < 0
+                            || nx >= width as i32 || ny >= height as i32
+                        {
+                            continue;
+                        }
+                        let nr = response[ny as usize * width + nx as usize];
+                        // Ties broken by scan order so plateaus yield a single corner
+                        if nr > r || (nr == r && (dy < 0 || (dy == 0 && dx < 0))) {
+                            is_max = false;
+                            break 'window;
+                        }
+                    }
+                }
+                if is_max {
+                    corners.push((r, y * width + x));
+                }
+            }
+        }
+        corners.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+    }
+    corners.into_iter().map(|(_, i)| i).collect()
+}
+
+impl SiteCollection {
+    /// Place sites at the strongest Harris corners of `image`.
+    ///
+    /// Corners are local maxima of the Harris response (5x5 non-max suppression),
+    /// taken in descending response order. If fewer than `count` corners are
+    /// found, the remainder are placed uniformly at random.
+    pub fn from_points_of_interest(image: &image::RgbImage, count: usize, seed: u64) -> Self {
+        let width = image.width() as usize;
+        let height = image.height() as usize;
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+        let corners = harris_corners(image);
+        let mut positions: Vec<Position> = corners.iter()
+            .take(count)
+            .map(|&i| Position::new((i % width) as f64 + 0.5, (i / width) as f64 + 0.5))
+            .collect();
+        while positions.len() < count {
+            positions.push(Position::new(
+                rng.gen::<f64>() * width as f64,
+                rng.gen::<f64>() * height as f64,
+            ));
+        }
+
+        let sites = positions.into_iter()
+            .map(|pos| Site::with_random_velocity(pos, &mut rng))
+            .collect();
+        Self::with_rng(sites, rng)
+    }
+
+    /// Place `total_count` sites mixing three strategies: the strongest
+    /// `corner_fraction · total_count` Harris corners (as in
+    /// `from_points_of_interest`), then `edge_fraction · total_count` pixels drawn
+    /// at random from those whose Sobel gradient magnitude (on 0-255 luma) exceeds
+    /// `edge_threshold`, then uniform random positions for the rest, including any
+    /// corners or edge pixels the image runs short of. Corner and edge sites sit at
+    /// pixel centers (no pixel is used twice); velocities are random.
+    ///
+    /// Errors if either fraction is negative or they sum to more than 1.
+    pub fn from_corners_and_edges(
+        image: &image::RgbImage,
+        corner_fraction: f64,
+        edge_fraction: f64,
+        total_count: usize,
+        edge_threshold: f32,
+        seed: u64,
+    ) -> Result<Self> {
+        if corner_fraction < 0.0 || edge_fraction < 0.0 || corner_fraction + edge_fraction > 1.0 {
+            return Err(VoronoiError::Config(format!(
+                "corner fraction {} and edge fraction {} must be non-negative with a sum of at most 1",
+                corner_fraction, edge_fraction,
+            )));
+        }
+        let width = image.width() as usize;
+        let height = image.height() as usize;
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let pixel_center = |i: usize| Position::new((i % width) as f64 + 0.5, (i / width) as f64 + 0.5);
+
+        let mut corners = harris_corners(image);
+        corners.truncate((corner_fraction * total_count as f64) as usize);
+        let mut is_corner = vec![false; width * height];
+        for &i in &corners {
+            is_corner[i] = true;
+        }
+        let edges: Vec<usize> = sobel_gradients(image).iter()
+            .enumerate()
+            .filter(|&(i, &(gx, gy))| gx.hypot(gy) > edge_threshold && !is_corner[i])
+            .map(|(i, _)| i)
+            .collect();
+        let edge_count = ((edge_fraction * total_count as f64) as usize).min(edges.len());
+
+        let mut positions: Vec<Position> = corners.iter().map(|&i| pixel_center(i)).collect();
+        positions.extend(
+            rand::seq::index::sample(&mut rng, edges.len(), edge_count).into_iter().map(|k| pixel_center(edges[k])),
+        );
+        while positions.len() < total_count {
+            positions.push(Position::new(
+                rng.gen::<f64>() * width as f64,
+                rng.gen::<f64>() * height as f64,
+            ));
+        }
+
+        let sites = positions.into_iter()
+            .map(|pos| Site::with_random_velocity(pos, &mut rng))
+            .collect();
+        Ok(Self::with_rng(sites, rng))
+    }
+
+    /// Place sites at the spatial centers of dominant-color regions.
+    ///
+    /// Runs 5 iterations of k-means (k-means++ seeding) over `(r, g, b, x/width,
+    /// y/height)` features, with color scaled to [0, 1] so color similarity and
+    /// spatial proximity carry similar weight (like SLIC superpixels, without the
+    /// connectivity constraint). Large images are subsampled on a regular stride.
+    /// If fewer than `count` distinct clusters can be formed, the remainder are
+    /// placed uniformly at random.
+    pub fn from_color_clusters(image: &image::RgbImage, count: usize, seed: u64) -> Self {
+        let width = image.width() as usize;
+        let height = image.height() as usize;
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+        let stride = (width * height).div_ceil(KMEANS_MAX_SAMPLES).max(1);
+        let features: Vec<[f32; 5]> = (0..width * height)
+            .step_by(stride)
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                let p = image.get_pixel(x as u32, y as u32);
+                [
+                    p[0] as f32 / 255.0,
+                    p[1] as f32 / 255.0,
+                    p[2] as f32 / 255.0,
+                    (x as f32 + 0.5) / width as f32,
+                    (y as f32 + 0.5) / height as f32,
+                ]
+            })
+            .collect();
+
+        // k-means++ seeding: each new center drawn with probability ∝ squared
+        // distance to the nearest existing center
+        let mut centers: Vec<[f32; 5]> = Vec::with_capacity(count);
+        let mut nearest = vec![f32::INFINITY; features.len()];
+        if !features.is_empty() && count > 0 {
+            centers.push(features[rng.gen_range(0..features.len())]);
+        }
+        while !centers.is_empty() && centers.len() < count {
+            let last = centers[centers.len() - 1];
+            for (d, f) in nearest.iter_mut().zip(&features) {
+                *d = d.min(feature_dist_sq(f, &last));
+            }
+            let total: f32 = nearest.iter().sum();
+            if total <= 0.0 {
+                break;
+            }
+            let mut target = rng.gen::<f32>() * total;
+            let idx = nearest.iter()
+                .position(|&d| { target -= d; target <= 0.0 && d > 0.0 })
+                .unwrap_or_else(|| nearest.iter().rposition(|&d| d > 0.0).unwrap());
+            centers.push(features[idx]);
+        }
+
+        let mut assignment = vec![0usize; features.len()];
+        for _ in 0..KMEANS_ITERATIONS {
+            for (a, f) in assignment.iter_mut().zip(&features) {
+                *a = (0..centers.len())
+                    .min_by(|&i, &j| feature_dist_sq(f, &centers[i]).total_cmp(&feature_dist_sq(f, &centers[j])))
+                    .unwrap();
+            }
+            let mut sums = vec![[0.0f64; 5]; centers.len()];
+            let mut counts = vec![0usize; centers.len()];
+            for (&a, f) in assignment.iter().zip(&features) {
+                counts[a] += 1;
+                for (s, &v) in sums[a].iter_mut().zip(f) {
+                    *s += v as f64;
+                }
+            }
+            // Empty clusters keep their previous center
+            for ((center, sum), &n) in centers.iter_mut().zip(&sums).zip(&counts) {
+                if n > 0 {
+                    *center = sum.map(|s| (s / n as f64) as f32);
+                }
+            }
+        }
+
+        let mut positions: Vec<Position> = centers.iter()
+            .map(|c| Position::new(c[3] as f64 * width as f64, c[4] as f64 * height as f64))
+            .collect();
+        while positions.len() < count {
+            positions.push(Position::new(
+                rng.gen::<f64>() * width as f64,
+                rng.gen::<f64>() * height as f64,
+            ));
+        }
+
+        let sites = positions.into_iter()
+            .map(|pos| Site::with_random_velocity(pos, &mut rng))
+            .collect();
+        Self::with_rng(sites, rng)
+    }
+
+    /// Place sites at the dots of a stipple image (dark dots on a light background).
+    ///
+    /// Pixels darker than `dot_threshold` are grouped into 4-connected components,
+    /// and each component's centroid (of pixel centers) becomes a site. Only the
+    /// `max_sites` largest components are kept, largest first (ties in scan order
+    /// of their first pixel). Velocities are random. Unlike the other image-driven
+    /// strategies, no random sites are added when there are fewer dots.
+    pub fn from_stipple_image(stipple: &image::GrayImage, dot_threshold: u8, max_sites: usize, seed: u64) -> Self {
+        let (width, height) = (stipple.width() as usize, stipple.height() as usize);
+        let dark = stipple.as_raw();
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+        // (pixel count, x sum, y sum) per component, by flood fill in scan order
+        let mut visited = vec![false; width * height];
+        let mut components: Vec<(usize, f64, f64)> = Vec::new();
+        let mut stack = Vec::new();
+        for start in 0..width * height {
+            if visited[start] || dark[start] >= dot_threshold {
+                continue;
+            }
+            visited[start] = true;
+            stack.push(start);
+            let mut component = (0, 0.0, 0.0);
+            while let Some(i) = stack.pop() {
+                let (x, y) = (i % width, i / width);
+                component.0 += 1;
+                component.1 += x as f64 + 0.5;
+                component.2 += y as f64 + 0.5;
+                let neighbors = [
+                    (x > 0).then(|| i - 1),
+                    (x + 1 < width).then(|| i + 1),
+                    (y > 0).then(|| i - width),
+                    (y + 1 < height).then(|| i + width),
+                ];
+                for n in neighbors.into_iter().flatten() {
+                    if !visited[n] && dark[n] < dot_threshold {
+                        visited[n] = true;
+                        stack.push(n);
+                    }
+                }
+            }
+            components.push(component);
+        }
+        // Stable, so equal sizes stay in scan order
+        components.sort_by_key(|c| std::cmp::Reverse(c.0));
+        components.truncate(max_sites);
+
+        let sites = components.into_iter()
+            .map(|(n, sx, sy)| Site::with_random_velocity(Position::new(sx / n as f64, sy / n as f64), &mut rng))
+            .collect();
+        Self::with_rng(sites, rng)
+    }
+
+    /// Place sites by Mandelbrot escape time, concentrating them along the set's
+    /// boundary.
+    ///
+    /// Each pixel of the `width × height` area maps onto `[-2.5, 1.0] × [-1.25,
+    /// 1.25]` of the complex plane. Its weight is its escape time normalized to
+    /// [0, 1], or 0 if it never escapes within `max_iter` iterations (the set's
+    /// interior), so points just outside the boundary are likeliest. `count`
+    /// distinct pixels are drawn by weighted reservoir sampling (A-ES) and jittered
+    /// within the pixel; if fewer have nonzero weight, the remainder are placed
+    /// uniformly at random. O(width · height · max_iter).
+    pub fn from_mandelbrot(count: usize, width: f64, height: f64, max_iter: u32, seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let (cols, rows) = (width.ceil().max(0.0) as usize, height.ceil().max(0.0) as usize);
+        let (re_min, re_max, im_min, im_max) = MANDELBROT_VIEW;
+        let max_iter = max_iter.max(1);
+
+        let weight = |i: usize| -> f64 {
+            let c_re = re_min + (re_max - re_min) * ((i % cols) as f64 + 0.5) / width;
+            let c_im = im_min + (im_max - im_min) * ((i / cols) as f64 + 0.5) / height;
+            let n = mandelbrot_escape(c_re, c_im, max_iter);
+            if n >= max_iter { 0.0 } else { n as f64 / max_iter as f64 }
+        };
+        #[cfg(feature = "parallel")]
+        let weights: Vec<f64> = {
+            use rayon::prelude::*;
+            (0..cols * rows).into_par_iter().map(weight).collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let weights: Vec<f64> = (0..cols * rows).map(weight).collect();
+
+        // A-ES: key u^(1/w) per pixel, keep the `count` largest (compared as ln(u)/w)
+        let mut keyed: Vec<(f64, usize)> = weights.iter()
+            .enumerate()
+            .filter(|&(_, &w)| w > 0.0)
+            .map(|(i, &w)| (rng.gen::<f64>().ln() / w, i))
+            .collect();
+        keyed.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+        keyed.truncate(count);
+
+        let mut positions: Vec<Position> = keyed.iter()
+            .map(|&(_, i)| Position::new(
+                ((i % cols) as f64 + rng.gen::<f64>()).min(width),
+                ((i / cols) as f64 + rng.gen::<f64>()).min(height),
+            ))
+            .collect();
+        while positions.len() < count {
+            positions.push(Position::new(rng.gen::<f64>() * width, rng.gen::<f64>() * height));
+        }
+
+        let sites = positions.into_iter()
+            .map(|pos| Site::with_random_velocity(pos, &mut rng))
+            .collect();
+        Self::with_rng(sites, rng)
+    }
+
+    /// Place `sides` sites on the vertices of a regular polygon of circumradius
+    /// `radius` around (`center_x`, `center_y`): vertex i sits at angle
+    /// `i · 2π / sides + rotation_rad`. Velocities are random. Positions are not
+    /// clamped to any image bounds.
+    pub fn from_regular_polygon(
+        sides: usize,
+        radius: f64,
+        center_x: f64,
+        center_y: f64,
+        rotation_rad: f64,
+        seed: u64,
+    ) -> Self {
+        Self::from_nested_polygons(&[(sides, radius)], center_x, center_y, rotation_rad, seed)
+    }
+
+    /// Concentric regular polygons, one per `(sides, radius)` ring, all sharing
+    /// the center and rotation (see `from_regular_polygon`). Sites are ordered
+    /// ring by ring.
+    pub fn from_nested_polygons(
+        rings: &[(usize, f64)],
+        center_x: f64,
+        center_y: f64,
+        rotation_rad: f64,
+        seed: u64,
+    ) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let sites = rings.iter()
+            .flat_map(|&(sides, radius)| (0..sides).map(move |i| {
+                let angle = i as f64 * std::f64::consts::TAU / sides as f64 + rotation_rad;
+                Position::new(center_x + radius * angle.cos(), center_y + radius * angle.sin())
+            }))
+            .map(|pos| Site::with_random_velocity(pos, &mut rng))
+            .collect();
+        Self::with_rng(sites, rng)
+    }
+
+    /// Concentric rings around (`center_x`, `center_y`): each `(count, radius)`
+    /// places `count` sites evenly around a circle of `radius` pixels, starting at
+    /// a random angle per ring. Sites head along their ring, clockwise on screen
+    /// (y down). Sites are ordered ring by ring; positions are not clamped.
+    pub fn from_concentric_rings(ring_specs: &[(usize, f64)], center_x: f64, center_y: f64, seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut sites = Vec::with_capacity(ring_specs.iter().map(|&(count, _)| count).sum());
+        for &(count, radius) in ring_specs {
+            let start = rng.gen::<f64>() * std::f64::consts::TAU;
+            for i in 0..count {
+                let angle = start + i as f64 * std::f64::consts::TAU / count as f64;
+                let (sin, cos) = angle.sin_cos();
+                sites.push(Site::new(
+                    Position::new(center_x + radius * cos, center_y + radius * sin),
+                    Velocity::new(-sin, cos),
+                ));
+            }
+        }
+        Self::with_rng(sites, rng)
+    }
+
+    /// Place `count` sites on the Lissajous curve
+    /// `(amplitude_x · cos(a·t + delta) + center_x, amplitude_y · sin(b·t) + center_y)`
+    /// at evenly spaced `t ∈ [0, 2π)`, heading along the curve (direction of
+    /// increasing `t`). Where the tangent vanishes, the heading is random.
+    /// Positions are not clamped; with integer `a` and `b` sharing a factor, the
+    /// curve retraces itself and sites coincide.
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_lissajous(
+        count: usize,
+        a: f64,
+        b: f64,
+        delta: f64,
+        amplitude_x: f64,
+        amplitude_y: f64,
+        center_x: f64,
+        center_y: f64,
+        seed: u64,
+    ) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let sites = (0..count)
+            .map(|i| {
+                let t = i as f64 * std::f64::consts::TAU / count as f64;
+                let pos = Position::new(
+                    amplitude_x * (a * t + delta).cos() + center_x,
+                    amplitude_y * (b * t).sin() + center_y,
+                );
+                let (dx, dy) = (-amplitude_x * a * (a * t + delta).sin(), amplitude_y * b * (b * t).cos());
+                let len = (dx * dx + dy * dy).sqrt();
+                if len > 1e-9 {
+                    Site::new(pos, Velocity::new(dx / len, dy / len))
+                } else {
+                    Site::with_random_velocity(pos, &mut rng)
+                }
+            })
+            .collect();
+        Self::with_rng(sites, rng)
+    }
+
+    /// `count` sites spread roughly like Poisson-disk samples: uniform random
+    /// positions, then `iterations` rounds of `repulsion_spring_step` (no
+    /// springs) pushing apart sites closer than the mean spacing `√(w·h / count)`,
+    /// each followed by clamping back into the image. Velocities are random.
+    /// O(count) per iteration.
+    pub fn scatter_with_repulsion(count: usize, width: f64, height: f64, seed: u64, iterations: usize) -> Self {
+        let mut sites = Self::random(count, width, height, seed);
+        if count < 2 {
+            return sites;
+        }
+        let spacing = (width * height / count as f64).sqrt();
+        for _ in 0..iterations {
+            sites.repulsion_spring_step(&[], SCATTER_REPULSION, 0.0, spacing, 1.0);
+            for site in &mut sites.sites {
+                site.pos.x = site.pos.x.clamp(0.0, width.next_down().max(0.0));
+                site.pos.y = site.pos.y.clamp(0.0, height.next_down().max(0.0));
+            }
+        }
+        sites
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 64x64 checkerboard with 16px squares has 9 interior X-junctions
+    #[test]
+    fn test_corners_on_checkerboard() {
+        let image = image::RgbImage::from_fn(64, 64, |x, y| {
+            if (x / 16 + y / 16) % 2 == 0 { image::Rgb([255, 255, 255]) } else { image::Rgb([0, 0, 0]) }
+        });
+        let corners: Vec<Position> = [16.0, 32.0, 48.0].iter()
+            .flat_map(|&y| [16.0, 32.0, 48.0].map(|x| Position::new(x, y)))
+            .collect();
+
+        let sites = SiteCollection::from_points_of_interest(&image, 9, 0);
+        assert_eq!(sites.len(), 9);
+        let near = sites.positions().iter()
+            .filter(|p| corners.iter().any(|c| p.dist(c) <= 3.0))
+            .count();
+        assert!(near >= 7, "only {} of 9 sites near a checkerboard corner", near);
+
+        // Asking for more sites than corners pads with random positions
+        let padded = SiteCollection::from_points_of_interest(&image, 40, 0);
+        assert_eq!(padded.len(), 40);
+    }
+
+    #[test]
+    fn test_corners_and_edges_on_checkerboard() {
+        let image = image::RgbImage::from_fn(64, 64, |x, y| {
+            if (x / 16 + y / 16) % 2 == 0 { image::Rgb([255, 255, 255]) } else { image::Rgb([0, 0, 0]) }
+        });
+        let harris: Vec<Position> = [16.0, 32.0, 48.0].iter()
+            .flat_map(|&y| [16.0, 32.0, 48.0].map(|x| Position::new(x, y)))
+            .collect();
+        let dist_to_nearest_harris_corner = |p: &Position| harris.iter().map(|c| p.dist(c)).fold(f64::INFINITY, f64::min);
+
+        let sites = SiteCollection::from_corners_and_edges(&image, 0.2, 0.5, 40, 100.0, 0).unwrap();
+        assert_eq!(sites.len(), 40);
+        let positions = sites.positions();
+        for p in &positions[..8] {
+            assert!(dist_to_nearest_harris_corner(p) <= 3.0, "corner site {:?}", p);
+        }
+        // Edge sites lie within a pixel of a square boundary
+        let on_edge = |v: f64| {
+            let boundary = (v / 16.0).round() * 16.0;
+            (v - boundary).abs() <= 1.0 && boundary > 0.0 && boundary < 64.0
+        };
+        for p in &positions[8..28] {
+            assert!(on_edge(p.x) || on_edge(p.y), "edge site {:?}", p);
+        }
+        let mut distinct = positions.clone();
+        distinct.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        distinct.dedup();
+        assert_eq!(distinct.len(), 40);
+
+        // A flat image has no corners or edges: all random
+        let flat = image::RgbImage::from_pixel(32, 32, image::Rgb([90, 90, 90]));
+        assert_eq!(SiteCollection::from_corners_and_edges(&flat, 0.5, 0.5, 10, 100.0, 0).unwrap().len(), 10);
+        assert!(SiteCollection::from_corners_and_edges(&image, 0.6, 0.5, 10, 100.0, 0).is_err());
+        assert!(SiteCollection::from_corners_and_edges(&image, -0.1, 0.5, 10, 100.0, 0).is_err());
+    }
+
+    #[test]
+    fn test_color_clusters_on_quadrants() {
+        let colors = [[220, 30, 30], [30, 200, 40], [40, 50, 210], [230, 220, 40]];
+        let quadrant = |x: u32, y: u32| (x / 32 + 2 * (y / 32)) as usize;
+        // Slight per-pixel variation so clusters have nonzero spread
+        let image = image::RgbImage::from_fn(64, 64, |x, y| {
+            let jitter = ((x * 7 + y * 13) % 11) as u8;
+            image::Rgb(colors[quadrant(x, y)].map(|c| c + jitter))
+        });
+
+        let sites = SiteCollection::from_color_clusters(&image, 4, 0);
+        assert_eq!(sites.len(), 4);
+        let positions = sites.positions();
+        for (qx, qy) in [(16.0, 16.0), (48.0, 16.0), (16.0, 48.0), (48.0, 48.0)] {
+            let center = Position::new(qx, qy);
+            assert!(positions.iter().any(|p| p.dist(&center) <= 4.0),
+                "no site near quadrant center {:?}: {:?}", center, positions);
+        }
+
+        // Label pixels by nearest site; compare cluster mean colors to spread within clusters
+        let rgb = |x: u32, y: u32| image.get_pixel(x, y).0.map(|c| c as f64);
+        let dist = |a: [f64; 3], b: [f64; 3]| a.iter().zip(&b).map(|(x, y)| (x - y).powi(2)).sum::<f64>().sqrt();
+        let label = |x: u32, y: u32| {
+            let p = Position::new(x as f64 + 0.5, y as f64 + 0.5);
+            (0..4).min_by(|&i, &j| p.dist(&positions[i]).total_cmp(&p.dist(&positions[j]))).unwrap()
+        };
+        let mut sums = [[0.0; 3]; 4];
+        let mut counts = [0.0; 4];
+        for (x, y, _) in image.enumerate_pixels() {
+            let l = label(x, y);
+            counts[l] += 1.0;
+            for (s, c) in sums[l].iter_mut().zip(rgb(x, y)) { *s += c; }
+        }
+        let means: Vec<[f64; 3]> = sums.iter().zip(&counts).map(|(s, &n)| s.map(|v| v / n)).collect();
+        let intra = image.enumerate_pixels()
+            .map(|(x, y, _)| dist(rgb(x, y), means[label(x, y)]))
+            .sum::<f64>() / (64.0 * 64.0);
+        let adjacent = [(16, 16, 48, 16), (16, 48, 48, 48), (16, 16, 16, 48), (48, 16, 48, 48)];
+        let inter = adjacent.iter()
+            .map(|&(ax, ay, bx, by)| dist(means[label(ax, ay)], means[label(bx, by)]))
+            .sum::<f64>() / adjacent.len() as f64;
+        assert!(inter > intra, "inter-cluster {} <= intra-cluster {}", inter, intra);
+    }
+
+    #[test]
+    fn test_stipple_image_dots() {
+        // Ten dots of (i % 3 + 1)² pixels on a light background
+        let dots: Vec<(u32, u32, u32)> = (0..10).map(|i| (5 + 12 * (i % 5), 6 + 15 * (i / 5), i % 3 + 1)).collect();
+        let stipple = image::GrayImage::from_fn(64, 40, |x, y| {
+            let dark = dots.iter().any(|&(dx, dy, s)| (dx..dx + s).contains(&x) && (dy..dy + s).contains(&y));
+            image::Luma([if dark { 20 } else { 230 }])
+        });
+
+        let sites = SiteCollection::from_stipple_image(&stipple, 64, 100, 0);
+        assert_eq!(sites.len(), 10);
+        for &(dx, dy, s) in &dots {
+            let center = Position::new(dx as f64 + s as f64 / 2.0, dy as f64 + s as f64 / 2.0);
+            assert!(sites.positions().iter().any(|p| p.dist(&center) < 1e-9), "no site at {:?}", center);
+        }
+
+        // Largest dots first; diagonal neighbors are separate dots
+        let largest = SiteCollection::from_stipple_image(&stipple, 64, 3, 0);
+        assert_eq!(largest.positions(), [Position::new(30.5, 7.5), Position::new(6.5, 22.5), Position::new(42.5, 22.5)]);
+        let diagonal = image::GrayImage::from_fn(4, 4, |x, y| image::Luma([if x == y { 0 } else { 255 }]));
+        assert_eq!(SiteCollection::from_stipple_image(&diagonal, 64, 100, 0).len(), 4);
+        assert!(SiteCollection::from_stipple_image(&stipple, 0, 100, 0).is_empty());
+    }
+
+    #[test]
+    fn test_mandelbrot_sites_near_boundary() {
+        let (width, height, max_iter) = (350.0, 250.0, 100);
+        let sites = SiteCollection::from_mandelbrot(400, width, height, max_iter, 3);
+        assert_eq!(sites.len(), 400);
+
+        // Classify pixels: inside the set, clearly escaped (< 4 iterations), or near its boundary
+        let (re_min, re_max, im_min, im_max) = MANDELBROT_VIEW;
+        let class = |x: f64, y: f64| {
+            let c_re = re_min + (re_max - re_min) * (x.floor() + 0.5) / width;
+            let c_im = im_min + (im_max - im_min) * (y.floor() + 0.5) / height;
+            match mandelbrot_escape(c_re, c_im, max_iter) {
+                n if n >= max_iter => 0,
+                n if n < 4 => 1,
+                _ => 2,
+            }
+        };
+        let mut pixels = [0.0; 3];
+        for y in 0..height as u32 {
+            for x in 0..width as u32 {
+                pixels[class(x as f64, y as f64)] += 1.0;
+            }
+        }
+        let mut hits = [0.0; 3];
+        for p in sites.positions() {
+            assert!(p.x >= 0.0 && p.x <= width && p.y >= 0.0 && p.y <= height);
+            hits[class(p.x.min(width - 0.5), p.y.min(height - 0.5))] += 1.0;
+        }
+        let density: Vec<f64> = hits.iter().zip(&pixels).map(|(h, n)| h / n).collect();
+        assert!(density[2] > 2.0 * density[0], "boundary {:?} vs interior", density);
+        assert!(density[2] > 2.0 * density[1], "boundary {:?} vs escaped", density);
+    }
+
+    #[test]
+    fn test_regular_polygon_square() {
+        let r = 50.0;
+        let sites = SiteCollection::from_regular_polygon(4, r, 100.0, 80.0, 0.3, 1);
+        let positions = sites.positions();
+        assert_eq!(positions.len(), 4);
+        let center = Position::new(100.0, 80.0);
+        for (i, p) in positions.iter().enumerate() {
+            assert!((p.dist(&center) - r).abs() < 1e-9);
+            let next = positions[(i + 1) % 4];
+            assert!((p.dist(&next) - r * 2f64.sqrt()).abs() < 1e-9);
+        }
+
+        let nested = SiteCollection::from_nested_polygons(&[(4, r), (6, 2.0 * r)], 100.0, 80.0, 0.3, 1);
+        assert_eq!(nested.len(), 10);
+        assert_eq!(nested.positions()[..4], positions[..]);
+        assert!(nested.positions()[4..].iter().all(|p| (p.dist(&center) - 2.0 * r).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_concentric_rings() {
+        let sites = SiteCollection::from_concentric_rings(&[(4, 50.0)], 100.0, 100.0, 0);
+        let center = Position::new(100.0, 100.0);
+        assert_eq!(sites.len(), 4);
+        for site in &sites.sites {
+            assert!((site.pos.dist(&center) - 50.0).abs() < 1e-9);
+            // Tangent: perpendicular to the radius, turning clockwise (y down)
+            let (rx, ry) = (site.pos.x - center.x, site.pos.y - center.y);
+            assert!((rx * site.vel.x + ry * site.vel.y).abs() < 1e-9);
+            assert!(rx * site.vel.y - ry * site.vel.x > 0.0);
+        }
+
+        let rings = SiteCollection::from_concentric_rings(&[(8, 100.0), (16, 200.0)], 0.0, 0.0, 4);
+        assert_eq!(rings.len(), 24);
+        assert!(rings.positions()[8..].iter().all(|p| (p.dist(&Position::new(0.0, 0.0)) - 200.0).abs() < 1e-9));
+    }
+
+    /// a = b = 1, delta = 0 traces the ellipse (x/ax)² + (y/ay)² = 1
+    #[test]
+    fn test_lissajous_ellipse() {
+        let (ax, ay) = (120.0, 45.0);
+        let sites = SiteCollection::from_lissajous(24, 1.0, 1.0, 0.0, ax, ay, 200.0, 100.0, 3);
+        assert_eq!(sites.len(), 24);
+        for site in &sites.sites {
+            let (x, y) = ((site.pos.x - 200.0) / ax, (site.pos.y - 100.0) / ay);
+            assert!((x * x + y * y - 1.0).abs() < 1e-9, "{:?}", site.pos);
+            // Tangent to the ellipse: perpendicular to its gradient
+            assert!((x / ax * site.vel.x + y / ay * site.vel.y).abs() < 1e-9);
+        }
+        assert!(sites.positions()[0].dist(&Position::new(320.0, 100.0)) < 1e-9);
+
+        let figure = SiteCollection::from_lissajous(50, 3.0, 2.0, std::f64::consts::FRAC_PI_2, ax, ay, 0.0, 0.0, 3);
+        assert!(figure.positions().iter().all(|p| p.x.abs() <= ax + 1e-9 && p.y.abs() <= ay + 1e-9));
+    }
+
+    /// Coefficient of variation of nearest-neighbor distances (brute force)
+    fn nn_cv(sites: &SiteCollection) -> f64 {
+        let positions = sites.positions();
+        let nn: Vec<f64> = positions.iter().enumerate()
+            .map(|(i, p)| positions.iter().enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, q)| p.dist(q))
+                .fold(f64::INFINITY, f64::min))
+            .collect();
+        let mean = nn.iter().sum::<f64>() / nn.len() as f64;
+        let var = nn.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / nn.len() as f64;
+        var.sqrt() / mean
+    }
+
+    #[test]
+    fn test_scatter_with_repulsion() {
+        let random = SiteCollection::scatter_with_repulsion(500, 300.0, 200.0, 5, 0);
+        let scattered = SiteCollection::scatter_with_repulsion(500, 300.0, 200.0, 5, 20);
+        assert_eq!(scattered.len(), 500);
+        assert!(scattered.positions().iter().all(|p| (0.0..300.0).contains(&p.x) && (0.0..200.0).contains(&p.y)));
+        let (before, after) = (nn_cv(&random), nn_cv(&scattered));
+        assert!(before > 0.4, "random CV {}", before);
+        assert!(after < 0.3, "scattered CV {}", after);
+    }
+}
@@ -0,0 +1,276 @@
+//! JSON save/restore of site state, for debuggable checkpoints.
+
+use std::path::Path;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde_json::{json, Map, Value};
+use crate::{Position, PullMode, Site, SiteCollection, Velocity, Vortex, VoronoiError};
+
+fn json_err(msg: impl Into<String>) -> VoronoiError {
+    VoronoiError::Json(msg.into())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex<const N: usize>(s: &str, field: &str) -> crate::Result<[u8; N]> {
+    let mut out = [0u8; N];
+    if s.len() != 2 * N {
+        return Err(json_err(format!("{}: expected {} hex digits, got {}", field, 2 * N, s.len())));
+    }
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = s.get(2 * i..2 * i + 2)
+            .and_then(|h| u8::from_str_radix(h, 16).ok())
+            .ok_or_else(|| json_err(format!("{}: invalid hex {:?}", field, s)))?;
+    }
+    Ok(out)
+}
+
+impl SiteCollection {
+    /// Serialize the collection as JSON, including the RNG state, so `from_json`
+    /// resumes with bit-identical steps.
+    ///
+    /// The RNG is stored as its hex seed, stream and hex word position, next to
+    /// the pull mode and vortices. Undo history (see `with_history`) and
+    /// callbacks are not saved.
+    pub fn to_json(&self) -> String {
+        let mut value = self.json_value();
+        value["rng"] = json!({
+            "seed": to_hex(&self.rng.get_seed()),
+            "stream": self.rng.get_stream(),
+            "word_pos": format!("{:032x}", self.rng.get_word_pos()),
+        });
+        value.to_string()
+    }
+
+    /// Like `to_json` but without the RNG state: positions and dynamics survive,
+    /// but later steps won't match the original's.
+    pub fn to_json_compact(&self) -> String {
+        self.json_value().to_string()
+    }
+
+    fn json_value(&self) -> Value {
+        let sites: Vec<Value> = self.sites.iter()
+            .map(|s| {
+                let mut site = json!({
+                    "x": s.pos.x,
+                    "y": s.pos.y,
+                    "vx": s.vel.x,
+                    "vy": s.vel.y,
+                    "turn_rate": s.turn_rate,
+                    "speed_mult": s.speed_mult,
+                });
+                // Per-site dynamics overrides, only when set
+                if let Some(theta) = s.theta { site["theta"] = json!(theta); }
+                if let Some(sigma) = s.sigma { site["sigma"] = json!(sigma); }
+                if s.weight != 1.0 { site["weight"] = json!(s.weight); }
+                site
+            })
+            .collect();
+        let vortices: Vec<Value> = self.vortices.iter()
+            .map(|v| json!({
+                "x": v.center.x,
+                "y": v.center.y,
+                "strength": v.strength,
+                "radius": v.radius,
+            }))
+            .collect();
+        json!({
+            "fractional_sites": self.fractional_sites,
+            "spiral_spawn_counter": self.spiral_spawn_counter,
+            "pull_mode": self.pull_mode.to_string(),
+            "vortices": vortices,
+            "sites": sites,
+        })
+    }
+
+    /// Write `to_json` output to `path`, to resume a run later with `load`
+    pub fn save(&self, path: &Path) -> crate::Result<()> {
+        std::fs::write(path, self.to_json())?;
+        Ok(())
+    }
+
+    /// Restore a collection written by `save`
+    pub fn load(path: &Path) -> crate::Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Restore a collection from `to_json` or `to_json_compact` output.
+    ///
+    /// Without saved RNG state (compact form), the RNG is seeded with 0.
+    pub fn from_json(data: &str) -> crate::Result<Self> {
+        let value: Value = serde_json::from_str(data).map_err(|e| json_err(e.to_string()))?;
+        let obj = value.as_object().ok_or_else(|| json_err("expected an object"))?;
+
+        let num = |o: &Map<String, Value>, key: &str, ctx: &str| -> crate::Result<f64> {
+            o.get(key)
+                .and_then(Value::as_f64)
+                .ok_or_else(|| json_err(format!("{}: missing or non-numeric {:?}", ctx, key)))
+        };
+
+        let sites = obj.get("sites")
+            .and_then(Value::as_array)
+            .ok_or_else(|| json_err("missing \"sites\" array"))?
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let ctx = format!("site {}", i);
+                let o = v.as_object().ok_or_else(|| json_err(format!("{}: expected an object", ctx)))?;
+                Ok(Site {
+                    pos: Position::new(num(o, "x", &ctx)?, num(o, "y", &ctx)?),
+                    vel: Velocity::new(num(o, "vx", &ctx)?, num(o, "vy", &ctx)?),
+                    turn_rate: num(o, "turn_rate", &ctx)?,
+                    speed_mult: num(o, "speed_mult", &ctx)?,
+                    theta: o.get("theta").and_then(Value::as_f64),
+                    sigma: o.get("sigma").and_then(Value::as_f64),
+                    weight: o.get("weight").and_then(Value::as_f64).unwrap_or(1.0),
+                })
+            })
+            .collect::<crate::Result<Vec<Site>>>()?;
+
+        let rng = match obj.get("rng") {
+            None => ChaCha8Rng::seed_from_u64(0),
+            Some(rng) => {
+                let seed = rng.get("seed").and_then(Value::as_str)
+                    .ok_or_else(|| json_err("rng: missing \"seed\""))?;
+                let stream = rng.get("stream").and_then(Value::as_u64)
+                    .ok_or_else(|| json_err("rng: missing \"stream\""))?;
+                let word_pos = rng.get("word_pos").and_then(Value::as_str)
+                    .ok_or_else(|| json_err("rng: missing \"word_pos\""))?;
+                let mut rng = ChaCha8Rng::from_seed(from_hex::<32>(seed, "rng.seed")?);
+                rng.set_stream(stream);
+                rng.set_word_pos(u128::from_be_bytes(from_hex::<16>(word_pos, "rng.word_pos")?));
+                rng
+            }
+        };
+
+        let mut collection = Self::with_rng(sites, rng);
+        collection.fractional_sites = num(obj, "fractional_sites", "collection")?;
+        // Absent in files saved before the spiral strategy existed
+        collection.spiral_spawn_counter = obj.get("spiral_spawn_counter").and_then(Value::as_u64).unwrap_or(0);
+        // Likewise absent in files saved before pull modes and vortices
+        if let Some(mode) = obj.get("pull_mode") {
+            let mode = mode.as_str().ok_or_else(|| json_err("pull_mode: expected a string"))?;
+            collection.pull_mode = mode.parse::<PullMode>().map_err(json_err)?;
+        }
+        if let Some(vortices) = obj.get("vortices") {
+            collection.vortices = vortices.as_array()
+                .ok_or_else(|| json_err("vortices: expected an array"))?
+                .iter()
+                .enumerate()
+                .map(|(i, v)| {
+                    let ctx = format!("vortex {}", i);
+                    let o = v.as_object().ok_or_else(|| json_err(format!("{}: expected an object", ctx)))?;
+                    Ok(Vortex {
+                        center: Position::new(num(o, "x", &ctx)?, num(o, "y", &ctx)?),
+                        strength: num(o, "strength", &ctx)?,
+                        radius: num(o, "radius", &ctx)?,
+                    })
+                })
+                .collect::<crate::Result<Vec<Vortex>>>()?;
+        }
+        Ok(collection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AdjustParams, Position, PullMode, SiteCollection, SplitStrategy};
+
+    fn advance(sites: &mut SiteCollection) {
+        sites.step(15.0, 1.0 / 30.0, 320.0, 240.0, None, 0.0, 3.0, 3.0);
+        sites.adjust_count(80, 0.5, 1.0 / 30.0, SplitStrategy::Weighted, AdjustParams { img_area: 320.0 * 240.0, ..Default::default() });
+    }
+
+    #[test]
+    fn test_json_round_trip_preserves_steps() {
+        let mut original = SiteCollection::random(50, 320.0, 240.0, 7);
+        original.set_theta(3, 0.5);
+        original.set_sigma(4, 8.0);
+        original.sites[5].weight = 2.5;
+        for _ in 0..5 {
+            advance(&mut original);
+        }
+
+        let mut restored = SiteCollection::from_json(&original.to_json()).unwrap();
+        assert_eq!(restored.fractional_sites, original.fractional_sites);
+        assert_eq!(restored.spiral_spawn_counter, original.spiral_spawn_counter);
+        for _ in 0..20 {
+            advance(&mut original);
+            advance(&mut restored);
+        }
+        assert_eq!(restored.len(), original.len());
+        for (a, b) in original.sites.iter().zip(&restored.sites) {
+            assert_eq!(a.pos.x.to_bits(), b.pos.x.to_bits());
+            assert_eq!(a.pos.y.to_bits(), b.pos.y.to_bits());
+            assert_eq!(a.vel.x.to_bits(), b.vel.x.to_bits());
+            assert_eq!(a.turn_rate.to_bits(), b.turn_rate.to_bits());
+            assert_eq!(a.speed_mult.to_bits(), b.speed_mult.to_bits());
+        }
+        assert_eq!(restored.weights(), original.weights());
+    }
+
+    #[test]
+    fn test_save_load_resumes() {
+        let mut reference = SiteCollection::random(40, 320.0, 240.0, 11);
+        advance(&mut reference);
+        let path = std::env::temp_dir().join(format!("voronoi-sites-{}.json", std::process::id()));
+        reference.save(&path).unwrap();
+        let mut resumed = SiteCollection::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        advance(&mut reference);
+        advance(&mut resumed);
+        assert_eq!(resumed.positions(), reference.positions());
+    }
+
+    #[test]
+    fn test_save_load_keeps_pull_mode_and_vortices() {
+        let mut reference = SiteCollection::random(40, 320.0, 240.0, 13);
+        reference.pull_mode = PullMode::Slerp;
+        reference.add_vortex(160.0, 120.0, 2.0, 80.0);
+        advance(&mut reference);
+        let path = std::env::temp_dir().join(format!("voronoi-sites-vortex-{}.json", std::process::id()));
+        reference.save(&path).unwrap();
+        let mut resumed = SiteCollection::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(resumed.pull_mode, PullMode::Slerp);
+        assert_eq!(resumed.vortices, reference.vortices);
+        // Pull everything toward one point so the pull mode matters
+        let centroids = vec![Position::new(100.0, 80.0); reference.len()];
+        for _ in 0..5 {
+            reference.step(15.0, 1.0 / 30.0, 320.0, 240.0, Some(&centroids), 2.0, 3.0, 3.0);
+            resumed.step(15.0, 1.0 / 30.0, 320.0, 240.0, Some(&centroids), 2.0, 3.0, 3.0);
+        }
+        assert_eq!(resumed.positions(), reference.positions());
+    }
+
+    /// The serde derives carry the RNG too
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut reference = SiteCollection::random(40, 320.0, 240.0, 12);
+        advance(&mut reference);
+        let mut resumed: SiteCollection = serde_json::from_str(&serde_json::to_string(&reference).unwrap()).unwrap();
+        advance(&mut reference);
+        advance(&mut resumed);
+        assert_eq!(resumed.positions(), reference.positions());
+    }
+
+    #[test]
+    fn test_json_compact_omits_rng() {
+        let original = SiteCollection::random(10, 100.0, 100.0, 3);
+        let compact = original.to_json_compact();
+        assert!(!compact.contains("\"rng\""));
+        assert!(compact.len() < original.to_json().len());
+
+        let restored = SiteCollection::from_json(&compact).unwrap();
+        assert_eq!(restored.positions(), original.positions());
+
+        assert!(SiteCollection::from_json("[]").is_err());
+        assert!(SiteCollection::from_json(r#"{"fractional_sites":0,"sites":[{"x":1}]}"#).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+//! Static 2D k-d tree for nearest-site lookups at high site counts.
+
+use crate::Position;
+
+/// Balanced k-d tree over site positions, stored implicitly: the node for a slice
+/// `[lo, hi)` is its median element at `(lo + hi) / 2`, split on x at even depths
+/// and y at odd ones, with the halves on either side as its subtrees.
+pub(crate) struct KdTree {
+    /// (x, y, site index), in tree order
+    points: Vec<(f32, f32, u32)>,
+}
+
+impl KdTree {
+    pub(crate) fn new(sites: &[Position]) -> Self {
+        let mut points: Vec<(f32, f32, u32)> = sites.iter()
+            .enumerate()
+            .map(|(i, p)| (p.x as f32, p.y as f32, i as u32))
+            .collect();
+        Self::build(&mut points, 0);
+        Self { points }
+    }
+
+    fn build(points: &mut [(f32, f32, u32)], depth: usize) {
+        if points.len() <= 1 {
+            return;
+        }
+        let mid = points.len() / 2;
+        if depth.is_multiple_of(2) {
+            points.select_nth_unstable_by(mid, |a, b| a.0.total_cmp(&b.0));
+        } else {
+            points.select_nth_unstable_by(mid, |a, b| a.1.total_cmp(&b.1));
+        }
+        let (left, right) = points.split_at_mut(mid);
+        Self::build(left, depth + 1);
+        Self::build(&mut right[1..], depth + 1);
+    }
+
+    /// Nearest site to (px, py) as (site index, squared distance); equidistant
+    /// sites go to the lowest index. Distances are computed in `f32` exactly like
+    /// `CpuBackend::nearest_site`.
+    pub(crate) fn nearest(&self, px: f32, py: f32) -> (u32, f32) {
+        let mut best = (0u32, f32::INFINITY);
+        self.search(0, self.points.len(), 0, px, py, &mut best);
+        best
+    }
+
+    /// Nearest and second-nearest sites as ((index, squared distance), (index or
+    /// -1, squared distance)), ties broken by lower index; the nearest matches
+    /// `nearest`
+    pub(crate) fn nearest_two(&self, px: f32, py: f32) -> ((u32, f32), (i32, f32)) {
+        let mut best = [(u32::MAX, f32::INFINITY); 2];
+        self.search_two(0, self.points.len(), 0, px, py, &mut best);
+        let second = if best[1].0 == u32::MAX { -1 } else { best[1].0 as i32 };
+        (best[0], (second, best[1].1))
+    }
+
+    fn search_two(&self, lo: usize, hi: usize, depth: usize, px: f32, py: f32, best: &mut [(u32, f32); 2]) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let (x, y, idx) = self.points[mid];
+        let (dx, dy) = (px - x, py - y);
+        let dist = dx * dx + dy * dy;
+        let beats = |b: (u32, f32)| dist < b.1 || (dist == b.1 && idx < b.0);
+        if beats(best[0]) {
+            best[1] = best[0];
+            best[0] = (idx, dist);
+        } else if beats(best[1]) {
+            best[1] = (idx, dist);
+        }
+
+        let diff = if depth.is_multiple_of(2) { dx } else { dy };
+        let (near, far) = if diff < 0.0 { ((lo, mid), (mid + 1, hi)) } else { ((mid + 1, hi), (lo, mid)) };
+        self.search_two(near.0, near.1, depth + 1, px, py, best);
+        if diff * diff <= best[1].1 {
+            self.search_two(far.0, far.1, depth + 1, px, py, best);
+        }
+    }
+
+    fn search(&self, lo: usize, hi: usize, depth: usize, px: f32, py: f32, best: &mut (u32, f32)) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let (x, y, idx) = self.points[mid];
+        let (dx, dy) = (px - x, py - y);
+        let dist = dx * dx + dy * dy;
+        if dist < best.1 || (dist == best.1 && idx < best.0) {
+            *best = (idx, dist);
+        }
+
+        let diff = if depth.is_multiple_of(2) { dx } else { dy };
+        let (near, far) = if diff < 0.0 { ((lo, mid), (mid + 1, hi)) } else { ((mid + 1, hi), (lo, mid)) };
+        self.search(near.0, near.1, depth + 1, px, py, best);
+        // Sites across the split are at least |diff| away on this axis
+        if diff * diff <= best.1 {
+            self.search(far.0, far.1, depth + 1, px, py, best);
+        }
+    }
+}
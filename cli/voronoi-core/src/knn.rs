@@ -0,0 +1,234 @@
+//! K-nearest-site lookups over the spatial grid.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use crate::{CpuBackend, Position, Result, SiteCollection, VoronoiResult};
+use crate::voronoi::ComputeBackend;
+
+/// Bucket grid over site positions, as used by `CpuBackend`'s nearest-site search
+pub struct SpatialGrid {
+    cells: Vec<Vec<u32>>,
+    cols: usize,
+    rows: usize,
+    cell_w: f32,
+    cell_h: f32,
+}
+
+impl SpatialGrid {
+    /// Bucket `sites` over a `width`×`height` image (about one site per cell)
+    pub fn new(sites: &[Position], width: u32, height: u32) -> Self {
+        let (cells, cols, rows, cell_w, cell_h) = CpuBackend::build_grid(sites, width, height);
+        Self { cells, cols, rows, cell_w, cell_h }
+    }
+
+    /// Fill `nearest` (length k) with the k nearest sites to (px, py) as (index,
+    /// squared distance), closest first; unfilled slots (fewer than k sites) are
+    /// (-1, ∞). `pos` gives the position of the site with the given index.
+    ///
+    /// `nearest` is a bounded max-heap kept sorted, so its last slot is the
+    /// current k-th distance and the ring search stops once no unvisited cell can
+    /// beat it. Sites are visited in the same order as `CpuBackend::nearest_site`
+    /// and equidistant ones keep visit order, so slot 0 is the site it returns.
+    #[inline]
+    fn k_nearest(&self, px: f32, py: f32, pos: impl Fn(usize) -> Position, nearest: &mut [(i32, f32)]) {
+        let Some(k) = nearest.len().checked_sub(1) else { return };
+        nearest.fill((-1, f32::INFINITY));
+        let gc = ((px / self.cell_w) as usize).min(self.cols - 1);
+        let gr = ((py / self.cell_h) as usize).min(self.rows - 1);
+        let ox = px - gc as f32 * self.cell_w;
+        let oy = py - gr as f32 * self.cell_h;
+
+        for radius in 0u32.. {
+            let r = radius as usize;
+            let r_start = gr.saturating_sub(r);
+            let r_end = (gr + r + 1).min(self.rows);
+            let c_start = gc.saturating_sub(r);
+            let c_end = (gc + r + 1).min(self.cols);
+
+            for ri in r_start..r_end {
+                for ci in c_start..c_end {
+                    if radius > 0
+                        && ri > r_start && ri < r_end - 1
+                        && ci > c_start && ci < c_end - 1
+                    {
+                        continue;
+                    }
+                    for &site_idx in &self.cells[ri * self.cols + ci] {
+                        let site = pos(site_idx as usize);
+                        let dx = px - site.x as f32;
+                        let dy = py - site.y as f32;
+                        let dist = dx * dx + dy * dy;
+                        // Rings clamped at the grid edge revisit cells
+                        if dist >= nearest[k].1 || nearest.iter().any(|&(i, _)| i == site_idx as i32) {
+                            continue;
+                        }
+                        let slot = nearest.iter().position(|&(_, d)| d > dist).unwrap_or(k);
+                        nearest.copy_within(slot..k, slot + 1);
+                        nearest[slot] = (site_idx as i32, dist);
+                    }
+                }
+            }
+
+            let rf = radius as f32;
+            let min_unchecked = (ox + rf * self.cell_w)
+                .min(self.cell_w * (rf + 1.0) - ox)
+                .min(oy + rf * self.cell_h)
+                .min(self.cell_h * (rf + 1.0) - oy);
+            if nearest[k].0 >= 0 && nearest[k].1 <= min_unchecked * min_unchecked {
+                break;
+            }
+            if r_start == 0 && c_start == 0
+                && r_end == self.rows && c_end == self.cols
+            {
+                break;
+            }
+        }
+    }
+}
+
+impl SiteCollection {
+    /// The `k` sites nearest to (px, py) as (site index, distance), closest first
+    /// (fewer if there are fewer sites). `grid` must be built from this
+    /// collection's current positions.
+    pub fn compute_knn(&self, px: f64, py: f64, k: usize, grid: &SpatialGrid) -> Vec<(usize, f64)> {
+        let mut nearest = vec![(-1, f32::INFINITY); k.min(self.sites.len())];
+        if !nearest.is_empty() {
+            grid.k_nearest(px as f32, py as f32, |i| self.sites[i].pos, &mut nearest);
+        }
+        nearest.into_iter()
+            .map(|(i, dist_sq)| (i as usize, (dist_sq as f64).sqrt()))
+            .collect()
+    }
+
+    /// Move the site nearest `target` onto it, keeping its velocity. Returns the
+    /// site's index, or None if there are no sites. `grid` must be built from this
+    /// collection's current positions (and is stale afterwards).
+    pub fn teleport_nearest_to(&mut self, target: Position, grid: &SpatialGrid) -> Option<usize> {
+        let &(index, _) = self.compute_knn(target.x, target.y, 1, grid).first()?;
+        self.sites[index].pos = target;
+        Some(index)
+    }
+}
+
+/// A diagram plus each pixel's `K` nearest sites (see `CpuBackend::compute_with_knn`)
+pub struct VoronoiResultKnn<const K: usize> {
+    /// The diagram as computed by `ComputeBackend::compute`
+    pub result: VoronoiResult,
+    /// Per pixel (row-major), the `K` nearest site indices, closest first; -1 past
+    /// the last site when there are fewer than `K`
+    pub knn_cells: Vec<[i32; K]>,
+    /// Euclidean distances matching `knn_cells` (∞ for -1 entries)
+    pub knn_distances: Vec<[f32; K]>,
+}
+
+impl CpuBackend {
+    /// Compute the diagram along with each pixel's `K` nearest sites, for `K` of
+    /// 2, 3, or 4 (e.g. `compute_with_knn::<3>`), for blending between neighboring
+    /// cells.
+    ///
+    /// **Memory:** `width * height * 8 * K` bytes on top of the result. With the
+    /// grid search, `knn_cells[i][0]` equals `result.cell_of[i]`.
+    pub fn compute_with_knn<const K: usize>(
+        &mut self,
+        image: &image::RgbImage,
+        sites: &[Position],
+    ) -> Result<VoronoiResultKnn<K>> {
+        const { assert!(K >= 2 && K <= 4, "compute_with_knn supports K of 2 to 4") };
+        let result = self.compute(image, sites)?;
+        let (width, height) = image.dimensions();
+        let grid = SpatialGrid::new(sites, width, height);
+        let num_pixels = (width * height) as usize;
+        let mut knn_cells = vec![[-1; K]; num_pixels];
+        let mut knn_distances = vec![[f32::INFINITY; K]; num_pixels];
+
+        let process_row = |y: usize, (cells, distances): (&mut [[i32; K]], &mut [[f32; K]])| {
+            let py = y as f32 + 0.5;
+            let mut nearest = [(-1, f32::INFINITY); K];
+            for (x, (cell, distance)) in cells.iter_mut().zip(distances).enumerate() {
+                grid.k_nearest(x as f32 + 0.5, py, |i| sites[i], &mut nearest);
+                for (j, &(site_idx, dist_sq)) in nearest.iter().enumerate() {
+                    cell[j] = site_idx;
+                    distance[j] = dist_sq.sqrt();
+                }
+            }
+        };
+
+        let row_len = (width as usize).max(1);
+        #[cfg(feature = "parallel")]
+        knn_cells.par_chunks_mut(row_len)
+            .zip(knn_distances.par_chunks_mut(row_len))
+            .enumerate()
+            .for_each(|(y, row)| process_row(y, row));
+        #[cfg(not(feature = "parallel"))]
+        knn_cells.chunks_mut(row_len)
+            .zip(knn_distances.chunks_mut(row_len))
+            .enumerate()
+            .for_each(|(y, row)| process_row(y, row));
+
+        Ok(VoronoiResultKnn { result, knn_cells, knn_distances })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_with_knn() {
+        let image = image::RgbImage::from_fn(120, 80, |x, y| image::Rgb([(x * 3) as u8, (y * 5) as u8, 0]));
+        let sites = SiteCollection::random(150, 120.0, 80.0, 8);
+        let mut positions = sites.positions();
+        // Integer positions put pixel centers equidistant from pairs of sites
+        positions.extend((0..6).map(|i| Position::new(10.0 * i as f64, 40.0)));
+
+        let mut backend = CpuBackend::with_second_nearest(true);
+        let knn = backend.compute_with_knn::<3>(&image, &positions).unwrap();
+        let second = knn.result.second_cell_of.as_ref().unwrap();
+        for (i, &second) in second.iter().enumerate() {
+            assert_eq!(knn.knn_cells[i][0], knn.result.cell_of[i], "pixel {}", i);
+            assert_eq!(knn.knn_distances[i][0], knn.result.pixel_distances.as_ref().unwrap()[i]);
+            assert!(knn.knn_distances[i].is_sorted());
+            if knn.knn_distances[i][1] < knn.knn_distances[i][2] {
+                assert_eq!(knn.knn_cells[i][1], second, "pixel {}", i);
+            }
+        }
+
+        // Against brute force (random positions, so no ties)
+        let grid = SpatialGrid::new(&sites.positions(), 120, 80);
+        for (px, py) in [(0.5, 0.5), (60.2, 33.7), (119.5, 79.5)] {
+            let mut expected: Vec<(usize, f64)> = sites.sites.iter().enumerate()
+                .map(|(i, s)| (i, ((s.pos.x - px).powi(2) + (s.pos.y - py).powi(2)).sqrt()))
+                .collect();
+            expected.sort_by(|a, b| a.1.total_cmp(&b.1));
+            let found = sites.compute_knn(px, py, 4, &grid);
+            assert_eq!(found.iter().map(|n| n.0).collect::<Vec<_>>(), expected[..4].iter().map(|n| n.0).collect::<Vec<_>>());
+        }
+
+        // Fewer sites than K
+        let two = [Position::new(10.0, 10.0), Position::new(100.0, 70.0)];
+        let knn = CpuBackend::new().compute_with_knn::<4>(&image, &two).unwrap();
+        assert_eq!(knn.knn_cells[0], [0, 1, -1, -1]);
+        assert_eq!(knn.knn_distances[0][3], f32::INFINITY);
+    }
+
+    #[test]
+    fn test_teleport_nearest_to() {
+        let mut sites = SiteCollection::random(200, 100.0, 100.0, 6);
+        sites.teleport_site(0, Position::new(12.5, 87.0)).unwrap();
+        let vel = sites.sites[0].vel;
+        let grid = SpatialGrid::new(&sites.positions(), 100, 100);
+        assert_eq!(sites.teleport_nearest_to(Position::new(12.6, 86.9), &grid), Some(0));
+        assert_eq!(sites.positions()[0], Position::new(12.6, 86.9));
+        assert_eq!((sites.sites[0].vel.x, sites.sites[0].vel.y), (vel.x, vel.y));
+
+        // Matches a linear scan
+        let target = Position::new(61.3, 20.4);
+        let grid = SpatialGrid::new(&sites.positions(), 100, 100);
+        let expected = (0..sites.len())
+            .min_by(|&a, &b| sites.sites[a].pos.dist(&target).total_cmp(&sites.sites[b].pos.dist(&target)));
+        assert_eq!(sites.teleport_nearest_to(target, &grid), expected);
+
+        let mut empty = SiteCollection::new(vec![], 0);
+        assert_eq!(empty.teleport_nearest_to(Position::new(1.0, 1.0), &SpatialGrid::new(&[], 10, 10)), None);
+    }
+}
@@ -0,0 +1,123 @@
+//! Cell-index labels drawn next to each site.
+
+use crate::{Position, VoronoiResult};
+
+/// 3×5 bitmap digits, one row per byte (low 3 bits, MSB = leftmost column)
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+const GLYPH_W: u32 = 3;
+const GLYPH_H: u32 = 5;
+
+const SITE_COLOR: image::Rgb<u8> = image::Rgb([0, 0, 0]);
+const TEXT_COLOR: image::Rgb<u8> = image::Rgb([255, 255, 255]);
+const SHADOW_COLOR: image::Rgb<u8> = image::Rgb([0, 0, 0]);
+
+/// Pixel scale of the digit font for a requested text height (at least 1)
+fn font_scale(font_size: u32) -> u32 {
+    (font_size / GLYPH_H).max(1)
+}
+
+/// Top-left corner of the label for a site at `pos`: offset down and to the right,
+/// clear of the site marker
+fn label_origin(pos: &Position, font_size: u32) -> (i64, i64) {
+    let offset = font_scale(font_size) as i64 + 2;
+    (pos.x.floor() as i64 + offset, pos.y.floor() as i64 + offset)
+}
+
+/// Draw decimal `n` with its top-left corner at `(x0, y0)`, clipped to the image
+fn draw_number(image: &mut image::RgbImage, n: usize, x0: i64, y0: i64, scale: u32, color: image::Rgb<u8>) {
+    let (w, h) = (image.width() as i64, image.height() as i64);
+    let advance = ((GLYPH_W + 1) * scale) as i64;
+    for (k, ch) in n.to_string().bytes().enumerate() {
+        let glyph = &DIGITS[(ch - b'0') as usize];
+        let gx = x0 + k as i64 * advance;
+        for (row, bits) in glyph.iter().enumerate() {
+            for col in 0..GLYPH_W {
+                if bits & (1 << (GLYPH_W - 1 - col)) == 0 {
+                    continue;
+                }
+                for dy in 0..scale as i64 {
+                    for dx in 0..scale as i64 {
+                        let x = gx + (col * scale) as i64 + dx;
+                        let y = y0 + row as i64 * scale as i64 + dy;
+                        if x >= 0 && y >= 0 && x < w && y < h {
+                            image.put_pixel(x as u32, y as u32, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl VoronoiResult {
+    /// Render the diagram with each site marked by a pixel and labeled with its
+    /// cell index, offset below-right of the site.
+    ///
+    /// Digits use a built-in 3×5 bitmap font scaled to about `font_size` pixels
+    /// tall, in white with a 1 px black shadow; `font_size = 0` draws only the
+    /// site pixels. Labels are clipped at the image edges.
+    pub fn render_with_site_labels(&self, sites: &[Position], font_size: u32) -> image::RgbImage {
+        let mut image = image::RgbImage::from_raw(self.width, self.height, self.render())
+            .expect("Buffer size mismatch");
+        let scale = font_scale(font_size);
+        if font_size > 0 {
+            for (i, site) in sites.iter().enumerate() {
+                let (x, y) = label_origin(site, font_size);
+                draw_number(&mut image, i, x + 1, y + 1, scale, SHADOW_COLOR);
+                draw_number(&mut image, i, x, y, scale, TEXT_COLOR);
+            }
+        }
+        for site in sites {
+            let (x, y) = (site.x.floor(), site.y.floor());
+            if x >= 0.0 && y >= 0.0 && x < self.width as f64 && y < self.height as f64 {
+                image.put_pixel(x as u32, y as u32, SITE_COLOR);
+            }
+        }
+        image
+    }
+}
+
+#[cfg(all(test, feature = "cpu"))]
+mod tests {
+    use super::label_origin;
+    use crate::{ComputeBackend, CpuBackend, Position};
+
+    #[test]
+    fn test_site_labels_drawn_near_sites() {
+        let image = image::RgbImage::from_pixel(120, 80, image::Rgb([90, 120, 60]));
+        let sites = [Position::new(20.0, 20.0), Position::new(80.0, 40.0)];
+        let result = CpuBackend::new().compute(&image, &sites).unwrap();
+        let plain = result.render();
+
+        let labeled = result.render_with_site_labels(&sites, 10);
+        for site in &sites {
+            let (x0, y0) = label_origin(site, 10);
+            let lit = (y0..y0 + 10)
+                .flat_map(|y| (x0..x0 + 6).map(move |x| (x as u32, y as u32)))
+                .filter(|&(x, y)| labeled.get_pixel(x, y).0 == [255, 255, 255])
+                .count();
+            assert!(lit > 0, "no label pixels near {:?}", site);
+            // Site pixel itself stays unoccluded
+            assert_eq!(labeled.get_pixel(site.x as u32, site.y as u32).0, [0, 0, 0]);
+        }
+
+        // font_size 0: only the site pixels change
+        let bare = result.render_with_site_labels(&sites, 0);
+        let changed: Vec<(u32, u32)> = bare.enumerate_pixels()
+            .filter(|(x, y, p)| p.0[..] != plain[((y * 120 + x) * 3) as usize..][..3])
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert_eq!(changed, vec![(20, 20), (80, 40)]);
+    }
+}
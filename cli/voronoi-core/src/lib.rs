@@ -3,23 +3,68 @@
 //! Provides both CPU (Rayon-parallelized) and GPU (wgpu) implementations
 //! for computing Voronoi diagrams and rendering them with averaged colors.
 
+mod ascii;
+mod builder;
+mod color_match;
+mod colormap;
+mod csv;
+mod dual;
+mod export;
+mod forces;
+mod history;
+mod init;
+mod json;
+mod labels;
+mod metrics;
+mod mst;
 mod site;
+mod stipple;
 mod voronoi;
 
+#[cfg(feature = "video-init")]
+mod motion;
+
+#[cfg(feature = "svg-init")]
+mod svg_path;
+
+#[cfg(any(feature = "webp", feature = "avif"))]
+mod encode;
+
 #[cfg(feature = "cpu")]
 mod cpu;
 
+#[cfg(feature = "cpu")]
+mod kdtree;
+
+#[cfg(feature = "cpu")]
+mod knn;
+
 #[cfg(feature = "gpu")]
 mod gpu;
 
-pub use site::{Position, Site, SiteCollection, SplitStrategy, Velocity};
+#[cfg(all(feature = "gpu", feature = "cpu"))]
+mod fallback;
+
+pub use builder::{InitStrategy, SiteCollectionBuilder};
+pub use colormap::HeatmapColormap;
+pub use csv::SITES_CSV_HEADER;
+pub use site::{
+    AdjustParams, EdgeBehavior, Position, PullMode, Site, SiteCallback, SiteCollection, SiteCollectionHandle, SplitStrategy,
+    StepParams, Velocity, Vortex,
+};
 pub use voronoi::{VoronoiComputer, VoronoiResult, ComputeBackend};
 
 #[cfg(feature = "cpu")]
-pub use cpu::CpuBackend;
+pub use cpu::{CentroidWeighting, CpuBackend};
+
+#[cfg(feature = "cpu")]
+pub use knn::{SpatialGrid, VoronoiResultKnn};
 
 #[cfg(feature = "gpu")]
-pub use gpu::GpuBackend;
+pub use gpu::{FrameToken, GpuBackend};
+
+#[cfg(all(feature = "gpu", feature = "cpu"))]
+pub use fallback::FallbackBackend;
 
 /// RGB color tuple
 pub type Rgb = [u8; 3];
@@ -33,6 +78,25 @@ pub enum VoronoiError {
     #[error("No sites provided")]
     NoSites,
 
+    #[error("Site index {index} out of range ({len} sites)")]
+    SiteIndex { index: usize, len: usize },
+
+    #[error("CSV error: {0}")]
+    Csv(String),
+
+    #[error("JSON error: {0}")]
+    Json(String),
+
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[cfg(feature = "svg-init")]
+    #[error("SVG path error: {0}")]
+    SvgPath(String),
+
     #[cfg(feature = "gpu")]
     #[error("GPU error: {0}")]
     Gpu(String),
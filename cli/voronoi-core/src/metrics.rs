@@ -0,0 +1,474 @@
+//! Diagnostic metrics and visualizations over site collections and Voronoi
+//! results (e.g. for `--output-stats`).
+
+use crate::{ComputeBackend, Position, Result, SiteCollection, VoronoiResult};
+
+/// z-component of (a - o) × (b - o); positive when o → a → b turns counter-clockwise (y up)
+fn cross(o: Position, a: Position, b: Position) -> f64 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+/// Convex hull of `points` by Graham scan, counter-clockwise (y up), without
+/// collinear points. Fewer than 3 non-collinear points yield a degenerate hull.
+pub(crate) fn convex_hull(points: &[Position]) -> Vec<Position> {
+    let Some(&pivot) = points.iter().min_by(|a, b| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x))) else {
+        return Vec::new();
+    };
+    // Every other point lies on or above the pivot, so the cross product orders them by angle
+    let mut rest: Vec<Position> = points.iter().copied().filter(|p| p.x != pivot.x || p.y != pivot.y).collect();
+    rest.sort_by(|&a, &b| {
+        cross(pivot, b, a).total_cmp(&0.0)
+            .then(pivot.dist(&a).total_cmp(&pivot.dist(&b)))
+    });
+
+    let mut hull = vec![pivot];
+    for p in rest {
+        while hull.len() >= 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+    hull
+}
+
+impl VoronoiResult {
+    /// Fraction of pixels assigned to the same cell index as in `prev`: 1.0 for an
+    /// unchanged tessellation, 0.0 when every pixel changed cell. Indices are
+    /// compared as-is, so sites added or removed mid-collection count as changes.
+    /// Results of different sizes score 0.0.
+    pub fn temporal_consistency(&self, prev: &VoronoiResult) -> f64 {
+        if self.cell_of.len() != prev.cell_of.len() || self.cell_of.is_empty() {
+            return 0.0;
+        }
+        let same = self.cell_of.iter().zip(&prev.cell_of).filter(|(a, b)| a == b).count();
+        same as f64 / self.cell_of.len() as f64
+    }
+
+    /// Peak signal-to-noise ratio (dB) of the rendered diagram against `image`
+    /// (the source it was computed from): `10 · log10(255² / MSE)` over all
+    /// channels. Infinite for an exact match.
+    pub fn psnr(&self, image: &image::RgbImage) -> f64 {
+        let rendered = self.render();
+        let sum_sq: f64 = rendered.iter().zip(image.as_raw())
+            .map(|(&a, &b)| (a as f64 - b as f64).powi(2))
+            .sum();
+        let mse = sum_sq / rendered.len().max(1) as f64;
+        10.0 * (255.0 * 255.0 / mse).log10()
+    }
+
+    /// `1 - mean_color_change / 255`, where the change is the absolute per-channel
+    /// difference between the rendered frames, averaged over pixels and channels.
+    /// 1.0 for identical renders; results of different sizes score 0.0.
+    pub fn temporal_color_consistency(&self, prev: &VoronoiResult) -> f64 {
+        if (self.width, self.height) != (prev.width, prev.height) || self.cell_of.is_empty() {
+            return 0.0;
+        }
+        let (a, b) = (self.render(), prev.render());
+        let total: u64 = a.iter().zip(&b).map(|(&x, &y)| x.abs_diff(y) as u64).sum();
+        1.0 - total as f64 / a.len() as f64 / 255.0
+    }
+}
+
+impl SiteCollection {
+    /// Smallest site count in `[1, max_sites]` whose diagram of
+    /// `SiteCollection::random(count, .., seed)` sites reaches `target_psnr` dB
+    /// against `image` (see `VoronoiResult::psnr`), by binary search with one
+    /// render per step. PSNR isn't strictly monotone in the count, so the result
+    /// is a count that reaches the target while `count - 1` doesn't. Returns
+    /// `max_sites` if even that falls short.
+    pub fn find_optimal_site_count(
+        backend: &mut dyn ComputeBackend,
+        image: &image::RgbImage,
+        target_psnr: f64,
+        seed: u64,
+        max_sites: usize,
+    ) -> Result<usize> {
+        let (width, height) = (image.width() as f64, image.height() as f64);
+        let mut psnr_at = |count: usize| -> Result<f64> {
+            let sites = SiteCollection::random(count, width, height, seed);
+            Ok(backend.compute(image, &sites.positions())?.psnr(image))
+        };
+        let max_sites = max_sites.max(1);
+        if psnr_at(max_sites)? < target_psnr {
+            return Ok(max_sites);
+        }
+        // Invariant: `lo` misses the target (0 sites trivially), `hi` reaches it
+        let (mut lo, mut hi) = (0, max_sites);
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if psnr_at(mid)? >= target_psnr {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+        Ok(hi)
+    }
+
+    /// Average cell area (pixels) with `n_sites` sites on a `width × height`
+    /// image
+    pub fn expected_cell_area(n_sites: usize, width: f64, height: f64) -> f64 {
+        width * height / n_sites as f64
+    }
+
+    /// Site count (at least 1) whose average cell area is closest to
+    /// `target_cell_pixels` on a `width × height` image
+    pub fn sites_for_area(target_cell_pixels: f64, width: f64, height: f64) -> usize {
+        ((width * height / target_cell_pixels).round() as usize).max(1)
+    }
+
+    /// Site count whose cells are about the size of `image`'s detail.
+    ///
+    /// The detail scale is the luminance correlation length: the smallest lag
+    /// (pixels) at which the normalized autocorrelation, averaged over
+    /// horizontal and vertical shifts, drops below `detail_threshold` (0–1;
+    /// higher means finer detail, so more sites). Cells are taken as squares of
+    /// that side. Lags are searched up to a quarter of the smaller dimension
+    /// (at most 256); a flat image gets 1 site.
+    pub fn sites_for_perceptual_detail(image: &image::RgbImage, detail_threshold: f64) -> usize {
+        let (w, h) = (image.width() as usize, image.height() as usize);
+        let luma: Vec<f64> = image.pixels()
+            .map(|p| 0.299 * p[0] as f64 + 0.587 * p[1] as f64 + 0.114 * p[2] as f64)
+            .collect();
+        let mean = luma.iter().sum::<f64>() / luma.len().max(1) as f64;
+        let centered: Vec<f64> = luma.iter().map(|l| l - mean).collect();
+        let variance = centered.iter().map(|c| c * c).sum::<f64>() / centered.len().max(1) as f64;
+        if variance < 1e-9 {
+            return 1;
+        }
+
+        // Mean of c(x, y) · c(x + dx, y + dy) over overlapping pixels, over the variance
+        let correlation = |dx: usize, dy: usize| -> f64 {
+            let (mut sum, mut count) = (0.0, 0usize);
+            for y in 0..h - dy {
+                let (row, shifted) = (&centered[y * w..y * w + w - dx], &centered[(y + dy) * w + dx..(y + dy) * w + w]);
+                sum += row.iter().zip(shifted).map(|(a, b)| a * b).sum::<f64>();
+                count += w - dx;
+            }
+            sum / count.max(1) as f64 / variance
+        };
+        let max_lag = (w.min(h) / 4).clamp(1, 256);
+        let scale = (1..=max_lag)
+            .find(|&lag| (correlation(lag, 0) + correlation(0, lag)) / 2.0 < detail_threshold)
+            .unwrap_or(max_lag);
+        Self::sites_for_area((scale * scale) as f64, w as f64, h as f64)
+    }
+
+    /// Area of the convex hull of all site positions, as a fraction of
+    /// `width * height`: near 1.0 when sites span the image, near 0.0 when they
+    /// have clumped together. O(n log n) (Graham scan).
+    pub fn convex_hull_coverage(&self, width: f64, height: f64) -> f64 {
+        let hull = convex_hull(&self.positions());
+        if hull.len() < 3 || width <= 0.0 || height <= 0.0 {
+            return 0.0;
+        }
+        // Shoelace formula
+        let twice_area: f64 = (0..hull.len())
+            .map(|i| {
+                let (a, b) = (hull[i], hull[(i + 1) % hull.len()]);
+                a.x * b.y - b.x * a.y
+            })
+            .sum();
+        twice_area.abs() / 2.0 / (width * height)
+    }
+
+    /// How aligned nearby sites' headings are, from 0 (random) to 1 (every
+    /// neighborhood moving in one direction), like a flock's order parameter.
+    ///
+    /// For each site, its heading and those of the sites within `radius` are
+    /// summed as unit vectors; the sum's length over their count is the site's
+    /// coherence (1 when all agree, ~1/√count when random). Returns the mean over
+    /// sites with at least one neighbor, or 0.0 if none has. Neighbors come from a
+    /// spatial grid with cells at least `radius` wide.
+    pub fn velocity_coherence(&self, radius: f64) -> f64 {
+        let n = self.sites.len();
+        if n < 2 || radius <= 0.0 || radius.is_nan() {
+            return 0.0;
+        }
+        let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for s in &self.sites {
+            min_x = min_x.min(s.pos.x);
+            min_y = min_y.min(s.pos.y);
+            max_x = max_x.max(s.pos.x);
+            max_y = max_y.max(s.pos.y);
+        }
+        // At least `radius` wide so neighbors are in adjacent cells, and at
+        // least span/√n so there are O(n) cells for tiny radii
+        let per_axis = (n as f64).sqrt();
+        let cell_w = radius.max((max_x - min_x) / per_axis);
+        let cell_h = radius.max((max_y - min_y) / per_axis);
+        let cols = ((max_x - min_x) / cell_w) as usize + 1;
+        let rows = ((max_y - min_y) / cell_h) as usize + 1;
+        let cell_of = |p: &Position| -> (usize, usize) {
+            (
+                (((p.x - min_x) / cell_w) as usize).min(cols - 1),
+                (((p.y - min_y) / cell_h) as usize).min(rows - 1),
+            )
+        };
+        let mut grid: Vec<Vec<usize>> = vec![Vec::new(); cols * rows];
+        for (i, s) in self.sites.iter().enumerate() {
+            let (cx, cy) = cell_of(&s.pos);
+            grid[cy * cols + cx].push(i);
+        }
+        let headings: Vec<(f64, f64)> = self.sites.iter()
+            .map(|s| {
+                let angle = s.vel.angle();
+                (angle.cos(), angle.sin())
+            })
+            .collect();
+
+        let (mut total, mut counted) = (0.0, 0usize);
+        for (i, s) in self.sites.iter().enumerate() {
+            let (cx, cy) = cell_of(&s.pos);
+            let (mut sx, mut sy) = headings[i];
+            let mut count = 1;
+            for gy in cy.saturating_sub(1)..(cy + 2).min(rows) {
+                for gx in cx.saturating_sub(1)..(cx + 2).min(cols) {
+                    for &j in &grid[gy * cols + gx] {
+                        if j != i && s.pos.dist(&self.sites[j].pos) <= radius {
+                            sx += headings[j].0;
+                            sy += headings[j].1;
+                            count += 1;
+                        }
+                    }
+                }
+            }
+            if count > 1 {
+                total += (sx * sx + sy * sy).sqrt() / count as f64;
+                counted += 1;
+            }
+        }
+        if counted == 0 { 0.0 } else { total / counted as f64 }
+    }
+
+    /// Rasterize site velocities onto a `grid_w × grid_h` image (one pixel per cell
+    /// of the `width × height` image area), averaging the sites in each cell.
+    ///
+    /// Signed components are offset from mid-gray: R = 128 + 127·vx (brighter =
+    /// rightward), G = 128 − 127·vy (brighter = upward); B = speed_mult / 3 (the
+    /// post-split maximum). Cells without sites are black; out-of-bounds sites
+    /// count toward the nearest edge cell.
+    pub fn velocity_field_snapshot(&self, grid_w: u32, grid_h: u32, width: f64, height: f64) -> image::RgbImage {
+        let (gw, gh) = (grid_w as usize, grid_h as usize);
+        // (count, sum vx, sum vy, sum speed_mult) per cell
+        let mut cells = vec![(0usize, 0.0f64, 0.0f64, 0.0f64); gw * gh];
+        if gw > 0 && gh > 0 && width > 0.0 && height > 0.0 {
+            for s in &self.sites {
+                let cx = ((s.pos.x / width * gw as f64).floor().max(0.0) as usize).min(gw - 1);
+                let cy = ((s.pos.y / height * gh as f64).floor().max(0.0) as usize).min(gh - 1);
+                let cell = &mut cells[cy * gw + cx];
+                cell.0 += 1;
+                cell.1 += s.vel.x;
+                cell.2 += s.vel.y;
+                cell.3 += s.speed_mult;
+            }
+        }
+
+        let to_u8 = |v: f64| v.round().clamp(0.0, 255.0) as u8;
+        image::RgbImage::from_fn(grid_w, grid_h, |x, y| {
+            let (n, vx, vy, mult) = cells[y as usize * gw + x as usize];
+            if n == 0 {
+                return image::Rgb([0, 0, 0]);
+            }
+            let n = n as f64;
+            image::Rgb([
+                to_u8(128.0 + 127.0 * vx / n),
+                to_u8(128.0 - 127.0 * vy / n),
+                to_u8(255.0 * mult / n / 3.0),
+            ])
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Site, Velocity};
+
+    #[test]
+    fn test_site_count_for_cell_size() {
+        assert_eq!(SiteCollection::sites_for_area(100.0, 1000.0, 1000.0), 10000);
+        assert_eq!(SiteCollection::expected_cell_area(10000, 1000.0, 1000.0), 100.0);
+        assert_eq!(SiteCollection::sites_for_area(1e9, 10.0, 10.0), 1);
+    }
+
+    /// Coarser image detail calls for fewer, larger cells
+    #[test]
+    fn test_sites_for_perceptual_detail() {
+        let blocks = |size: u32| image::RgbImage::from_fn(256, 256, |x, y| {
+            let (bx, by) = (x / size, y / size);
+            let v = ((bx * 7919 + by * 104_729) % 251) as u8;
+            image::Rgb([v, v, v])
+        });
+        let fine = SiteCollection::sites_for_perceptual_detail(&blocks(4), 0.5);
+        let coarse = SiteCollection::sites_for_perceptual_detail(&blocks(16), 0.5);
+        assert!(fine > 4 * coarse, "fine {} vs coarse {}", fine, coarse);
+        // Correlation halves within about half to one block width
+        assert!((256..=2048).contains(&coarse), "coarse {}", coarse);
+        let flat = image::RgbImage::from_pixel(64, 64, image::Rgb([90, 90, 90]));
+        assert_eq!(SiteCollection::sites_for_perceptual_detail(&flat, 0.5), 1);
+    }
+
+    fn grid(xs: &[f64], ys: &[f64]) -> SiteCollection {
+        let positions = ys.iter()
+            .flat_map(|&y| xs.iter().map(move |&x| Position::new(x, y)))
+            .collect();
+        SiteCollection::random_from_positions(positions, 0)
+    }
+
+    #[test]
+    fn test_hull_coverage_grid() {
+        // 4x4 grid spanning the whole image (corners included)
+        let full = grid(&[0.0, 40.0, 80.0, 120.0], &[0.0, 30.0, 60.0, 90.0]);
+        assert!((full.convex_hull_coverage(120.0, 90.0) - 1.0).abs() < 1e-12);
+
+        // 4x4 grid at cell centers spans 3/4 of each axis
+        let centers = grid(&[15.0, 45.0, 75.0, 105.0], &[11.25, 33.75, 56.25, 78.75]);
+        assert!((centers.convex_hull_coverage(120.0, 90.0) - 0.5625).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_velocity_field_random() {
+        let sites = SiteCollection::random(400, 320.0, 240.0, 3);
+        let field = sites.velocity_field_snapshot(8, 6, 320.0, 240.0);
+        assert_eq!(field.dimensions(), (8, 6));
+
+        let mut occupied = 0;
+        let (mut sum_vx, mut sum_vy) = (0.0, 0.0);
+        for (x, y, px) in field.enumerate_pixels() {
+            let has_site = sites.sites.iter().any(|s| {
+                (s.pos.x / 40.0).floor() as u32 == x && (s.pos.y / 40.0).floor() as u32 == y
+            });
+            if !has_site {
+                assert_eq!(px.0, [0, 0, 0]);
+                continue;
+            }
+            assert!(px[0] > 0 && px[1] > 0, "cell ({}, {}) = {:?}", x, y, px);
+            // Fresh sites have speed_mult 1
+            assert_eq!(px[2], 85);
+            occupied += 1;
+            sum_vx += (px[0] as f64 - 128.0) / 127.0;
+            sum_vy += (128.0 - px[1] as f64) / 127.0;
+        }
+        assert!(occupied > 0);
+        assert!((sum_vx / occupied as f64).abs() < 0.3);
+        assert!((sum_vy / occupied as f64).abs() < 0.3);
+    }
+
+    #[test]
+    fn test_velocity_field_directions() {
+        let mut right = Site::new(Position::new(5.0, 5.0), Velocity::new(1.0, 0.0));
+        right.speed_mult = 3.0;
+        let up = Site::new(Position::new(15.0, 5.0), Velocity::new(0.0, -1.0));
+        let sites = SiteCollection::new(vec![right, up], 0);
+        let field = sites.velocity_field_snapshot(2, 1, 20.0, 10.0);
+        assert_eq!(field.get_pixel(0, 0).0, [255, 128, 255]);
+        assert_eq!(field.get_pixel(1, 0).0, [128, 255, 85]);
+    }
+
+    #[test]
+    fn test_velocity_coherence() {
+        // Circular flow around the center: neighbors' headings nearly agree
+        let mut circular = SiteCollection::random(300, 200.0, 200.0, 5);
+        for s in &mut circular.sites {
+            let (dx, dy) = (s.pos.x - 100.0, s.pos.y - 100.0);
+            let r = (dx * dx + dy * dy).sqrt().max(1e-9);
+            s.vel = Velocity::new(-dy / r, dx / r);
+        }
+        let aligned = circular.velocity_coherence(15.0);
+        assert!(aligned > 0.9, "circular coherence {}", aligned);
+
+        // Independent O-U wandering decorrelates headings
+        let mut wandering = SiteCollection::random(300, 200.0, 200.0, 5);
+        for _ in 0..100 {
+            wandering.step(15.0, 1.0 / 30.0, 200.0, 200.0, None, 0.0, 3.0, 3.0);
+        }
+        let random = wandering.velocity_coherence(30.0);
+        assert!(random < 0.5, "random coherence {}", random);
+
+        // No neighbors within the radius
+        assert_eq!(grid(&[0.0, 100.0], &[0.0]).velocity_coherence(10.0), 0.0);
+    }
+
+    #[test]
+    fn test_velocity_coherence_tiny_radius() {
+        // The grid stays O(n) and still finds the one pair within the radius
+        let mut sites = SiteCollection::random(20_000, 4000.0, 4000.0, 6);
+        let twin = Site::new(Position::new(sites.sites[42].pos.x + 1e-7, sites.sites[42].pos.y), sites.sites[42].vel);
+        sites.sites.push(twin);
+        assert!((sites.velocity_coherence(1e-6) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_hull_coverage_degenerate() {
+        let clumped = grid(&[50.0; 8], &[40.0; 4]);
+        assert_eq!(clumped.convex_hull_coverage(120.0, 90.0), 0.0);
+
+        let line = grid(&[10.0, 20.0, 30.0, 40.0], &[5.0]);
+        assert_eq!(line.convex_hull_coverage(120.0, 90.0), 0.0);
+
+        let triangle = SiteCollection::random_from_positions(
+            vec![Position::new(0.0, 0.0), Position::new(10.0, 0.0), Position::new(0.0, 10.0), Position::new(2.0, 2.0)],
+            0,
+        );
+        assert!((triangle.convex_hull_coverage(10.0, 10.0) - 0.5).abs() < 1e-12);
+    }
+}
+
+#[cfg(all(test, feature = "cpu"))]
+mod cpu_tests {
+    use super::*;
+    use crate::{ComputeBackend, CpuBackend};
+
+    #[test]
+    fn test_temporal_consistency() {
+        let image = image::RgbImage::from_fn(80, 60, |x, y| image::Rgb([(x * 3) as u8, (y * 4) as u8, 90]));
+        let mut backend = CpuBackend::new();
+        let pair = [Position::new(20.0, 30.0), Position::new(60.0, 30.0)];
+        let result = backend.compute(&image, &pair).unwrap();
+        assert_eq!(result.temporal_consistency(&result), 1.0);
+        assert_eq!(result.temporal_color_consistency(&result), 1.0);
+
+        // Swapping the sites relabels every pixel but renders the same image
+        let swapped = backend.compute(&image, &[pair[1], pair[0]]).unwrap();
+        assert_eq!(swapped.temporal_consistency(&result), 0.0);
+        assert_eq!(swapped.temporal_color_consistency(&result), 1.0);
+
+        // Faster sites reshuffle more pixels per frame
+        let mut mean_consistency = |speed: f64| {
+            let mut sites = SiteCollection::random(40, 80.0, 60.0, 4);
+            let mut prev = backend.compute(&image, &sites.positions()).unwrap();
+            let mut total = 0.0;
+            for _ in 0..10 {
+                sites.step(speed, 1.0 / 30.0, 80.0, 60.0, None, 0.0, 3.0, 3.0);
+                let next = backend.compute(&image, &sites.positions()).unwrap();
+                total += next.temporal_consistency(&prev);
+                prev = next;
+            }
+            total / 10.0
+        };
+        let by_speed: Vec<f64> = [5.0, 20.0, 80.0].into_iter().map(&mut mean_consistency).collect();
+        assert!(by_speed.windows(2).all(|w| w[1] < w[0]), "consistency by speed: {:?}", by_speed);
+    }
+
+    #[test]
+    fn test_find_optimal_site_count() {
+        let image = image::RgbImage::from_fn(64, 48, |x, y| {
+            image::Rgb([(x * 4) as u8, (y * 5) as u8, ((x + y) * 2) as u8])
+        });
+        let mut backend = CpuBackend::new();
+        let target = 24.0;
+        let count = SiteCollection::find_optimal_site_count(&mut backend, &image, target, 7, 2000).unwrap();
+        let mut psnr_at = |n: usize| {
+            let sites = SiteCollection::random(n, 64.0, 48.0, 7);
+            backend.compute(&image, &sites.positions()).unwrap().psnr(&image)
+        };
+        assert!(count > 1 && count < 2000, "count = {}", count);
+        assert!(psnr_at(count) >= target);
+        assert!(psnr_at(count - 1) < target);
+
+        // Unreachable targets return the cap
+        assert_eq!(SiteCollection::find_optimal_site_count(&mut backend, &image, 200.0, 7, 50).unwrap(), 50);
+    }
+}
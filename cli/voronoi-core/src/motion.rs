@@ -0,0 +1,164 @@
+//! Video-driven site initialization from block motion between two frames.
+
+use rand::Rng;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use crate::init::luminance;
+use crate::{Position, Site, SiteCollection, Velocity};
+
+/// Side length of the square blocks matched between frames
+const BLOCK_SIZE: usize = 8;
+/// Maximum displacement (in pixels, each axis) searched for each block
+const SEARCH_RADIUS: i32 = 16;
+/// Mean absolute luma difference above which a block's best match is treated as
+/// unreliable (e.g. content entering or leaving the frame) and ignored
+const MAX_MATCH_ERROR: f32 = 12.0;
+
+/// Block-matching motion estimate: for each 8x8 block of `a`, the offset into `b`
+/// with the smallest sum of absolute luma differences. Ties keep the smaller
+/// displacement, so flat regions report no motion. Blocks without a match within
+/// `MAX_MATCH_ERROR` are skipped. Returns (block center, dx, dy).
+fn block_motion(
+    a: &[f32], b: &[f32], width: usize, height: usize, stride_a: usize, stride_b: usize,
+) -> Vec<(Position, i32, i32)> {
+    let bs = BLOCK_SIZE as i32;
+    let mut offsets: Vec<(i32, i32)> = (-SEARCH_RADIUS..=SEARCH_RADIUS)
+        .flat_map(|dy| (-SEARCH_RADIUS..=SEARCH_RADIUS).map(move |dx| (dx, dy)))
+        .collect();
+    // Search nearest offsets first so equal-cost matches resolve to the smallest motion
+    offsets.sort_by_key(|&(dx, dy)| dx * dx + dy * dy);
+
+    let mut vectors = Vec::new();
+    for by in (0..height / BLOCK_SIZE).map(|i| i * BLOCK_SIZE) {
+        for bx in (0..width / BLOCK_SIZE).map(|i| i * BLOCK_SIZE) {
+            let mut best = (f32::INFINITY, 0, 0);
+            for &(dx, dy) in &offsets {
+                let (tx, ty) = (bx as i32 + dx, by as i32 + dy);
+                if tx < 0 || ty < 0 || tx + bs > width as i32 || ty + bs > height as i32 {
+                    continue;
+                }
+                let mut sad = 0.0;
+                'rows: for row in 0..BLOCK_SIZE {
+                    let ia = (by + row) * stride_a + bx;
+                    let ib = (ty as usize + row) * stride_b + tx as usize;
+                    for col in 0..BLOCK_SIZE {
+                        sad += (a[ia + col] - b[ib + col]).abs();
+                    }
+                    if sad >= best.0 {
+                        break 'rows;
+                    }
+                }
+                if sad < best.0 {
+                    best = (sad, dx, dy);
+                }
+            }
+            if best.0 > MAX_MATCH_ERROR * (BLOCK_SIZE * BLOCK_SIZE) as f32 {
+                continue;
+            }
+            let center = Position::new(
+                (bx + BLOCK_SIZE / 2) as f64,
+                (by + BLOCK_SIZE / 2) as f64,
+            );
+            vectors.push((center, best.1, best.2));
+        }
+    }
+    vectors
+}
+
+impl SiteCollection {
+    /// Place `count` sites at the centers of the fastest-moving 8x8 blocks between
+    /// two video frames, with velocities pointing along each block's motion.
+    ///
+    /// Motion is estimated by exhaustive block matching within ±16 px; blocks with
+    /// no good match are ignored. Moving blocks are ranked by motion magnitude (ties
+    /// in scan order); if fewer than `count` blocks move, the rest are placed
+    /// randomly with random velocities, as in `random`.
+    /// Frames of different sizes are compared over their overlapping region.
+    pub fn from_video_frames(
+        frame_a: &image::RgbImage,
+        frame_b: &image::RgbImage,
+        count: usize,
+        seed: u64,
+    ) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let width = frame_a.width().min(frame_b.width()) as usize;
+        let height = frame_a.height().min(frame_b.height()) as usize;
+
+        let mut moving: Vec<(f64, Position, Velocity)> = block_motion(
+            &luminance(frame_a), &luminance(frame_b),
+            width, height, frame_a.width() as usize, frame_b.width() as usize,
+        )
+            .into_iter()
+            .filter(|&(_, dx, dy)| dx != 0 || dy != 0)
+            .map(|(center, dx, dy)| {
+                let (dx, dy) = (dx as f64, dy as f64);
+                let mag = (dx * dx + dy * dy).sqrt();
+                (mag, center, Velocity::new(dx / mag, dy / mag))
+            })
+            .collect();
+        // Stable sort keeps scan order among equal magnitudes
+        moving.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut sites: Vec<Site> = moving.into_iter()
+            .take(count)
+            .map(|(_, pos, vel)| Site::new(pos, vel))
+            .collect();
+        let (fw, fh) = (frame_a.width() as f64, frame_a.height() as f64);
+        while sites.len() < count {
+            let pos = Position::new(rng.gen::<f64>() * fw, rng.gen::<f64>() * fh);
+            sites.push(Site::with_random_velocity(pos, &mut rng));
+        }
+        Self::with_rng(sites, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_video_frames_recover_translation() {
+        let (w, h) = (96u32, 80u32);
+        let (shift_x, shift_y) = (5i64, -3i64);
+        // Deterministic value noise, so every block has a unique match
+        let noise = |x: i64, y: i64| -> u8 {
+            let mut v = (x.wrapping_mul(73_856_093) ^ y.wrapping_mul(19_349_663)) as u64;
+            v ^= v >> 13;
+            v = v.wrapping_mul(0x5bd1_e995);
+            (v >> 24) as u8
+        };
+        let frame_a = image::RgbImage::from_fn(w, h, |x, y| {
+            let v = noise(x as i64, y as i64);
+            image::Rgb([v, v, v])
+        });
+        // Content moves by (shift_x, shift_y) between frames
+        let frame_b = image::RgbImage::from_fn(w, h, |x, y| {
+            let v = noise(x as i64 - shift_x, y as i64 - shift_y);
+            image::Rgb([v, v, v])
+        });
+
+        let sites = SiteCollection::from_video_frames(&frame_a, &frame_b, 20, 1);
+        assert_eq!(sites.len(), 20);
+
+        let mag = ((shift_x * shift_x + shift_y * shift_y) as f64).sqrt();
+        let (ex, ey) = (shift_x as f64 / mag, shift_y as f64 / mag);
+        for site in &sites.sites {
+            assert!((site.vel.x - ex).abs() < 1e-9 && (site.vel.y - ey).abs() < 1e-9,
+                "velocity {:?} at {:?}", site.vel, site.pos);
+            // Block centers lie on the 8px grid, offset by half a block
+            assert_eq!(site.pos.x % 8.0, 4.0);
+            assert_eq!(site.pos.y % 8.0, 4.0);
+        }
+    }
+
+    #[test]
+    fn test_video_frames_static_falls_back_to_random() {
+        let frame = image::RgbImage::from_fn(64, 64, |x, y| image::Rgb([(x * 4) as u8, (y * 4) as u8, 0]));
+        let sites = SiteCollection::from_video_frames(&frame, &frame, 10, 3);
+        let random = SiteCollection::random(10, 64.0, 64.0, 3);
+        for (a, b) in sites.sites.iter().zip(&random.sites) {
+            assert_eq!(a.pos.x, b.pos.x);
+            assert_eq!(a.pos.y, b.pos.y);
+        }
+    }
+}
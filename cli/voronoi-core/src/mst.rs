@@ -0,0 +1,251 @@
+//! Euclidean minimum spanning tree over site positions.
+
+use std::fmt::Write;
+use crate::{Position, SiteCollection, VoronoiResult};
+
+/// Up to this many sites, Kruskal runs over every pair of sites (exact MST);
+/// above it, only over pairs in nearby spatial-grid cells (approximate)
+const EXACT_MAX_SITES: usize = 2000;
+
+/// Disjoint-set forest with path halving and union by size
+struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(n: usize) -> Self {
+        Self { parent: (0..n).collect(), size: vec![1; n] }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    /// Merge the sets containing `a` and `b`; false if they were already one set
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        true
+    }
+}
+
+/// Add the edges of `candidates` (i, j, squared length) that join two
+/// components, shortest first, stopping once everything is connected
+fn kruskal(
+    points: &[Position],
+    mut candidates: Vec<(usize, usize, f64)>,
+    sets: &mut UnionFind,
+    edges: &mut Vec<(usize, usize)>,
+) {
+    candidates.sort_by(|a, b| a.2.total_cmp(&b.2).then((a.0, a.1).cmp(&(b.0, b.1))));
+    for (i, j, _) in candidates {
+        if edges.len() + 1 >= points.len() {
+            break;
+        }
+        if sets.union(i, j) {
+            edges.push((i, j));
+        }
+    }
+}
+
+/// MST edges `(i, j)` with `i < j`, in the order Kruskal adds them (shortest
+/// first). Exact up to `EXACT_MAX_SITES` points; above that, candidate edges
+/// come from a spatial grid (~4 points per cell), first between points in
+/// the same or adjacent cells, widening one ring of cells at a time until the
+/// tree is connected.
+pub(crate) fn min_spanning_tree(points: &[Position]) -> Vec<(usize, usize)> {
+    let n = points.len();
+    let mut sets = UnionFind::new(n);
+    let mut edges = Vec::with_capacity(n.saturating_sub(1));
+    if n < 2 {
+        return edges;
+    }
+
+    if n <= EXACT_MAX_SITES {
+        let candidates = (0..n)
+            .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+            .map(|(i, j)| (i, j, points[i].dist_sq(&points[j])))
+            .collect();
+        kruskal(points, candidates, &mut sets, &mut edges);
+        return edges;
+    }
+
+    let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+    let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for p in points {
+        min_x = min_x.min(p.x);
+        min_y = min_y.min(p.y);
+        max_x = max_x.max(p.x);
+        max_y = max_y.max(p.y);
+    }
+    let grid_size = ((n as f64 / 4.0).sqrt().ceil() as usize).max(1);
+    let cell_w = (max_x - min_x).max(1.0) / grid_size as f64;
+    let cell_h = (max_y - min_y).max(1.0) / grid_size as f64;
+    let cell_of = |p: Position| -> (usize, usize) {
+        let cx = ((p.x - min_x) / cell_w).min((grid_size - 1) as f64) as usize;
+        let cy = ((p.y - min_y) / cell_h).min((grid_size - 1) as f64) as usize;
+        (cx, cy)
+    };
+    let mut grid: Vec<Vec<usize>> = vec![vec![]; grid_size * grid_size];
+    for (i, &p) in points.iter().enumerate() {
+        let (cx, cy) = cell_of(p);
+        grid[cy * grid_size + cx].push(i);
+    }
+
+    // Ring 0 pairs points within a cell; ring r pairs cells whose Chebyshev
+    // distance is exactly r (each unordered pair of cells once)
+    for ring in 0..grid_size as isize {
+        let mut candidates = Vec::new();
+        for cy in 0..grid_size as isize {
+            for cx in 0..grid_size as isize {
+                let here = &grid[cy as usize * grid_size + cx as usize];
+                if here.is_empty() { continue; }
+                if ring == 0 {
+                    for (a, &i) in here.iter().enumerate() {
+                        for &j in &here[a + 1..] {
+                            candidates.push((i.min(j), i.max(j), points[i].dist_sq(&points[j])));
+                        }
+                    }
+                    continue;
+                }
+                for gy in (cy - ring)..=(cy + ring) {
+                    for gx in (cx - ring)..=(cx + ring) {
+                        let on_ring = (gy - cy).abs() == ring || (gx - cx).abs() == ring;
+                        // Visit each pair of cells from its lower-indexed side only
+                        let forward = (gy, gx) > (cy, cx);
+                        if !on_ring || !forward || gx < 0 || gy >= grid_size as isize || gx >= grid_size as isize {
+                            continue;
+                        }
+                        for &j in &grid[gy as usize * grid_size + gx as usize] {
+                            for &i in here {
+                                if sets.find(i) != sets.find(j) {
+                                    candidates.push((i.min(j), i.max(j), points[i].dist_sq(&points[j])));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        kruskal(points, candidates, &mut sets, &mut edges);
+        // Rings 0 and 1 are the grid-adjacent pairs; go further only if needed
+        if ring >= 1 && edges.len() + 1 >= n {
+            break;
+        }
+    }
+    edges
+}
+
+impl SiteCollection {
+    /// Euclidean minimum spanning tree over the current site positions, as
+    /// n − 1 edges `(i, j)` with `i < j`, shortest first.
+    ///
+    /// Kruskal's algorithm with a union-find; exact up to 2000 sites. Above
+    /// that, only pairs in adjacent spatial-grid cells are considered (widening
+    /// the neighborhood where that leaves the tree disconnected), so the tree
+    /// is approximate but still spanning.
+    pub fn min_spanning_tree(&self) -> Vec<(usize, usize)> {
+        min_spanning_tree(&self.positions())
+    }
+}
+
+impl VoronoiResult {
+    /// SVG of this frame's cells (convex-hull polygons filled with their mean
+    /// colors, see `cell_polygon_approx`) with `edges` between `sites` drawn
+    /// on top as line segments, e.g. `SiteCollection::min_spanning_tree`.
+    pub fn edges_svg(&self, sites: &[Position], edges: &[(usize, usize)]) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = self.width, h = self.height,
+        );
+        out.push_str("<g shape-rendering=\"crispEdges\">\n");
+        for (hull, [r, g, b]) in self.cell_polygon_approx().iter().zip(&self.cell_colors) {
+            if hull.is_empty() { continue; }
+            out.push_str("<polygon points=\"");
+            for (k, p) in hull.iter().enumerate() {
+                let _ = write!(out, "{}{},{}", if k > 0 { " " } else { "" }, p.x, p.y);
+            }
+            let _ = writeln!(out, r##"" fill="#{:02X}{:02X}{:02X}"/>"##, r, g, b);
+        }
+        out.push_str("</g>\n<g stroke=\"white\" stroke-width=\"1.5\" stroke-linecap=\"round\">\n");
+        for &(i, j) in edges {
+            let (a, b) = (sites[i], sites[j]);
+            let _ = writeln!(out, r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}"/>"#, a.x, a.y, b.x, b.y);
+        }
+        out.push_str("</g>\n</svg>\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lattice(side: usize, spacing: f64) -> Vec<Position> {
+        (0..side * side)
+            .map(|k| Position::new((k % side) as f64 * spacing + 5.0, (k / side) as f64 * spacing + 5.0))
+            .collect()
+    }
+
+    fn assert_spanning(points: &[Position], edges: &[(usize, usize)]) {
+        assert_eq!(edges.len(), points.len() - 1);
+        let mut sets = UnionFind::new(points.len());
+        for &(i, j) in edges {
+            assert!(i < j);
+            assert!(sets.union(i, j), "edge ({}, {}) closes a cycle", i, j);
+        }
+    }
+
+    fn weight(points: &[Position], edges: &[(usize, usize)]) -> f64 {
+        edges.iter().map(|&(i, j)| points[i].dist(&points[j])).sum()
+    }
+
+    /// On a regular grid the optimal MST weight is (n − 1) · spacing
+    #[test]
+    fn test_mst_regular_grid() {
+        for side in [10, 60] {
+            let points = lattice(side, 7.0);
+            let edges = min_spanning_tree(&points);
+            assert_spanning(&points, &edges);
+            let optimal = (points.len() - 1) as f64 * 7.0;
+            assert!(weight(&points, &edges) <= 2.0 * optimal, "side {}", side);
+        }
+        // Exact below the threshold
+        let points = lattice(10, 7.0);
+        assert!((weight(&points, &min_spanning_tree(&points)) - 99.0 * 7.0).abs() < 1e-9);
+    }
+
+    /// Two far-apart clusters still end up in one tree on the grid path
+    #[test]
+    fn test_mst_connects_distant_clusters() {
+        let mut points = lattice(35, 2.0);
+        points.extend(lattice(35, 2.0).iter().map(|p| Position::new(p.x + 5000.0, p.y + 3000.0)));
+        assert!(points.len() > EXACT_MAX_SITES);
+        let edges = min_spanning_tree(&points);
+        assert_spanning(&points, &edges);
+        let long = edges.iter().filter(|&&(i, j)| points[i].dist(&points[j]) > 100.0).count();
+        assert_eq!(long, 1);
+    }
+
+    #[test]
+    fn test_site_collection_mst() {
+        let sites = SiteCollection::random_from_positions(lattice(4, 10.0), 0);
+        let edges = sites.min_spanning_tree();
+        assert_spanning(&sites.positions(), &edges);
+        assert!(SiteCollection::random_from_positions(vec![], 0).min_spanning_tree().is_empty());
+    }
+}
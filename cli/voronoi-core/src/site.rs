@@ -1,9 +1,13 @@
 //! Site and position types for Voronoi computation.
 
+use std::collections::VecDeque;
 use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
 use rand::Rng;
 use rand_chacha::ChaCha8Rng;
 use rand::SeedableRng;
+use crate::history::SnapshotEntry;
+use crate::VoronoiError;
 
 /// Strategy for adding new sites when growing
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -18,12 +22,30 @@ pub enum SplitStrategy {
     Centroid,
     /// Spawn new site at the point furthest from any site
     Farthest,
+    /// Spawn new site at the centroid of the cell with the highest color variance
+    /// (see `VoronoiResult::cell_color_variance`), adding detail where the image is busiest
+    ColorVariance,
+    /// Bisect the largest cell: children placed at the centroids of the two halves
+    /// on either side of the line through the cell centroid, perpendicular to the
+    /// site-to-centroid axis
+    BisectLargest,
+    /// Spawn new sites at cell centroids chosen like k-means++ seeds: the first at a
+    /// random cell's centroid, each later one (in the same `adjust_count` call) with
+    /// probability proportional to squared distance from the nearest site already
+    /// spawned, spreading a frame's new sites apart
+    KMeansStep,
     /// Poisson distance-threshold: sites spawn at centroids of large cells,
     /// but only when well-spaced from neighbors. Rate scales with excess spacing.
     /// Parameters: (threshold_k, lambda)
     ///   threshold_k: multiplier on expected spacing sqrt(area/n); ~1.0-2.0
     ///   lambda: Poisson rate; higher = more aggressive spawning when eligible
     Poisson(f64, f64),
+    /// Spawn the K-th new site (counted over the collection's lifetime, see
+    /// `spiral_spawn_counter`) on a golden-angle spiral around the image center:
+    /// radius `max_r * sqrt(K / target)`, angle `arm_count * 2π * K / φ` plus
+    /// `turns` full turns of twist from center to rim (`max_r` = half the shorter
+    /// image side)
+    Spiral { arm_count: u32, turns: f64 },
 }
 
 impl fmt::Display for SplitStrategy {
@@ -34,7 +56,11 @@ impl fmt::Display for SplitStrategy {
             SplitStrategy::Isolated => write!(f, "isolated"),
             SplitStrategy::Centroid => write!(f, "centroid"),
             SplitStrategy::Farthest => write!(f, "farthest"),
+            SplitStrategy::ColorVariance => write!(f, "color-variance"),
+            SplitStrategy::BisectLargest => write!(f, "bisect"),
+            SplitStrategy::KMeansStep => write!(f, "kmeans"),
             SplitStrategy::Poisson(k, l) => write!(f, "poisson({},{})", k, l),
+            SplitStrategy::Spiral { arm_count, turns } => write!(f, "spiral({},{})", arm_count, turns),
         }
     }
 }
@@ -49,6 +75,9 @@ impl std::str::FromStr for SplitStrategy {
             "isolated" => Ok(SplitStrategy::Isolated),
             "centroid" => Ok(SplitStrategy::Centroid),
             "farthest" => Ok(SplitStrategy::Farthest),
+            "color-variance" | "colorvariance" => Ok(SplitStrategy::ColorVariance),
+            "bisect" | "bisect-largest" => Ok(SplitStrategy::BisectLargest),
+            "kmeans" | "kmeans-step" => Ok(SplitStrategy::KMeansStep),
             _ if lower.starts_with("poisson") => {
                 // Parse "poisson" (defaults) or "poisson(k,lambda)"
                 if let Some(params) = lower.strip_prefix("poisson(").and_then(|s| s.strip_suffix(')')) {
@@ -66,15 +95,162 @@ impl std::str::FromStr for SplitStrategy {
                     Err(format!("invalid poisson syntax: '{}', expected poisson or poisson(k,lambda)", s))
                 }
             }
+            "spiral" => Ok(SplitStrategy::Spiral { arm_count: 3, turns: 5.0 }),
+            _ if lower.starts_with("spiral(") => {
+                // Parse "spiral(arm_count,turns)"
+                let params = lower.strip_prefix("spiral(").and_then(|s| s.strip_suffix(')'))
+                    .ok_or_else(|| format!("invalid spiral syntax: '{}', expected spiral or spiral(arms,turns)", s))?;
+                match params.split(',').collect::<Vec<&str>>()[..] {
+                    [arms, turns] => Ok(SplitStrategy::Spiral {
+                        arm_count: arms.trim().parse().map_err(|e| format!("bad arm_count: {}", e))?,
+                        turns: turns.trim().parse().map_err(|e| format!("bad turns: {}", e))?,
+                    }),
+                    ref parts => Err(format!("spiral expects 2 params: spiral(arms,turns), got {}", parts.len())),
+                }
+            }
+            _ => Err(format!(
+                "unknown split strategy: '{}' (expected max, weighted, isolated, centroid, farthest, color-variance, bisect, kmeans, poisson, or spiral)", s
+            )),
+        }
+    }
+}
+
+/// Per-cell inputs and limits for `SiteCollection::adjust_count`; per-cell
+/// slices are indexed like the sites they were computed from
+#[derive(Debug, Clone, Copy)]
+pub struct AdjustParams<'a> {
+    /// Pixel count of each cell (see `VoronoiResult::cell_areas`)
+    pub cell_areas: Option<&'a [u32]>,
+    /// Centroid of each cell, for spawn strategies and split children
+    pub centroids: Option<&'a [Position]>,
+    /// Point furthest from any site, for `SplitStrategy::Farthest`
+    pub farthest_point: Option<Position>,
+    /// Image area in pixels, for Poisson's density threshold and Spiral's
+    /// fallback center
+    pub img_area: f64,
+    /// Per-cell half-centroids for BisectLargest (see
+    /// `VoronoiResult::bisect_centroids`); without them, children start at the
+    /// parent position
+    pub bisections: Option<&'a [[Position; 2]]>,
+    /// Per-cell color variance for ColorVariance (see
+    /// `VoronoiResult::cell_color_variance`); without it, it spawns like Centroid
+    pub cell_variances: Option<&'a [f32]>,
+    /// At most this many sites are added or removed per call (default
+    /// `usize::MAX`, no limit); the rest stays buffered in `fractional_sites`
+    pub max_per_frame: usize,
+    /// With `cell_areas`, first remove sites with empty cells
+    pub auto_remove_dead: bool,
+}
+
+impl Default for AdjustParams<'_> {
+    fn default() -> Self {
+        Self {
+            cell_areas: None,
+            centroids: None,
+            farthest_point: None,
+            img_area: 0.0,
+            bisections: None,
+            cell_variances: None,
+            max_per_frame: usize::MAX,
+            auto_remove_dead: false,
+        }
+    }
+}
+
+/// What happens to a site that moves past the image boundary
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum EdgeBehavior {
+    /// Bounce back, mirroring the velocity component normal to the edge
+    #[default]
+    Reflect,
+    /// Reappear at the opposite edge (toroidal topology)
+    Wrap,
+    /// Remove the site from the collection
+    Absorb,
+    /// Stop at the edge, zeroing the velocity component normal to it
+    Clamp,
+}
+
+impl fmt::Display for EdgeBehavior {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EdgeBehavior::Reflect => write!(f, "reflect"),
+            EdgeBehavior::Wrap => write!(f, "wrap"),
+            EdgeBehavior::Absorb => write!(f, "absorb"),
+            EdgeBehavior::Clamp => write!(f, "clamp"),
+        }
+    }
+}
+
+impl std::str::FromStr for EdgeBehavior {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "reflect" | "bounce" => Ok(EdgeBehavior::Reflect),
+            "wrap" => Ok(EdgeBehavior::Wrap),
+            "absorb" => Ok(EdgeBehavior::Absorb),
+            "clamp" => Ok(EdgeBehavior::Clamp),
             _ => Err(format!(
-                "unknown split strategy: '{}' (expected max, weighted, isolated, centroid, farthest, or poisson)", s
+                "unknown edge behavior: '{}' (expected reflect, wrap, absorb, or clamp)", s
             )),
         }
     }
 }
 
+/// How centroid pull turns a site's heading toward its cell centroid
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PullMode {
+    /// Rotate the heading angle by `centroid_pull · dt` of the angle to the
+    /// centroid (can overshoot when `centroid_pull · dt` > 1)
+    #[default]
+    LinearAngle,
+    /// Spherical linear interpolation of the heading toward the centroid
+    /// direction by `t = centroid_pull · dt`, clamped to [0, 1]
+    Slerp,
+}
+
+impl fmt::Display for PullMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PullMode::LinearAngle => write!(f, "lerp"),
+            PullMode::Slerp => write!(f, "slerp"),
+        }
+    }
+}
+
+impl std::str::FromStr for PullMode {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "lerp" | "linear" | "linear-angle" => Ok(PullMode::LinearAngle),
+            "slerp" => Ok(PullMode::Slerp),
+            _ => Err(format!("unknown pull mode: '{}' (expected lerp or slerp)", s)),
+        }
+    }
+}
+
+/// Swirl added to site headings in `SiteCollection::step` (see `add_vortex`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vortex {
+    pub center: Position,
+    /// Angular velocity (rad/s); positive turns clockwise on screen (y down)
+    pub strength: f64,
+    /// Sites farther than this many pixels from `center` are unaffected
+    pub radius: f64,
+}
+
+/// Standard normal sample (Box-Muller)
+fn standard_normal(rng: &mut impl Rng) -> f64 {
+    let u1: f64 = 1.0 - rng.gen::<f64>();
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+}
+
 /// 2D position
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position {
     pub x: f64,
     pub y: f64,
@@ -100,6 +276,7 @@ impl Position {
 
 /// Unit velocity vector (magnitude 1)
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Velocity {
     pub x: f64,
     pub y: f64,
@@ -129,6 +306,26 @@ impl Velocity {
         self.y.atan2(self.x)
     }
 
+    /// Spherical linear interpolation from this heading toward `target` by `t`
+    /// (0 = self, 1 = target), renormalized so the result stays a unit vector.
+    /// Exactly opposite headings turn counter-clockwise (y up).
+    pub fn slerp(&self, target: &Velocity, t: f64) -> Velocity {
+        let dot = (self.x * target.x + self.y * target.y).clamp(-1.0, 1.0);
+        let omega = dot.acos();
+        let sin_omega = omega.sin();
+        let (x, y) = if sin_omega > 1e-9 {
+            let (a, b) = (((1.0 - t) * omega).sin() / sin_omega, (t * omega).sin() / sin_omega);
+            (a * self.x + b * target.x, a * self.y + b * target.y)
+        } else if dot > 0.0 {
+            (self.x, self.y)
+        } else {
+            let (sin, cos) = (t * std::f64::consts::PI).sin_cos();
+            (self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+        };
+        let len = (x * x + y * y).sqrt();
+        Velocity::new(x / len, y / len)
+    }
+
     /// Reflect off vertical boundary (left/right edge)
     pub fn reflect_x(&mut self) {
         self.x = -self.x;
@@ -140,8 +337,54 @@ impl Velocity {
     }
 }
 
+/// Rate at which a split's speed boost decays back to 1 (per second; half-life ~0.14s)
+const SPEED_DECAY: f64 = 5.0;
+
+/// Neighbors each site is balanced against in `density_equalization_step`
+const DENSITY_NEIGHBORS: usize = 6;
+
+/// Value of `map` at continuous pixel coordinates (`u`, `v`), interpolated
+/// between pixel centers and clamped at the edges
+fn sample_bilinear(map: &image::GrayImage, u: f64, v: f64) -> f64 {
+    let (w, h) = map.dimensions();
+    if w == 0 || h == 0 {
+        return 0.0;
+    }
+    let x = (u - 0.5).clamp(0.0, (w - 1) as f64);
+    let y = (v - 0.5).clamp(0.0, (h - 1) as f64);
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let (x1, y1) = ((x0 + 1).min(w - 1), (y0 + 1).min(h - 1));
+    let (tx, ty) = (x - x0 as f64, y - y0 as f64);
+    let at = |x: u32, y: u32| map.get_pixel(x, y)[0] as f64;
+    let top = at(x0, y0) * (1.0 - tx) + at(x1, y0) * tx;
+    let bottom = at(x0, y1) * (1.0 - tx) + at(x1, y1) * tx;
+    top * (1.0 - ty) + bottom * ty
+}
+
+/// `speed_mult` sentinel marking a site dead (see `SiteCollection::mark_dead_sites`)
+const DEAD_SPEED_MULT: f64 = -1.0;
+
+/// Per-site physics for `SiteCollection::step_with_params_fn`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StepParams {
+    /// O-U mean-reversion rate of the turn rate
+    pub theta: f64,
+    /// O-U noise volatility of the turn rate
+    pub sigma: f64,
+    /// Decay rate (per second) of the speed multiplier toward 1
+    pub speed_decay: f64,
+}
+
+impl StepParams {
+    /// O-U parameters with the default speed decay
+    pub fn new(theta: f64, sigma: f64) -> Self {
+        Self { theta, sigma, speed_decay: SPEED_DECAY }
+    }
+}
+
 /// A Voronoi site with position, velocity, and dynamics
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Site {
     pub pos: Position,
     pub vel: Velocity,
@@ -149,11 +392,18 @@ pub struct Site {
     pub turn_rate: f64,
     /// Speed multiplier, decays toward 1.0 (used for split boost)
     pub speed_mult: f64,
+    /// Per-site O-U mean-reversion rate, overriding the `theta` passed to `step`
+    pub theta: Option<f64>,
+    /// Per-site O-U noise volatility, overriding the `sigma` passed to `step`
+    pub sigma: Option<f64>,
+    /// Power-diagram weight: with `CpuBackend::compute_weighted`, the site owns
+    /// pixels where `dist² - weight²` is least (default 1.0)
+    pub weight: f64,
 }
 
 impl Site {
     pub fn new(pos: Position, vel: Velocity) -> Self {
-        Self { pos, vel, turn_rate: 0.0, speed_mult: 1.0 }
+        Self { pos, vel, turn_rate: 0.0, speed_mult: 1.0, theta: None, sigma: None, weight: 1.0 }
     }
 
     /// Create with random velocity
@@ -163,37 +413,105 @@ impl Site {
             vel: Velocity::random(rng),
             turn_rate: 0.0,
             speed_mult: 1.0,
+            theta: None,
+            sigma: None,
+            weight: 1.0,
         }
     }
 
     /// Move site by velocity * speed * dt, with smooth random steering and edge bouncing.
-    /// `theta` = O-U mean-reversion rate; `sigma` = O-U noise volatility.
+    /// `theta` = O-U mean-reversion rate; `sigma` = O-U noise volatility (unless the
+    /// site has its own `theta` / `sigma`).
+    #[allow(clippy::too_many_arguments)]
     pub fn step(&mut self, speed: f64, dt: f64, width: f64, height: f64, theta: f64, sigma: f64, rng: &mut impl Rng) {
+        self.step_with_edge(speed, dt, width, height, theta, sigma, EdgeBehavior::Reflect, rng);
+    }
+
+    /// Like `step`, with configurable handling of image edges.
+    /// Returns false if the site left the image under `EdgeBehavior::Absorb`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn step_with_edge(
+        &mut self,
+        speed: f64,
+        dt: f64,
+        width: f64,
+        height: f64,
+        theta: f64,
+        sigma: f64,
+        edge: EdgeBehavior,
+        rng: &mut impl Rng,
+    ) -> bool {
+        let params = StepParams::new(self.theta.unwrap_or(theta), self.sigma.unwrap_or(sigma));
+        self.step_with_params(speed, dt, width, height, params, edge, rng)
+    }
+
+    /// Like `step_with_edge`, with explicit physics parameters (the site's own
+    /// `theta` / `sigma` are ignored)
+    #[allow(clippy::too_many_arguments)]
+    fn step_with_params(
+        &mut self,
+        speed: f64,
+        dt: f64,
+        width: f64,
+        height: f64,
+        params: StepParams,
+        edge: EdgeBehavior,
+        rng: &mut impl Rng,
+    ) -> bool {
         // Rotate velocity direction by turn_rate
         let angle = self.vel.angle() + self.turn_rate * dt;
         self.vel = Velocity::from_angle(angle);
         let noise: f64 = rng.gen_range(-1.73..1.73);
-        self.turn_rate += -theta * self.turn_rate * dt + sigma * dt.sqrt() * noise;
+        self.turn_rate += -params.theta * self.turn_rate * dt + params.sigma * dt.sqrt() * noise;
 
-        // Decay speed multiplier toward 1.0 (half-life ~0.14s)
-        self.speed_mult = 1.0 + (self.speed_mult - 1.0) * (-5.0 * dt).exp();
+        // Decay speed multiplier toward 1.0
+        self.speed_mult = 1.0 + (self.speed_mult - 1.0) * (-params.speed_decay * dt).exp();
 
         // Move
         let movement = speed * self.speed_mult * dt;
         self.pos.x += self.vel.x * movement;
         self.pos.y += self.vel.y * movement;
 
-        // Bounce off edges
-        if self.pos.x < 0.0 || self.pos.x >= width {
-            self.vel.reflect_x();
-            self.turn_rate = -self.turn_rate;
-            self.pos.x = self.pos.x.clamp(0.0, width - 1.0);
-        }
-        if self.pos.y < 0.0 || self.pos.y >= height {
-            self.vel.reflect_y();
-            self.turn_rate = -self.turn_rate;
-            self.pos.y = self.pos.y.clamp(0.0, height - 1.0);
+        let out_x = self.pos.x < 0.0 || self.pos.x >= width;
+        let out_y = self.pos.y < 0.0 || self.pos.y >= height;
+        match edge {
+            EdgeBehavior::Reflect => {
+                // Bounce off edges
+                if out_x {
+                    self.vel.reflect_x();
+                    self.turn_rate = -self.turn_rate;
+                    self.pos.x = self.pos.x.clamp(0.0, width - 1.0);
+                }
+                if out_y {
+                    self.vel.reflect_y();
+                    self.turn_rate = -self.turn_rate;
+                    self.pos.y = self.pos.y.clamp(0.0, height - 1.0);
+                }
+            }
+            EdgeBehavior::Wrap => {
+                // rem_euclid can round up to exactly the modulus for tiny negatives
+                if out_x {
+                    self.pos.x = self.pos.x.rem_euclid(width);
+                    if self.pos.x >= width { self.pos.x = 0.0; }
+                }
+                if out_y {
+                    self.pos.y = self.pos.y.rem_euclid(height);
+                    if self.pos.y >= height { self.pos.y = 0.0; }
+                }
+            }
+            EdgeBehavior::Absorb => return !(out_x || out_y),
+            EdgeBehavior::Clamp => {
+                if out_x {
+                    self.vel.x = 0.0;
+                    self.pos.x = self.pos.x.clamp(0.0, width - 1.0);
+                }
+                if out_y {
+                    self.vel.y = 0.0;
+                    self.pos.y = self.pos.y.clamp(0.0, height - 1.0);
+                }
+            }
         }
+        true
     }
 
     /// Split into two sites at the same position, moving in opposite directions.
@@ -217,43 +535,112 @@ impl Site {
         let vel1 = Velocity::from_angle(angle);
         let vel2 = Velocity::from_angle(angle + std::f64::consts::PI);
 
-        // Opposite turn rates so children curve away from each other, plus speed boost
+        // Opposite turn rates so children curve away from each other, plus speed boost.
+        // Children inherit the parent's dynamics overrides and weight.
         let turn = rng.gen_range(1.0..4.0);
-        (
-            Site { pos: self.pos, vel: vel1, turn_rate: turn, speed_mult: 3.0 },
-            Site { pos: self.pos, vel: vel2, turn_rate: -turn, speed_mult: 3.0 },
-        )
+        let child = |vel, turn_rate| Site {
+            pos: self.pos, vel, turn_rate, speed_mult: 3.0, theta: self.theta, sigma: self.sigma, weight: self.weight,
+        };
+        (child(vel1, turn), child(vel2, -turn))
+    }
+}
+
+/// `v` mirrored back into `[0, max)` at whichever edge it crossed (clamped if it
+/// overshoots by more than `max`)
+fn reflect_into(v: f64, max: f64) -> f64 {
+    let v = if v < 0.0 { -v } else if v >= max { 2.0 * max - v } else { v };
+    v.clamp(0.0, max.next_down().max(0.0))
+}
+
+/// Doubling time scaled down by cell-area disparity:
+/// `base / (1 + disparity_factor * (max_area / mean_area - 1))`
+fn adaptive_doubling_time(base: f64, cell_areas: &[u32], disparity_factor: f64) -> f64 {
+    let max = cell_areas.iter().copied().max().unwrap_or(0) as f64;
+    let mean = cell_areas.iter().map(|&a| a as f64).sum::<f64>() / cell_areas.len().max(1) as f64;
+    if mean <= 0.0 {
+        return base;
+    }
+    base / (1.0 + disparity_factor.max(0.0) * (max / mean - 1.0))
+}
+
+/// Site lifecycle callback: receives the site's index and data (see
+/// `SiteCollection::on_site_added` / `on_site_removed`)
+pub type SiteCallback = Arc<dyn Fn(usize, &Site) + Send + Sync>;
+
+/// Registered lifecycle callbacks; clones share the same callbacks
+#[derive(Clone, Default)]
+pub(crate) struct SiteCallbacks {
+    added: Vec<SiteCallback>,
+    removed: Vec<SiteCallback>,
+}
+
+impl fmt::Debug for SiteCallbacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SiteCallbacks")
+            .field("added", &self.added.len())
+            .field("removed", &self.removed.len())
+            .finish()
     }
 }
 
 /// Collection of sites with physics simulation and seeded RNG
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SiteCollection {
     pub sites: Vec<Site>,
     pub fractional_sites: f64,
-    rng: ChaCha8Rng,
+    pub(crate) rng: ChaCha8Rng,
+    /// Undo history (see `with_history`); empty with zero capacity when disabled
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) history: VecDeque<SnapshotEntry>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) history_capacity: usize,
+    /// New sites spawned so far by `SplitStrategy::Spiral` (its K)
+    pub spiral_spawn_counter: u64,
+    /// How `step` applies centroid pull (default `PullMode::LinearAngle`)
+    pub pull_mode: PullMode,
+    /// Called by `adjust_count` as sites are added and removed
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) callbacks: SiteCallbacks,
+    /// Swirls applied to headings in `step` (see `add_vortex`)
+    pub vortices: Vec<Vortex>,
 }
 
 impl SiteCollection {
     pub fn new(sites: Vec<Site>, seed: u64) -> Self {
+        Self::with_rng(sites, ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    /// Create from sites and an already-advanced RNG (for initializers that
+    /// consume randomness before assigning velocities)
+    pub(crate) fn with_rng(sites: Vec<Site>, rng: ChaCha8Rng) -> Self {
         Self {
             sites,
             fractional_sites: 0.0,
-            rng: ChaCha8Rng::seed_from_u64(seed),
+            rng,
+            history: VecDeque::new(),
+            history_capacity: 0,
+            spiral_spawn_counter: 0,
+            pull_mode: PullMode::default(),
+            callbacks: SiteCallbacks::default(),
+            vortices: Vec::new(),
         }
     }
 
     /// Create from existing positions with random velocities
     pub fn random_from_positions(positions: Vec<Position>, seed: u64) -> Self {
-        let mut rng = ChaCha8Rng::seed_from_u64(seed);
-        let sites = positions.into_iter()
-            .map(|pos| Site::with_random_velocity(pos, &mut rng))
-            .collect();
-        Self {
-            sites,
-            fractional_sites: 0.0,
-            rng,
-        }
+        Self::from_iterator(positions.into_iter(), seed, 0)
+    }
+
+    /// Create from positions streamed from `iter` (e.g. parsed lazily from a file),
+    /// with random velocities drawn from `velocity_seed` in order, without first
+    /// collecting the positions. `capacity_hint` pre-sizes the site list when the
+    /// iterator can't report its length.
+    pub fn from_iterator<I: Iterator<Item = Position>>(iter: I, velocity_seed: u64, capacity_hint: usize) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(velocity_seed);
+        let mut sites = Vec::with_capacity(capacity_hint);
+        sites.extend(iter.map(|pos| Site::with_random_velocity(pos, &mut rng)));
+        Self::with_rng(sites, rng)
     }
 
     /// Create sites at random positions with random velocities
@@ -268,11 +655,31 @@ impl SiteCollection {
                 Site::with_random_velocity(pos, &mut rng)
             })
             .collect();
-        Self {
-            sites,
-            fractional_sites: 0.0,
-            rng,
-        }
+        Self::with_rng(sites, rng)
+    }
+
+    /// Place sites along a 2D random walk from the image center: `count` steps of
+    /// `step_size` pixels in uniformly random directions, wrapping at the image
+    /// edges. Each site sits where its step lands, heading in that step's direction.
+    pub fn from_random_walk(count: usize, step_size: f64, width: f64, height: f64, seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        // rem_euclid can round up to the modulus for tiny negative inputs
+        let wrap = |v: f64, max: f64| {
+            let v = v.rem_euclid(max);
+            if v >= max { 0.0 } else { v }
+        };
+        let mut pos = Position::new(width / 2.0, height / 2.0);
+        let sites = (0..count)
+            .map(|_| {
+                let angle = rng.gen::<f64>() * std::f64::consts::TAU;
+                pos = Position::new(
+                    wrap(pos.x + step_size * angle.cos(), width),
+                    wrap(pos.y + step_size * angle.sin(), height),
+                );
+                Site::new(pos, Velocity::from_angle(angle))
+            })
+            .collect();
+        Self::with_rng(sites, rng)
     }
 
     /// Average velocity vector across all sites (for drift detection)
@@ -285,10 +692,46 @@ impl SiteCollection {
         (sx / n, sy / n)
     }
 
+    /// Low-pass filter velocities: replace each site's heading with the weighted
+    /// circular mean of its own and its `window_size` nearest neighbors' headings,
+    /// renormalized to unit length. Neighbors are weighted by `s / (s + d)`, where `d`
+    /// is their distance and `s` the mean site spacing; the site itself has weight 1.
+    /// All sites are updated from the same snapshot, so results don't depend on order.
+    /// No-op when `window_size` is 0.
+    pub fn smooth_velocities(&mut self, window_size: usize) {
+        let n = self.sites.len();
+        if window_size == 0 || n < 2 {
+            return;
+        }
+        let neighbors = self.k_nearest_neighbors(window_size);
+        let (min_x, max_x, min_y, max_y) = self.sites.iter().fold(
+            (f64::INFINITY, f64::NEG_INFINITY, f64::INFINITY, f64::NEG_INFINITY),
+            |(x0, x1, y0, y1), s| (x0.min(s.pos.x), x1.max(s.pos.x), y0.min(s.pos.y), y1.max(s.pos.y)),
+        );
+        let spacing = ((max_x - min_x).max(1.0) * (max_y - min_y).max(1.0) / n as f64).sqrt();
+
+        let smoothed: Vec<Velocity> = (0..n)
+            .map(|i| {
+                let own = self.sites[i].vel;
+                let (sx, sy) = neighbors[i].iter().fold((own.x, own.y), |(ax, ay), &(j, d)| {
+                    let w = spacing / (spacing + d);
+                    (ax + w * self.sites[j].vel.x, ay + w * self.sites[j].vel.y)
+                });
+                let len = (sx * sx + sy * sy).sqrt();
+                if len > 1e-12 { Velocity::new(sx / len, sy / len) } else { own }
+            })
+            .collect();
+        for (site, vel) in self.sites.iter_mut().zip(smoothed) {
+            site.vel = vel;
+        }
+    }
+
     /// Step all sites forward (index-based to allow disjoint borrows of sites + rng)
     ///
     /// If `centroids` and `centroid_pull` > 0, each site's velocity is steered
-    /// toward its cell centroid (continuous Lloyd's relaxation).
+    /// toward its cell centroid (continuous Lloyd's relaxation). Registered
+    /// vortices (see `add_vortex`) then add their swirl.
+    #[allow(clippy::too_many_arguments)]
     pub fn step(
         &mut self,
         speed: f64,
@@ -300,16 +743,195 @@ impl SiteCollection {
         theta: f64,
         sigma: f64,
     ) {
+        self.step_with_edge_behavior(
+            speed, dt, width, height, centroids, centroid_pull, theta, sigma, EdgeBehavior::Reflect,
+        );
+    }
+
+    /// `step` with the largest `dt` ≤ `max_dt` at which no site moves more than
+    /// half a typical cell width: `dt = min(max_dt, s / (2 · speed · m))`, with
+    /// `s = √(width · height / n)` and `m` the largest current `speed_mult`
+    /// (e.g. 3 right after a split). Returns the `dt` used.
+    #[allow(clippy::too_many_arguments)]
+    pub fn adaptive_step_dt(
+        &mut self,
+        speed: f64,
+        max_dt: f64,
+        width: f64,
+        height: f64,
+        centroids: Option<&[Position]>,
+        centroid_pull: f64,
+        theta: f64,
+        sigma: f64,
+    ) -> f64 {
+        let dt = self.adaptive_dt(speed, max_dt, width, height);
+        self.step(speed, dt, width, height, centroids, centroid_pull, theta, sigma);
+        dt
+    }
+
+    /// The `dt` `adaptive_step_dt` would step by (e.g. to step with other edge
+    /// handling)
+    pub fn adaptive_dt(&self, speed: f64, max_dt: f64, width: f64, height: f64) -> f64 {
+        let max_mult = self.sites.iter().map(|s| s.speed_mult).fold(1.0, f64::max);
+        if self.sites.is_empty() || speed <= 0.0 {
+            return max_dt;
+        }
+        let cell_size = (width * height / self.sites.len() as f64).sqrt();
+        max_dt.min(cell_size / (2.0 * speed * max_mult))
+    }
+
+    /// Like `step`, with configurable handling of image edges.
+    ///
+    /// Under `EdgeBehavior::Absorb`, sites that leave the image are removed; returns
+    /// their indices (ascending, relative to the collection before the step).
+    #[allow(clippy::too_many_arguments)]
+    pub fn step_with_edge_behavior(
+        &mut self,
+        speed: f64,
+        dt: f64,
+        width: f64,
+        height: f64,
+        centroids: Option<&[Position]>,
+        centroid_pull: f64,
+        theta: f64,
+        sigma: f64,
+        edge: EdgeBehavior,
+    ) -> Vec<usize> {
+        self.steer_toward_centroids(centroids, centroid_pull, dt);
+        self.apply_vortices();
+        self.step_sites(|_| speed, dt, width, height, edge, |_, site| {
+            StepParams::new(site.theta.unwrap_or(theta), site.sigma.unwrap_or(sigma))
+        })
+    }
+
+    /// Like `step_with_edge_behavior`, with each site's speed scaled by the
+    /// brightness of `speed_map` under it: `base_speed · (1 − 0.9 · v / 255)`,
+    /// so black moves at full speed and white at 10% (e.g. a detail map, to
+    /// keep sites in busy regions still). `v` is bilinearly interpolated between
+    /// pixel centers, with the map stretched over the `width × height` image if
+    /// its size differs. Returns indices of absorbed sites, as there.
+    #[allow(clippy::too_many_arguments)]
+    pub fn step_with_speed_map(
+        &mut self,
+        speed_map: &image::GrayImage,
+        base_speed: f64,
+        dt: f64,
+        width: f64,
+        height: f64,
+        centroids: Option<&[Position]>,
+        centroid_pull: f64,
+        theta: f64,
+        sigma: f64,
+        edge: EdgeBehavior,
+    ) -> Vec<usize> {
+        self.steer_toward_centroids(centroids, centroid_pull, dt);
+        self.apply_vortices();
+        let speed = |site: &Site| {
+            let u = site.pos.x / width * speed_map.width() as f64;
+            let v = site.pos.y / height * speed_map.height() as f64;
+            base_speed * (1.0 - 0.9 * sample_bilinear(speed_map, u, v) / 255.0)
+        };
+        self.step_sites(speed, dt, width, height, edge, |_, site| {
+            StepParams::new(site.theta.unwrap_or(theta), site.sigma.unwrap_or(sigma))
+        })
+    }
+
+    /// Like `step_with_edge_behavior`, with each site's physics from `params_fn(site_index)`
+    /// (e.g. closing over elapsed time, or the site count, to vary motion over an
+    /// animation). Sites' own `theta` / `sigma` are ignored.
+    #[allow(clippy::too_many_arguments)]
+    pub fn step_with_params_fn(
+        &mut self,
+        speed: f64,
+        dt: f64,
+        width: f64,
+        height: f64,
+        centroids: Option<&[Position]>,
+        centroid_pull: f64,
+        edge: EdgeBehavior,
+        params_fn: impl Fn(usize) -> StepParams,
+    ) -> Vec<usize> {
+        self.steer_toward_centroids(centroids, centroid_pull, dt);
+        self.apply_vortices();
+        self.step_sites(|_| speed, dt, width, height, edge, |i, _| params_fn(i))
+    }
+
+    /// Step with headings following an optical flow field: `flow` holds one `(dx, dy)`
+    /// per pixel, row-major with rows `width` long (e.g. from an external optical
+    /// flow library). Each site's heading becomes `lerp(vel, normalize(flow), blend)`,
+    /// renormalized, using the vector at the site's pixel; sites outside the field,
+    /// or over a zero or non-finite vector, keep their heading. Vortices then apply
+    /// as in `step`, and sites move with their own `theta` / `sigma` (default 0, so
+    /// without per-site O-U params they just follow the flow), reflecting off the
+    /// edges of the field.
+    pub fn step_with_image_flow(&mut self, flow: &[(f64, f64)], width: u32, blend: f64, speed: f64, dt: f64) {
+        let height = if width == 0 { 0 } else { flow.len() / width as usize };
+        let blend = blend.clamp(0.0, 1.0);
+        for site in &mut self.sites {
+            if site.pos.x < 0.0 || site.pos.y < 0.0 {
+                continue;
+            }
+            let (px, py) = (site.pos.x as usize, site.pos.y as usize);
+            if px >= width as usize || py >= height {
+                continue;
+            }
+            let (fx, fy) = flow[py * width as usize + px];
+            let flow_len = (fx * fx + fy * fy).sqrt();
+            if !flow_len.is_finite() || flow_len < 1e-12 {
+                continue;
+            }
+            let target = Velocity::new(fx / flow_len, fy / flow_len);
+            let vx = site.vel.x + (target.x - site.vel.x) * blend;
+            let vy = site.vel.y + (target.y - site.vel.y) * blend;
+            let len = (vx * vx + vy * vy).sqrt();
+            // Opposite headings can cancel out; take the flow's
+            site.vel = if len > 1e-12 { Velocity::new(vx / len, vy / len) } else { target };
+        }
+        self.apply_vortices();
+        self.step_sites(|_| speed, dt, width as f64, height as f64, EdgeBehavior::Reflect, |_, site| {
+            StepParams::new(site.theta.unwrap_or(0.0), site.sigma.unwrap_or(0.0))
+        });
+    }
+
+    /// Step each site at `speed(site)` with `params(index, site)`, removing absorbed
+    /// sites; returns their indices
+    fn step_sites(
+        &mut self,
+        speed: impl Fn(&Site) -> f64,
+        dt: f64,
+        width: f64,
+        height: f64,
+        edge: EdgeBehavior,
+        params: impl Fn(usize, &Site) -> StepParams,
+    ) -> Vec<usize> {
+        let mut removed = Vec::new();
+        for i in 0..self.sites.len() {
+            let p = params(i, &self.sites[i]);
+            let speed = speed(&self.sites[i]);
+            if !self.sites[i].step_with_params(speed, dt, width, height, p, edge, &mut self.rng) {
+                removed.push(i);
+            }
+        }
+        for &i in removed.iter().rev() {
+            self.sites.remove(i);
+        }
+        removed
+    }
+
+    /// Steer each site's heading toward its cell centroid (continuous Lloyd's
+    /// relaxation) per `pull_mode`; no-op unless `centroid_pull` > 0 and centroids
+    /// are given
+    fn steer_toward_centroids(&mut self, centroids: Option<&[Position]>, centroid_pull: f64, dt: f64) {
         if centroid_pull > 0.0 {
             if let Some(centroids) = centroids {
-                let n = self.sites.len().min(centroids.len());
-                for i in 0..n {
-                    let site = &mut self.sites[i];
-                    let c = &centroids[i];
+                for (site, c) in self.sites.iter_mut().zip(centroids) {
                     let dx = c.x - site.pos.x;
                     let dy = c.y - site.pos.y;
                     let dist = (dx * dx + dy * dy).sqrt();
-                    if dist > 0.5 {
+                    if dist > 0.5 && self.pull_mode == PullMode::Slerp {
+                        let target = Velocity::new(dx / dist, dy / dist);
+                        site.vel = site.vel.slerp(&target, (centroid_pull * dt).clamp(0.0, 1.0));
+                    } else if dist > 0.5 {
                         // Blend velocity toward centroid direction
                         let target_angle = dy.atan2(dx);
                         let current_angle = site.vel.angle();
@@ -321,54 +943,330 @@ impl SiteCollection {
                         site.vel = Velocity::from_angle(current_angle + steer);
                     }
                 }
+            }
+        }
+    }
+
+    /// Register a vortex at (`cx`, `cy`): each `step`, after centroid pull, sites
+    /// within `radius` pixels get `strength · e^(−r/radius)` of tangential
+    /// velocity added to their heading, which is then renormalized. Multiple
+    /// vortices add up.
+    pub fn add_vortex(&mut self, cx: f64, cy: f64, strength: f64, radius: f64) {
+        self.vortices.push(Vortex { center: Position::new(cx, cy), strength, radius });
+    }
 
+    /// Remove all vortices registered with `add_vortex`
+    pub fn clear_vortices(&mut self) {
+        self.vortices.clear();
+    }
+
+    /// Add each vortex's tangential component to the headings of sites within
+    /// its radius, then renormalize
+    fn apply_vortices(&mut self) {
+        if self.vortices.is_empty() {
+            return;
+        }
+        for site in &mut self.sites {
+            let (mut vx, mut vy) = (site.vel.x, site.vel.y);
+            for v in &self.vortices {
+                let (dx, dy) = (site.pos.x - v.center.x, site.pos.y - v.center.y);
+                let r = (dx * dx + dy * dy).sqrt();
+                if r < 1e-9 || r > v.radius {
+                    continue;
+                }
+                let k = v.strength * (-r / v.radius).exp() / r;
+                vx -= k * dy;
+                vy += k * dx;
+            }
+            let len = (vx * vx + vy * vy).sqrt();
+            if len > 1e-12 {
+                site.vel = Velocity::new(vx / len, vy / len);
             }
         }
-        for i in 0..self.sites.len() {
-            self.sites[i].step(speed, dt, width, height, theta, sigma, &mut self.rng);
+    }
+
+    /// Lloyd relaxation step: move each site `strength` (0–1) of the way to its cell
+    /// centroid (1 = full step). Velocities are left unchanged.
+    pub fn apply_lloyd(&mut self, centroids: &[Position], strength: f64) {
+        let strength = strength.clamp(0.0, 1.0);
+        for (site, c) in self.sites.iter_mut().zip(centroids) {
+            site.pos.x += (c.x - site.pos.x) * strength;
+            site.pos.y += (c.y - site.pos.y) * strength;
         }
     }
 
-    /// Gradually adjust site count toward target using exponential growth/decay.
+    /// One gradient step toward equal cell areas: each site moves toward its
+    /// neighbors with larger cells and away from those with smaller ones.
     ///
-    /// For Poisson strategy, `img_area` is used to compute density-dependent threshold.
-    /// Returns indices of newly added sites or removed sites.
-    pub fn adjust_count(
-        &mut self,
-        target: usize,
-        doubling_time: f64,
-        dt: f64,
-        cell_areas: Option<&[u32]>,
-        split_strategy: SplitStrategy,
-        centroids: Option<&[Position]>,
-        farthest_point: Option<Position>,
-        img_area: f64,
-    ) -> (Vec<usize>, Vec<usize>) {
-        if doubling_time <= 0.0 || target == self.sites.len() {
-            return (vec![], vec![]);
+    /// With target area `t = width · height / n` and mean spacing `s = √t`, site i moves
+    /// by `step_size · s · Σ_j ((a_j − a_i) / t) · û_ij / k` over its `k` (6)
+    /// nearest neighbors j, where û_ij is the unit vector from i to j. This is
+    /// gradient descent on the area variance, taking shared boundaries as about
+    /// `s` long; `step_size` around 1 converges in tens of steps. Crowded
+    /// (small-cell) sites thus spread into under-served regions while the sites
+    /// around an oversized cell close in on it. All moves are computed from the
+    /// same snapshot; a move that would leave the `width × height` image is
+    /// mirrored back in at the edge, like a bounce. `cell_areas` are indexed like the sites (e.g.
+    /// `VoronoiResult::cell_areas`); velocities are untouched.
+    pub fn density_equalization_step(&mut self, cell_areas: &[u32], width: f64, height: f64, step_size: f64) {
+        let img_area = width * height;
+        let n = self.sites.len();
+        if n < 2 || cell_areas.len() < n || img_area <= 0.0 {
+            return;
         }
+        let target = img_area / n as f64;
+        let spacing = target.sqrt();
+        let neighbors = self.k_nearest_neighbors(DENSITY_NEIGHBORS);
+        let moves: Vec<(f64, f64)> = (0..n)
+            .map(|i| {
+                let p = self.sites[i].pos;
+                let (mut dx, mut dy) = (0.0, 0.0);
+                for &(j, d) in &neighbors[i] {
+                    if d <= 0.0 { continue; }
+                    let w = (cell_areas[j] as f64 - cell_areas[i] as f64) / target;
+                    dx += w * (self.sites[j].pos.x - p.x) / d;
+                    dy += w * (self.sites[j].pos.y - p.y) / d;
+                }
+                let scale = step_size * spacing / neighbors[i].len().max(1) as f64;
+                (scale * dx, scale * dy)
+            })
+            .collect();
+        for (site, (dx, dy)) in self.sites.iter_mut().zip(moves) {
+            site.pos.x = reflect_into(site.pos.x + dx, width);
+            site.pos.y = reflect_into(site.pos.y + dy, height);
+        }
+    }
 
-        let current = self.sites.len();
-        let growing = target > current;
-
-        // Poisson strategy: use exponential clock but gate spawns by NN distance.
-        // Pre-compute eligible sites once, before the spawn loop.
-        let poisson_eligible: Option<Vec<usize>> = if let SplitStrategy::Poisson(threshold_k, _lambda) = split_strategy {
-            if growing {
-                let expected_spacing = (img_area / current as f64).sqrt();
-                let threshold = threshold_k * expected_spacing;
-                let nn_dists = self.nearest_neighbor_dists();
-                // Sites whose NN distance exceeds threshold are eligible to "trigger" a spawn
-                let eligible: Vec<usize> = (0..current)
-                    .filter(|&i| nn_dists[i] > threshold)
-                    .collect();
-                Some(eligible)
-            } else {
-                None
+    /// Cell division at a target size: each cell whose area exceeds
+    /// `target_area · (1 + sigma · |z|)`, with `z` a fresh standard normal draw
+    /// per cell, spawns a new site at its centroid (as `SplitStrategy::Centroid`
+    /// does). The noise staggers splits of similar cells across frames instead
+    /// of dividing them all at once; it's one-sided so that cells at or below
+    /// `target_area` never split, and the mean area settles instead of drifting
+    /// lower with every unlucky draw. `centroids` and `cell_areas` are indexed like
+    /// the sites (e.g. from `VoronoiResult`); new sites are appended, and
+    /// `on_site_added` callbacks fire for them. Returns the number of splits.
+    pub fn bifurcation_step(&mut self, centroids: &[Position], cell_areas: &[u32], target_area: u32, sigma: f64) -> usize {
+        let n = self.sites.len().min(centroids.len()).min(cell_areas.len());
+        let start = self.sites.len();
+        for i in 0..n {
+            let threshold = target_area as f64 * (1.0 + sigma * standard_normal(&mut self.rng).abs());
+            if cell_areas[i] as f64 > threshold {
+                self.sites.push(Site::with_random_velocity(centroids[i], &mut self.rng));
             }
-        } else {
-            None
-        };
+        }
+        for i in start..self.sites.len() {
+            for cb in &self.callbacks.added {
+                cb(i, &self.sites[i]);
+            }
+        }
+        self.sites.len() - start
+    }
+
+    /// Territorial competition at a constant site count: `death_rate · n · dt`
+    /// sites (rounded stochastically, so fractional rates still act over time)
+    /// lose a tournament and are replaced by offspring of the largest cell. Each
+    /// tournament draws `k` distinct random sites and the one with the smallest
+    /// area dies; the largest cell's site then `split`s toward its centroid, one
+    /// child taking its index and the other the loser's. The pair's areas are
+    /// counted as halves of the parent's for later rounds in the same call, so
+    /// one big cell doesn't absorb every replacement. `cell_areas` and
+    /// `centroids` are indexed like the sites. `on_site_removed` and
+    /// `on_site_added` callbacks fire for each replaced index. Returns the
+    /// number of replacements.
+    pub fn tournament_step(&mut self, cell_areas: &[u32], centroids: &[Position], k: usize, death_rate: f64, dt: f64) -> usize {
+        let n = self.sites.len().min(cell_areas.len()).min(centroids.len());
+        if n < 2 || k == 0 {
+            return 0;
+        }
+        let expected = (death_rate * n as f64 * dt).max(0.0);
+        let deaths = expected as usize + (self.rng.gen::<f64>() < expected.fract()) as usize;
+        let mut areas = cell_areas[..n].to_vec();
+        let mut replaced = 0;
+        for _ in 0..deaths.min(n) {
+            let loser = rand::seq::index::sample(&mut self.rng, n, k.min(n))
+                .into_iter()
+                .min_by_key(|&i| (areas[i], i))
+                .unwrap();
+            let winner = (0..n).max_by_key(|&i| (areas[i], std::cmp::Reverse(i))).unwrap();
+            if areas[winner] <= areas[loser] {
+                break;
+            }
+            let (a, b) = self.sites[winner].split(Some(&centroids[winner]), &mut self.rng);
+            for cb in &self.callbacks.removed {
+                cb(loser, &self.sites[loser]);
+            }
+            self.sites[winner] = a;
+            self.sites[loser] = b;
+            for cb in &self.callbacks.added {
+                cb(loser, &self.sites[loser]);
+            }
+            areas[loser] = areas[winner] / 2;
+            areas[winner] -= areas[loser];
+            replaced += 1;
+        }
+        replaced
+    }
+
+    /// Give every site its own O-U mean-reversion rate, overriding the `theta` passed to `step`
+    pub fn set_theta_all(&mut self, theta: f64) {
+        for site in &mut self.sites {
+            site.theta = Some(theta);
+        }
+    }
+
+    /// Give every site its own O-U noise volatility, overriding the `sigma` passed to `step`
+    pub fn set_sigma_all(&mut self, sigma: f64) {
+        for site in &mut self.sites {
+            site.sigma = Some(sigma);
+        }
+    }
+
+    /// Set site `i`'s O-U mean-reversion rate (no-op if out of range)
+    pub fn set_theta(&mut self, i: usize, theta: f64) {
+        if let Some(site) = self.sites.get_mut(i) {
+            site.theta = Some(theta);
+        }
+    }
+
+    /// Set site `i`'s O-U noise volatility (no-op if out of range)
+    pub fn set_sigma(&mut self, i: usize, sigma: f64) {
+        if let Some(site) = self.sites.get_mut(i) {
+            site.sigma = Some(sigma);
+        }
+    }
+
+    /// Move site `index` to `pos`, keeping its velocity
+    pub fn teleport_site(&mut self, index: usize, pos: Position) -> crate::Result<()> {
+        let len = self.sites.len();
+        let site = self.sites.get_mut(index).ok_or(VoronoiError::SiteIndex { index, len })?;
+        site.pos = pos;
+        Ok(())
+    }
+
+    /// Jitter every site by uniform noise in `[-magnitude, magnitude]` on each axis,
+    /// mirrored back into `[0, width) × [0, height)` at whichever edge it crosses;
+    /// velocities are untouched. Useful for shaking a converged (e.g.
+    /// Lloyd-relaxed) layout loose.
+    ///
+    /// Noise is drawn from the collection's RNG with its stream XOR'd with
+    /// `seed_offset` (restored afterwards), so different offsets give different
+    /// patterns while staying reproducible from the collection's seed.
+    pub fn perturb_positions(&mut self, magnitude: f64, seed_offset: u32, width: f64, height: f64) {
+        if magnitude <= 0.0 {
+            return;
+        }
+        let stream = self.rng.get_stream();
+        self.rng.set_stream(stream ^ seed_offset as u64);
+        for site in &mut self.sites {
+            let dx = self.rng.gen_range(-magnitude..=magnitude);
+            let dy = self.rng.gen_range(-magnitude..=magnitude);
+            site.pos.x = reflect_into(site.pos.x + dx, width);
+            site.pos.y = reflect_into(site.pos.y + dy, height);
+        }
+        self.rng.set_stream(stream);
+    }
+
+    /// Re-randomize `(fraction · len)` sites chosen at random (fraction clamped to
+    /// [0, 1]): each gets a uniform random position in the image and a random
+    /// heading, with `turn_rate` and `speed_mult` reset (reviving dead sites).
+    /// Per-site `theta` / `sigma` / `weight` are kept, as are all other sites. Useful for
+    /// re-energizing a converged layout without starting over.
+    pub fn randomize_subset(&mut self, fraction: f64, width: f64, height: f64) {
+        let count = (fraction.clamp(0.0, 1.0) * self.sites.len() as f64) as usize;
+        for i in rand::seq::index::sample(&mut self.rng, self.sites.len(), count) {
+            let pos = Position::new(self.rng.gen::<f64>() * width, self.rng.gen::<f64>() * height);
+            let site = &mut self.sites[i];
+            *site = Site { theta: site.theta, sigma: site.sigma, weight: site.weight, ..Site::with_random_velocity(pos, &mut self.rng) };
+        }
+    }
+
+    /// Register `cb` to be called for each site `adjust_count` adds, with the new
+    /// site's index and data, once the call's additions are done. Callbacks run in
+    /// registration order; clones of the collection keep them.
+    pub fn on_site_added(&mut self, cb: SiteCallback) {
+        self.callbacks.added.push(cb);
+    }
+
+    /// Register `cb` to be called for each site `adjust_count` removes, with its
+    /// index and data just before removal (indices are as of that moment, like
+    /// `adjust_count`'s returned removals).
+    pub fn on_site_removed(&mut self, cb: SiteCallback) {
+        self.callbacks.removed.push(cb);
+    }
+
+    /// Drop all `on_site_added` / `on_site_removed` callbacks
+    pub fn clear_site_callbacks(&mut self) {
+        self.callbacks = SiteCallbacks::default();
+    }
+
+    /// Gradually adjust site count toward target using exponential growth/decay.
+    ///
+    /// Per-cell inputs and limits come from `params` (see `AdjustParams`).
+    /// With `auto_remove_dead` and `cell_areas`, sites with empty cells are removed
+    /// first (highest index first, not counted against `max_per_frame`), and the
+    /// per-cell inputs are re-indexed to match before growing or shrinking.
+    /// Returns indices of newly added sites or removed sites.
+    pub fn adjust_count(
+        &mut self,
+        target: usize,
+        doubling_time: f64,
+        dt: f64,
+        split_strategy: SplitStrategy,
+        params: AdjustParams<'_>,
+    ) -> (Vec<usize>, Vec<usize>) {
+        let AdjustParams {
+            cell_areas, centroids, farthest_point, img_area, bisections, cell_variances, max_per_frame, auto_remove_dead,
+        } = params;
+        let mut removed = vec![];
+        // Per-cell inputs with dead sites' entries dropped; borrowed again below
+        let (live_areas, live_centroids, live_bisections, live_variances);
+        let (cell_areas, centroids, bisections, cell_variances) = match cell_areas {
+            Some(areas) if auto_remove_dead => {
+                let dead = self.mark_dead_sites(areas);
+                for &i in dead.iter().rev() {
+                    for cb in &self.callbacks.removed {
+                        cb(i, &self.sites[i]);
+                    }
+                    self.sites.remove(i);
+                    removed.push(i);
+                }
+                let live = |i: &usize| areas.get(*i).is_none_or(|&a| a > 0);
+                live_areas = areas.iter().copied().filter(|&a| a > 0).collect::<Vec<_>>();
+                live_centroids = centroids.map(|c| (0..c.len()).filter(live).map(|i| c[i]).collect::<Vec<_>>());
+                live_bisections = bisections.map(|b| (0..b.len()).filter(live).map(|i| b[i]).collect::<Vec<_>>());
+                live_variances = cell_variances.map(|v| (0..v.len()).filter(live).map(|i| v[i]).collect::<Vec<_>>());
+                (Some(&live_areas[..]), live_centroids.as_deref(), live_bisections.as_deref(), live_variances.as_deref())
+            }
+            _ => (cell_areas, centroids, bisections, cell_variances),
+        };
+
+        // Nothing to split or spawn from once every site is gone
+        if doubling_time <= 0.0 || target == self.sites.len() || self.sites.is_empty() {
+            return (vec![], removed);
+        }
+
+        let current = self.sites.len();
+        let growing = target > current;
+
+        // Poisson strategy: use exponential clock but gate spawns by NN distance.
+        // Pre-compute eligible sites once, before the spawn loop.
+        let poisson_eligible: Option<Vec<usize>> = if let SplitStrategy::Poisson(threshold_k, _lambda) = split_strategy {
+            if growing {
+                let expected_spacing = (img_area / current as f64).sqrt();
+                let threshold = threshold_k * expected_spacing;
+                let nn_dists = self.nearest_neighbor_dists();
+                // Sites whose NN distance exceeds threshold are eligible to "trigger" a spawn
+                let eligible: Vec<usize> = (0..current)
+                    .filter(|&i| nn_dists[i] > threshold)
+                    .collect();
+                Some(eligible)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
 
         // Rate: ln(2) / doubling_time gives exponential growth with specified doubling time
         let rate = std::f64::consts::LN_2 / doubling_time;
@@ -382,7 +1280,7 @@ impl SiteCollection {
         }
 
         let mut added = vec![];
-        let mut removed = vec![];
+        let dead_removed = removed.len();
 
         // Local mutable copy of areas: after each split, we zero the split cell's weight
         // so it can't be re-selected (without-replacement sampling, max one split per site per frame).
@@ -391,8 +1289,14 @@ impl SiteCollection {
             .unwrap_or_default();
         // Track already-split sites for Isolated strategy
         let mut split_mask: Vec<bool> = vec![false; self.sites.len()];
+        // Positions spawned so far this call, for KMeansStep
+        let mut spawned: Vec<Position> = vec![];
 
         while self.fractional_sites >= 1.0 {
+            // Stop once on target; with a tiny doubling time the buffered change can be huge
+            if self.sites.len() == target || added.len() + removed.len() - dead_removed >= max_per_frame {
+                break;
+            }
             self.fractional_sites -= 1.0;
 
             if growing && self.sites.len() < target {
@@ -407,16 +1311,22 @@ impl SiteCollection {
                 match split_strategy {
                     // Poisson: spawn at centroid of largest cell (like Centroid), gated by NN distance
                     SplitStrategy::Poisson(_, _) |
+                    // ColorVariance: spawn at centroid of the most color-varied cell
+                    // (largest cell, like Centroid, without `cell_variances`)
+                    SplitStrategy::ColorVariance |
                     // Spawn strategies: create a new site at a computed position
                     SplitStrategy::Centroid => {
                         // Spawn at centroid of largest cell
                         let pos = if let (Some(areas), Some(cents)) = (cell_areas, centroids) {
-                            let n = self.sites.len().min(areas.len()).min(cents.len());
-                            let mut max_area = 0u32;
+                            let variances = cell_variances.filter(|_| split_strategy == SplitStrategy::ColorVariance);
+                            let n = self.sites.len().min(areas.len()).min(cents.len())
+                                .min(variances.map_or(usize::MAX, |v| v.len()));
+                            let score = |i: usize| variances.map_or(areas[i] as f64, |v| v[i] as f64);
+                            let mut max_score = 0.0;
                             let mut idx = 0;
                             for i in 0..n {
-                                if !split_mask.get(i).copied().unwrap_or(false) && areas[i] > max_area {
-                                    max_area = areas[i];
+                                if !split_mask.get(i).copied().unwrap_or(false) && score(i) > max_score {
+                                    max_score = score(i);
                                     idx = i;
                                 }
                             }
@@ -432,6 +1342,49 @@ impl SiteCollection {
                         self.sites.push(Site::with_random_velocity(pos, &mut self.rng));
                         added.push(self.sites.len() - 1);
                     }
+                    SplitStrategy::KMeansStep => {
+                        let pos = match centroids {
+                            Some(cents) => {
+                                let n = current.min(cents.len());
+                                // k-means++ weights: uniform for the first spawn, then
+                                // squared distance to the nearest site spawned this call
+                                let weights: Vec<f64> = (0..n)
+                                    .map(|i| if split_mask[i] {
+                                        0.0
+                                    } else if spawned.is_empty() {
+                                        1.0
+                                    } else {
+                                        spawned.iter().map(|p| cents[i].dist_sq(p)).fold(f64::INFINITY, f64::min)
+                                    })
+                                    .collect();
+                                let total: f64 = weights.iter().sum();
+                                let idx = if total > 0.0 {
+                                    let mut r = self.rng.gen::<f64>() * total;
+                                    weights.iter().position(|&w| { r -= w; w > 0.0 && r < 0.0 })
+                                        .or_else(|| weights.iter().rposition(|&w| w > 0.0))
+                                        .unwrap()
+                                } else {
+                                    self.rng.gen_range(0..n.max(1))
+                                };
+                                if idx < split_mask.len() { split_mask[idx] = true; }
+                                cents.get(idx).copied()
+                            }
+                            None => None,
+                        };
+                        let pos = pos.unwrap_or_else(|| Position::new(
+                            self.rng.gen::<f64>() * 100.0,
+                            self.rng.gen::<f64>() * 100.0,
+                        ));
+                        spawned.push(pos);
+                        self.sites.push(Site::with_random_velocity(pos, &mut self.rng));
+                        added.push(self.sites.len() - 1);
+                    }
+                    SplitStrategy::Spiral { arm_count, turns } => {
+                        let pos = self.spiral_position(arm_count, turns, target, cell_areas, centroids, img_area);
+                        self.spiral_spawn_counter += 1;
+                        self.sites.push(Site::with_random_velocity(pos, &mut self.rng));
+                        added.push(self.sites.len() - 1);
+                    }
                     SplitStrategy::Farthest => {
                         // Spawn at the point furthest from any site
                         let pos = farthest_point.unwrap_or_else(|| Position::new(
@@ -453,7 +1406,7 @@ impl SiteCollection {
                             _ => {
                                 let n = self.sites.len().min(local_areas.len());
                                 match split_strategy {
-                                    SplitStrategy::Max => {
+                                    SplitStrategy::Max | SplitStrategy::BisectLargest => {
                                         let mut max_area = 0u64;
                                         let mut idx = 0;
                                         for (i, &area) in local_areas[..n].iter().enumerate() {
@@ -488,7 +1441,13 @@ impl SiteCollection {
                         };
 
                         let centroid = centroids.and_then(|c| c.get(src_idx));
-                        let (site1, site2) = self.sites[src_idx].split(centroid, &mut self.rng);
+                        let (mut site1, mut site2) = self.sites[src_idx].split(centroid, &mut self.rng);
+                        if split_strategy == SplitStrategy::BisectLargest {
+                            if let Some(&[near, far]) = bisections.and_then(|b| b.get(src_idx)) {
+                                site1.pos = far;
+                                site2.pos = near;
+                            }
+                        }
                         self.sites[src_idx] = site1;
                         self.sites.push(site2);
                         added.push(self.sites.len() - 1);
@@ -505,9 +1464,18 @@ impl SiteCollection {
                 // Remove site with closest neighbor (maintains spatial distribution)
                 let remove_idx = self.find_closest_neighbor_site();
                 removed.push(remove_idx);
+                for cb in &self.callbacks.removed {
+                    cb(remove_idx, &self.sites[remove_idx]);
+                }
                 self.sites.remove(remove_idx);
             }
         }
+        // Additions only push (and replace split parents in place), so indices are final
+        for &i in &added {
+            for cb in &self.callbacks.added {
+                cb(i, &self.sites[i]);
+            }
+        }
 
         if self.sites.len() == target {
             self.fractional_sites = 0.0;
@@ -517,6 +1485,212 @@ impl SiteCollection {
     }
 
 
+    /// Next `SplitStrategy::Spiral` spawn position. The image center is the
+    /// area-weighted mean of the cell centroids (cells tile the image); without
+    /// them, a square image of `img_area` is assumed.
+    fn spiral_position(
+        &self,
+        arm_count: u32,
+        turns: f64,
+        target: usize,
+        cell_areas: Option<&[u32]>,
+        centroids: Option<&[Position]>,
+        img_area: f64,
+    ) -> Position {
+        let (mut sum_a, mut sum_x, mut sum_y) = (0.0, 0.0, 0.0);
+        if let (Some(areas), Some(cents)) = (cell_areas, centroids) {
+            for (&a, c) in areas.iter().zip(cents) {
+                sum_a += a as f64;
+                sum_x += a as f64 * c.x;
+                sum_y += a as f64 * c.y;
+            }
+        }
+        let center = if sum_a > 0.0 {
+            Position::new(sum_x / sum_a, sum_y / sum_a)
+        } else {
+            let half = img_area.sqrt() / 2.0;
+            Position::new(half, half)
+        };
+        let max_r = center.x.min(center.y);
+
+        let k = self.spiral_spawn_counter as f64;
+        let frac = (k / target.max(1) as f64).min(1.0).sqrt();
+        let golden_ratio = (1.0 + 5f64.sqrt()) / 2.0;
+        let theta = std::f64::consts::TAU * (arm_count as f64 * k / golden_ratio + turns * frac);
+        Position::new(center.x + max_r * frac * theta.cos(), center.y + max_r * frac * theta.sin())
+    }
+
+    /// `adjust_count` with the doubling time shortened when cell areas are uneven:
+    /// `base_doubling_time / (1 + disparity_factor * (max_area / mean_area - 1))`
+    /// Equal areas, or no `cell_areas`, leave it unchanged.
+    pub fn adjust_count_adaptive(
+        &mut self,
+        target: usize,
+        base_doubling_time: f64,
+        disparity_factor: f64,
+        dt: f64,
+        split_strategy: SplitStrategy,
+        params: AdjustParams<'_>,
+    ) -> (Vec<usize>, Vec<usize>) {
+        let doubling_time = match params.cell_areas {
+            Some(areas) => adaptive_doubling_time(base_doubling_time, &areas[..areas.len().min(self.sites.len())], disparity_factor),
+            None => base_doubling_time,
+        };
+        self.adjust_count(target, doubling_time, dt, split_strategy, params)
+    }
+
+    /// Merge sites closer than `min_dist`: of each close pair, the site with the
+    /// larger cell area survives (the lower index when areas are missing or tied).
+    /// Returns the number of sites removed.
+    ///
+    /// Pairs come from a spatial grid with `min_dist` cells (O(n) expected) and are
+    /// resolved closest first, skipping pairs with an already-removed site. Survivors
+    /// don't move, so afterwards no two sites are within `min_dist`. `fractional_sites`
+    /// is left alone.
+    pub fn coalesce_clusters(&mut self, min_dist: f64, cell_areas: Option<&[u32]>) -> usize {
+        let n = self.sites.len();
+        if n <= 1 || min_dist.is_nan() || min_dist <= 0.0 {
+            return 0;
+        }
+
+        let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for s in &self.sites {
+            min_x = min_x.min(s.pos.x);
+            min_y = min_y.min(s.pos.y);
+            max_x = max_x.max(s.pos.x);
+            max_y = max_y.max(s.pos.y);
+        }
+        // Cells at least min_dist wide so close pairs are in adjacent cells, and
+        // at least span/√n so there are O(n) of them for tiny thresholds
+        let per_axis = (n as f64).sqrt();
+        let cell_w = min_dist.max((max_x - min_x) / per_axis);
+        let cell_h = min_dist.max((max_y - min_y) / per_axis);
+        let cols = ((max_x - min_x) / cell_w) as usize + 1;
+        let rows = ((max_y - min_y) / cell_h) as usize + 1;
+        let cell_of = |p: Position| -> (usize, usize) {
+            (
+                (((p.x - min_x) / cell_w) as usize).min(cols - 1),
+                (((p.y - min_y) / cell_h) as usize).min(rows - 1),
+            )
+        };
+        let mut grid: Vec<Vec<usize>> = vec![vec![]; cols * rows];
+        for (i, s) in self.sites.iter().enumerate() {
+            let (cx, cy) = cell_of(s.pos);
+            grid[cy * cols + cx].push(i);
+        }
+
+        let mut pairs: Vec<(f64, usize, usize)> = Vec::new();
+        for (i, s) in self.sites.iter().enumerate() {
+            let (cx, cy) = cell_of(s.pos);
+            for gy in cy.saturating_sub(1)..(cy + 2).min(rows) {
+                for gx in cx.saturating_sub(1)..(cx + 2).min(cols) {
+                    for &j in &grid[gy * cols + gx] {
+                        let d = s.pos.dist(&self.sites[j].pos);
+                        if j > i && d < min_dist {
+                            pairs.push((d, i, j));
+                        }
+                    }
+                }
+            }
+        }
+        pairs.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+
+        let area = |i: usize| cell_areas.and_then(|a| a.get(i)).copied().unwrap_or(0);
+        let mut keep = vec![true; n];
+        for &(_, i, j) in &pairs {
+            if keep[i] && keep[j] {
+                // i < j, so i wins ties
+                let loser = if area(j) > area(i) { i } else { j };
+                keep[loser] = false;
+            }
+        }
+
+        let mut keep = keep.into_iter();
+        self.sites.retain(|_| keep.next().unwrap_or(true));
+        n - self.sites.len()
+    }
+
+    /// Remove sites whose cell is both small (`cell_areas[i] < area_threshold`)
+    /// and flat (`color_variance[i] < variance_threshold`, e.g. from
+    /// `VoronoiResult::cell_color_variance`): resolution spent on a uniform
+    /// region. Sites beyond either slice are kept. Returns the number removed.
+    pub fn merge_isolated_sites(
+        &mut self,
+        cell_areas: &[u32],
+        color_variance: &[f32],
+        area_threshold: u32,
+        variance_threshold: f32,
+    ) -> usize {
+        let n = self.sites.len();
+        let mut i = 0;
+        self.sites.retain(|_| {
+            let prune = matches!(
+                (cell_areas.get(i), color_variance.get(i)),
+                (Some(&area), Some(&var)) if area < area_threshold && var < variance_threshold
+            );
+            i += 1;
+            !prune
+        });
+        n - self.sites.len()
+    }
+
+    /// Mark sites whose cell in the last frame had no pixels (off-screen, stacked on
+    /// another site, or crowded out by neighbors) as dead, by setting their
+    /// `speed_mult` to the sentinel `-1.0`. Sites beyond `cell_areas` are left
+    /// alone. Returns the indices marked.
+    pub fn mark_dead_sites(&mut self, cell_areas: &[u32]) -> Vec<usize> {
+        let dead: Vec<usize> = (0..self.sites.len().min(cell_areas.len()))
+            .filter(|&i| cell_areas[i] == 0)
+            .collect();
+        for &i in &dead {
+            self.sites[i].speed_mult = DEAD_SPEED_MULT;
+        }
+        dead
+    }
+
+    /// Remove every site marked dead (`speed_mult < 0`, see `mark_dead_sites`).
+    /// Returns the number removed.
+    pub fn remove_dead_sites(&mut self) -> usize {
+        let n = self.sites.len();
+        self.sites.retain(|s| s.speed_mult >= 0.0);
+        n - self.sites.len()
+    }
+
+    /// Move each site marked dead to a random point near a live site, chosen with
+    /// probability proportional to its `cell_areas` entry, within half that cell's
+    /// equal-area radius (clamped to the image), and clear its mark. Without any
+    /// live area, dead sites are placed uniformly over the image.
+    pub fn revive_dead_sites(&mut self, cell_areas: &[u32], width: f64, height: f64) {
+        let n = self.sites.len().min(cell_areas.len());
+        let weights: Vec<u64> = (0..n)
+            .map(|i| if self.sites[i].speed_mult < 0.0 { 0 } else { cell_areas[i] as u64 })
+            .collect();
+        let total: u64 = weights.iter().sum();
+        for i in 0..self.sites.len() {
+            if self.sites[i].speed_mult >= 0.0 {
+                continue;
+            }
+            let pos = if total > 0 {
+                let mut r = self.rng.gen_range(0..total);
+                let host = weights.iter().position(|&w| if r < w { true } else { r -= w; false }).unwrap();
+                let radius = (cell_areas[host] as f64 / std::f64::consts::PI).sqrt() / 2.0;
+                let angle = self.rng.gen::<f64>() * std::f64::consts::TAU;
+                let dist = radius * self.rng.gen::<f64>().sqrt();
+                let center = self.sites[host].pos;
+                Position::new(
+                    (center.x + dist * angle.cos()).clamp(0.0, width.next_down().max(0.0)),
+                    (center.y + dist * angle.sin()).clamp(0.0, height.next_down().max(0.0)),
+                )
+            } else {
+                Position::new(self.rng.gen::<f64>() * width, self.rng.gen::<f64>() * height)
+            };
+            let site = &mut self.sites[i];
+            site.pos = pos;
+            site.speed_mult = 1.0;
+        }
+    }
+
     /// Compute nearest-neighbor distance for each site using a spatial grid (O(n) expected).
     fn nearest_neighbor_dists(&self) -> Vec<f64> {
         let n = self.sites.len();
@@ -553,7 +1727,7 @@ impl SiteCollection {
 
         // For each site, search expanding rings until we can guarantee nearest found
         let mut dists = vec![f64::INFINITY; n];
-        for i in 0..n {
+        for (i, dist) in dists.iter_mut().enumerate() {
             let sx = self.sites[i].pos.x;
             let sy = self.sites[i].pos.y;
             let cx = ((sx - min_x) / cell_w).min((cols - 1) as f64) as usize;
@@ -587,11 +1761,81 @@ impl SiteCollection {
                     }
                 }
             }
-            dists[i] = best;
+            *dist = best;
         }
         dists
     }
 
+    /// The `k` nearest other sites of each site as (index, distance), nearest first,
+    /// using a spatial grid searched in expanding rings (O(n·k) expected).
+    fn k_nearest_neighbors(&self, k: usize) -> Vec<Vec<(usize, f64)>> {
+        let n = self.sites.len();
+        let k = k.min(n.saturating_sub(1));
+        if k == 0 {
+            return vec![Vec::new(); n];
+        }
+
+        let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for s in &self.sites {
+            min_x = min_x.min(s.pos.x);
+            min_y = min_y.min(s.pos.y);
+            max_x = max_x.max(s.pos.x);
+            max_y = max_y.max(s.pos.y);
+        }
+        let w = (max_x - min_x).max(1.0);
+        let h = (max_y - min_y).max(1.0);
+
+        // ~k sites per grid cell, so a site's neighbors are usually within ring 1
+        let grid_size = ((n as f64 / k as f64).sqrt().ceil() as usize).max(1);
+        let cell_w = w / grid_size as f64;
+        let cell_h = h / grid_size as f64;
+        let cell_of = |p: Position| -> (usize, usize) {
+            let cx = ((p.x - min_x) / cell_w).min((grid_size - 1) as f64) as usize;
+            let cy = ((p.y - min_y) / cell_h).min((grid_size - 1) as f64) as usize;
+            (cx, cy)
+        };
+        let mut grid: Vec<Vec<usize>> = vec![vec![]; grid_size * grid_size];
+        for (i, s) in self.sites.iter().enumerate() {
+            let (cx, cy) = cell_of(s.pos);
+            grid[cy * grid_size + cx].push(i);
+        }
+
+        (0..n)
+            .map(|i| {
+                let p = self.sites[i].pos;
+                let (cx, cy) = cell_of(p);
+                let mut best: Vec<(usize, f64)> = Vec::with_capacity(k + 1);
+                for ring in 0..grid_size {
+                    // Sites in this ring are at least (ring - 1) cells away on some axis
+                    let min_ring_dist = (ring as f64 - 1.0).max(0.0) * cell_w.min(cell_h);
+                    if best.len() == k && min_ring_dist > best[k - 1].1 {
+                        break;
+                    }
+                    let ring = ring as isize;
+                    for gy in (cy as isize - ring)..=(cy as isize + ring) {
+                        for gx in (cx as isize - ring)..=(cx as isize + ring) {
+                            let on_ring = (gy - cy as isize).abs() == ring || (gx - cx as isize).abs() == ring;
+                            if !on_ring || gx < 0 || gy < 0 || gx >= grid_size as isize || gy >= grid_size as isize {
+                                continue;
+                            }
+                            for &j in &grid[gy as usize * grid_size + gx as usize] {
+                                if j == i { continue; }
+                                let d = p.dist(&self.sites[j].pos);
+                                if best.len() < k || d < best[k - 1].1 {
+                                    let at = best.partition_point(|&(_, bd)| bd <= d);
+                                    best.insert(at, (j, d));
+                                    best.truncate(k);
+                                }
+                            }
+                        }
+                    }
+                }
+                best
+            })
+            .collect()
+    }
+
     /// Find site with the closest neighbor (most "redundant" spatially)
     fn find_closest_neighbor_site(&mut self) -> usize {
         if self.sites.len() <= 1 {
@@ -669,6 +1913,11 @@ impl SiteCollection {
         self.sites.iter().map(|s| s.pos).collect()
     }
 
+    /// Get power-diagram weights, parallel to `positions` (for `CpuBackend::compute_weighted`)
+    pub fn weights(&self) -> Vec<f64> {
+        self.sites.iter().map(|s| s.weight).collect()
+    }
+
     /// Get current site count
     pub fn len(&self) -> usize {
         self.sites.len()
@@ -679,3 +1928,927 @@ impl SiteCollection {
         self.sites.is_empty()
     }
 }
+
+/// Sites at the collected positions, with velocities as from
+/// `SiteCollection::from_iterator` with seed 0
+impl FromIterator<Position> for SiteCollection {
+    fn from_iter<I: IntoIterator<Item = Position>>(iter: I) -> Self {
+        Self::from_iterator(iter.into_iter(), 0, 0)
+    }
+}
+
+/// Thread-safe shared handle to a `SiteCollection`, e.g. for running physics
+/// and rendering on separate threads. Clones share the same collection.
+#[derive(Debug, Clone)]
+pub struct SiteCollectionHandle(Arc<Mutex<SiteCollection>>);
+
+impl SiteCollectionHandle {
+    pub fn new(collection: SiteCollection) -> Self {
+        Self(Arc::new(Mutex::new(collection)))
+    }
+
+    /// Lock the collection for direct access. A panic on another thread while
+    /// holding the lock doesn't invalidate site state, so poisoning is ignored.
+    pub fn lock(&self) -> MutexGuard<'_, SiteCollection> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// See `SiteCollection::step`
+    #[allow(clippy::too_many_arguments)]
+    pub fn step(
+        &self,
+        speed: f64,
+        dt: f64,
+        width: f64,
+        height: f64,
+        centroids: Option<&[Position]>,
+        centroid_pull: f64,
+        theta: f64,
+        sigma: f64,
+    ) {
+        self.lock().step(speed, dt, width, height, centroids, centroid_pull, theta, sigma);
+    }
+
+    /// See `SiteCollection::adjust_count`
+    pub fn adjust_count(
+        &self,
+        target: usize,
+        doubling_time: f64,
+        dt: f64,
+        split_strategy: SplitStrategy,
+        params: AdjustParams<'_>,
+    ) -> (Vec<usize>, Vec<usize>) {
+        self.lock().adjust_count(target, doubling_time, dt, split_strategy, params)
+    }
+
+    /// Snapshot of current site positions
+    pub fn positions(&self) -> Vec<Position> {
+        self.lock().positions()
+    }
+
+    /// Snapshot of current site weights
+    pub fn weights(&self) -> Vec<f64> {
+        self.lock().weights()
+    }
+
+    /// Current site count
+    pub fn site_count(&self) -> usize {
+        self.lock().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One site at (98, 50) heading right at 10 px/s; a 0.5s step carries it to x=103
+    fn heading_right() -> SiteCollection {
+        SiteCollection::new(vec![Site::new(Position::new(98.0, 50.0), Velocity::new(1.0, 0.0))], 1)
+    }
+
+    fn step_edge(sites: &mut SiteCollection, edge: EdgeBehavior) -> Vec<usize> {
+        // sigma = 0 keeps the heading fixed
+        sites.step_with_edge_behavior(10.0, 0.5, 100.0, 100.0, None, 0.0, 3.0, 0.0, edge)
+    }
+
+    /// Circular standard deviation of site headings, sqrt(-2 ln R)
+    fn heading_spread(sites: &SiteCollection) -> f64 {
+        let n = sites.len() as f64;
+        let (sx, sy) = sites.sites.iter().fold((0.0, 0.0), |(ax, ay), s| (ax + s.vel.x, ay + s.vel.y));
+        let r = (sx * sx + sy * sy).sqrt() / n;
+        (-2.0 * r.ln()).sqrt()
+    }
+
+    #[test]
+    fn test_smooth_velocities() {
+        let mut sites = SiteCollection::random(300, 320.0, 240.0, 4);
+        let before = heading_spread(&sites);
+        sites.smooth_velocities(8);
+        let after = heading_spread(&sites);
+        assert!(after < before, "spread {} -> {}", before, after);
+        for s in &sites.sites {
+            assert!((s.vel.x.hypot(s.vel.y) - 1.0).abs() < 1e-12);
+        }
+
+        // Neighbor search agrees with brute force
+        let knn = sites.k_nearest_neighbors(5);
+        for (i, found) in knn.iter().enumerate() {
+            let mut brute: Vec<(usize, f64)> = (0..sites.len())
+                .filter(|&j| j != i)
+                .map(|j| (j, sites.sites[i].pos.dist(&sites.sites[j].pos)))
+                .collect();
+            brute.sort_by(|a, b| a.1.total_cmp(&b.1));
+            let dists: Vec<f64> = found.iter().map(|&(_, d)| d).collect();
+            let expected: Vec<f64> = brute[..5].iter().map(|&(_, d)| d).collect();
+            assert_eq!(dists, expected);
+        }
+    }
+
+    #[test]
+    fn test_adjust_count_max_per_frame() {
+        let mut sites = SiteCollection::random(10, 100.0, 100.0, 2);
+        // A 100s frame with a 1s doubling time would add every missing site at once
+        for frame in 1..=5 {
+            let (added, _) = sites.adjust_count(
+                40, 1.0, 100.0, SplitStrategy::Max, AdjustParams { max_per_frame: 1, ..Default::default() },
+            );
+            assert_eq!(added.len(), 1);
+            assert_eq!(sites.len(), 10 + frame);
+        }
+        // The unused growth stays buffered for later frames
+        assert!(sites.fractional_sites > 1.0);
+
+        let (_, removed) = sites.adjust_count(5, 1.0, 100.0, SplitStrategy::Max, AdjustParams { max_per_frame: 2, ..Default::default() });
+        assert_eq!(removed.len(), 2);
+    }
+
+    #[test]
+    fn test_adjust_count_color_variance() {
+        let mut sites = SiteCollection::random(4, 100.0, 100.0, 5);
+        let areas = [2500, 2500, 9000, 2500];
+        let cents = [Position::new(25.0, 25.0), Position::new(75.0, 25.0), Position::new(25.0, 75.0), Position::new(75.0, 75.0)];
+        let variances = [10.0, 800.0, 0.0, 300.0];
+        // Busiest cells first, not the largest
+        let params = AdjustParams { cell_areas: Some(&areas), centroids: Some(&cents), ..Default::default() };
+        let (added, _) = sites.adjust_count(
+            6, 1.0, 100.0, SplitStrategy::ColorVariance,
+            AdjustParams { cell_variances: Some(&variances), max_per_frame: 2, ..params },
+        );
+        assert_eq!(added, vec![4, 5]);
+        assert_eq!(sites.sites[4].pos, cents[1]);
+        assert_eq!(sites.sites[5].pos, cents[3]);
+
+        // Without variances it spawns like Centroid
+        let (added, _) = sites.adjust_count(7, 1.0, 100.0, SplitStrategy::ColorVariance, AdjustParams { max_per_frame: 1, ..params });
+        assert_eq!(sites.sites[added[0]].pos, cents[2]);
+        assert_eq!("color-variance".parse::<SplitStrategy>(), Ok(SplitStrategy::ColorVariance));
+    }
+
+    #[test]
+    fn test_site_callbacks() {
+        let mut sites = SiteCollection::random(4, 100.0, 100.0, 3);
+        let added = Arc::new(Mutex::new(Vec::new()));
+        let removed = Arc::new(Mutex::new(Vec::new()));
+        let log = added.clone();
+        sites.on_site_added(Arc::new(move |i, site: &Site| log.lock().unwrap().push((i, site.pos))));
+        let log = removed.clone();
+        sites.on_site_removed(Arc::new(move |i, site: &Site| log.lock().unwrap().push((i, site.pos))));
+
+        // One split of the largest cell
+        let areas = [10, 500, 20, 30];
+        let (new, _) = sites.adjust_count(
+            5, 1.0, 100.0, SplitStrategy::Max, AdjustParams { cell_areas: Some(&areas), max_per_frame: 1, ..Default::default() },
+        );
+        assert_eq!(new, vec![4]);
+        assert_eq!(*added.lock().unwrap(), vec![(4, sites.sites[4].pos)]);
+        assert!(removed.lock().unwrap().is_empty());
+
+        let before = sites.positions();
+        let (_, gone) = sites.adjust_count(4, 1.0, 100.0, SplitStrategy::Max, AdjustParams { max_per_frame: 1, ..Default::default() });
+        assert_eq!(*removed.lock().unwrap(), vec![(gone[0], before[gone[0]])]);
+        assert_eq!(added.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_coalesce_clusters() {
+        let mut sites = SiteCollection::random(300, 200.0, 200.0, 4);
+        // A tight clump, as a shrink phase might leave behind
+        for k in 0..20 {
+            let angle = k as f64 * 0.7;
+            sites.sites.push(Site::new(Position::new(100.0 + angle.cos() * k as f64 * 0.3, 100.0 + angle.sin() * k as f64 * 0.3), Velocity::new(1.0, 0.0)));
+        }
+        let before = sites.len();
+        let removed = sites.coalesce_clusters(10.0, None);
+        assert_eq!(sites.len(), before - removed);
+        assert!(removed >= 19);
+        let positions = sites.positions();
+        for (i, a) in positions.iter().enumerate() {
+            for b in &positions[i + 1..] {
+                assert!(a.dist(b) >= 10.0, "{:?} and {:?} still within 10px", a, b);
+            }
+        }
+
+        // The larger cell survives a merge
+        let mut pair = SiteCollection::random_from_positions(vec![Position::new(0.0, 0.0), Position::new(3.0, 0.0)], 0);
+        assert_eq!(pair.coalesce_clusters(5.0, Some(&[10, 50])), 1);
+        assert_eq!(pair.positions(), vec![Position::new(3.0, 0.0)]);
+
+        // A tiny threshold over many sites still finds the one near-duplicate
+        let mut many = SiteCollection::random(20_000, 4000.0, 4000.0, 5);
+        let dup = many.sites[123].pos;
+        many.sites.push(Site::new(Position::new(dup.x + 1e-7, dup.y), Velocity::new(1.0, 0.0)));
+        assert_eq!(many.coalesce_clusters(1e-6, None), 1);
+    }
+
+    #[test]
+    fn test_from_random_walk() {
+        let sites = SiteCollection::from_random_walk(500, 20.0, 120.0, 80.0, 9);
+        assert_eq!(sites.len(), 500);
+        for p in sites.positions() {
+            assert!((0.0..120.0).contains(&p.x) && (0.0..80.0).contains(&p.y), "{:?} outside image", p);
+        }
+        // Unwrapped steps are step_size long and head along the walk
+        let ps = sites.positions();
+        for (pair, site) in ps.windows(2).zip(&sites.sites[1..]) {
+            let (dx, dy) = (pair[1].x - pair[0].x, pair[1].y - pair[0].y);
+            if dx.abs() < 60.0 && dy.abs() < 40.0 {
+                assert!((pair[0].dist(&pair[1]) - 20.0).abs() < 1e-9);
+                assert!((dx / 20.0 - site.vel.x).abs() < 1e-9 && (dy / 20.0 - site.vel.y).abs() < 1e-9);
+            }
+        }
+
+        let again = SiteCollection::from_random_walk(500, 20.0, 120.0, 80.0, 9);
+        assert_eq!(again.positions(), sites.positions());
+        assert_ne!(SiteCollection::from_random_walk(500, 20.0, 120.0, 80.0, 10).positions(), sites.positions());
+    }
+
+    #[test]
+    fn test_teleport() {
+        let mut sites = SiteCollection::random(20, 100.0, 100.0, 6);
+        let vel = sites.sites[0].vel;
+        sites.teleport_site(0, Position::new(12.5, 87.0)).unwrap();
+        assert_eq!(sites.positions()[0], Position::new(12.5, 87.0));
+        assert_eq!((sites.sites[0].vel.x, sites.sites[0].vel.y), (vel.x, vel.y));
+        assert!(matches!(sites.teleport_site(20, Position::new(0.0, 0.0)), Err(VoronoiError::SiteIndex { index: 20, len: 20 })));
+    }
+
+    #[test]
+    fn test_perturb_positions() {
+        let original = SiteCollection::random(200, 100.0, 80.0, 4);
+        let mut sites = original.clone();
+        sites.perturb_positions(0.0, 0, 100.0, 80.0);
+        assert_eq!(sites.positions(), original.positions());
+
+        sites.perturb_positions(3.0, 0, 100.0, 80.0);
+        let mut moved = 0;
+        for (p, q) in sites.positions().iter().zip(original.positions()) {
+            assert!(p.dist(&q) <= 3.0 * 2f64.sqrt());
+            assert!(p.x >= 0.0 && p.x < 100.0 && p.y >= 0.0 && p.y < 80.0);
+            moved += (*p != q) as usize;
+        }
+        assert!(moved > 190);
+
+        // Offsets pick different patterns; the same offset repeats one
+        let perturbed = |offset| {
+            let mut sites = original.clone();
+            sites.perturb_positions(3.0, offset, 100.0, 80.0);
+            sites.positions()
+        };
+        assert_eq!(perturbed(7), perturbed(7));
+        assert_ne!(perturbed(7), perturbed(8));
+    }
+
+    /// A corner site with an oversized cell is pushed away from its crowded
+    /// neighbors, past the corner, and mirrored back in
+    #[test]
+    fn test_density_equalization_stays_in_bounds() {
+        let mut sites = SiteCollection::random_from_positions(
+            vec![Position::new(1.0, 1.0), Position::new(6.0, 4.0), Position::new(4.0, 7.0), Position::new(90.0, 70.0)],
+            0,
+        );
+        sites.density_equalization_step(&[7000, 10, 10, 980], 100.0, 80.0, 5.0);
+        let positions = sites.positions();
+        assert_ne!(positions[0], Position::new(1.0, 1.0));
+        for p in positions {
+            assert!(p.x >= 0.0 && p.x < 100.0 && p.y >= 0.0 && p.y < 80.0, "{:?}", p);
+        }
+    }
+
+    #[test]
+    fn test_from_iterator() {
+        let random = SiteCollection::random(300, 100.0, 80.0, 6);
+        let positions = random.positions();
+        let streamed = SiteCollection::from_iterator(positions.clone().into_iter(), 6, 0);
+        assert_eq!(streamed.positions(), positions);
+        let expected = SiteCollection::random_from_positions(positions.clone(), 6);
+        for (a, b) in streamed.sites.iter().zip(&expected.sites) {
+            assert_eq!(a.vel, b.vel);
+        }
+
+        // Lazy source with no length, pre-sized by the hint
+        let lazy = SiteCollection::from_iterator(positions.iter().copied().filter(|p| p.x < 50.0), 6, 300);
+        assert!(lazy.sites.capacity() >= 300);
+        assert_eq!(lazy.positions(), positions.iter().copied().filter(|p| p.x < 50.0).collect::<Vec<_>>());
+
+        let collected: SiteCollection = positions.iter().copied().collect();
+        assert_eq!(collected.positions(), positions);
+        assert_eq!(collected.sites[0].vel, SiteCollection::random_from_positions(positions, 0).sites[0].vel);
+    }
+
+    #[test]
+    fn test_randomize_subset() {
+        let mut original = SiteCollection::random(200, 100.0, 80.0, 5);
+        for site in &mut original.sites {
+            site.turn_rate = 0.3;
+            site.speed_mult = 2.0;
+            site.theta = Some(1.5);
+        }
+        let mut sites = original.clone();
+        sites.randomize_subset(0.1, 100.0, 80.0);
+
+        let mut changed = 0;
+        for (site, before) in sites.sites.iter().zip(&original.sites) {
+            if site.pos == before.pos {
+                assert_eq!(site.vel, before.vel);
+                assert_eq!((site.turn_rate, site.speed_mult), (0.3, 2.0));
+            } else {
+                changed += 1;
+                assert!(site.pos.x >= 0.0 && site.pos.x < 100.0 && site.pos.y >= 0.0 && site.pos.y < 80.0);
+                assert_eq!((site.turn_rate, site.speed_mult), (0.0, 1.0));
+            }
+            assert_eq!(site.theta, Some(1.5));
+        }
+        assert_eq!(changed, 20);
+
+        sites.randomize_subset(0.0, 100.0, 80.0);
+        sites.randomize_subset(-1.0, 100.0, 80.0);
+        assert_eq!(sites.sites.iter().filter(|s| s.speed_mult == 1.0).count(), 20);
+    }
+
+    #[test]
+    fn test_spiral_spawns() {
+        assert_eq!("spiral(1,0)".parse::<SplitStrategy>(), Ok(SplitStrategy::Spiral { arm_count: 1, turns: 0.0 }));
+        assert_eq!(SplitStrategy::Spiral { arm_count: 3, turns: 5.0 }.to_string().parse(), Ok(SplitStrategy::Spiral { arm_count: 3, turns: 5.0 }));
+
+        // One 200x100 "cell" centered in the image, so the spiral centers on (100, 50)
+        let mut sites = SiteCollection::random_from_positions(vec![Position::new(10.0, 10.0)], 1);
+        let spiral = SplitStrategy::Spiral { arm_count: 1, turns: 0.0 };
+        let center = Position::new(100.0, 50.0);
+        let (added, _) = sites.adjust_count(64, 1e-9, 1.0, spiral, AdjustParams {
+            cell_areas: Some(&[20_000]),
+            centroids: Some(&[center]),
+            img_area: 20_000.0,
+            ..Default::default()
+        });
+        assert_eq!(added.len(), 63);
+        assert_eq!(sites.spiral_spawn_counter, 63);
+
+        // Radii grow with K, and consecutive spawns are a constant (golden) angle apart
+        let spawned: Vec<Position> = added.iter().map(|&i| sites.sites[i].pos).collect();
+        let polar: Vec<(f64, f64)> = spawned.iter()
+            .map(|p| (p.dist(&center), (p.y - center.y).atan2(p.x - center.x)))
+            .collect();
+        assert!(polar.windows(2).all(|w| w[1].0 > w[0].0));
+        assert!(polar.iter().all(|&(r, _)| r <= 50.0 + 1e-9));
+        let golden_step = std::f64::consts::TAU / ((1.0 + 5f64.sqrt()) / 2.0);
+        for w in polar[1..].windows(2) {
+            let step = (w[1].1 - w[0].1).rem_euclid(std::f64::consts::TAU);
+            assert!((step - golden_step.rem_euclid(std::f64::consts::TAU)).abs() < 1e-6, "angle step {}", step);
+        }
+    }
+
+    #[test]
+    fn test_step_with_params_fn() {
+        let mut sites = SiteCollection::random(400, 400.0, 400.0, 13);
+        // Even sites wander freely, odd ones are pinned to straight lines
+        let params = |i: usize| if i.is_multiple_of(2) { StepParams::new(0.5, 4.0) } else { StepParams::new(50.0, 0.0) };
+        for _ in 0..60 {
+            sites.step_with_params_fn(10.0, 1.0 / 30.0, 400.0, 400.0, None, 0.0, EdgeBehavior::Wrap, params);
+        }
+        let mean_abs_turn = |parity: usize| {
+            let group: Vec<f64> = sites.sites.iter().skip(parity).step_by(2).map(|s| s.turn_rate.abs()).collect();
+            group.iter().sum::<f64>() / group.len() as f64
+        };
+        assert!(mean_abs_turn(1) < 1e-9, "odd sites turned: {}", mean_abs_turn(1));
+        assert!(mean_abs_turn(0) > 0.5, "even sites barely turned: {}", mean_abs_turn(0));
+
+        // Constant params match plain step (with the same RNG stream)
+        let mut a = SiteCollection::random(20, 100.0, 100.0, 2);
+        let mut b = a.clone();
+        a.step(15.0, 0.1, 100.0, 100.0, None, 0.0, 3.0, 2.0);
+        b.step_with_params_fn(15.0, 0.1, 100.0, 100.0, None, 0.0, EdgeBehavior::Reflect, |_| StepParams::new(3.0, 2.0));
+        assert_eq!(a.positions(), b.positions());
+    }
+
+    /// Variance of turn rates across sites
+    fn turn_rate_variance(sites: &SiteCollection) -> f64 {
+        let n = sites.len() as f64;
+        let mean = sites.sites.iter().map(|s| s.turn_rate).sum::<f64>() / n;
+        sites.sites.iter().map(|s| (s.turn_rate - mean).powi(2)).sum::<f64>() / n
+    }
+
+    #[test]
+    fn test_per_site_dynamics() {
+        // sigma = 0: turn rates stay 0, so headings never change
+        let mut sites = SiteCollection::random(50, 1000.0, 1000.0, 6);
+        sites.set_sigma_all(0.0);
+        let headings: Vec<Velocity> = sites.sites.iter().map(|s| s.vel).collect();
+        for _ in 0..50 {
+            // The per-site sigma overrides the global 3.0
+            sites.step(1.0, 0.1, 1000.0, 1000.0, None, 0.0, 3.0, 3.0);
+        }
+        for (site, vel) in sites.sites.iter().zip(&headings) {
+            assert_eq!(site.turn_rate, 0.0);
+            assert!((site.vel.x - vel.x).abs() < 1e-9 && (site.vel.y - vel.y).abs() < 1e-9);
+        }
+
+        // theta = 0: turn rates random-walk, variance growing linearly with time;
+        // with mean reversion it levels off
+        let growth = |theta: f64| {
+            let mut sites = SiteCollection::random(2000, 1e6, 1e6, 8);
+            sites.set_theta_all(theta);
+            let mut variances = vec![];
+            for _ in 0..2 {
+                for _ in 0..100 {
+                    sites.step(0.0, 0.05, 1e6, 1e6, None, 0.0, 3.0, 1.0);
+                }
+                variances.push(turn_rate_variance(&sites));
+            }
+            variances[1] / variances[0]
+        };
+        let free = growth(0.0);
+        assert!((free - 2.0).abs() < 0.3, "variance ratio {}", free);
+        let reverting = growth(3.0);
+        assert!((reverting - 1.0).abs() < 0.2, "variance ratio {}", reverting);
+
+        sites.set_theta(0, 0.5);
+        assert_eq!(sites.sites[0].theta, Some(0.5));
+        assert_eq!(sites.sites[1].theta, None);
+    }
+
+    #[test]
+    fn test_adaptive_doubling_time() {
+        assert_eq!(adaptive_doubling_time(2.0, &[100, 100, 100], 3.0), 2.0);
+        // max/mean = 10 with factor 1 -> 10x faster
+        assert!((adaptive_doubling_time(2.0, &[1000, 0, 0, 0, 0, 0, 0, 0, 0, 0], 1.0) - 0.2).abs() < 1e-12);
+        assert_eq!(adaptive_doubling_time(2.0, &[], 1.0), 2.0);
+    }
+
+    #[test]
+    fn test_edge_wrap() {
+        let mut sites = heading_right();
+        assert!(step_edge(&mut sites, EdgeBehavior::Wrap).is_empty());
+        let pos = sites.sites[0].pos;
+        assert!((pos.x - 3.0).abs() < 1e-9, "x = {}", pos.x);
+        assert_eq!(pos.y, 50.0);
+        assert_eq!(sites.sites[0].vel.x, 1.0);
+    }
+
+    #[test]
+    fn test_edge_absorb() {
+        let mut sites = heading_right();
+        sites.sites.push(Site::new(Position::new(50.0, 50.0), Velocity::new(1.0, 0.0)));
+        assert_eq!(step_edge(&mut sites, EdgeBehavior::Absorb), vec![0]);
+        assert_eq!(sites.len(), 1);
+        assert!((sites.sites[0].pos.x - 55.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_edge_clamp() {
+        let mut sites = heading_right();
+        step_edge(&mut sites, EdgeBehavior::Clamp);
+        let site = &sites.sites[0];
+        assert_eq!(site.pos.x, 99.0);
+        assert_eq!(site.vel.x, 0.0);
+    }
+
+    #[test]
+    fn test_slerp_pull_keeps_unit_velocity() {
+        let h = std::f64::consts::FRAC_1_SQRT_2;
+        let mid = Velocity::new(1.0, 0.0).slerp(&Velocity::new(0.0, 1.0), 0.5);
+        assert!((mid.x - h).abs() < 1e-12 && (mid.y - h).abs() < 1e-12);
+        let turned = Velocity::new(1.0, 0.0).slerp(&Velocity::new(-1.0, 0.0), 0.5);
+        assert!(turned.x.abs() < 1e-12 && (turned.y - 1.0).abs() < 1e-12);
+
+        let mut sites = SiteCollection::random(20, 200.0, 200.0, 5);
+        sites.pull_mode = PullMode::Slerp;
+        let centroids = SiteCollection::random(20, 200.0, 200.0, 6).positions();
+        for _ in 0..1000 {
+            sites.step(30.0, 1.0 / 30.0, 200.0, 200.0, Some(&centroids), 2.0, 3.0, 3.0);
+        }
+        for site in &sites.sites {
+            let len = (site.vel.x * site.vel.x + site.vel.y * site.vel.y).sqrt();
+            assert!((len - 1.0).abs() < 1e-12, "|vel| = {}", len);
+        }
+    }
+
+    /// A site inside a strong vortex circles its center; one outside keeps going straight
+    #[test]
+    fn test_vortex_orbits_sites() {
+        let mut sites = SiteCollection::new(vec![
+            Site::new(Position::new(110.0, 100.0), Velocity::new(1.0, 0.0)),
+            Site::new(Position::new(100.0, 300.0), Velocity::new(1.0, 0.0)),
+        ], 0);
+        sites.add_vortex(100.0, 100.0, 50.0, 40.0);
+        let mut swept = 0.0;
+        let mut prev_angle = 0.0f64;
+        for _ in 0..300 {
+            sites.step(10.0, 0.05, 400.0, 400.0, None, 0.0, 0.0, 0.0);
+            let p = sites.sites[0].pos;
+            let angle = (p.y - 100.0).atan2(p.x - 100.0);
+            let mut delta = angle - prev_angle;
+            if delta > std::f64::consts::PI { delta -= std::f64::consts::TAU; }
+            if delta < -std::f64::consts::PI { delta += std::f64::consts::TAU; }
+            swept += delta;
+            prev_angle = angle;
+            assert!(p.dist(&Position::new(100.0, 100.0)) < 40.0);
+        }
+        assert!(swept > std::f64::consts::TAU, "swept {} rad", swept);
+        let v = sites.sites[1].vel;
+        assert!((v.x - 1.0).abs() < 1e-12 && v.y.abs() < 1e-12);
+
+        sites.clear_vortices();
+        assert!(sites.vortices.is_empty());
+    }
+
+    /// A uniform rightward flow at full blend carries every site right; a zero
+    /// flow vector leaves a site's heading alone
+    #[test]
+    fn test_step_with_image_flow() {
+        let (w, h) = (200u32, 100u32);
+        let mut flow = vec![(3.0, 0.0); (w * h) as usize];
+        let mut sites = SiteCollection::random(30, 150.0, 100.0, 8);
+        let start = sites.positions();
+        for _ in 0..20 {
+            sites.step_with_image_flow(&flow, w, 1.0, 20.0, 0.05);
+        }
+        for (before, after) in start.iter().zip(sites.positions()) {
+            assert!(after.x - before.x > 19.0, "{:?} -> {:?}", before, after);
+            assert!((after.y - before.y).abs() < 1.0);
+        }
+
+        flow[10 * w as usize + 10] = (0.0, 0.0);
+        let mut sites = SiteCollection::new(vec![
+            Site::new(Position::new(10.5, 10.5), Velocity::new(0.0, -1.0)),
+            Site::new(Position::new(50.5, 50.5), Velocity::new(0.0, -1.0)),
+        ], 0);
+        sites.step_with_image_flow(&flow, w, 1.0, 0.0, 0.05);
+        assert!((sites.sites[0].vel.angle() + std::f64::consts::FRAC_PI_2).abs() < 1e-12);
+        assert!(sites.sites[1].vel.angle().abs() < 1e-12);
+    }
+
+    /// White slows a site to 10% of the base speed, black leaves it at 100%, and
+    /// mid-gray between pixel centers interpolates
+    #[test]
+    fn test_step_with_speed_map() {
+        let map = image::GrayImage::from_fn(20, 10, |x, _| image::Luma([if x < 10 { 255 } else { 0 }]));
+        let mut sites = SiteCollection::new(vec![
+            Site::new(Position::new(40.0, 50.0), Velocity::new(0.0, 1.0)),
+            Site::new(Position::new(160.0, 50.0), Velocity::new(0.0, 1.0)),
+            // Between the last white and first black pixel centers (x = 95 and 105)
+            Site::new(Position::new(100.0, 20.0), Velocity::new(0.0, 1.0)),
+        ], 0);
+        let start = sites.positions();
+        sites.step_with_speed_map(&map, 30.0, 0.1, 200.0, 100.0, None, 0.0, 0.0, 0.0, EdgeBehavior::Reflect);
+        let moved: Vec<f64> = sites.positions().iter().zip(&start).map(|(a, b)| a.dist(b)).collect();
+        assert!((moved[0] - 0.3).abs() < 1e-9, "white moved {}", moved[0]);
+        assert!((moved[1] - 3.0).abs() < 1e-9, "black moved {}", moved[1]);
+        assert!((moved[2] - 3.0 * 0.55).abs() < 1e-9, "edge moved {}", moved[2]);
+    }
+
+    /// A split's speed boost shrinks the step; no site then moves more than a cell
+    #[test]
+    fn test_adaptive_step_dt() {
+        let (w, h) = (400.0, 400.0);
+        let mut sites = SiteCollection::random(100, w, h, 3);
+        let cell = (w * h / 100.0f64).sqrt();
+        let (speed, max_dt) = (100.0, 0.1);
+        assert_eq!(sites.adaptive_step_dt(speed, max_dt, w, h, None, 0.0, 3.0, 3.0), max_dt);
+
+        let (a, b) = sites.sites[0].split(None, &mut sites.rng);
+        sites.sites[0] = a;
+        sites.sites.push(b);
+        for _ in 0..20 {
+            let before = sites.positions();
+            let dt = sites.adaptive_step_dt(speed, max_dt, w, h, None, 0.0, 3.0, 3.0);
+            assert!(dt <= max_dt);
+            for (p, q) in before.iter().zip(sites.positions()) {
+                assert!(p.dist(&q) <= cell, "moved {} in one step", p.dist(&q));
+            }
+        }
+        sites.sites[0].speed_mult = 3.0;
+        assert!(sites.adaptive_dt(speed, max_dt, w, h) < max_dt);
+    }
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_handle_is_send_sync() {
+        assert_send_sync::<SiteCollectionHandle>();
+    }
+
+    /// Physics thread steps and grows the collection while a render thread
+    /// snapshots positions; every snapshot must be internally consistent.
+    #[test]
+    fn test_handle_physics_and_render_threads() {
+        let handle = SiteCollectionHandle::new(SiteCollection::random(16, 100.0, 100.0, 4));
+        let (steps, frames) = if cfg!(miri) { (5, 5) } else { (200, 100) };
+
+        let physics = {
+            let handle = handle.clone();
+            std::thread::spawn(move || {
+                for _ in 0..steps {
+                    handle.step(15.0, 1.0 / 60.0, 100.0, 100.0, None, 0.0, 3.0, 3.0);
+                    let mut sites = handle.lock();
+                    sites.fractional_sites += 0.5;
+                    sites.adjust_count(64, 1.0, 1.0 / 60.0, SplitStrategy::Max, AdjustParams::default());
+                }
+            })
+        };
+        let render = {
+            let handle = handle.clone();
+            std::thread::spawn(move || {
+                let mut last_count = 0;
+                for _ in 0..frames {
+                    let positions = handle.positions();
+                    assert!(positions.len() >= last_count);
+                    assert!(positions.iter().all(|p| (0.0..100.0).contains(&p.x) && (0.0..100.0).contains(&p.y)));
+                    last_count = positions.len();
+                }
+            })
+        };
+        physics.join().unwrap();
+        render.join().unwrap();
+        assert!(handle.site_count() > 16);
+    }
+}
+
+#[cfg(all(test, feature = "cpu"))]
+mod cpu_tests {
+    use super::*;
+    use crate::{ComputeBackend, CpuBackend, VoronoiResult};
+
+    /// Repeated bifurcation grows a few sites into cells near the target area
+    #[test]
+    fn test_bifurcation_converges_to_target_area() {
+        let (w, h) = (200u32, 200u32);
+        let image = image::RgbImage::from_pixel(w, h, image::Rgb([80, 120, 160]));
+        let mut sites = SiteCollection::random(8, w as f64, h as f64, 21);
+        let target_area = 1000;
+        let mut backend = CpuBackend::new();
+        let mut mean_areas = Vec::new();
+        for _ in 0..80 {
+            let result = backend.compute(&image, &sites.positions()).unwrap();
+            sites.bifurcation_step(&result.cell_centroids, &result.cell_areas, target_area, 0.2);
+            sites.step(20.0, 1.0 / 30.0, w as f64, h as f64, Some(&result.cell_centroids), 2.0, 3.0, 3.0);
+            mean_areas.push((w * h) as f64 / sites.len() as f64);
+        }
+        let last = *mean_areas.last().unwrap();
+        assert!((0.5..=1.0).contains(&(last / target_area as f64)), "mean area {}", last);
+        // Settled: splits over the last stretch barely shrink the mean
+        assert!(mean_areas[60] / last < 1.1);
+    }
+
+    /// Sites bunched in one corner spread out as small cells lose tournaments
+    /// to offspring of the big ones
+    #[test]
+    fn test_tournament_step_evens_areas() {
+        let (w, h) = (200u32, 200u32);
+        let image = image::RgbImage::from_pixel(w, h, image::Rgb([80, 120, 160]));
+        let mut sites = SiteCollection::random(40, 60.0, 60.0, 22);
+        let mut backend = CpuBackend::new();
+        let before = area_std(&backend.compute(&image, &sites.positions()).unwrap());
+        for _ in 0..100 {
+            let result = backend.compute(&image, &sites.positions()).unwrap();
+            sites.tournament_step(&result.cell_areas, &result.cell_centroids, 5, 0.1, 1.0);
+            assert_eq!(sites.len(), 40);
+            sites.step(20.0, 1.0 / 30.0, w as f64, h as f64, None, 0.0, 3.0, 3.0);
+        }
+        let after = area_std(&backend.compute(&image, &sites.positions()).unwrap());
+        assert!(after < 0.25 * before, "area std {} -> {}", before, after);
+
+        // Nothing happens at a zero rate
+        let positions = sites.positions();
+        let result = backend.compute(&image, &positions).unwrap();
+        assert_eq!(sites.tournament_step(&result.cell_areas, &result.cell_centroids, 5, 0.0, 1.0), 0);
+        assert_eq!(sites.positions(), positions);
+    }
+
+    /// A site at (0, 0) loses every pixel (including the (0.5, 0.5) tie) to an
+    /// earlier site at (1, 1)
+    #[test]
+    fn test_dead_sites() {
+        let image = image::RgbImage::new(100, 100);
+        let mut backend = CpuBackend::new();
+        let mut sites = SiteCollection::random_from_positions(vec![Position::new(1.0, 1.0), Position::new(0.0, 0.0)], 4);
+        let result = backend.compute(&image, &sites.positions()).unwrap();
+        assert_eq!(sites.mark_dead_sites(&result.cell_areas), vec![1]);
+        assert_eq!(sites.sites[1].speed_mult, -1.0);
+        assert_eq!(sites.sites[0].speed_mult, 1.0);
+
+        let mut revived = sites.clone();
+        revived.revive_dead_sites(&result.cell_areas, 100.0, 100.0);
+        assert_eq!(revived.sites[1].speed_mult, 1.0);
+        let areas = backend.compute(&image, &revived.positions()).unwrap().cell_areas;
+        assert!(areas.iter().all(|&a| a > 0), "{:?}", areas);
+
+        assert_eq!(sites.remove_dead_sites(), 1);
+        assert_eq!(sites.positions(), vec![Position::new(1.0, 1.0)]);
+
+        // adjust_count drops the dead site, then grows back to the target
+        let mut sites = SiteCollection::random_from_positions(
+            vec![Position::new(1.0, 1.0), Position::new(0.0, 0.0), Position::new(60.0, 60.0)], 4,
+        );
+        let result = backend.compute(&image, &sites.positions()).unwrap();
+        let (added, removed) = sites.adjust_count(3, 1e-9, 1.0, SplitStrategy::Centroid, AdjustParams {
+            cell_areas: Some(&result.cell_areas),
+            centroids: Some(&result.cell_centroids),
+            img_area: 1e4,
+            auto_remove_dead: true,
+            ..Default::default()
+        });
+        assert_eq!((added, removed), (vec![2], vec![1]));
+        assert!(!sites.positions().contains(&Position::new(0.0, 0.0)));
+    }
+
+    /// Small cells over pure white are pruned; equally small cells over a
+    /// checkerboard are kept
+    #[test]
+    fn test_merge_isolated_sites() {
+        let image = image::RgbImage::from_fn(200, 100, |x, y| {
+            let v = if x < 100 || (x + y) % 2 == 0 { 255 } else { 0 };
+            image::Rgb([v, v, v])
+        });
+        // The same tight 4x4 lattice (5px spacing) on each half, plus sparse corners
+        let mut positions: Vec<Position> = [40.0, 140.0].iter()
+            .flat_map(|&x0| (0..16).map(move |k| Position::new(x0 + (k % 4) as f64 * 5.0, 40.0 + (k / 4) as f64 * 5.0)))
+            .collect();
+        positions.extend([(5.0, 5.0), (95.0, 95.0), (105.0, 5.0), (195.0, 95.0)].map(|(x, y)| Position::new(x, y)));
+        let mut sites = SiteCollection::random_from_positions(positions.clone(), 0);
+        let result = CpuBackend::new().compute(&image, &positions).unwrap();
+        let variance = result.cell_color_variance(&image);
+        assert_eq!(variance[5], 0.0);
+        assert!(variance[16 + 5] > 10.0);
+
+        let removed = sites.merge_isolated_sites(&result.cell_areas, &variance, 50, 10.0);
+        assert!(removed > 0);
+        let kept = sites.positions();
+        // Interior lattice sites (area 25) go on the white half only
+        assert!(!kept.contains(&positions[5]));
+        assert!(positions[16..32].iter().all(|p| kept.contains(p)));
+        assert!(positions[32..].iter().all(|p| kept.contains(p)));
+        assert_eq!(kept.len(), positions.len() - removed);
+    }
+
+    /// Max/min cell area
+    fn area_ratio(result: &VoronoiResult) -> f64 {
+        let max = *result.cell_areas.iter().max().unwrap() as f64;
+        let min = *result.cell_areas.iter().min().unwrap() as f64;
+        max / min.max(1.0)
+    }
+
+    #[test]
+    fn test_apply_lloyd_evens_cell_areas() {
+        let image = image::RgbImage::from_pixel(120, 90, image::Rgb([50, 50, 50]));
+        let mut backend = CpuBackend::new();
+        // Clustered in one corner, as after a shrink phase
+        let mut sites = SiteCollection::random(30, 40.0, 30.0, 3);
+        let before = backend.compute(&image, &sites.positions()).unwrap();
+
+        let start = sites.positions();
+        let mut half = sites.clone();
+        half.apply_lloyd(&before.cell_centroids, 0.5);
+        sites.apply_lloyd(&before.cell_centroids, 1.0);
+        for ((p, c), h) in start.iter().zip(&before.cell_centroids).zip(half.positions()) {
+            assert!((h.x - (p.x + c.x) / 2.0).abs() < 1e-9 && (h.y - (p.y + c.y) / 2.0).abs() < 1e-9);
+        }
+        assert_eq!(sites.positions(), before.cell_centroids);
+
+        let after = backend.compute(&image, &sites.positions()).unwrap();
+        let after_half = backend.compute(&image, &half.positions()).unwrap();
+        assert!(area_ratio(&after) < area_ratio(&before));
+        assert!(area_ratio(&after_half) < area_ratio(&before));
+    }
+
+    fn area_std(result: &VoronoiResult) -> f64 {
+        let n = result.cell_areas.len() as f64;
+        let mean = result.cell_areas.iter().map(|&a| a as f64).sum::<f64>() / n;
+        (result.cell_areas.iter().map(|&a| (a as f64 - mean).powi(2)).sum::<f64>() / n).sqrt()
+    }
+
+    /// Density equalization evens out cell areas about as well as Lloyd's
+    /// relaxation. Pixel discretization makes single steps noisy, so the decrease
+    /// is checked every 10 steps.
+    #[test]
+    fn test_density_equalization_reduces_area_variance() {
+        let (width, height) = (200, 150);
+        let image = image::RgbImage::from_pixel(width, height, image::Rgb([50, 50, 50]));
+        let mut backend = CpuBackend::new();
+        let start = SiteCollection::random(100, width as f64, height as f64, 3);
+
+        let mut sites = start.clone();
+        let mut stds = Vec::new();
+        for step in 0..=50 {
+            let result = backend.compute(&image, &sites.positions()).unwrap();
+            if step % 10 == 0 {
+                stds.push(area_std(&result));
+            }
+            sites.density_equalization_step(&result.cell_areas, width as f64, height as f64, 1.0);
+        }
+        assert!(stds.windows(2).all(|w| w[1] < w[0]), "area std by 10 steps: {:?}", stds);
+
+        let mut lloyd = start;
+        for _ in 0..100 {
+            let result = backend.compute(&image, &lloyd.positions()).unwrap();
+            lloyd.apply_lloyd(&result.cell_centroids, 1.0);
+        }
+        let lloyd_std = area_std(&backend.compute(&image, &lloyd.positions()).unwrap());
+        let final_std = *stds.last().unwrap();
+        assert!(final_std <= 1.1 * lloyd_std, "area std: equalized {:.1}, Lloyd {:.1}", final_std, lloyd_std);
+    }
+
+    /// Growing from a clustered start, adaptive growth evens out cell areas sooner
+    #[test]
+    fn test_adaptive_growth_reduces_area_ratio_faster() {
+        let image = image::RgbImage::from_pixel(160, 120, image::Rgb([50, 50, 50]));
+        let mut backend = CpuBackend::new();
+        let start = SiteCollection::random(8, 40.0, 30.0, 5);
+
+        let mut ratio_after = |disparity_factor: f64| {
+            let mut sites = start.clone();
+            for _ in 0..20 {
+                let result = backend.compute(&image, &sites.positions()).unwrap();
+                sites.adjust_count_adaptive(200, 4.0, disparity_factor, 0.1, SplitStrategy::Centroid, AdjustParams {
+                    cell_areas: Some(&result.cell_areas),
+                    centroids: Some(&result.cell_centroids),
+                    farthest_point: Some(result.farthest_point),
+                    img_area: (160 * 120) as f64,
+                    ..Default::default()
+                });
+            }
+            area_ratio(&backend.compute(&image, &sites.positions()).unwrap())
+        };
+
+        let fixed = ratio_after(0.0);
+        let adaptive = ratio_after(2.0);
+        assert!(adaptive < fixed, "area ratio: adaptive {:.1}, fixed {:.1}", adaptive, fixed);
+    }
+
+    /// Two sites spawned by KMeansStep in one frame land farther apart than two from Max
+    #[test]
+    fn test_kmeans_step_spreads_new_sites() {
+        let (w, h) = (160u32, 160u32);
+        let image = image::RgbImage::from_pixel(w, h, image::Rgb([128, 128, 128]));
+        let grid: Vec<Position> = (0..16)
+            .map(|i| Position::new(20.0 + 40.0 * (i % 4) as f64, 20.0 + 40.0 * (i / 4) as f64))
+            .collect();
+        let result = CpuBackend::new().compute(&image, &grid).unwrap();
+
+        let mean_gap = |strategy: SplitStrategy| {
+            let total: f64 = (0..10)
+                .map(|seed| {
+                    let mut sites = SiteCollection::random_from_positions(grid.clone(), seed);
+                    // Force exactly two spawns
+                    sites.fractional_sites = 2.0;
+                    sites.adjust_count(18, 1e9, 0.0, strategy, AdjustParams {
+                        cell_areas: Some(&result.cell_areas),
+                        centroids: Some(&result.cell_centroids),
+                        farthest_point: Some(result.farthest_point),
+                        img_area: (w * h) as f64,
+                        ..Default::default()
+                    });
+                    let new: Vec<Position> = sites.positions()[16..].to_vec();
+                    assert_eq!(new.len(), 2);
+                    new[0].dist(&new[1])
+                })
+                .sum();
+            total / 10.0
+        };
+
+        let kmeans = mean_gap(SplitStrategy::KMeansStep);
+        let max = mean_gap(SplitStrategy::Max);
+        assert!(kmeans > max, "kmeans gap {:.1} not above max gap {:.1}", kmeans, max);
+    }
+
+    /// A BisectLargest split yields children with more balanced areas than Max
+    #[test]
+    fn test_bisect_largest_balances_children() {
+        let (w, h) = (120u32, 80u32);
+        let image = image::RgbImage::from_pixel(w, h, image::Rgb([128, 128, 128]));
+        let start = vec![
+            Site::new(Position::new(20.0, 40.0), Velocity::from_angle(0.0)),
+            Site::new(Position::new(100.0, 40.0), Velocity::from_angle(0.0)),
+        ];
+
+        let child_ratio = |strategy: SplitStrategy| {
+            let mut backend = CpuBackend::new();
+            let mut sites = SiteCollection::new(start.clone(), 7);
+            let positions = sites.positions();
+            let result = backend.compute(&image, &positions).unwrap();
+            let bisections = result.bisect_centroids(&positions);
+            // Force exactly one split
+            sites.fractional_sites = 1.0;
+            let (added, _) = sites.adjust_count(3, 1e9, 0.0, strategy, AdjustParams {
+                cell_areas: Some(&result.cell_areas),
+                centroids: Some(&result.cell_centroids),
+                farthest_point: Some(result.farthest_point),
+                img_area: (w * h) as f64,
+                bisections: Some(&bisections),
+                ..Default::default()
+            });
+            assert_eq!(added.len(), 1);
+
+            let after = backend.compute(&image, &sites.positions()).unwrap();
+            let parent = (0..2).find(|&i| sites.sites[i].speed_mult > 1.0).unwrap();
+            let (a, b) = (after.cell_areas[parent] as f64, after.cell_areas[added[0]] as f64);
+            a.max(b) / a.min(b).max(1.0)
+        };
+
+        let bisect = child_ratio(SplitStrategy::BisectLargest);
+        let max = child_ratio(SplitStrategy::Max);
+        assert!(
+            (bisect - 1.0).abs() < (max - 1.0).abs(),
+            "bisect ratio {:.2} not closer to 1 than max ratio {:.2}", bisect, max,
+        );
+    }
+}
@@ -0,0 +1,99 @@
+//! Stippled rendering: cells drawn as scattered dots of their mean color.
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use crate::VoronoiResult;
+
+/// Pixels of cell area per dot
+const AREA_PER_DOT: u32 = 100;
+/// Dot radius in pixels
+const DOT_RADIUS: i32 = 1;
+/// Random samples tried per requested dot before giving up on a cell
+const ATTEMPTS_PER_DOT: usize = 20;
+
+impl VoronoiResult {
+    /// Dot centers `(cell, x, y)`: `min(area / 100, dot_count_per_cell)` per cell,
+    /// sampled uniformly in the cell's bounding box and rejected outside the cell
+    fn stipple_dots(&self, dot_count_per_cell: usize, seed: u64) -> Vec<(usize, u32, u32)> {
+        let n = self.cell_areas.len();
+        let mut bounds = vec![(u32::MAX, u32::MAX, 0u32, 0u32); n];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let c = self.cell_of[(y * self.width + x) as usize];
+                if c < 0 || c as usize >= n { continue; }
+                let b = &mut bounds[c as usize];
+                *b = (b.0.min(x), b.1.min(y), b.2.max(x), b.3.max(y));
+            }
+        }
+
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut dots = Vec::new();
+        for (i, &(x0, y0, x1, y1)) in bounds.iter().enumerate() {
+            let count = ((self.cell_areas[i] / AREA_PER_DOT) as usize).min(dot_count_per_cell);
+            let mut placed = 0;
+            for _ in 0..count * ATTEMPTS_PER_DOT {
+                if placed == count { break; }
+                let (x, y) = (rng.gen_range(x0..=x1), rng.gen_range(y0..=y1));
+                if self.cell_of[(y * self.width + x) as usize] == i as i32 {
+                    dots.push((i, x, y));
+                    placed += 1;
+                }
+            }
+        }
+        dots
+    }
+
+    /// Render cells as dots of their mean color on white, with more dots in larger
+    /// cells (halftone-like): `min(cell_area / 100, dot_count_per_cell)` dots per
+    /// cell, uniformly placed within it. Dots are clipped to their own cell.
+    pub fn render_voronoi_stipple_textured(&self, dot_count_per_cell: usize, seed: u64) -> image::RgbImage {
+        let mut img = image::RgbImage::from_pixel(self.width, self.height, image::Rgb([255, 255, 255]));
+        let (w, h) = (self.width as i32, self.height as i32);
+        for (cell, cx, cy) in self.stipple_dots(dot_count_per_cell, seed) {
+            let color = image::Rgb(self.cell_colors[cell]);
+            for dy in -DOT_RADIUS..=DOT_RADIUS {
+                for dx in -DOT_RADIUS..=DOT_RADIUS {
+                    let (x, y) = (cx as i32 + dx, cy as i32 + dy);
+                    if dx * dx + dy * dy > DOT_RADIUS * DOT_RADIUS || x < 0 || y < 0 || x >= w || y >= h {
+                        continue;
+                    }
+                    if self.cell_of[(y * w + x) as usize] == cell as i32 {
+                        img.put_pixel(x as u32, y as u32, color);
+                    }
+                }
+            }
+        }
+        img
+    }
+}
+
+#[cfg(all(test, feature = "cpu"))]
+mod tests {
+    use crate::{ComputeBackend, CpuBackend, Position};
+
+    #[test]
+    fn test_stipple_dots_stay_in_cell() {
+        let image = image::RgbImage::from_fn(120, 80, |x, y| image::Rgb([(x * 2) as u8, (y * 3) as u8, 60]));
+        // Left cell is three times the area of the right one
+        let sites = [Position::new(70.0, 40.0), Position::new(110.0, 40.0)];
+        let result = CpuBackend::new().compute(&image, &sites).unwrap();
+        assert!(result.cell_areas[0] > 2 * result.cell_areas[1]);
+
+        let dots = result.stipple_dots(1000, 3);
+        for &(cell, x, y) in &dots {
+            assert_eq!(result.cell_of[(y * 120 + x) as usize], cell as i32);
+        }
+        let count = |c: usize| dots.iter().filter(|d| d.0 == c).count();
+        assert_eq!(count(0), (result.cell_areas[0] / 100) as usize);
+        assert!(count(0) > count(1));
+        assert_eq!(result.stipple_dots(5, 3).len(), 10);
+
+        let img = result.render_voronoi_stipple_textured(1000, 3);
+        for (x, y, px) in img.enumerate_pixels() {
+            if px.0 != [255, 255, 255] {
+                let cell = result.cell_of[(y * 120 + x) as usize] as usize;
+                assert_eq!(px.0, result.cell_colors[cell]);
+            }
+        }
+    }
+}
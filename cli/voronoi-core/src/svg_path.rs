@@ -0,0 +1,177 @@
+//! Site initialization along an SVG path (`svg-init` feature).
+
+use rand::Rng;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use svgtypes::{SimplePathSegment, SimplifyingPathParser};
+use crate::{Position, Site, SiteCollection, Velocity, VoronoiError};
+
+/// Maximum angle (radians) by which initial velocities deviate from the path tangent
+const TANGENT_JITTER: f64 = 0.15;
+
+/// Flatten path data into straight segments. Curves are subdivided into pieces of
+/// roughly 1px (by control polygon length), which keeps flattening error far below
+/// a pixel. Zero-length segments are dropped.
+fn flatten(path_d: &str) -> crate::Result<Vec<(Position, Position)>> {
+    let mut segments = Vec::new();
+    let mut start = Position::new(0.0, 0.0);
+    let mut at = start;
+    let push = |segments: &mut Vec<(Position, Position)>, a: Position, b: Position| {
+        if a.dist(&b) > 0.0 {
+            segments.push((a, b));
+        }
+    };
+
+    for segment in SimplifyingPathParser::from(path_d) {
+        let segment = segment.map_err(|e| VoronoiError::SvgPath(e.to_string()))?;
+        match segment {
+            SimplePathSegment::MoveTo { x, y } => {
+                at = Position::new(x, y);
+                start = at;
+            }
+            SimplePathSegment::LineTo { x, y } => {
+                let to = Position::new(x, y);
+                push(&mut segments, at, to);
+                at = to;
+            }
+            SimplePathSegment::ClosePath => {
+                push(&mut segments, at, start);
+                at = start;
+            }
+            SimplePathSegment::Quadratic { x1, y1, x, y } => {
+                let (c, to) = (Position::new(x1, y1), Position::new(x, y));
+                let steps = (at.dist(&c) + c.dist(&to)).ceil().clamp(8.0, 4096.0) as usize;
+                let mut prev = at;
+                for i in 1..=steps {
+                    let t = i as f64 / steps as f64;
+                    let u = 1.0 - t;
+                    let p = Position::new(
+                        u * u * at.x + 2.0 * u * t * c.x + t * t * to.x,
+                        u * u * at.y + 2.0 * u * t * c.y + t * t * to.y,
+                    );
+                    push(&mut segments, prev, p);
+                    prev = p;
+                }
+                at = to;
+            }
+            SimplePathSegment::CurveTo { x1, y1, x2, y2, x, y } => {
+                let (c1, c2, to) = (Position::new(x1, y1), Position::new(x2, y2), Position::new(x, y));
+                let steps = (at.dist(&c1) + c1.dist(&c2) + c2.dist(&to)).ceil().clamp(8.0, 4096.0) as usize;
+                let mut prev = at;
+                for i in 1..=steps {
+                    let t = i as f64 / steps as f64;
+                    let u = 1.0 - t;
+                    let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+                    let p = Position::new(
+                        a * at.x + b * c1.x + c * c2.x + d * to.x,
+                        a * at.y + b * c1.y + c * c2.y + d * to.y,
+                    );
+                    push(&mut segments, prev, p);
+                    prev = p;
+                }
+                at = to;
+            }
+        }
+    }
+    Ok(segments)
+}
+
+impl SiteCollection {
+    /// Place `count` sites at equal arc-length spacing along SVG path data
+    /// (`M`, `L`, `H`, `V`, `C`, `S`, `Q`, `T`, `A`, `Z`; absolute or relative).
+    ///
+    /// Site `i` sits at arc length `(i + 0.5) / count` of the total, so spacing is
+    /// uniform on closed and open paths alike; jumps between subpaths don't count.
+    /// Velocities follow the path direction, rotated by up to ±0.15 rad at random.
+    /// Errors if the path doesn't parse or has zero length.
+    pub fn from_svg_path(path_d: &str, count: usize, seed: u64) -> crate::Result<Self> {
+        let segments = flatten(path_d)?;
+        let total: f64 = segments.iter().map(|(a, b)| a.dist(b)).sum();
+        if total <= 0.0 || !total.is_finite() {
+            return Err(VoronoiError::SvgPath(format!("path has no length: {:?}", path_d)));
+        }
+
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut sites = Vec::with_capacity(count);
+        let mut seg_iter = segments.iter();
+        let mut current = seg_iter.next();
+        // Arc length at the start of `current`
+        let mut seg_start = 0.0;
+        for i in 0..count {
+            let target = (i as f64 + 0.5) / count as f64 * total;
+            while let Some((a, b)) = current {
+                let len = a.dist(b);
+                if target <= seg_start + len || seg_iter.len() == 0 {
+                    let t = ((target - seg_start) / len).clamp(0.0, 1.0);
+                    let pos = Position::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t);
+                    let angle = (b.y - a.y).atan2(b.x - a.x)
+                        + rng.gen_range(-TANGENT_JITTER..=TANGENT_JITTER);
+                    sites.push(Site::new(pos, Velocity::new(angle.cos(), angle.sin())));
+                    break;
+                }
+                seg_start += len;
+                current = seg_iter.next();
+            }
+        }
+        Ok(Self::with_rng(sites, rng))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimum distance from `p` to a densely sampled parametric curve
+    fn dist_to_curve(p: &Position, curve: impl Fn(f64) -> Position) -> f64 {
+        (0..=10_000)
+            .map(|i| p.dist(&curve(i as f64 / 10_000.0)))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    #[test]
+    fn test_svg_path_sites_on_curve() {
+        let sites = SiteCollection::from_svg_path("M 10 10 L 90 90 Q 50 0 90 10", 50, 1).unwrap();
+        assert_eq!(sites.len(), 50);
+
+        let line = |t: f64| Position::new(10.0 + 80.0 * t, 10.0 + 80.0 * t);
+        let quad = |t: f64| {
+            let u = 1.0 - t;
+            Position::new(
+                u * u * 90.0 + 2.0 * u * t * 50.0 + t * t * 90.0,
+                u * u * 90.0 + 2.0 * u * t * 0.0 + t * t * 10.0,
+            )
+        };
+        for site in &sites.sites {
+            let d = dist_to_curve(&site.pos, line).min(dist_to_curve(&site.pos, quad));
+            assert!(d <= 1.0, "site {:?} is {} px from the path", site.pos, d);
+        }
+
+        // Sites on the diagonal are evenly spaced and head roughly along it
+        let on_line: Vec<&Site> = sites.sites.iter()
+            .filter(|s| (s.pos.x - s.pos.y).abs() < 1e-9 && s.pos.x < 90.0)
+            .collect();
+        assert!(on_line.len() > 10);
+        let spacing = on_line[0].pos.dist(&on_line[1].pos);
+        for pair in on_line.windows(2) {
+            assert!((pair[0].pos.dist(&pair[1].pos) - spacing).abs() < 1e-9);
+        }
+        for s in &on_line {
+            let along = (s.vel.x + s.vel.y) / 2f64.sqrt();
+            assert!(along >= TANGENT_JITTER.cos() - 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_svg_path_arcs_and_errors() {
+        // Circle of radius 30 around (50, 50), as two relative arcs
+        let sites = SiteCollection::from_svg_path("M 50 20 a 30 30 0 1 1 0 60 a 30 30 0 1 1 0 -60 Z", 24, 2).unwrap();
+        assert_eq!(sites.len(), 24);
+        for site in &sites.sites {
+            let r = site.pos.dist(&Position::new(50.0, 50.0));
+            assert!((r - 30.0).abs() <= 1.0, "site {:?} at radius {}", site.pos, r);
+        }
+
+        assert!(SiteCollection::from_svg_path("M 10 10 X 5", 5, 0).is_err());
+        assert!(SiteCollection::from_svg_path("M 10 10", 5, 0).is_err());
+    }
+}
@@ -1,6 +1,7 @@
 //! Voronoi computation traits and result types.
 
-use crate::{Position, Rgb, Result};
+use crate::{HeatmapColormap, Position, Rgb, Result};
+use crate::colormap::{hsl_to_rgb, index_palette, rgb_to_hsl};
 
 /// Result of Voronoi computation
 #[derive(Debug)]
@@ -15,11 +16,93 @@ pub struct VoronoiResult {
     pub cell_centroids: Vec<Position>,
     /// Point on the canvas furthest from any site
     pub farthest_point: Position,
+    /// Distance from each pixel center to its nearest site (row-major), if the
+    /// backend was asked to record it (see `CpuBackend::with_distances`).
+    /// `width * height * 4` bytes when present.
+    pub pixel_distances: Option<Vec<f32>>,
+    /// Second-nearest site of each pixel (row-major; -1 with a single site), if
+    /// the backend was asked to record it (see `CpuBackend::with_second_nearest`)
+    pub second_cell_of: Option<Vec<i32>>,
+    /// Distance from each pixel center to its second-nearest site (infinite with
+    /// a single site); recorded along with `second_cell_of`
+    pub second_distances: Option<Vec<f32>>,
     /// Image dimensions
     pub width: u32,
     pub height: u32,
 }
 
+/// Weight of the nearest site's color in `render_soft_boundary`, for a pixel at
+/// distances `d1` ≤ `d2` from its nearest and second-nearest sites: exactly 0.5
+/// when `d1 == d2`, 1 without a second site
+pub(crate) fn soft_boundary_weight(d1: f64, d2: f64, softness: f64) -> f64 {
+    if !d2.is_finite() {
+        return 1.0;
+    }
+    let total = d1 + d2;
+    if total <= 0.0 {
+        return 0.5;
+    }
+    let near = (d2 / total).powf(softness);
+    let far = (d1 / total).powf(softness);
+    near / (near + far)
+}
+
+/// Most common cell index in `block` (sorting it), lowest on ties; -1 (masked)
+/// counts like any other index. -1 for an empty block.
+pub(crate) fn majority_cell(block: &mut [i32]) -> i32 {
+    block.sort_unstable();
+    let mut majority = (-1, 0);
+    for run in block.chunk_by(|a, b| a == b) {
+        if run.len() > majority.1 {
+            majority = (run[0], run.len());
+        }
+    }
+    majority.0
+}
+
+/// Scanline-fill a polygon (pixel coordinates, implicitly closed) with the even-odd
+/// rule, calling `fill(y, start, end)` for each run of pixels whose centers fall
+/// inside (columns `start..end`, clipped to `width`). Fewer than 3 vertices fill nothing.
+pub(crate) fn scanline_fill(
+    polygon: &[(f64, f64)],
+    width: usize,
+    height: usize,
+    mut fill: impl FnMut(usize, usize, usize),
+) {
+    if polygon.len() < 3 {
+        return;
+    }
+    // Only rows whose centers can fall inside the polygon's vertical extent
+    let (min_y, max_y) = polygon.iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &(_, y)| (lo.min(y), hi.max(y)));
+    let first_row = (min_y - 0.5).ceil().clamp(0.0, height as f64) as usize;
+    let last_row = (max_y - 0.5).ceil().clamp(0.0, height as f64) as usize;
+
+    let mut crossings: Vec<f64> = Vec::new();
+    for y in first_row..last_row {
+        let sy = y as f64 + 0.5;
+        crossings.clear();
+        for i in 0..polygon.len() {
+            let (x0, y0) = polygon[i];
+            let (x1, y1) = polygon[(i + 1) % polygon.len()];
+            // Half-open on y so shared vertices are counted once
+            if (y0 <= sy) != (y1 <= sy) {
+                crossings.push(x0 + (sy - y0) / (y1 - y0) * (x1 - x0));
+            }
+        }
+        crossings.sort_by(f64::total_cmp);
+
+        for span in crossings.chunks_exact(2) {
+            // Pixels with centers in [span[0], span[1])
+            let start = (span[0] - 0.5).ceil().clamp(0.0, width as f64) as usize;
+            let end = (span[1] - 0.5).ceil().clamp(0.0, width as f64) as usize;
+            if start < end {
+                fill(y, start, end);
+            }
+        }
+    }
+}
+
 impl VoronoiResult {
     /// Render the Voronoi diagram to an RGB image buffer
     pub fn render(&self) -> Vec<u8> {
@@ -38,12 +121,517 @@ impl VoronoiResult {
         pixels
     }
 
+    /// Like `render`, but writing into a caller-owned buffer (e.g. one kept across
+    /// frames) instead of allocating one. `canvas` holds rows of `canvas_width`
+    /// pixels of `stride` bytes each (3 for RGB, 4 for RGBA); this diagram's pixel
+    /// (x, y) goes to canvas pixel (x + x_offset, y + y_offset), and parts falling
+    /// outside the canvas are skipped. Only the first 3 bytes of each pixel are
+    /// written (masked pixels black), so an RGBA canvas keeps its alpha. Strides
+    /// below 3 write nothing.
+    pub fn render_into_shared(&self, canvas: &mut [u8], stride: u32, x_offset: u32, y_offset: u32, canvas_width: u32) {
+        let stride = stride as usize;
+        if stride < 3 {
+            return;
+        }
+        let row_bytes = canvas_width as usize * stride;
+        let cols = (self.width.min(canvas_width.saturating_sub(x_offset))) as usize;
+        for y in 0..self.height as usize {
+            let start = (y + y_offset as usize) * row_bytes + x_offset as usize * stride;
+            let Some(row) = canvas.get_mut(start..(start + cols * stride).min(canvas.len())) else {
+                break;
+            };
+            let cells = &self.cell_of[y * self.width as usize..][..cols];
+            for (px, &cell) in row.chunks_mut(stride).zip(cells) {
+                if px.len() < 3 {
+                    break;
+                }
+                let color = usize::try_from(cell).ok()
+                    .and_then(|c| self.cell_colors.get(c))
+                    .unwrap_or(&[0, 0, 0]);
+                px[..3].copy_from_slice(color);
+            }
+        }
+    }
+
+    /// Cell index at pixel (x, y), or None if out of bounds
+    pub fn cell_at(&self, x: u32, y: u32) -> Option<usize> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let cell = self.cell_of[(y * self.width + x) as usize];
+        (cell >= 0).then_some(cell as usize)
+    }
+
+    /// Cell index of the pixel containing (px, py), clamped to the image bounds
+    pub fn nearest_site_to(&self, px: f64, py: f64) -> usize {
+        let x = (px.floor().max(0.0) as u32).min(self.width.saturating_sub(1));
+        let y = (py.floor().max(0.0) as u32).min(self.height.saturating_sub(1));
+        self.cell_at(x, y).unwrap_or(0)
+    }
+
+    /// Split each cell by the line through its centroid, perpendicular to the
+    /// site-to-centroid axis, and return `[near, far]` half-centroids (near = the
+    /// half containing the site). When site and centroid coincide, the axis falls
+    /// back to the longer side of the cell's bounding box. Empty halves fall back
+    /// to the cell centroid.
+    pub fn bisect_centroids(&self, sites: &[Position]) -> Vec<[Position; 2]> {
+        let n = self.cell_centroids.len();
+        let w = self.width as usize;
+
+        // Bounding boxes (min_x, min_y, max_x, max_y) for the degenerate-axis fallback
+        let mut bboxes = vec![(u32::MAX, u32::MAX, 0u32, 0u32); n];
+        for (i, &cell) in self.cell_of.iter().enumerate() {
+            if cell < 0 || cell as usize >= n { continue; }
+            let (x, y) = ((i % w) as u32, (i / w) as u32);
+            let b = &mut bboxes[cell as usize];
+            *b = (b.0.min(x), b.1.min(y), b.2.max(x), b.3.max(y));
+        }
+
+        let axes: Vec<(f64, f64)> = (0..n)
+            .map(|c| {
+                let centroid = self.cell_centroids[c];
+                let (dx, dy) = match sites.get(c) {
+                    Some(site) => (centroid.x - site.x, centroid.y - site.y),
+                    None => (0.0, 0.0),
+                };
+                let len = (dx * dx + dy * dy).sqrt();
+                if len > 1e-6 {
+                    (dx / len, dy / len)
+                } else {
+                    let (x0, y0, x1, y1) = bboxes[c];
+                    if x1.saturating_sub(x0) >= y1.saturating_sub(y0) { (1.0, 0.0) } else { (0.0, 1.0) }
+                }
+            })
+            .collect();
+
+        // Per cell, per side: (x_sum, y_sum, count)
+        let mut sums = vec![[(0.0f64, 0.0f64, 0u32); 2]; n];
+        for (i, &cell) in self.cell_of.iter().enumerate() {
+            if cell < 0 || cell as usize >= n { continue; }
+            let c = cell as usize;
+            let px = (i % w) as f64 + 0.5;
+            let py = (i / w) as f64 + 0.5;
+            let centroid = self.cell_centroids[c];
+            let (ax, ay) = axes[c];
+            let side = if (px - centroid.x) * ax + (py - centroid.y) * ay < 0.0 { 0 } else { 1 };
+            let acc = &mut sums[c][side];
+            acc.0 += px;
+            acc.1 += py;
+            acc.2 += 1;
+        }
+
+        sums.iter().enumerate()
+            .map(|(c, halves)| {
+                halves.map(|(sx, sy, count)| {
+                    if count > 0 {
+                        Position::new(sx / count as f64, sy / count as f64)
+                    } else {
+                        self.cell_centroids[c]
+                    }
+                })
+            })
+            .collect()
+    }
+
     /// Render to an image::RgbImage
     pub fn to_image(&self) -> image::RgbImage {
         let pixels = self.render();
         image::RgbImage::from_raw(self.width, self.height, pixels)
             .expect("Buffer size mismatch")
     }
+
+    /// Render at `scale`× resolution by assigning each sub-pixel to its nearest site.
+    ///
+    /// `sites` must be the positions this result was computed from. Cell colors are
+    /// reused as-is (no re-averaging), so edges come out sharp without recomputing.
+    /// `scale = 1` reproduces `to_image()` exactly.
+    #[cfg(feature = "cpu")]
+    pub fn supersample(&self, sites: &[Position], scale: u32) -> image::RgbImage {
+        use crate::CpuBackend;
+
+        let scale = scale.max(1);
+        let (out_w, out_h) = (self.width * scale, self.height * scale);
+        let (grid, grid_cols, grid_rows, gcell_w, gcell_h) =
+            CpuBackend::build_grid(sites, self.width, self.height);
+        let inv = 1.0 / scale as f32;
+
+        let render_row = |y: usize, row: &mut [u8]| {
+            let py = (y as f32 + 0.5) * inv;
+            for x in 0..out_w as usize {
+                let px = (x as f32 + 0.5) * inv;
+                let (nearest, _) = CpuBackend::nearest_site(
+                    px, py, &grid, grid_cols, grid_rows, gcell_w, gcell_h, sites,
+                );
+                if let Some(color) = self.cell_colors.get(nearest as usize) {
+                    row[x * 3..x * 3 + 3].copy_from_slice(color);
+                }
+            }
+        };
+
+        let mut pixels = vec![0u8; (out_w * out_h * 3) as usize];
+        if !sites.is_empty() {
+            let row_len = out_w as usize * 3;
+            #[cfg(feature = "parallel")]
+            {
+                use rayon::prelude::*;
+                pixels.par_chunks_mut(row_len).enumerate().for_each(|(y, row)| render_row(y, row));
+            }
+            #[cfg(not(feature = "parallel"))]
+            pixels.chunks_mut(row_len).enumerate().for_each(|(y, row)| render_row(y, row));
+        }
+
+        image::RgbImage::from_raw(out_w, out_h, pixels)
+            .expect("Buffer size mismatch")
+    }
+
+    /// Render with motion blur: the diagram at `blur_samples` evenly spaced times
+    /// over the interval from `prev` (sites at `prev_sites`) to this frame (sites at
+    /// `curr_sites`), averaged per pixel.
+    ///
+    /// Sample `k` of `n` is at time `(k + 1) / n`, with each site linearly
+    /// interpolated between its previous and current position; sites are matched by
+    /// index, and those with no previous position stay put. A sample pixel takes its
+    /// nearest site's cell color from whichever result is closer in time (`prev`
+    /// for `t < 0.5`). No backend calls; `blur_samples <= 1` reproduces `to_image()`
+    /// when `curr_sites` are the positions this result was computed from.
+    #[cfg(feature = "cpu")]
+    pub fn render_motion_blur(
+        &self,
+        prev: &VoronoiResult,
+        blur_samples: usize,
+        prev_sites: &[Position],
+        curr_sites: &[Position],
+    ) -> image::RgbImage {
+        use crate::CpuBackend;
+
+        let samples = blur_samples.max(1);
+        let (w, h) = (self.width as usize, self.height as usize);
+        let mut sums = vec![0u32; w * h * 3];
+        if !curr_sites.is_empty() {
+            for k in 0..samples {
+                let t = (k + 1) as f64 / samples as f64;
+                let sites: Vec<Position> = curr_sites.iter().enumerate()
+                    .map(|(i, c)| match prev_sites.get(i) {
+                        Some(p) => Position::new(p.x + (c.x - p.x) * t, p.y + (c.y - p.y) * t),
+                        None => *c,
+                    })
+                    .collect();
+                let colors = if t < 0.5 { &prev.cell_colors } else { &self.cell_colors };
+                let (grid, grid_cols, grid_rows, gcell_w, gcell_h) =
+                    CpuBackend::build_grid(&sites, self.width, self.height);
+
+                let add_row = |y: usize, row: &mut [u32]| {
+                    let py = y as f32 + 0.5;
+                    for x in 0..w {
+                        let px = x as f32 + 0.5;
+                        let (nearest, _) = CpuBackend::nearest_site(
+                            px, py, &grid, grid_cols, grid_rows, gcell_w, gcell_h, &sites,
+                        );
+                        // Sites past the end of `prev` fall back to this frame's colors
+                        let color = colors.get(nearest as usize)
+                            .or_else(|| self.cell_colors.get(nearest as usize));
+                        if let Some(color) = color {
+                            for (sum, &c) in row[x * 3..x * 3 + 3].iter_mut().zip(color) {
+                                *sum += c as u32;
+                            }
+                        }
+                    }
+                };
+                #[cfg(feature = "parallel")]
+                {
+                    use rayon::prelude::*;
+                    sums.par_chunks_mut(w * 3).enumerate().for_each(|(y, row)| add_row(y, row));
+                }
+                #[cfg(not(feature = "parallel"))]
+                sums.chunks_mut(w * 3).enumerate().for_each(|(y, row)| add_row(y, row));
+            }
+        }
+
+        let n = samples as u32;
+        let pixels: Vec<u8> = sums.iter().map(|&s| ((s + n / 2) / n) as u8).collect();
+        image::RgbImage::from_raw(self.width, self.height, pixels)
+            .expect("Buffer size mismatch")
+    }
+
+    /// Rasterize a polygon (pixel coordinates) into a `width * height` row-major mask,
+    /// true for pixels whose centers fall inside. Uses a scanline fill with the
+    /// even-odd rule; the polygon is implicitly closed.
+    pub fn rasterize_polygon_mask(&self, polygon: &[(f64, f64)]) -> Vec<bool> {
+        let (w, h) = (self.width as usize, self.height as usize);
+        let mut mask = vec![false; w * h];
+        scanline_fill(polygon, w, h, |y, start, end| mask[y * w + start..y * w + end].fill(true));
+        mask
+    }
+
+    /// Render like `to_image`, painting pixels where `mask` is false with `background`
+    pub fn render_masked(&self, mask: &[bool], background: Rgb) -> image::RgbImage {
+        let mut pixels = self.render();
+        for (i, &inside) in mask.iter().enumerate().take(self.cell_of.len()) {
+            if !inside {
+                pixels[i * 3..i * 3 + 3].copy_from_slice(&background);
+            }
+        }
+        image::RgbImage::from_raw(self.width, self.height, pixels)
+            .expect("Buffer size mismatch")
+    }
+
+    /// Render the diagram as if lying on a plane tilted away from the viewer.
+    ///
+    /// `horizon_y` and `vanishing_x` are fractions of the image height and width.
+    /// Each output row is scaled horizontally about `vanishing_x` by a factor that
+    /// varies linearly from `scale_bottom` at the bottom edge to `scale_top` at
+    /// `horizon_y` (extrapolated beyond it), and rows are foreshortened by the same
+    /// factor. Pixels are backward-mapped and sampled nearest-neighbor from
+    /// `cell_of`; those mapping outside the image, or past where the scale reaches
+    /// zero, are black. Equal scales of 1 reproduce `to_image()`.
+    pub fn render_perspective_warp(&self, horizon_y: f64, vanishing_x: f64, scale_bottom: f64, scale_top: f64) -> image::RgbImage {
+        let (w, h) = (self.width as f64, self.height as f64);
+        let vx = vanishing_x * w;
+        // Scale change per row going up from the bottom edge
+        let k = (scale_top - scale_bottom) / (h - horizon_y * h).max(f64::EPSILON);
+        let mut img = image::RgbImage::new(self.width, self.height);
+        for y in 0..self.height {
+            // Rows are stretched by 1/scale, so the source row is the integral of
+            // 1/scale from the bottom edge up to this row's center
+            let u = h - (y as f64 + 0.5);
+            let scale = scale_bottom + k * u;
+            if scale <= 0.0 {
+                continue;
+            }
+            let depth = if k == 0.0 { u / scale_bottom } else { (scale / scale_bottom).ln() / k };
+            let sy = (h - depth).floor();
+            if !(0.0..h).contains(&sy) {
+                continue;
+            }
+            for x in 0..self.width {
+                let sx = (vx + (x as f64 + 0.5 - vx) / scale).floor();
+                if !(0.0..w).contains(&sx) {
+                    continue;
+                }
+                let cell = self.cell_of[sy as usize * self.width as usize + sx as usize];
+                if let Some(color) = usize::try_from(cell).ok().and_then(|c| self.cell_colors.get(c)) {
+                    img.put_pixel(x, y, image::Rgb(*color));
+                }
+            }
+        }
+        img
+    }
+
+    /// Per-cell color variance of `image` (the image this result was computed
+    /// from): the mean over R, G and B of each channel's variance across the
+    /// cell's pixels, in squared 0–255 units. 0 for uniform and empty cells.
+    pub fn cell_color_variance(&self, image: &image::RgbImage) -> Vec<f32> {
+        let n = self.cell_colors.len();
+        let raw = image.as_raw();
+        // Per cell: per-channel sums and sums of squares
+        let mut sums = vec![[0u64; 6]; n];
+        for (i, &cell) in self.cell_of.iter().enumerate() {
+            let Some(acc) = usize::try_from(cell).ok().and_then(|c| sums.get_mut(c)) else { continue };
+            for ch in 0..3 {
+                let v = raw[i * 3 + ch] as u64;
+                acc[ch] += v;
+                acc[3 + ch] += v * v;
+            }
+        }
+        sums.iter().zip(&self.cell_areas)
+            .map(|(acc, &area)| {
+                if area == 0 {
+                    return 0.0;
+                }
+                let a = area as f64;
+                let var: f64 = (0..3).map(|ch| {
+                    let mean = acc[ch] as f64 / a;
+                    (acc[3 + ch] as f64 / a - mean * mean).max(0.0)
+                }).sum();
+                (var / 3.0) as f32
+            })
+            .collect()
+    }
+
+    /// Per-cell RGB histograms of `image` (the image this result was computed from):
+    /// `histograms[cell][bin] = [r_count, g_count, b_count]`, where channel value `v`
+    /// falls in bin `v * bins / 256`. Each channel's counts sum to the cell's area.
+    ///
+    /// One pass over `cell_of` (Rayon-parallel over rows, merging per-thread
+    /// histograms), so peak memory is a few `cells * bins` tables.
+    pub fn cell_color_histograms(&self, image: &image::RgbImage, bins: usize) -> Vec<Vec<[u32; 3]>> {
+        let n = self.cell_colors.len();
+        let w = self.width as usize;
+        if bins == 0 || w == 0 {
+            return vec![Vec::new(); n];
+        }
+        let raw = image.as_raw();
+        let empty = || vec![vec![[0u32; 3]; bins]; n];
+        let count_row = |mut hist: Vec<Vec<[u32; 3]>>, (y, row): (usize, &[i32])| {
+            for (x, &cell) in row.iter().enumerate() {
+                if cell < 0 || cell as usize >= n { continue; }
+                let px = &raw[(y * w + x) * 3..(y * w + x) * 3 + 3];
+                let cell_hist = &mut hist[cell as usize];
+                for ch in 0..3 {
+                    cell_hist[px[ch] as usize * bins / 256][ch] += 1;
+                }
+            }
+            hist
+        };
+
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            self.cell_of.par_chunks(w)
+                .enumerate()
+                .fold(empty, count_row)
+                .reduce(empty, |mut a, b| {
+                    for (cell_a, cell_b) in a.iter_mut().zip(&b) {
+                        for (bin_a, bin_b) in cell_a.iter_mut().zip(cell_b) {
+                            for ch in 0..3 {
+                                bin_a[ch] += bin_b[ch];
+                            }
+                        }
+                    }
+                    a
+                })
+        }
+        #[cfg(not(feature = "parallel"))]
+        self.cell_of.chunks(w).enumerate().fold(empty(), count_row)
+    }
+
+    /// Render each cell colored by its area (relative to the largest cell)
+    pub fn render_heatmap(&self, colormap: HeatmapColormap) -> image::RgbImage {
+        self.render_cell_colors(&colormap.area_colors(&self.cell_areas))
+    }
+
+    /// Render each cell in a categorical color by index: cell `i` gets entry
+    /// `i % palette_size` of a golden-ratio HSL palette, so cells spawned by
+    /// `adjust_count` (high indices) stand out from their neighbors. Useful for
+    /// debugging splits and merges.
+    pub fn render_cell_index_coloring(&self, palette_size: usize) -> image::RgbImage {
+        let palette = index_palette(palette_size);
+        let colors: Vec<Rgb> = (0..self.cell_colors.len()).map(|i| palette[i % palette.len()]).collect();
+        self.render_cell_colors(&colors)
+    }
+
+    /// Render with each cell's color quantized to `levels` evenly spaced values
+    /// per channel (`round(c / 255 · (levels − 1)) / (levels − 1) · 255`), e.g.
+    /// 2 levels gives the 8 corners of the RGB cube, 4 levels 64 colors.
+    /// `levels` is clamped to 2..=256; 256 reproduces `to_image()`.
+    pub fn render_posterized(&self, levels: u16) -> image::RgbImage {
+        let steps = (levels.clamp(2, 256) - 1) as f64;
+        let quantize = |c: u8| ((c as f64 / 255.0 * steps).round() / steps * 255.0).round() as u8;
+        let colors: Vec<Rgb> = self.cell_colors.iter().map(|c| c.map(quantize)).collect();
+        self.render_cell_colors(&colors)
+    }
+
+    /// Render as `block_size`×`block_size` pixel blocks (partial at the right and
+    /// bottom edges), each filled with the color of the cell owning most of its
+    /// pixels (lowest index on ties; black if mostly masked). The output keeps the
+    /// original resolution; `block_size` 1 (or 0) reproduces `to_image()`.
+    pub fn render_pixel_art(&self, block_size: u32) -> image::RgbImage {
+        let block_size = block_size.max(1) as usize;
+        let (w, h) = (self.width as usize, self.height as usize);
+        let mut pixels = vec![0u8; w * h * 3];
+        let mut block = Vec::with_capacity(block_size * block_size);
+        for y0 in (0..h).step_by(block_size) {
+            let y1 = (y0 + block_size).min(h);
+            for x0 in (0..w).step_by(block_size) {
+                let x1 = (x0 + block_size).min(w);
+                block.clear();
+                for y in y0..y1 {
+                    block.extend_from_slice(&self.cell_of[y * w + x0..y * w + x1]);
+                }
+                let Some(color) = usize::try_from(majority_cell(&mut block))
+                    .ok()
+                    .and_then(|cell| self.cell_colors.get(cell))
+                else {
+                    continue;
+                };
+                for y in y0..y1 {
+                    for px in pixels[(y * w + x0) * 3..(y * w + x1) * 3].chunks_exact_mut(3) {
+                        px.copy_from_slice(color);
+                    }
+                }
+            }
+        }
+        image::RgbImage::from_raw(self.width, self.height, pixels)
+            .expect("Buffer size mismatch")
+    }
+
+    /// Cell colors with their HSL lightness histogram-equalized across cells:
+    /// each cell's lightness (as one of 256 levels) is replaced by the fraction
+    /// of cells at or below it, rescaled so the darkest level maps to 0 and the
+    /// brightest to 1. Hue and saturation are kept. Colors are unchanged when
+    /// every cell has the same lightness.
+    pub fn equalize_cell_colors(&self) -> Vec<Rgb> {
+        let hsl: Vec<(f64, f64, f64)> = self.cell_colors.iter().map(|&c| rgb_to_hsl(c)).collect();
+        let level = |l: f64| (l * 255.0).round() as usize;
+        let mut cdf = [0usize; 256];
+        for &(_, _, l) in &hsl {
+            cdf[level(l)] += 1;
+        }
+        for i in 1..256 {
+            cdf[i] += cdf[i - 1];
+        }
+        let n = hsl.len();
+        let cdf_min = cdf.iter().copied().find(|&c| c > 0).unwrap_or(0);
+        if n == cdf_min {
+            return self.cell_colors.clone();
+        }
+        hsl.iter()
+            .map(|&(h, s, l)| hsl_to_rgb(h, s, (cdf[level(l)] - cdf_min) as f64 / (n - cdf_min) as f64))
+            .collect()
+    }
+
+    /// Render with `equalize_cell_colors`
+    pub fn render_equalized(&self) -> image::RgbImage {
+        self.render_cell_colors(&self.equalize_cell_colors())
+    }
+
+    /// Render with colors blended across cell boundaries: each pixel mixes its
+    /// nearest and second-nearest cells' colors with weights proportional to
+    /// `(d2 / (d1 + d2))^softness` and `(d1 / (d1 + d2))^softness` (d1, d2 the
+    /// distances to the two sites), so pixels on a boundary are an even mix and
+    /// the nearest cell dominates near its site. Larger `softness` narrows the
+    /// blended band; 0 averages the two colors everywhere. Needs
+    /// `second_cell_of` / `second_distances` and `pixel_distances` (see
+    /// `CpuBackend::with_second_nearest`); without them this is `to_image()`.
+    pub fn render_soft_boundary(&self, softness: f64) -> image::RgbImage {
+        let (Some(second_cell_of), Some(second_distances), Some(distances)) =
+            (&self.second_cell_of, &self.second_distances, &self.pixel_distances)
+        else {
+            return self.to_image();
+        };
+        let mut pixels = self.render();
+        for (i, &second) in second_cell_of.iter().enumerate() {
+            let cell = self.cell_of[i];
+            if cell < 0 || second < 0 {
+                continue;
+            }
+            let w = soft_boundary_weight(distances[i] as f64, second_distances[i] as f64, softness);
+            let (c1, c2) = (self.cell_colors[cell as usize], self.cell_colors[second as usize]);
+            for c in 0..3 {
+                pixels[i * 3 + c] = (w * c1[c] as f64 + (1.0 - w) * c2[c] as f64).round() as u8;
+            }
+        }
+        image::RgbImage::from_raw(self.width, self.height, pixels)
+            .expect("Buffer size mismatch")
+    }
+
+    /// Render with one color per cell (pixels of out-of-range cells are black)
+    fn render_cell_colors(&self, colors: &[Rgb]) -> image::RgbImage {
+        let mut pixels = vec![0u8; (self.width * self.height * 3) as usize];
+
+        for (i, &cell) in self.cell_of.iter().enumerate() {
+            if cell >= 0 && (cell as usize) < colors.len() {
+                let color = colors[cell as usize];
+                let px = i * 3;
+                pixels[px] = color[0];
+                pixels[px + 1] = color[1];
+                pixels[px + 2] = color[2];
+            }
+        }
+
+        image::RgbImage::from_raw(self.width, self.height, pixels)
+            .expect("Buffer size mismatch")
+    }
 }
 
 /// Trait for Voronoi computation backends
@@ -54,6 +642,22 @@ pub trait ComputeBackend {
         image: &image::RgbImage,
         sites: &[Position],
     ) -> Result<VoronoiResult>;
+
+    /// Compute from any `DynamicImage`. The default converts to RGB first;
+    /// backends may override to read common formats without the copy.
+    fn compute_dynamic(
+        &mut self,
+        image: &image::DynamicImage,
+        sites: &[Position],
+    ) -> Result<VoronoiResult> {
+        self.compute(&image.to_rgb8(), sites)
+    }
+
+    /// GPU execution time of the most recent `compute`, in nanoseconds, for
+    /// backends that record timestamp queries. `None` otherwise.
+    fn last_gpu_time_ns(&self) -> Option<u64> {
+        None
+    }
 }
 
 /// High-level Voronoi computer that can use different backends
@@ -73,4 +677,325 @@ impl<B: ComputeBackend> VoronoiComputer<B> {
     ) -> Result<VoronoiResult> {
         self.backend.compute(image, sites)
     }
+
+    pub fn compute_dynamic(
+        &mut self,
+        image: &image::DynamicImage,
+        sites: &[Position],
+    ) -> Result<VoronoiResult> {
+        self.backend.compute_dynamic(image, sites)
+    }
+}
+
+#[cfg(all(test, feature = "cpu"))]
+mod tests {
+    use super::*;
+    use crate::{CpuBackend, SiteCollection};
+
+    /// Zero-area cells map to the bottom of the colormap, the largest cell to the top
+    #[test]
+    fn test_render_heatmap_extremes() {
+        use crate::HeatmapColormap;
+
+        let mut backend = CpuBackend::new();
+        let image = image::RgbImage::from_pixel(100, 100, image::Rgb([255, 0, 0]));
+        // Site 1 duplicates site 0, so it never wins a pixel (zero area)
+        let sites = vec![
+            Position::new(25.0, 25.0),
+            Position::new(25.0, 25.0),
+            Position::new(80.0, 80.0),
+        ];
+        let result = backend.compute(&image, &sites).unwrap();
+        assert_eq!(result.cell_areas[1], 0);
+
+        for colormap in [
+            HeatmapColormap::Inferno, HeatmapColormap::Viridis,
+            HeatmapColormap::Turbo, HeatmapColormap::Grayscale,
+        ] {
+            let heat = colormap.area_colors(&result.cell_areas);
+            assert_eq!(heat[1], colormap.lookup(0.0), "{}: zero-area cell", colormap);
+
+            // Site 0 at (25,25) owns the larger cell
+            let img = result.render_heatmap(colormap);
+            assert!(result.cell_areas[0] > result.cell_areas[2]);
+            assert_eq!(img.get_pixel(25, 25).0, colormap.lookup(1.0), "{}: max-area cell", colormap);
+        }
+    }
+
+    #[test]
+    fn test_render_cell_index_coloring() {
+        let palette = crate::colormap::index_palette(12);
+        assert_eq!(palette.len(), 12);
+        let dist = |a: Rgb, b: Rgb| a.iter().zip(&b).map(|(&x, &y)| (x as f64 - y as f64).powi(2)).sum::<f64>().sqrt();
+        for k in 0..12 {
+            assert!(dist(palette[k], palette[(k + 1) % 12]) > 30.0, "palette[{}] vs next", k);
+        }
+
+        let image = image::RgbImage::from_pixel(90, 60, image::Rgb([0, 0, 0]));
+        let sites = SiteCollection::random(40, 90.0, 60.0, 6).positions();
+        let result = CpuBackend::new().compute(&image, &sites).unwrap();
+        let img = result.render_cell_index_coloring(12);
+        for (x, y, p) in img.enumerate_pixels() {
+            let cell = result.cell_at(x, y).unwrap();
+            assert_eq!(p.0, palette[cell % 12]);
+        }
+        // Adjacent cells with consecutive indices are clearly distinct
+        let adjacent = result.cell_adjacency_graph();
+        let pairs = (0..39).filter(|&i| adjacent[i].contains(&(i + 1))).count();
+        assert!(pairs > 0);
+    }
+
+    #[test]
+    fn test_render_posterized() {
+        let image = image::RgbImage::from_fn(80, 60, |x, y| image::Rgb([(x * 3) as u8, (y * 4) as u8, ((x + y) * 2) as u8]));
+        let sites = SiteCollection::random(50, 80.0, 60.0, 2).positions();
+        let result = CpuBackend::new().compute(&image, &sites).unwrap();
+        let img = result.render_posterized(3);
+        assert!(img.pixels().flat_map(|p| p.0).all(|c| [0, 128, 255].contains(&c)));
+        assert_eq!(result.render_posterized(256), result.to_image());
+    }
+
+    #[test]
+    fn test_render_into_shared() {
+        let image = image::RgbImage::from_fn(40, 30, |x, y| image::Rgb([(x * 6) as u8, (y * 8) as u8, 50]));
+        let sites = SiteCollection::random(12, 40.0, 30.0, 4).positions();
+        let mut result = CpuBackend::new().compute(&image, &sites).unwrap();
+        result.cell_of[5] = -1;
+        let expected = result.render();
+
+        let mut rgb = vec![7u8; expected.len()];
+        result.render_into_shared(&mut rgb, 3, 0, 0, 40);
+        assert_eq!(rgb, expected);
+
+        // RGBA keeps alpha
+        let mut rgba = vec![255u8; 40 * 30 * 4];
+        result.render_into_shared(&mut rgba, 4, 0, 0, 40);
+        assert!(rgba.chunks(4).zip(expected.chunks(3)).all(|(a, b)| a[..3] == *b && a[3] == 255));
+
+        // Offset into a larger canvas, clipped at its right and bottom edges
+        let mut canvas = vec![1u8; 50 * 35 * 3];
+        result.render_into_shared(&mut canvas, 3, 20, 10, 50);
+        for y in 0..35 {
+            for x in 0..50 {
+                let px = &canvas[(y * 50 + x) * 3..][..3];
+                if x >= 20 && y >= 10 {
+                    let src = ((y - 10) * 40 + x - 20) * 3;
+                    assert_eq!(px, &expected[src..src + 3], "({}, {})", x, y);
+                } else {
+                    assert_eq!(px, [1, 1, 1]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_pixel_art() {
+        let image = image::RgbImage::from_fn(100, 100, |x, y| image::Rgb([(x * 2) as u8, (y * 2) as u8, 90]));
+        let sites = SiteCollection::random(30, 100.0, 100.0, 3).positions();
+        let result = CpuBackend::new().compute(&image, &sites).unwrap();
+        let img = result.render_pixel_art(10);
+        let mut blocks = 0;
+        for by in (0..100).step_by(10) {
+            for bx in (0..100).step_by(10) {
+                let color = img.get_pixel(bx, by).0;
+                assert!(result.cell_colors.contains(&color));
+                for y in by..by + 10 {
+                    for x in bx..bx + 10 {
+                        assert_eq!(img.get_pixel(x, y).0, color, "block ({}, {})", bx, by);
+                    }
+                }
+                blocks += 1;
+            }
+        }
+        assert_eq!(blocks, 100);
+        assert_eq!(result.render_pixel_art(1), result.to_image());
+    }
+
+    /// Equalized cell lightness fills its histogram more evenly than a
+    /// low-contrast original
+    #[test]
+    fn test_equalize_cell_colors() {
+        let image = image::RgbImage::from_fn(120, 90, |x, y| {
+            let v = 100 + ((x + y) / 6) as u8;
+            image::Rgb([v, v / 2 + 40, v])
+        });
+        let sites = SiteCollection::random(200, 120.0, 90.0, 8).positions();
+        let result = CpuBackend::new().compute(&image, &sites).unwrap();
+        let bin_std = |colors: &[Rgb]| {
+            let mut bins = [0.0f64; 8];
+            for c in colors {
+                let gray = 0.299 * c[0] as f64 + 0.587 * c[1] as f64 + 0.114 * c[2] as f64;
+                bins[((gray / 256.0) * 8.0) as usize] += 1.0;
+            }
+            let mean = colors.len() as f64 / 8.0;
+            (bins.iter().map(|b| (b - mean).powi(2)).sum::<f64>() / 8.0).sqrt()
+        };
+        let equalized = result.equalize_cell_colors();
+        assert_eq!(equalized.len(), result.cell_colors.len());
+        assert!(bin_std(&equalized) < 0.5 * bin_std(&result.cell_colors));
+
+        let img = result.render_equalized();
+        let cell = result.cell_at(10, 10).unwrap();
+        assert_eq!(img.get_pixel(10, 10).0, equalized[cell]);
+    }
+
+    #[test]
+    fn test_cell_at() {
+        let mut backend = CpuBackend::new();
+        let image = image::RgbImage::from_pixel(40, 20, image::Rgb([0, 0, 0]));
+        let sites = vec![Position::new(5.0, 5.0), Position::new(35.0, 15.0)];
+        let result = backend.compute(&image, &sites).unwrap();
+
+        assert_eq!(result.cell_at(0, 0), Some(result.cell_of[0] as usize));
+        assert_eq!(result.cell_at(39, 19), Some(1));
+        assert_eq!(result.cell_at(40, 0), None);
+        assert_eq!(result.cell_at(0, 20), None);
+        assert_eq!(result.nearest_site_to(4.6, 5.2), 0);
+        assert_eq!(result.nearest_site_to(100.0, 100.0), 1);
+    }
+
+    #[test]
+    fn test_render_soft_boundary() {
+        use crate::voronoi::soft_boundary_weight;
+
+        let image = image::RgbImage::from_fn(41, 21, |x, _| if x < 20 { image::Rgb([0, 0, 0]) } else { image::Rgb([200, 100, 50]) });
+        let sites = [Position::new(10.5, 10.5), Position::new(30.5, 10.5)];
+        let result = CpuBackend::with_second_nearest(true).compute(&image, &sites).unwrap();
+        let d1 = result.pixel_distances.as_ref().unwrap();
+        let d2 = result.second_distances.as_ref().unwrap();
+        let weight = |x: usize, y: usize| soft_boundary_weight(d1[y * 41 + x] as f64, d2[y * 41 + x] as f64, 2.0);
+
+        // Column 20's pixel centers are equidistant from both sites
+        for y in 0..21 {
+            assert_eq!(weight(20, y), 0.5);
+        }
+        for i in (0..41 * 21).filter(|&i| d1[i] <= 1.0) {
+            assert!(weight(i % 41, i / 41) >= 0.99, "pixel {}", i);
+        }
+
+        let img = result.render_soft_boundary(2.0);
+        let (a, b) = (result.cell_colors[0], result.cell_colors[1]);
+        let mid: Vec<u8> = (0..3).map(|c| ((a[c] as f64 + b[c] as f64) / 2.0).round() as u8).collect();
+        assert_eq!(img.get_pixel(20, 10).0.to_vec(), mid);
+        assert_eq!(img.get_pixel(10, 10).0, a);
+        assert_eq!(CpuBackend::new().compute(&image, &sites).unwrap().render_soft_boundary(2.0), result.to_image());
+    }
+
+    #[test]
+    fn test_polygon_mask() {
+        let (w, h) = (40u32, 30u32);
+        let image = image::RgbImage::from_pixel(w, h, image::Rgb([200, 100, 50]));
+        let sites = vec![Position::new(10.0, 10.0), Position::new(30.0, 20.0)];
+        let result = CpuBackend::new().compute(&image, &sites).unwrap();
+
+        // Left half rectangle: exactly the right-half pixels are masked out
+        let rect = [(0.0, 0.0), (20.0, 0.0), (20.0, 30.0), (0.0, 30.0)];
+        let mask = result.rasterize_polygon_mask(&rect);
+        for y in 0..h as usize {
+            for x in 0..w as usize {
+                assert_eq!(mask[y * w as usize + x], x < 20, "({}, {})", x, y);
+            }
+        }
+        let masked = result.render_masked(&mask, [1, 2, 3]);
+        assert_eq!(masked.get_pixel(19, 5).0, [200, 100, 50]);
+        assert_eq!(masked.get_pixel(20, 5).0, [1, 2, 3]);
+
+        // Right triangle with legs along the top and left edges: row y covers
+        // pixel centers x + 0.5 < 20 - (y + 0.5), i.e. x < 19 - y
+        let tri = [(0.0, 0.0), (20.0, 0.0), (0.0, 20.0)];
+        let mask = result.rasterize_polygon_mask(&tri);
+        for y in 0..h as usize {
+            let row = &mask[y * w as usize..(y + 1) * w as usize];
+            let expected = 19usize.saturating_sub(y);
+            assert_eq!(row.iter().filter(|&&m| m).count(), expected, "row {}", y);
+            assert!(row.iter().take(expected).all(|&m| m));
+        }
+    }
+
+    #[test]
+    fn test_supersample() {
+        let mut backend = CpuBackend::new();
+        let image = image::RgbImage::from_fn(64, 48, |x, y| image::Rgb([(x * 4) as u8, (y * 5) as u8, 90]));
+        let sites = SiteCollection::random(30, 64.0, 48.0, 5).positions();
+        let result = backend.compute(&image, &sites).unwrap();
+
+        assert_eq!(result.supersample(&sites, 1), result.to_image());
+
+        let ss = result.supersample(&sites, 2);
+        assert_eq!(ss.dimensions(), (128, 96));
+        // Sub-pixels take their colors from the existing cell palette
+        assert!(ss.pixels().all(|p| result.cell_colors.contains(&p.0)));
+    }
+
+    #[test]
+    fn test_render_motion_blur() {
+        let mut backend = CpuBackend::new();
+        let image = image::RgbImage::from_fn(80, 40, |x, _| if x < 40 { image::Rgb([220, 20, 20]) } else { image::Rgb([20, 20, 220]) });
+        // Both sites move right, dragging the cell boundary from x=40 to x=50
+        let prev_sites = [Position::new(20.0, 20.0), Position::new(60.0, 20.0)];
+        let curr_sites = [Position::new(30.0, 20.0), Position::new(70.0, 20.0)];
+        let prev = backend.compute(&image, &prev_sites).unwrap();
+        let curr = backend.compute(&image, &curr_sites).unwrap();
+
+        assert_eq!(curr.render_motion_blur(&prev, 1, &prev_sites, &curr_sites), curr.to_image());
+
+        let blurred = curr.render_motion_blur(&prev, 4, &prev_sites, &curr_sites);
+        // Away from the boundary a pixel stays in one cell: 1 sample of its previous
+        // color (t = 0.25), 3 of its current one
+        let expected = |cell: usize| -> Rgb {
+            std::array::from_fn(|c| ((prev.cell_colors[cell][c] as u32 + 3 * curr.cell_colors[cell][c] as u32 + 2) / 4) as u8)
+        };
+        let (left, right) = (expected(0), expected(1));
+        assert_eq!(blurred.get_pixel(5, 20).0, left);
+        assert_eq!(blurred.get_pixel(75, 20).0, right);
+        // Pixels the boundary swept over mix the two cells
+        let mixed: Vec<u32> = (0..80)
+            .filter(|&x| ![left, right].contains(&blurred.get_pixel(x, 20).0))
+            .collect();
+        assert!(!mixed.is_empty() && mixed.iter().all(|&x| (40..50).contains(&x)), "mixed columns {:?}", mixed);
+    }
+
+    #[test]
+    fn test_render_perspective_warp() {
+        let mut backend = CpuBackend::new();
+        let image = image::RgbImage::from_fn(64, 48, |x, y| image::Rgb([(x * 4) as u8, (y * 5) as u8, 90]));
+        let sites = SiteCollection::random(30, 64.0, 48.0, 5).positions();
+        let result = backend.compute(&image, &sites).unwrap();
+
+        for horizon in [0.0, 0.5, 0.9] {
+            assert_eq!(result.render_perspective_warp(horizon, 0.5, 1.0, 1.0), result.to_image());
+        }
+
+        let warped = result.render_perspective_warp(0.5, 0.5, 1.0, 0.1);
+        // Near the vanishing point the bottom row is barely scaled; the top rows lie
+        // past the vanishing line
+        let plain = result.to_image();
+        for x in 0..64 {
+            if (16..48).contains(&x) {
+                assert_eq!(warped.get_pixel(x, 47), plain.get_pixel(x, 47));
+            }
+            assert_eq!(warped.get_pixel(x, 0).0, [0, 0, 0]);
+        }
+        assert_ne!(warped.get_pixel(32, 40).0, [0, 0, 0]);
+        assert!(warped.pixels().all(|p| p.0 == [0, 0, 0] || result.cell_colors.contains(&p.0)));
+    }
+
+    #[test]
+    fn test_cell_color_histograms() {
+        let mut backend = CpuBackend::new();
+        let image = image::RgbImage::from_fn(64, 48, |x, y| image::Rgb([(x * 4) as u8, (y * 5) as u8, 90]));
+        let sites = SiteCollection::random(30, 64.0, 48.0, 5).positions();
+        let result = backend.compute(&image, &sites).unwrap();
+
+        let histograms = result.cell_color_histograms(&image, 16);
+        assert_eq!(histograms.len(), 30);
+        for (hist, &area) in histograms.iter().zip(&result.cell_areas) {
+            assert_eq!(hist.len(), 16);
+            for ch in 0..3 {
+                assert_eq!(hist.iter().map(|bin| bin[ch]).sum::<u32>(), area);
+            }
+            // Constant blue channel (90) lands entirely in bin 90 * 16 / 256 = 5
+            assert_eq!(hist[5][2], area);
+        }
+    }
 }
@@ -5,10 +5,18 @@
 
 use wasm_bindgen::prelude::*;
 use voronoi_core::{
-    CpuBackend, ComputeBackend, Position, Site, SiteCollection, SplitStrategy,
-    Velocity, VoronoiResult,
+    AdjustParams, CpuBackend, ComputeBackend, EdgeBehavior, HeatmapColormap, InitStrategy, Position, PullMode, Site,
+    SiteCollection, SiteCollectionBuilder, SpatialGrid, SplitStrategy, StepParams, Velocity, Vortex, VoronoiResult,
 };
 
+use std::borrow::Cow;
+use std::rc::Rc;
+
+/// Label text height in pixels for `VoronoiFrame::render_with_labels`
+const SITE_LABEL_SIZE: u32 = 10;
+/// Sobel gradient magnitude (0-255 luma) marking edge pixels for `VoronoiEngine::init_hybrid`
+const HYBRID_EDGE_THRESHOLD: f32 = 100.0;
+
 #[wasm_bindgen(start)]
 pub fn init_panic_hook() {
     console_error_panic_hook::set_once();
@@ -26,18 +34,39 @@ fn rgba_to_rgb_image(rgba: &[u8], width: u32, height: u32) -> image::RgbImage {
         .expect("buffer size mismatch in rgba_to_rgb_image")
 }
 
+/// Call each of `fns` as `fn(index, x, y)`; JS exceptions are ignored.
+fn call_site_fns(fns: &[js_sys::Function], index: usize, site: &Site) {
+    for f in fns {
+        let _ = f.call3(
+            &JsValue::NULL, &JsValue::from(index as u32), &JsValue::from(site.pos.x), &JsValue::from(site.pos.y),
+        );
+    }
+}
+
 /// Result of a single Voronoi computation frame.
 /// All data is exposed as flat typed arrays for zero-copy JS access.
 #[wasm_bindgen]
 pub struct VoronoiFrame {
-    cell_of: Vec<i32>,
-    cell_colors_flat: Vec<u8>,
-    cell_areas: Vec<u32>,
-    cell_centroids_flat: Vec<f64>,
-    farthest_x: f64,
-    farthest_y: f64,
-    width: u32,
-    height: u32,
+    result: VoronoiResult,
+    /// Image the frame was computed from (for per-cell pixel statistics)
+    source: FrameSource,
+}
+
+/// Source pixels of a frame: shared with the engine, or owned when computed from
+/// caller-provided pixels (converted to RGB only if needed)
+enum FrameSource {
+    Engine(Rc<image::RgbImage>),
+    Dynamic(image::DynamicImage),
+}
+
+impl FrameSource {
+    fn rgb(&self) -> Cow<'_, image::RgbImage> {
+        match self {
+            FrameSource::Engine(image) => Cow::Borrowed(image),
+            FrameSource::Dynamic(image::DynamicImage::ImageRgb8(image)) => Cow::Borrowed(image),
+            FrameSource::Dynamic(image) => Cow::Owned(image.to_rgb8()),
+        }
+    }
 }
 
 #[wasm_bindgen]
@@ -45,66 +74,172 @@ impl VoronoiFrame {
     /// Cell index for each pixel (row-major, length = width*height)
     #[wasm_bindgen(getter)]
     pub fn cell_of(&self) -> Vec<i32> {
-        self.cell_of.clone()
+        self.result.cell_of.clone()
     }
 
     /// Flat RGB colors per cell (length = num_cells * 3)
     #[wasm_bindgen(getter)]
     pub fn cell_colors(&self) -> Vec<u8> {
-        self.cell_colors_flat.clone()
+        self.result.cell_colors.iter()
+            .flat_map(|&[r, g, b]| [r, g, b])
+            .collect()
     }
 
     /// Pixel count per cell (length = num_cells)
     #[wasm_bindgen(getter)]
     pub fn cell_areas(&self) -> Vec<u32> {
-        self.cell_areas.clone()
+        self.result.cell_areas.clone()
     }
 
     /// Flat [x0,y0, x1,y1, ...] centroids per cell (length = num_cells * 2)
     #[wasm_bindgen(getter)]
     pub fn cell_centroids(&self) -> Vec<f64> {
-        self.cell_centroids_flat.clone()
+        self.result.cell_centroids.iter()
+            .flat_map(|p| [p.x, p.y])
+            .collect()
     }
 
     #[wasm_bindgen(getter)]
     pub fn farthest_x(&self) -> f64 {
-        self.farthest_x
+        self.result.farthest_point.x
     }
 
     #[wasm_bindgen(getter)]
     pub fn farthest_y(&self) -> f64 {
-        self.farthest_y
+        self.result.farthest_point.y
     }
 
     #[wasm_bindgen(getter)]
     pub fn width(&self) -> u32 {
-        self.width
+        self.result.width
     }
 
     #[wasm_bindgen(getter)]
     pub fn height(&self) -> u32 {
-        self.height
+        self.result.height
+    }
+
+    /// Per-pixel distance to the nearest site (row-major, length = width*height).
+    /// Empty unless enabled via `VoronoiEngine::enable_distances`.
+    pub fn pixel_distances(&self) -> Vec<f32> {
+        self.result.pixel_distances.clone().unwrap_or_default()
+    }
+
+    /// Cell index at pixel (x, y), or -1 if out of bounds
+    pub fn cell_at_pixel(&self, x: u32, y: u32) -> i32 {
+        self.result.cell_at(x, y).map_or(-1, |c| c as i32)
+    }
+
+    /// Cell indices along the fewest-hops path between two cells through shared
+    /// boundaries, both ends included (empty if there is none).
+    pub fn shortest_path(&self, from_cell: usize, to_cell: usize) -> Vec<u32> {
+        self.result.shortest_path(from_cell, to_cell)
+            .map(|path| path.into_iter().map(|c| c as u32).collect())
+            .unwrap_or_default()
+    }
+
+    /// RGB histogram of cell `cell_idx` in the source image, flattened as
+    /// [r0, g0, b0, r1, g1, b1, ...] over `bins` bins (empty if out of range).
+    /// Computes histograms for all cells; cache the results when querying many.
+    pub fn cell_histogram_flat(&self, cell_idx: usize, bins: usize) -> Vec<u32> {
+        let mut histograms = self.result.cell_color_histograms(&self.source.rgb(), bins);
+        if cell_idx >= histograms.len() {
+            return Vec::new();
+        }
+        histograms.swap_remove(cell_idx).into_iter().flatten().collect()
+    }
+
+    /// Convex-hull polygon of cell `cell_idx` as flat [x0,y0, x1,y1, ...] pixel-corner
+    /// coordinates, e.g. for a CSS `clip-path` (empty if out of range or empty).
+    /// Computes hulls for all cells; cache the results when querying many.
+    pub fn cell_polygon(&self, cell_idx: usize) -> Vec<f64> {
+        let mut hulls = self.result.cell_polygon_approx();
+        if cell_idx >= hulls.len() {
+            return Vec::new();
+        }
+        hulls.swap_remove(cell_idx).into_iter().flat_map(|p| [p.x, p.y]).collect()
+    }
+
+    /// Each cell's `k` nearest cells by average color, flat as [neighbor, distance,
+    /// ...] per cell (`k` pairs each, fewer if there are ≤ k cells), sorted nearest
+    /// first. Distances are RGB L2 in 24.8 fixed point (divide by 256).
+    pub fn nearest_color_neighbors(&self, k: usize) -> Vec<u32> {
+        self.result.nearest_color_neighbors(k).into_iter()
+            .flatten()
+            .flat_map(|(j, d)| [j as u32, (d * 256.0).round() as u32])
+            .collect()
+    }
+
+    /// Fraction of pixels in the same cell as in `other` (e.g. the previous frame):
+    /// 1.0 = unchanged, 0.0 = every pixel reassigned
+    pub fn consistency_vs(&self, other: &VoronoiFrame) -> f64 {
+        self.result.temporal_consistency(&other.result)
+    }
+
+    /// Render the diagram with each site labeled by its cell index as flat RGB
+    /// (length = width*height*3). `sites_flat` is [x0,y0, x1,y1, ...].
+    pub fn render_with_labels(&self, sites_flat: &[f64]) -> Vec<u8> {
+        let sites: Vec<Position> = sites_flat.chunks_exact(2)
+            .map(|p| Position::new(p[0], p[1]))
+            .collect();
+        self.result.render_with_site_labels(&sites, SITE_LABEL_SIZE).into_raw()
+    }
+
+    /// Encode the rendered diagram as lossless WebP. Empty on failure.
+    #[cfg(feature = "webp")]
+    pub fn to_webp(&self) -> Vec<u8> {
+        self.result.to_webp_bytes().unwrap_or_default()
+    }
+
+    /// Encode the rendered diagram as AVIF at `quality` (1-100). Empty on failure.
+    #[cfg(feature = "avif")]
+    pub fn to_avif(&self, quality: u8) -> Vec<u8> {
+        self.result.to_avif_bytes(quality).unwrap_or_default()
+    }
+
+    /// Render cells colored by index (mod `palette_size`) as flat RGB (length =
+    /// width*height*3), so newly spawned cells stand out.
+    pub fn render_by_index(&self, palette_size: usize) -> Vec<u8> {
+        self.result.render_cell_index_coloring(palette_size).into_raw()
+    }
+
+    /// Render into `buffer` (e.g. a persistent `Uint8ClampedArray` backing an
+    /// `ImageData`, reused every frame) with `stride` bytes per pixel (3 = RGB,
+    /// 4 = RGBA; alpha is left as is) and rows of `width` pixels.
+    pub fn render_into_shared_buffer(&self, buffer: &mut [u8], stride: u32) {
+        self.result.render_into_shared(buffer, stride, 0, 0, self.result.width);
+    }
+
+    /// Render with cell colors quantized to `levels` (2-256) values per channel,
+    /// as flat RGB (length = width*height*3).
+    pub fn render_posterized(&self, levels: u16) -> Vec<u8> {
+        self.result.render_posterized(levels).into_raw()
+    }
+
+    /// Render as `block_size`×`block_size` blocks, each the color of its majority
+    /// cell, as flat RGB (length = width*height*3).
+    pub fn render_pixel_art(&self, block_size: u32) -> Vec<u8> {
+        self.result.render_pixel_art(block_size).into_raw()
+    }
+
+    /// Cell colors with lightness histogram-equalized across cells, flat as
+    /// [r0,g0,b0, r1,g1,b1, ...] per cell.
+    pub fn equalized_colors(&self) -> Vec<u8> {
+        self.result.equalize_cell_colors().into_iter().flatten().collect()
+    }
+
+    /// Render cells colored by area as flat RGB (length = width*height*3).
+    /// Unknown colormap names fall back to viridis.
+    pub fn render_heatmap(&self, colormap: &str) -> Vec<u8> {
+        let colormap: HeatmapColormap = colormap.parse()
+            .unwrap_or(HeatmapColormap::Viridis);
+        self.result.render_heatmap(colormap).into_raw()
     }
 }
 
 impl VoronoiFrame {
-    fn from_result(result: VoronoiResult) -> Self {
-        let cell_colors_flat: Vec<u8> = result.cell_colors.iter()
-            .flat_map(|&[r, g, b]| [r, g, b])
-            .collect();
-        let cell_centroids_flat: Vec<f64> = result.cell_centroids.iter()
-            .flat_map(|p| [p.x, p.y])
-            .collect();
-        Self {
-            cell_of: result.cell_of,
-            cell_colors_flat,
-            cell_areas: result.cell_areas,
-            cell_centroids_flat,
-            farthest_x: result.farthest_point.x,
-            farthest_y: result.farthest_point.y,
-            width: result.width,
-            height: result.height,
-        }
+    fn from_result(result: VoronoiResult, source: FrameSource) -> Self {
+        Self { result, source }
     }
 }
 
@@ -113,11 +248,31 @@ impl VoronoiFrame {
 /// for computation, physics stepping, and site count adjustment.
 #[wasm_bindgen]
 pub struct VoronoiEngine {
-    image: image::RgbImage,
+    image: Rc<image::RgbImage>,
     width: u32,
     height: u32,
     backend: CpuBackend,
     sites: SiteCollection,
+    seed: u64,
+    edge_behavior: EdgeBehavior,
+    /// Centroid pull mode for `step` (see `set_pull_mode`); kept here so it
+    /// survives re-initializing the sites
+    pull_mode: PullMode,
+    /// Vortices applied in `step` (see `add_vortex`), likewise kept across re-inits
+    vortices: Vec<Vortex>,
+    /// Grayscale map scaling site speed in `step` (see `set_speed_map`)
+    speed_map: Option<image::GrayImage>,
+    /// Clip polygon applied in `compute` (see `set_polygon_mask`)
+    polygon_mask: Option<Vec<(f64, f64)>>,
+    /// Per-call cap on sites added/removed by `adjust_count`
+    max_splits_per_frame: usize,
+    /// Whether `adjust_count` first removes sites with empty cells
+    auto_remove_dead: bool,
+    /// Per-site physics callback for `step` (see `set_physics_params_fn`)
+    physics_params_fn: Option<js_sys::Function>,
+    /// Site lifecycle callbacks for `adjust_count` (see `on_site_added`)
+    site_added_fns: Vec<js_sys::Function>,
+    site_removed_fns: Vec<js_sys::Function>,
 }
 
 #[wasm_bindgen]
@@ -125,19 +280,35 @@ impl VoronoiEngine {
     /// Create a new engine from RGBA pixel data.
     #[wasm_bindgen(constructor)]
     pub fn new(rgba_data: &[u8], width: u32, height: u32, seed: u32) -> Self {
-        let image = rgba_to_rgb_image(rgba_data, width, height);
+        let image = Rc::new(rgba_to_rgb_image(rgba_data, width, height));
         Self {
             image,
             width,
             height,
             backend: CpuBackend::new(),
             sites: SiteCollection::new(vec![], seed as u64),
+            seed: seed as u64,
+            edge_behavior: EdgeBehavior::Reflect,
+            pull_mode: PullMode::default(),
+            vortices: Vec::new(),
+            speed_map: None,
+            polygon_mask: None,
+            max_splits_per_frame: usize::MAX,
+            auto_remove_dead: false,
+            physics_params_fn: None,
+            site_added_fns: Vec::new(),
+            site_removed_fns: Vec::new(),
         }
     }
 
+    /// Start configuring an engine; see `VoronoiEngineBuilder`.
+    pub fn builder() -> VoronoiEngineBuilder {
+        VoronoiEngineBuilder::default()
+    }
+
     /// Replace the source image (e.g. on resize).
     pub fn set_image(&mut self, rgba_data: &[u8], width: u32, height: u32) {
-        self.image = rgba_to_rgb_image(rgba_data, width, height);
+        self.image = Rc::new(rgba_to_rgb_image(rgba_data, width, height));
         self.width = width;
         self.height = height;
     }
@@ -166,16 +337,204 @@ impl VoronoiEngine {
         );
     }
 
+    /// Initialize `count` sites at the strongest Harris corners of the current image.
+    /// Falls back to random positions if too few corners are found.
+    pub fn init_from_corners(&mut self, count: usize) {
+        self.sites = SiteCollection::from_points_of_interest(&self.image, count, self.seed);
+    }
+
+    /// Initialize `count` sites: `corner_frac` of them on Harris corners of the
+    /// current image, `edge_frac` on random strong-gradient pixels, the rest at
+    /// random. Returns false (leaving sites unchanged) if the fractions are
+    /// negative or sum to more than 1.
+    pub fn init_hybrid(&mut self, corner_frac: f64, edge_frac: f64, count: usize) -> bool {
+        match SiteCollection::from_corners_and_edges(&self.image, corner_frac, edge_frac, count, HYBRID_EDGE_THRESHOLD, self.seed) {
+            Ok(sites) => {
+                self.sites = sites;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Initialize `count` sites at the centers of dominant-color regions of the
+    /// current image (k-means over color and position).
+    pub fn init_from_color_clusters(&mut self, count: usize) {
+        self.sites = SiteCollection::from_color_clusters(&self.image, count, self.seed);
+    }
+
+    /// Initialize `count` sites along a random walk from the image center with
+    /// `step_size`-pixel steps (wrapping at the edges), heading along the walk.
+    pub fn init_random_walk(&mut self, count: usize, step_size: f64) {
+        self.sites = SiteCollection::from_random_walk(
+            count, step_size, self.width as f64, self.height as f64, self.seed,
+        );
+    }
+
+    /// Initialize `count` sites weighted by Mandelbrot escape time (`max_iter`
+    /// iterations), concentrated along the set's boundary.
+    pub fn init_from_mandelbrot(&mut self, count: usize, max_iter: u32) {
+        self.sites = SiteCollection::from_mandelbrot(
+            count, self.width as f64, self.height as f64, max_iter, self.seed,
+        );
+    }
+
+    /// Initialize `sides` sites on the vertices of a regular polygon of circumradius
+    /// `radius` centered in the image, rotated by `rotation` radians.
+    pub fn init_polygon(&mut self, sides: usize, radius: f64, rotation: f64) {
+        self.sites = SiteCollection::from_regular_polygon(
+            sides, radius, self.width as f64 / 2.0, self.height as f64 / 2.0, rotation, self.seed,
+        );
+    }
+
+    /// Initialize concentric rings centered in the image from flat [count0, radius0,
+    /// count1, radius1, ...], each ring evenly spaced from a random start angle and
+    /// heading clockwise.
+    pub fn init_concentric_rings(&mut self, ring_specs_flat: &[f64]) {
+        let specs: Vec<(usize, f64)> = ring_specs_flat.chunks_exact(2)
+            .map(|spec| (spec[0].max(0.0) as usize, spec[1]))
+            .collect();
+        self.sites = SiteCollection::from_concentric_rings(
+            &specs, self.width as f64 / 2.0, self.height as f64 / 2.0, self.seed,
+        );
+    }
+
+    /// Initialize `count` random sites spread evenly by `iterations` rounds of
+    /// mutual repulsion (close to a Poisson-disk pattern).
+    pub fn init_scatter(&mut self, count: usize, iterations: usize) {
+        self.sites = SiteCollection::scatter_with_repulsion(
+            count, self.width as f64, self.height as f64, self.seed, iterations,
+        );
+    }
+
+    /// Initialize `count` sites evenly along the Lissajous curve
+    /// `(amp_x · cos(a·t + delta), amp_y · sin(b·t))` centered in the image,
+    /// heading along the curve.
+    pub fn init_lissajous(&mut self, count: usize, a: f64, b: f64, delta: f64, amp_x: f64, amp_y: f64) {
+        self.sites = SiteCollection::from_lissajous(
+            count, a, b, delta, amp_x, amp_y, self.width as f64 / 2.0, self.height as f64 / 2.0, self.seed,
+        );
+    }
+
+    /// Initialize `count` sites evenly spaced along SVG path data `d`, heading along
+    /// the curve. Returns false (leaving sites unchanged) if the path is invalid.
+    #[cfg(feature = "svg-init")]
+    pub fn init_from_svg_path(&mut self, d: &str, count: usize) -> bool {
+        match SiteCollection::from_svg_path(d, count, self.seed) {
+            Ok(sites) => {
+                self.sites = sites;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Record per-pixel nearest-site distances in subsequent frames
+    /// (`VoronoiFrame::pixel_distances`). Costs width*height*4 bytes per frame.
+    pub fn enable_distances(&mut self, enabled: bool) {
+        self.backend.distances = enabled;
+    }
+
+    /// Clip subsequent `compute` results to a polygon given as flat
+    /// [x0,y0, x1,y1, ...] pixel coordinates (even-odd fill). Pixels outside get
+    /// cell index -1 in `cell_of`. Fewer than 3 vertices clears the mask.
+    pub fn set_polygon_mask(&mut self, vertices_flat: &[f64]) {
+        let polygon: Vec<(f64, f64)> = vertices_flat.chunks_exact(2)
+            .map(|xy| (xy[0], xy[1]))
+            .collect();
+        self.polygon_mask = (polygon.len() >= 3).then_some(polygon);
+    }
+
+    /// Set each site's power-diagram weight (extra entries are ignored, missing ones
+    /// keep their weight). While any weight differs from 1, `compute` gives site `i`
+    /// the pixels where `dist² - weights[i]²` is least; split children inherit it.
+    pub fn set_site_weights(&mut self, weights: &[f64]) {
+        for (site, &weight) in self.sites.sites.iter_mut().zip(weights) {
+            site.weight = weight;
+        }
+    }
+
     /// Run Voronoi computation on current image and sites.
     pub fn compute(&mut self) -> VoronoiFrame {
+        let result = self.compute_current().expect("Voronoi computation failed");
+        self.masked_frame(result)
+    }
+
+    /// Like `compute`, but only for the `w × h` pixel rectangle at (x, y), e.g. after
+    /// editing that part of the image. Pixels outside it have cell -1, and cell
+    /// colors/areas/centroids cover only the rectangle.
+    pub fn compute_roi(&mut self, x: u32, y: u32, w: u32, h: u32) -> VoronoiFrame {
         let positions = self.sites.positions();
-        let result = self.backend.compute(&self.image, &positions)
+        let roi = [x, y, x.saturating_add(w), y.saturating_add(h)];
+        let result = self.backend.compute_with_roi(&self.image, &positions, roi)
             .expect("Voronoi computation failed");
-        VoronoiFrame::from_result(result)
+        self.masked_frame(result)
+    }
+
+    /// Run Voronoi computation on the given RGBA pixels (length = width*height*4)
+    /// with current sites, without replacing the stored image.
+    pub fn compute_from_rgba(&mut self, rgba_data: &[u8], width: u32, height: u32) -> VoronoiFrame {
+        let image = image::RgbaImage::from_raw(width, height, rgba_data.to_vec())
+            .expect("buffer size mismatch in compute_from_rgba");
+        self.compute_dynamic(image::DynamicImage::ImageRgba8(image))
+    }
+
+    /// Run Voronoi computation on the given 8-bit grayscale pixels (length = width*height)
+    /// with current sites, without replacing the stored image.
+    pub fn compute_from_gray(&mut self, data: &[u8], width: u32, height: u32) -> VoronoiFrame {
+        let image = image::GrayImage::from_raw(width, height, data.to_vec())
+            .expect("buffer size mismatch in compute_from_gray");
+        self.compute_dynamic(image::DynamicImage::ImageLuma8(image))
+    }
+
+    /// Set how sites behave at the image edges: "reflect", "wrap", "absorb", or "clamp".
+    /// Returns false (leaving the current behavior) for unknown names.
+    pub fn set_edge_behavior(&mut self, behavior: &str) -> bool {
+        match behavior.parse() {
+            Ok(edge) => {
+                self.edge_behavior = edge;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Set how centroid pull turns headings in `step`: "lerp" or "slerp".
+    /// Returns false (leaving the current mode) for unknown names.
+    pub fn set_pull_mode(&mut self, mode: &str) -> bool {
+        match mode.parse() {
+            Ok(mode) => {
+                self.pull_mode = mode;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Add a vortex at (cx, cy) that swirls sites within `radius` pixels at
+    /// `strength` rad/s (clockwise on screen for positive strength) in `step`.
+    /// Vortices accumulate until `clear_vortices`.
+    pub fn add_vortex(&mut self, cx: f64, cy: f64, strength: f64, radius: f64) {
+        self.vortices.push(Vortex { center: Position::new(cx, cy), strength, radius });
+    }
+
+    /// Remove all vortices added with `add_vortex`.
+    pub fn clear_vortices(&mut self) {
+        self.vortices.clear();
+    }
+
+    /// Scale site speed in `step` by a `width`×`height` grayscale map (one byte
+    /// per pixel, stretched over the image): black = full speed, white = 10%.
+    /// Ignored while a `set_physics_params_fn` callback is set. Data of the
+    /// wrong length clears the map.
+    pub fn set_speed_map(&mut self, gray_data: &[u8], width: u32, height: u32) {
+        self.speed_map = image::GrayImage::from_raw(width, height, gray_data.to_vec());
     }
 
     /// Advance site physics by one time step.
-    /// Uses Ornstein-Uhlenbeck steering + centroid pull + edge bouncing.
+    /// Uses Ornstein-Uhlenbeck steering + centroid pull + vortices + edge handling
+    /// (see `set_edge_behavior`). Returns the indices of sites absorbed at the edges
+    /// (ascending, as of before the step), so per-cell data can be realigned.
     pub fn step(
         &mut self,
         speed: f64,
@@ -184,27 +543,86 @@ impl VoronoiEngine {
         centroid_pull: f64,
         theta: f64,
         sigma: f64,
-    ) {
+    ) -> Vec<u32> {
         let centroid_positions: Option<Vec<Position>> = centroids.map(|flat| {
             flat.chunks_exact(2)
                 .map(|xy| Position::new(xy[0], xy[1]))
                 .collect()
         });
 
-        self.sites.step(
-            speed,
-            dt,
-            self.width as f64,
-            self.height as f64,
-            centroid_positions.as_deref(),
-            centroid_pull,
-            theta,
-            sigma,
-        );
+        let (width, height) = (self.width as f64, self.height as f64);
+        self.sites.pull_mode = self.pull_mode;
+        self.sites.vortices.clone_from(&self.vortices);
+        match self.physics_params_fn {
+            Some(ref f) => self.sites.step_with_params_fn(
+                speed, dt, width, height, centroid_positions.as_deref(), centroid_pull, self.edge_behavior,
+                |i| {
+                    // Missing or non-numeric entries fall back to step's theta/sigma
+                    let params = f.call1(&JsValue::NULL, &JsValue::from(i as u32))
+                        .map(|v| js_sys::Array::from(&v))
+                        .unwrap_or_default();
+                    let get = |k: u32| params.get(k).as_f64();
+                    let defaults = StepParams::new(theta, sigma);
+                    StepParams {
+                        theta: get(0).unwrap_or(defaults.theta),
+                        sigma: get(1).unwrap_or(defaults.sigma),
+                        speed_decay: get(2).unwrap_or(defaults.speed_decay),
+                    }
+                },
+            ),
+            None => match self.speed_map {
+                Some(ref map) => self.sites.step_with_speed_map(
+                    map, speed, dt, width, height, centroid_positions.as_deref(), centroid_pull, theta, sigma,
+                    self.edge_behavior,
+                ),
+                None => self.sites.step_with_edge_behavior(
+                    speed, dt, width, height, centroid_positions.as_deref(), centroid_pull, theta, sigma,
+                    self.edge_behavior,
+                ),
+            },
+        }.into_iter().map(|i| i as u32).collect()
+    }
+
+    /// `step` with `dt` capped so no site moves more than half a typical cell
+    /// width (smaller right after splits, whose children move faster); at most
+    /// `max_dt`. Returns the `dt` used.
+    pub fn step_adaptive(
+        &mut self,
+        speed: f64,
+        max_dt: f64,
+        centroids: Option<Vec<f64>>,
+        centroid_pull: f64,
+        theta: f64,
+        sigma: f64,
+    ) -> f64 {
+        let dt = self.sites.adaptive_dt(speed, max_dt, self.width as f64, self.height as f64);
+        self.step(speed, dt, centroids, centroid_pull, theta, sigma);
+        dt
+    }
+
+    /// Advance sites along an optical flow field: `flow_flat` is `[dx0, dy0, dx1,
+    /// dy1, ...]`, one vector per pixel in row-major order at the image width.
+    /// Each site's heading is blended toward the flow at its pixel by `blend`
+    /// (0 = keep, 1 = follow), then vortices apply and sites move, reflecting
+    /// off the image edges.
+    pub fn step_with_flow(&mut self, flow_flat: &[f64], blend: f64, speed: f64, dt: f64) {
+        let flow: Vec<(f64, f64)> = flow_flat.chunks_exact(2).map(|v| (v[0], v[1])).collect();
+        self.sites.vortices.clone_from(&self.vortices);
+        self.sites.step_with_image_flow(&flow, self.width, blend, speed, dt);
+    }
+
+    /// Have `step` take each site's physics from `js_fn(site_index)`, which returns
+    /// `[theta, sigma, speed_decay]` (overriding `step`'s theta/sigma and per-site
+    /// dynamics). Pass `undefined` to go back to `step`'s parameters.
+    pub fn set_physics_params_fn(&mut self, js_fn: Option<js_sys::Function>) {
+        self.physics_params_fn = js_fn;
     }
 
     /// Gradually adjust site count toward target.
     /// Returns the number of sites added (positive) or removed (negative).
+    /// "bisect" recomputes the diagram for the current sites to split cells along
+    /// their bisecting line.
+    #[allow(clippy::too_many_arguments)]
     pub fn adjust_count(
         &mut self,
         target: usize,
@@ -232,22 +650,78 @@ impl VoronoiEngine {
         };
 
         let img_area = (self.width as f64) * (self.height as f64);
+        let bisections = (split_strategy == SplitStrategy::BisectLargest)
+            .then(|| self.compute_current().ok())
+            .flatten()
+            .map(|result| result.bisect_centroids(&self.sites.positions()));
 
         let before = self.sites.len();
-        self.sites.adjust_count(
-            target,
-            doubling_time,
-            dt,
-            cell_areas.as_deref(),
-            split_strategy,
-            centroid_positions.as_deref(),
-            farthest,
+        // Removed sites' data is gone afterwards; keep a copy to replay removals on
+        let mut snapshot = (!self.site_removed_fns.is_empty()).then(|| self.sites.sites.clone());
+        let (added, removed) = self.sites.adjust_count(target, doubling_time, dt, split_strategy, AdjustParams {
+            cell_areas: cell_areas.as_deref(),
+            centroids: centroid_positions.as_deref(),
+            farthest_point: farthest,
             img_area,
-        );
+            bisections: bisections.as_deref(),
+            max_per_frame: self.max_splits_per_frame,
+            auto_remove_dead: self.auto_remove_dead,
+            ..Default::default()
+        });
+        if let Some(ref mut snapshot) = snapshot {
+            for i in removed {
+                let site = snapshot.remove(i);
+                call_site_fns(&self.site_removed_fns, i, &site);
+            }
+        }
+        for i in added {
+            call_site_fns(&self.site_added_fns, i, &self.sites.sites[i]);
+        }
         let after = self.sites.len();
         (after as i32) - (before as i32)
     }
 
+    /// Call `js_fn(index, x, y)` for each site `adjust_count` adds, after the call's
+    /// additions are done. Multiple callbacks run in registration order.
+    pub fn on_site_added(&mut self, js_fn: js_sys::Function) {
+        self.site_added_fns.push(js_fn);
+    }
+
+    /// Call `js_fn(index, x, y)` for each site `adjust_count` removes, with its index
+    /// and position just before removal.
+    pub fn on_site_removed(&mut self, js_fn: js_sys::Function) {
+        self.site_removed_fns.push(js_fn);
+    }
+
+    /// Drop all `on_site_added` / `on_site_removed` callbacks
+    pub fn clear_site_callbacks(&mut self) {
+        self.site_added_fns.clear();
+        self.site_removed_fns.clear();
+    }
+
+    /// Limit `adjust_count` to adding or removing `n` sites per call; growth beyond
+    /// that carries over to later calls. 0 removes the limit.
+    pub fn set_max_splits_per_frame(&mut self, n: usize) {
+        self.max_splits_per_frame = if n == 0 { usize::MAX } else { n };
+    }
+
+    /// Have `adjust_count` remove sites whose cell has zero area in the
+    /// `cell_areas` it's given, before growing or shrinking toward the target.
+    pub fn set_auto_remove_dead(&mut self, enabled: bool) {
+        self.auto_remove_dead = enabled;
+    }
+
+    /// Per-site O-U dynamics: `theta_flat[i]` / `sigma_flat[i]` override the `theta` /
+    /// `sigma` passed to `step` for site i. Sites beyond either array keep their values.
+    pub fn set_site_dynamics(&mut self, theta_flat: &[f64], sigma_flat: &[f64]) {
+        for (i, &theta) in theta_flat.iter().enumerate() {
+            self.sites.set_theta(i, theta);
+        }
+        for (i, &sigma) in sigma_flat.iter().enumerate() {
+            self.sites.set_sigma(i, sigma);
+        }
+    }
+
     /// Get current site positions as flat [x0,y0, x1,y1, ...].
     pub fn get_positions(&self) -> Vec<f64> {
         self.sites.positions().iter()
@@ -255,6 +729,44 @@ impl VoronoiEngine {
             .collect()
     }
 
+    /// Minimum spanning tree over the current sites, flat as [i0,j0, i1,j1, ...]
+    /// (n − 1 edges, shortest first; approximate above 2000 sites).
+    pub fn min_spanning_tree(&self) -> Vec<u32> {
+        self.sites.min_spanning_tree().into_iter()
+            .flat_map(|(i, j)| [i as u32, j as u32])
+            .collect()
+    }
+
+    /// Jitter every site by uniform noise of up to `magnitude` pixels per axis,
+    /// kept inside the image. Repeated calls give different patterns.
+    pub fn perturb(&mut self, magnitude: f64) {
+        self.sites.perturb_positions(magnitude, 0, self.width as f64, self.height as f64);
+    }
+
+    /// Move a random `fraction` (0-1) of the sites to random positions in the
+    /// image with fresh headings, leaving the rest untouched.
+    pub fn randomize_subset(&mut self, fraction: f64) {
+        self.sites.randomize_subset(fraction, self.width as f64, self.height as f64);
+    }
+
+    /// Nudge sites toward equal cell areas, given the last frame's `cell_areas`
+    /// (`VoronoiFrame::cell_areas`); `step_size` is in site spacings.
+    pub fn density_equalization_step(&mut self, cell_areas: Vec<u32>, step_size: f64) {
+        self.sites.density_equalization_step(&cell_areas, self.width as f64, self.height as f64, step_size);
+    }
+
+    /// Move site `index` to (x, y), keeping its velocity. Returns false if out of range.
+    pub fn teleport_site(&mut self, index: usize, x: f64, y: f64) -> bool {
+        self.sites.teleport_site(index, Position::new(x, y)).is_ok()
+    }
+
+    /// Move the site nearest (x, y) onto it, keeping its velocity. Returns its
+    /// index, or -1 if there are no sites.
+    pub fn teleport_nearest(&mut self, x: f64, y: f64) -> i32 {
+        let grid = SpatialGrid::new(&self.sites.positions(), self.width, self.height);
+        self.sites.teleport_nearest_to(Position::new(x, y), &grid).map_or(-1, |i| i as i32)
+    }
+
     /// Get current site velocities as flat [vx0,vy0, vx1,vy1, ...].
     pub fn get_velocities(&self) -> Vec<f64> {
         self.sites.sites.iter()
@@ -270,8 +782,211 @@ impl VoronoiEngine {
         }
     }
 
+    /// Fraction of the image covered by the convex hull of the sites
+    /// (near 0 when sites have clumped together).
+    pub fn hull_coverage(&self) -> f64 {
+        self.sites.convex_hull_coverage(self.width as f64, self.height as f64)
+    }
+
+    /// How aligned sites' headings are within `radius` pixels of each other:
+    /// 1.0 = moving together, near 0.0 = random.
+    pub fn velocity_coherence(&self, radius: f64) -> f64 {
+        self.sites.velocity_coherence(radius)
+    }
+
+    /// Approximate Voronoi vertices of the current sites farther than `min_dist`
+    /// from any site, as flat [x0,y0,dist0, x1,y1,dist1, ...] sorted by distance
+    /// descending (pixel-center precision).
+    pub fn get_voronoi_vertices(&self, min_dist: f64) -> Vec<f64> {
+        let positions = self.sites.positions();
+        let Ok(result) = CpuBackend::with_distances(true).compute(&self.image, &positions) else {
+            return vec![];
+        };
+        self.sites.approximate_voronoi_vertices(&result, min_dist).into_iter()
+            .flat_map(|(p, d)| [p.x, p.y, d])
+            .collect()
+    }
+
+    /// Site velocities averaged onto a grid_w×grid_h grid, as flat RGB
+    /// (length = grid_w*grid_h*3): R/G = vx/-vy offset from 128, B = speed_mult,
+    /// black where a grid cell has no sites.
+    pub fn velocity_field_image(&self, grid_w: u32, grid_h: u32) -> Vec<u8> {
+        self.sites.velocity_field_snapshot(grid_w, grid_h, self.width as f64, self.height as f64)
+            .into_raw()
+    }
+
+    /// Keep up to `capacity` snapshots for `undo` (0 disables). Applies to the
+    /// current sites and survives `load_state`/`import_sites_csv`;
+    /// re-initializing sites starts a fresh, disabled history.
+    pub fn enable_history(&mut self, capacity: usize) {
+        let sites = std::mem::replace(&mut self.sites, SiteCollection::new(vec![], self.seed));
+        self.sites = sites.with_history(capacity);
+    }
+
+    /// Save the current site state; call before `step` to make it undoable.
+    pub fn snapshot(&mut self) {
+        self.sites.snapshot();
+    }
+
+    /// Restore the state saved by the last `snapshot`. Returns false if there is none.
+    pub fn undo(&mut self) -> bool {
+        self.sites.undo()
+    }
+
     /// Get current site count.
     pub fn site_count(&self) -> usize {
         self.sites.len()
     }
+
+    /// Export sites as CSV (`id,x,y,vx,vy,turn_rate,speed_mult,age`).
+    pub fn export_sites_csv(&self) -> String {
+        self.sites.export_csv()
+    }
+
+    /// Replace sites with those parsed from CSV (as produced by `export_sites_csv`).
+    /// Returns false (leaving sites unchanged) if the CSV is malformed.
+    pub fn import_sites_csv(&mut self, csv: &str) -> bool {
+        match SiteCollection::import_csv(csv, self.seed) {
+            Ok(sites) => {
+                self.sites = sites.with_history(self.sites.history_capacity());
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Save full site state, including the RNG, as JSON.
+    pub fn save_state(&self) -> String {
+        self.sites.to_json()
+    }
+
+    /// Restore sites from `save_state` output; later steps match the saved engine's.
+    /// Returns false (leaving sites unchanged) if the JSON is malformed.
+    pub fn load_state(&mut self, json: &str) -> bool {
+        match SiteCollection::from_json(json) {
+            Ok(sites) => {
+                self.sites = sites.with_history(self.sites.history_capacity());
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+impl VoronoiEngine {
+    fn compute_dynamic(&mut self, image: image::DynamicImage) -> VoronoiFrame {
+        let positions = self.sites.positions();
+        let result = self.backend.compute_dynamic(&image, &positions)
+            .expect("Voronoi computation failed");
+        VoronoiFrame::from_result(result, FrameSource::Dynamic(image))
+    }
+
+    /// Diagram of the engine's image and current sites, as a power diagram while
+    /// any site weight differs from 1
+    fn compute_current(&mut self) -> voronoi_core::Result<VoronoiResult> {
+        let positions = self.sites.positions();
+        let weights = self.sites.weights();
+        if weights.iter().any(|&w| w != 1.0) {
+            self.backend.compute_weighted(&self.image, &positions, &weights)
+        } else {
+            self.backend.compute(&self.image, &positions)
+        }
+    }
+
+    /// Wrap a result computed from the engine's image, clearing pixels outside the
+    /// polygon mask (see `set_polygon_mask`)
+    fn masked_frame(&self, mut result: VoronoiResult) -> VoronoiFrame {
+        if let Some(polygon) = &self.polygon_mask {
+            let mask = result.rasterize_polygon_mask(polygon);
+            for (cell, inside) in result.cell_of.iter_mut().zip(mask) {
+                if !inside {
+                    *cell = -1;
+                }
+            }
+        }
+        VoronoiFrame::from_result(result, FrameSource::Engine(self.image.clone()))
+    }
+}
+
+/// Configures a `VoronoiEngine` with its initial sites in one chain:
+/// `VoronoiEngine.builder().count(500).init_strategy("corners").build(rgba, w, h)`.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct VoronoiEngineBuilder {
+    sites: SiteCollectionBuilder,
+    seed: u64,
+    edge_behavior: Option<EdgeBehavior>,
+    max_splits_per_frame: usize,
+}
+
+#[wasm_bindgen]
+impl VoronoiEngineBuilder {
+    /// Number of initial sites
+    pub fn count(mut self, count: usize) -> Self {
+        self.sites = self.sites.count(count);
+        self
+    }
+
+    /// Seed for site placement and dynamics
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.seed = seed as u64;
+        self.sites = self.sites.seed(seed as u64);
+        self
+    }
+
+    /// Initial placement: "random", "corners", "clusters", "walk", "mandelbrot",
+    /// "polygon", "rings", "lissajous", "scatter" or "hybrid", with the core
+    /// builder's default parameters. Unknown names, and "stipple" (which needs a
+    /// stipple image), fall back to random.
+    pub fn init_strategy(mut self, strategy: &str) -> Self {
+        let strategy = strategy.parse().ok().filter(|&s| s != InitStrategy::Stipple);
+        self.sites = self.sites.init_strategy(strategy.unwrap_or(InitStrategy::Random));
+        self
+    }
+
+    /// Step length in pixels for the "walk" strategy (default 20)
+    pub fn walk_step(mut self, step: f64) -> Self {
+        self.sites = self.sites.walk_step(step);
+        self
+    }
+
+    /// Escape-time iteration limit for the "mandelbrot" strategy (default 100)
+    pub fn fractal_iters(mut self, max_iter: u32) -> Self {
+        self.sites = self.sites.fractal_iters(max_iter);
+        self
+    }
+
+    /// Undo history depth (see `VoronoiEngine::enable_history`)
+    pub fn history_capacity(mut self, capacity: usize) -> Self {
+        self.sites = self.sites.history_capacity(capacity);
+        self
+    }
+
+    /// Edge behavior: "reflect", "wrap", "absorb", or "clamp" (unknown names keep reflect)
+    pub fn edge_behavior(mut self, behavior: &str) -> Self {
+        self.edge_behavior = behavior.parse().ok();
+        self
+    }
+
+    /// Per-site O-U `theta` and `sigma` for every initial site
+    pub fn ou_params(mut self, theta: f64, sigma: f64) -> Self {
+        self.sites = self.sites.ou_params(theta, sigma);
+        self
+    }
+
+    /// Cap on sites added/removed per `adjust_count` call (0 = no limit)
+    pub fn max_splits_per_frame(mut self, n: usize) -> Self {
+        self.max_splits_per_frame = n;
+        self
+    }
+
+    /// Create the engine for RGBA pixel data, placing its initial sites
+    pub fn build(&self, rgba_data: &[u8], width: u32, height: u32) -> VoronoiEngine {
+        let mut engine = VoronoiEngine::new(rgba_data, width, height, self.seed as u32);
+        engine.sites = self.sites.build(Some(&engine.image))
+            .expect("image-driven init strategies have the engine's image");
+        engine.edge_behavior = self.edge_behavior.unwrap_or(engine.edge_behavior);
+        engine.set_max_splits_per_frame(self.max_splits_per_frame);
+        engine
+    }
 }